@@ -1,4 +1,10 @@
 import { generateCookedMapAssets } from "./map-generation/cooked-assets.mjs";
+import {
+  addCookReportMap,
+  createCookReport,
+  recordCookReportResult,
+  writeCookReport,
+} from "./map-generation/cook-report.mjs";
 import { createGenerationContext } from "./map-generation/shared.mjs";
 import {
   assertRebuildPlanWithinBudget,
@@ -8,16 +14,21 @@ import {
   hasRebuildRequest,
 } from "./map-generation/world-rebuild-planner.mjs";
 
+let context = null;
+let report = null;
+
 async function main() {
-  const context = createGenerationContext();
+  context = createGenerationContext();
   const rebuildRequest = createRebuildRequestFromArgs(context.args);
   const usesRebuildPlan = hasRebuildRequest(rebuildRequest);
   const results = [];
+  report = createCookReport(context, "cook", rebuildRequest);
 
   for (const preset of context.presets) {
     const rebuildPlan = usesRebuildPlan
       ? await createWorldRebuildPlanFromContext(context, preset, rebuildRequest)
       : null;
+    const reportEntry = addCookReportMap(report, preset, rebuildPlan, rebuildRequest);
     if (rebuildPlan && !rebuildRequest.dryRun) {
       assertRebuildPlanWithinBudget(rebuildPlan, rebuildRequest);
     }
@@ -27,11 +38,9 @@ async function main() {
       continue;
     }
 
-    results.push({
-      preset,
-      rebuildPlan,
-      cooked: await generateCookedMapAssets(context, preset, { rebuildPlan }),
-    });
+    const cooked = await generateCookedMapAssets(context, preset, { rebuildPlan });
+    recordCookReportResult(reportEntry, cooked);
+    results.push({ preset, rebuildPlan, cooked });
   }
 
   console.log(`${rebuildRequest.dryRun ? "Planned" : "Generated"} cooked map data for ${results.length} map(s) in ${context.projectArg}`);
@@ -49,10 +58,18 @@ async function main() {
     console.log(`  Cooked package artifacts: ${result.cooked.artifactCount}`);
     console.log(`  Cooked manifest: ${result.cooked.path}`);
   }
+
+  await writeCookReport(context, report);
 }
 
-main().catch((error) => {
+main().catch(async (error) => {
   console.error("Failed to generate cooked map data.");
   console.error(error);
   process.exitCode = 1;
+  if (context && report) {
+    await writeCookReport(context, report, error).catch((reportError) => {
+      console.error("Failed to write cook report.");
+      console.error(reportError);
+    });
+  }
 });
\ No newline at end of file
@@ -1,3 +1,10 @@
+import {
+  addCookReportMap,
+  createCookReport,
+  recordCookReportResult,
+  recordCookReportStages,
+  writeCookReport,
+} from "./map-generation/cook-report.mjs";
 import { createGenerationContext } from "./map-generation/shared.mjs";
 import {
   assertRebuildPlanWithinBudget,
@@ -8,20 +15,30 @@ import {
 } from "./map-generation/world-rebuild-planner.mjs";
 import { dispatchWorldGenerationStages } from "./map-generation/stage-executor-dispatcher.mjs";
 
+let context = null;
+let report = null;
+
 async function main() {
-  const context = createGenerationContext();
+  context = createGenerationContext();
   const request = createRebuildRequestFromArgs(context.args);
   const dryRun = request.dryRun;
   const results = [];
+  report = createCookReport(context, "graph", request);
 
   for (const preset of context.presets) {
     const graph = await readWorldGenerationGraph(context, preset);
     const rebuildPlan = await createWorldRebuildPlanFromContext(context, preset, request);
+    const reportEntry = addCookReportMap(report, preset, rebuildPlan, request);
     if (!dryRun) {
       assertRebuildPlanWithinBudget(rebuildPlan, request);
     }
 
     const stages = await dispatchWorldGenerationStages(context, preset, graph, rebuildPlan, { dryRun });
+    const cooked = stages.find((stage) => stage.result?.writtenArtifacts)?.result ?? null;
+    if (cooked) {
+      recordCookReportResult(reportEntry, cooked);
+    }
+    recordCookReportStages(reportEntry, stages);
     results.push({ preset, rebuildPlan, stages });
   }
 
@@ -32,10 +49,18 @@ async function main() {
       console.log(`  - ${stage.stage}: ${stage.status} by ${stage.executor} (${stage.keyCount} keys)`);
     }
   }
+
+  await writeCookReport(context, report);
 }
 
-main().catch((error) => {
+main().catch(async (error) => {
   console.error("Failed to execute world generation graph.");
   console.error(error);
   process.exitCode = 1;
+  if (context && report) {
+    await writeCookReport(context, report, error).catch((reportError) => {
+      console.error("Failed to write cook report.");
+      console.error(reportError);
+    });
+  }
 });
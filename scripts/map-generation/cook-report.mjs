@@ -0,0 +1,103 @@
+import { mkdir } from "node:fs/promises";
+import path from "node:path";
+import {
+  cookReportFormat,
+  cookReportVersion,
+  readFlagValue,
+  writeJsonFile,
+} from "./shared.mjs";
+
+export function readCookReportPath(args) {
+  const reportPath = readFlagValue(args, "--report");
+  return reportPath ? path.resolve(reportPath) : null;
+}
+
+export function createCookReport(context, workflow, request) {
+  return {
+    version: cookReportVersion,
+    format: cookReportFormat,
+    workflow,
+    status: "running",
+    dryRun: Boolean(request.dryRun),
+    project: context.projectArg,
+    startedAt: new Date().toISOString(),
+    finishedAt: null,
+    maps: [],
+    warnings: [],
+    errors: [],
+  };
+}
+
+export function addCookReportMap(report, preset, rebuildPlan, request) {
+  const entry = {
+    mapId: preset.id,
+    planId: rebuildPlan?.planId ?? null,
+    mode: rebuildPlan?.mode ?? "full",
+    blocked: Boolean(rebuildPlan?.budget.exceeded && !request.dryRun && !request.allowBudgetOverrun),
+    stages: (rebuildPlan?.actions ?? []).map((action) => ({
+      stage: action.stage,
+      executor: action.executor,
+      keyCount: action.keys.length,
+      status: "planned",
+    })),
+    cacheHit: null,
+    manifestPath: null,
+    artifacts: {
+      written: [],
+      reused: [],
+    },
+    cells: [],
+    warnings: [...(rebuildPlan?.budget.warnings ?? [])],
+    errors: [...(rebuildPlan?.budget.errors ?? [])],
+  };
+
+  report.maps.push(entry);
+  return entry;
+}
+
+export function recordCookReportStages(entry, stageResults) {
+  entry.stages = stageResults.map((stage) => ({
+    stage: stage.stage,
+    executor: stage.executor,
+    keyCount: stage.keyCount,
+    status: stage.status,
+  }));
+}
+
+export function recordCookReportResult(entry, cooked) {
+  // A cache hit reuses the previous cook output, so no stage actually ran.
+  const stageStatus = cooked.cacheHit ? "cached" : "executed";
+  for (const stage of entry.stages) {
+    stage.status = stageStatus;
+  }
+
+  entry.cacheHit = cooked.cacheHit;
+  entry.manifestPath = cooked.path;
+  entry.artifacts = {
+    written: cooked.writtenArtifacts,
+    reused: cooked.reusedArtifacts,
+  };
+  entry.cells = cooked.cellBudgets;
+  for (const cell of cooked.cellBudgets) {
+    if (cell.rating === "over") {
+      entry.warnings.push(`partition cell ${cell.key} is over budget (estimated cost ${cell.estimatedCost})`);
+    }
+  }
+}
+
+export async function writeCookReport(context, report, error = null) {
+  report.status = error ? "failed" : "succeeded";
+  report.finishedAt = new Date().toISOString();
+  if (error) {
+    report.errors.push(error instanceof Error ? error.message : String(error));
+  }
+
+  const reportPath = readCookReportPath(context.args);
+  if (!reportPath) {
+    return null;
+  }
+
+  await mkdir(path.dirname(reportPath), { recursive: true });
+  await writeJsonFile(reportPath, report);
+  return reportPath;
+}
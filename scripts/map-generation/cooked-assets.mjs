@@ -125,9 +125,11 @@ export async function generateCookedMapAssets(context, preset, options = {}) {
 
   const outputPath = path.join(cookedDir, cookedMapManifestFile);
   await writeJsonFile(outputPath, manifest);
-  await writeCookCache(context, mapId, manifest);
+  await writeCookCache(context, mapId, manifest, {
+    invalidations: collectUncoveredCacheInvalidations(cache, rebuildPlan),
+  });
 
-  return createCookResultFromManifest(context, outputPath, manifest, false);
+  return createCookResultFromManifest(context, outputPath, manifest, false, packageBuilder.listWrittenArtifactPaths());
 }
 
 async function readExistingCookedManifest(cookedDir, mapId) {
@@ -175,6 +177,10 @@ async function isCookCacheUsable(context, cookedDir, cache, inputSignature) {
     return false;
   }
 
+  if (Array.isArray(cache.invalidations) && cache.invalidations.length > 0) {
+    return false;
+  }
+
   const manifestPath = path.join(cookedDir, cookedMapManifestFile);
   if (!existsSync(manifestPath)) {
     return false;
@@ -235,7 +241,28 @@ function createCookedRebuildMetadata(rebuildPlan) {
   };
 }
 
-async function writeCookCache(context, mapId, manifest) {
+function collectUncoveredCacheInvalidations(cache, rebuildPlan) {
+  const invalidations = Array.isArray(cache?.invalidations) ? cache.invalidations : [];
+  if (!rebuildPlan || rebuildPlan.mode === "full") {
+    return [];
+  }
+
+  return invalidations.filter((invalidation) => !isCacheInvalidationCovered(invalidation, rebuildPlan));
+}
+
+function isCacheInvalidationCovered(invalidation, rebuildPlan) {
+  const stages = Array.isArray(invalidation?.stages) ? invalidation.stages : [];
+  if (!stages.every((stage) => rebuildPlan.stages.includes(stage))) {
+    return false;
+  }
+
+  return Object.entries(invalidation?.scopes ?? {}).every(([scopeName, keys]) => {
+    const plannedKeys = new Set(rebuildPlan.scopes?.[scopeName] ?? []);
+    return !Array.isArray(keys) || keys.every((key) => plannedKeys.has(key));
+  });
+}
+
+async function writeCookCache(context, mapId, manifest, options = {}) {
   const artifacts = Object.values(manifest.package.artifacts)
     .flatMap((artifact) => [artifact.path, artifact.blobPath, artifact.compression?.blobPath].filter(Boolean))
     .sort();
@@ -251,6 +278,7 @@ async function writeCookCache(context, mapId, manifest) {
     packageLayout: manifest.package.layout,
     artifactCount: manifest.package.artifactCount,
     artifacts,
+    ...(options.invalidations?.length > 0 ? { invalidations: options.invalidations } : {}),
   });
 }
 
@@ -260,7 +288,8 @@ async function createCookResult(context, cookedDir, mapId, cacheHit) {
   return createCookResultFromManifest(context, outputPath, manifest, cacheHit);
 }
 
-function createCookResultFromManifest(context, outputPath, manifest, cacheHit) {
+function createCookResultFromManifest(context, outputPath, manifest, cacheHit, writtenArtifacts = []) {
+  const written = new Set(writtenArtifacts);
   return {
     mapId: manifest.mapId,
     path: projectRelativePath(outputPath, context),
@@ -273,6 +302,9 @@ function createCookResultFromManifest(context, outputPath, manifest, cacheHit) {
     objectCellCount: Object.keys(manifest.assets.objects.cells).length,
     collisionCellCount: Object.keys(manifest.assets.collision.cells).length,
     navCellCount: Object.keys(manifest.assets.nav.cells).length,
+    writtenArtifacts: [...written],
+    reusedArtifacts: Object.keys(manifest.package.artifacts).filter((runtimePath) => !written.has(runtimePath)),
+    cellBudgets: manifest.partition.cells.map((cell) => ({ key: cell.key, ...cell.budget })),
   };
 }
 
@@ -471,6 +503,19 @@ async function copyCookedVegetationModelAssets(context, mapDir, cookedDir, packa
     nextModel[field] = await copyMapRelativeAssetToCooked(context, mapDir, cookedDir, packageBuilder, value);
   }
 
+  if (nextModel.impostor && typeof nextModel.impostor === "object") {
+    const nextImpostor = { ...nextModel.impostor };
+    for (const field of ["albedoPath", "normalPath"]) {
+      const value = nextImpostor[field];
+      if (typeof value !== "string" || isExternalAssetPath(value)) {
+        continue;
+      }
+
+      nextImpostor[field] = await copyMapRelativeAssetToCooked(context, mapDir, cookedDir, packageBuilder, value);
+    }
+    nextModel.impostor = nextImpostor;
+  }
+
   return nextModel;
 }
 
@@ -5,6 +5,7 @@ import { brotliCompress, constants as zlibConstants } from "node:zlib";
 import {
   cookedBlobDirectory,
   cookedPackageLayout,
+  cookedTextureManifestPath,
   createSha256Hex,
 } from "./shared.mjs";
 
@@ -22,6 +23,7 @@ const kindOrder = new Map([
 
 export function createCookedPackageBuilder(context, seedPackage = null) {
   const artifacts = new Map(Object.entries(seedPackage?.artifacts ?? {}));
+  const writtenRuntimePaths = new Set();
 
   return {
     async copyFile(sourcePath, runtimePath, kind, sourceRelativePath = null) {
@@ -55,6 +57,10 @@ export function createCookedPackageBuilder(context, seedPackage = null) {
       }));
     },
 
+    listWrittenArtifactPaths() {
+      return [...writtenRuntimePaths].sort();
+    },
+
     async createPackage() {
       const hydratedEntries = await Promise.all([...artifacts.entries()].map(async ([runtimePath, artifact]) => {
         const hydrated = artifact.compression?.algorithm === "brotli" && artifact.compression?.blobPath
@@ -105,6 +111,9 @@ export function createCookedPackageBuilder(context, seedPackage = null) {
       artifact.sourcePath = sourceRelativePath;
     }
 
+    if (artifacts.get(runtimePath)?.sha256 !== sha256) {
+      writtenRuntimePaths.add(runtimePath);
+    }
     artifacts.set(runtimePath, artifact);
     return artifact;
   }
@@ -223,6 +232,10 @@ async function listFilesSafe(directoryPath) {
 
 async function pruneBlobRoots(context, artifacts) {
   const used = new Set(artifacts.flatMap((artifact) => [artifact.blobPath, artifact.compression?.blobPath].filter(Boolean)));
+  // Imported textures share the blob root but are owned by the texture manifest, not the map package.
+  for (const blobPath of await readTextureManifestBlobPaths(context)) {
+    used.add(blobPath);
+  }
   const roots = [cookedBlobDirectory, compressedBlobDirectory];
   for (const root of roots) {
     const rootPath = path.join(context.projectDir, root);
@@ -236,6 +249,19 @@ async function pruneBlobRoots(context, artifacts) {
   }
 }
 
+async function readTextureManifestBlobPaths(context) {
+  try {
+    const manifest = JSON.parse(await readFile(path.join(context.projectDir, cookedTextureManifestPath), "utf8"));
+    return Object.values(manifest.textures ?? {}).map((texture) => texture.blobPath).filter(Boolean);
+  } catch (error) {
+    if (error?.code === "ENOENT") {
+      return [];
+    }
+
+    throw error;
+  }
+}
+
 function createBlobPath(runtimePath, sha256) {
   const extension = path.extname(runtimePath).replace(/[^.a-z0-9]/gi, "");
   return `${cookedBlobDirectory}/${sha256.slice(0, 2)}/${sha256}${extension}`;
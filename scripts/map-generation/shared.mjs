@@ -48,11 +48,14 @@ export const cookedMapVersion = 4;
 export const cookedBuildCacheDirectory = "cooked/cache/maps";
 export const cookedBlobDirectory = "cooked/blobs/sha256";
 export const cookedPackageLayout = "content-addressed-sha256-v1";
+export const cookedTextureManifestPath = "cooked/textures/manifest.json";
 export const cookedWorldPartitionCellSizePages = 8;
 export const cookedWorldPartitionDependencyKinds = ["terrain", "paint", "vegetation", "objects", "collision", "nav"];
 export const cookedObjectCellFormat = worldObjectCellFormat;
 export const cookedCollisionCellFormat = "world-collision-cell-pack-v1";
 export const cookedNavCellFormat = "world-nav-cell-pack-v1";
+export const cookReportFormat = "open-fps-cook-report-v1";
+export const cookReportVersion = 1;
 
 export const defaultPageBounds = {
   minPageX: -8,
@@ -254,7 +257,7 @@ function selectPresets(mapFilter, generateAll) {
   return generateAll ? mapPresets : [mapPresets[0]];
 }
 
-function readFlagValue(args, flag) {
+export function readFlagValue(args, flag) {
   const inlinePrefix = `${flag}=`;
   const inlineValue = args.find((arg) => arg.startsWith(inlinePrefix));
   if (inlineValue) {
@@ -276,6 +279,7 @@ function readProjectArg(args) {
     "--map",
     "--paint-region",
     "--partition-cell",
+    "--report",
     "--stage",
     "--terrain-region",
     "--vegetation-region",
@@ -299,6 +303,7 @@ function readProjectArg(args) {
       || arg.startsWith("--map=")
       || arg.startsWith("--paint-region=")
       || arg.startsWith("--partition-cell=")
+      || arg.startsWith("--report=")
       || arg.startsWith("--stage=")
       || arg.startsWith("--terrain-region=")
       || arg.startsWith("--vegetation-region=")
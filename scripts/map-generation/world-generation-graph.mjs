@@ -190,9 +190,56 @@ function createVegetationStage() {
       "tree-cluster-understory",
       "impostor-distance-budget",
     ],
+    species: createVegetationSpeciesProfiles(),
   };
 }
 
+function createVegetationSpeciesProfiles() {
+  return [
+    {
+      modelId: "quiverTree",
+      salt: 9100,
+      spacingMeters: 18,
+      maxDensity: 0.5,
+      clusterScaleMeters: 160,
+      clusterCoverage: 0.55,
+      slopeDegrees: [28, 42],
+      snowlineMeters: [132, 182],
+      wetlandBoost: 0.22,
+      shoulderPenalty: 0.55,
+      edgeRegrowthBoost: 0,
+      scaleRange: [0.82, 1.44],
+      paintAffinity: [
+        { layer: "mudLeaves", affinity: 0.35 },
+        { layer: "snow", affinity: -0.8 },
+        { layer: "gravelEmbeddedConcrete", affinity: -0.7 },
+        { layer: "beachSand", affinity: -0.4 },
+      ],
+    },
+    {
+      modelId: "fern",
+      salt: 9300,
+      spacingMeters: 5.5,
+      maxDensity: 0.4,
+      clusterScaleMeters: 48,
+      clusterCoverage: 0.6,
+      slopeDegrees: [24, 38],
+      snowlineMeters: [116, 166],
+      wetlandBoost: 0.35,
+      shoulderPenalty: 0.75,
+      edgeRegrowthBoost: 0.25,
+      scaleRange: [0.55, 1.25],
+      paintAffinity: [
+        { layer: "mudLeaves", affinity: 0.5 },
+        { layer: "snow", affinity: -0.9 },
+        { layer: "gravelEmbeddedConcrete", affinity: -0.8 },
+        { layer: "beachSand", affinity: -0.2 },
+      ],
+      understoryOf: { modelId: "quiverTree", radiusMeters: 36 },
+    },
+  ];
+}
+
 function createObjectsStage() {
   return {
     kind: "semantic-object-placement-graph",
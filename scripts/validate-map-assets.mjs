@@ -32,6 +32,7 @@ const PAINT_REGION_EXTENSION = ".paintpack";
 const VEGETATION_PATH = "vegetation/models.json";
 const VEGETATION_REGION_DIRECTORY = "vegetation/regions";
 const VEGETATION_REGION_EXTENSION = ".vegpack";
+const VEGETATION_IMPOSTOR_FORMAT = "vegetation-impostor-atlas-v1";
 const WORLD_OBJECTS_PATH = "objects/manifest.json";
 const WORLD_OBJECT_CELLS_DIRECTORY = "objects/cells";
 const WORLD_OBJECT_MANIFEST_FORMAT = "world-object-manifest-v1";
@@ -473,6 +474,33 @@ function validateVegetationModelAssetReferences(mapDirectory, models, assetRegis
         validateRegisteredProjectAssetPath(assetRegistry, projectRelativePath, "model", label, `vegetation model '${modelId}' ${field}`);
       }
     }
+
+    if (model.impostor === undefined) {
+      continue;
+    }
+
+    if (!isRecord(model.impostor) || model.impostor.format !== VEGETATION_IMPOSTOR_FORMAT) {
+      addError(label, `Vegetation model '${modelId}' impostor must use ${VEGETATION_IMPOSTOR_FORMAT}.`);
+      continue;
+    }
+
+    for (const field of ["albedoPath", "normalPath"]) {
+      const value = model.impostor[field];
+      const fieldName = `vegetation model '${modelId}' impostor.${field}`;
+      if (typeof value !== "string" || value.length === 0) {
+        addError(label, `${fieldName} must be a non-empty string.`);
+        continue;
+      }
+
+      if (isExternalAssetPath(value)) {
+        continue;
+      }
+
+      const projectRelativePath = resolveProjectAssetReference(projectDirectory, mapDirectory, value, label, fieldName);
+      if (projectRelativePath) {
+        validateRegisteredProjectAssetPath(assetRegistry, projectRelativePath, "model", label, fieldName);
+      }
+    }
   }
 }
 
@@ -1445,6 +1473,25 @@ async function validateCookedVegetationModelFiles(projectPath, mapId, models, la
       await readRequiredFileBytes(filePath, `cooked vegetation model '${modelId}' ${field}`);
     });
   }));
+  await Promise.all(Object.entries(models).flatMap(([modelId, model]) => {
+    if (!isRecord(model) || !isRecord(model.impostor)) {
+      return [];
+    }
+
+    return ["albedoPath", "normalPath"].map(async (field) => {
+      const value = model.impostor[field];
+      if (typeof value !== "string" || isExternalAssetPath(value)) {
+        return;
+      }
+
+      const filePath = path.resolve(cookedMapDirectory, value);
+      if (!isInsideDirectory(filePath, path.join(projectPath, "cooked"))) {
+        addError(label, `Cooked vegetation model '${modelId}' impostor.${field} must resolve inside cooked assets.`);
+        return;
+      }
+      await readRequiredFileBytes(filePath, `cooked vegetation model '${modelId}' impostor.${field}`);
+    });
+  }));
 }
 
 function validateCookedPartition(partition, world, assets, contentPackage, label) {
@@ -0,0 +1,414 @@
+// Asset import from local folders with provenance capture.
+// 从本地文件夹导入资源并记录来源信息
+//
+// EN: Files are staged next to their final imported root and renamed into place; the registry entry is written last and rolled back on failure.
+// 中文: 文件先暂存在最终导入根目录旁再重命名到位；注册表条目最后写入，失败时回滚。
+
+use crate::asset_registry::{AssetRegistry, store_asset_registry_entry};
+use crate::commands::{safe_write, validate_cook_project_path, validate_single_path_segment};
+use crate::map_layout::sha256_hex;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const MATERIAL_ROLE_SUFFIXES: &[(&str, &[&str])] = &[
+    ("normal", &["nor_gl", "normal_gl", "normal", "nor", "nrm"]),
+    ("arm", &["arm"]),
+    (
+        "diffuse",
+        &[
+            "diff",
+            "diffuse",
+            "albedo",
+            "basecolor",
+            "base_color",
+            "col",
+            "color",
+        ],
+    ),
+    ("displacement", &["disp", "displacement", "height"]),
+    ("roughness", &["rough", "roughness"]),
+    ("ambientOcclusion", &["ao"]),
+    ("metallic", &["metal", "metallic", "metalness"]),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportAssetKind {
+    Model,
+    Material,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAssetRequest {
+    project_path: String,
+    source_dir: String,
+    kind: ImportAssetKind,
+    provider: String,
+    license: String,
+    source_url: String,
+    #[serde(default)]
+    license_url: Option<String>,
+    #[serde(default)]
+    asset_name: Option<String>,
+    #[serde(default)]
+    display_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAssetResult {
+    asset_id: String,
+    entry: Value,
+    source_metadata_path: String,
+    file_count: usize,
+    total_bytes: u64,
+}
+
+/// A copied file with its project-relative destination and content hash.
+/// 已复制的文件，包含项目相对目标路径与内容哈希
+struct ImportedFile {
+    source: PathBuf,
+    path: String,
+    byte_length: u64,
+    sha256: String,
+}
+
+// --- Asset import commands / 资源导入命令 ---
+
+/// Copy a glTF model or texture set into `assets/imported`, write `source.json` and register it.
+/// 将 glTF 模型或纹理集复制到 `assets/imported`，写入 `source.json` 并注册
+#[tauri::command]
+pub async fn import_asset(request: ImportAssetRequest) -> Result<ImportAssetResult, String> {
+    tauri::async_runtime::spawn_blocking(move || import_asset_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join asset import task: {}", e))?
+}
+
+fn import_asset_blocking(request: ImportAssetRequest) -> Result<ImportAssetResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let registry = AssetRegistry::read(&project_root)?;
+    let accepted_licenses = registry.accepted_licenses();
+    if !accepted_licenses.contains(&request.license.as_str()) {
+        return Err(format!(
+            "License '{}' is not accepted by the registry policy; allowed: {}",
+            request.license,
+            accepted_licenses.join(", ")
+        ));
+    }
+
+    let source_dir = PathBuf::from(&request.source_dir);
+    if !source_dir.is_dir() {
+        return Err(format!(
+            "Asset source folder does not exist: {}",
+            request.source_dir
+        ));
+    }
+    let asset_name = match &request.asset_name {
+        Some(asset_name) => asset_name.clone(),
+        None => source_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(str::to_string)
+            .ok_or_else(|| "Asset source folder has no usable name".to_string())?,
+    };
+    validate_single_path_segment(&asset_name, "asset_name")?;
+    let provider_slug: String = request
+        .provider
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|character| character.to_ascii_lowercase())
+        .collect();
+    if provider_slug.is_empty() {
+        return Err("Asset provider must contain letters or digits".to_string());
+    }
+
+    let (type_name, root_name) = match request.kind {
+        ImportAssetKind::Model => ("model", "importedModels"),
+        ImportAssetKind::Material => ("material", "importedMaterials"),
+    };
+    let asset_id = format!("{}:{}:{}", provider_slug, type_name, asset_name);
+    if registry.assets().contains_key(&asset_id) {
+        return Err(format!("Asset '{}' is already in the registry", asset_id));
+    }
+    let imported_root = format!(
+        "{}/{}",
+        registry
+            .root(root_name)
+            .ok_or_else(|| format!("Asset registry roots.{} is missing", root_name))?,
+        asset_name
+    );
+    let source_metadata_directory = format!(
+        "{}/{}/{}s/{}",
+        registry
+            .root("sourceMetadata")
+            .ok_or_else(|| "Asset registry roots.sourceMetadata is missing".to_string())?,
+        provider_slug,
+        type_name,
+        asset_name
+    );
+    let source_metadata_path = format!("{}/source.json", source_metadata_directory);
+    for existing in [&imported_root, &source_metadata_directory] {
+        if project_root.join(existing).exists() {
+            return Err(format!("'{}' already exists in the project", existing));
+        }
+    }
+
+    let files = collect_import_files(&source_dir, &imported_root)?;
+    let role_files = match request.kind {
+        ImportAssetKind::Model => model_file_roles(&files)?,
+        ImportAssetKind::Material => material_file_roles(&files),
+    };
+    if role_files.is_empty() {
+        return Err("Asset source folder contains no importable files".to_string());
+    }
+
+    copy_import_files(&project_root, &imported_root, &files)?;
+
+    let license_url = request
+        .license_url
+        .clone()
+        .unwrap_or_else(|| format!("https://spdx.org/licenses/{}.html", request.license));
+    // EN: Only the source folder name is recorded; the absolute local path stays out of the project.
+    // 中文: 只记录源文件夹名称，本地绝对路径不会写入项目。
+    let source_metadata = json!({
+        "id": asset_id,
+        "provider": request.provider,
+        "sourceUrl": request.source_url,
+        "license": request.license,
+        "licenseUrl": license_url,
+        "importedRoot": imported_root,
+        "importedFrom": source_dir.file_name().and_then(|name| name.to_str()),
+        "files": files.iter().map(|file| json!({
+            "path": file.path,
+            "byteLength": file.byte_length,
+            "sha256": file.sha256,
+        })).collect::<Vec<_>>(),
+    });
+    let entry = json!({
+        "id": asset_id,
+        "type": type_name,
+        "name": request.display_name.clone().unwrap_or_else(|| display_name_from_slug(&asset_name)),
+        "provider": request.provider,
+        "sourceUrl": request.source_url,
+        "license": request.license,
+        "licenseUrl": license_url,
+        "sourceMetadataPath": source_metadata_path,
+        "imported": {
+            "root": imported_root,
+            "files": role_files,
+        },
+    });
+
+    // EN: Any failure after the copy removes the imported root and provenance so the project is left unchanged.
+    // 中文: 复制完成后的任何失败都会删除导入根目录与来源信息，使项目保持不变。
+    let registered = serde_json::to_string_pretty(&source_metadata)
+        .map_err(|e| format!("Failed to serialize source metadata: {}", e))
+        .and_then(|text| {
+            safe_write(
+                &project_root.join(&source_metadata_path),
+                format!("{}\n", text).as_bytes(),
+            )
+            .map_err(|e| format!("Failed to write source metadata: {}", e))
+        })
+        .and_then(|_| store_asset_registry_entry(&project_root, entry, false));
+    let entry = match registered {
+        Ok(entry) => entry,
+        Err(error) => {
+            let _ = fs::remove_dir_all(project_root.join(&imported_root));
+            let _ = fs::remove_dir_all(project_root.join(&source_metadata_directory));
+            return Err(error);
+        }
+    };
+
+    Ok(ImportAssetResult {
+        asset_id,
+        entry,
+        source_metadata_path,
+        file_count: files.len(),
+        total_bytes: files.iter().map(|file| file.byte_length).sum(),
+    })
+}
+
+/// List every non-hidden file under the source folder, sorted, with hashes.
+/// 列出源文件夹下所有非隐藏文件（已排序）及其哈希
+fn collect_import_files(
+    source_dir: &Path,
+    imported_root: &str,
+) -> Result<Vec<ImportedFile>, String> {
+    let mut pending = vec![source_dir.to_path_buf()];
+    let mut sources = Vec::new();
+    while let Some(directory) = pending.pop() {
+        let entries = fs::read_dir(&directory)
+            .map_err(|e| format!("Failed to read asset source folder: {}", e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read asset source entry: {}", e))?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.is_file() {
+                sources.push(path);
+            }
+        }
+    }
+    sources.sort();
+
+    sources
+        .into_iter()
+        .map(|source| {
+            let relative = source
+                .strip_prefix(source_dir)
+                .map_err(|e| format!("Failed to resolve asset source file: {}", e))?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            let bytes = fs::read(&source)
+                .map_err(|e| format!("Failed to read asset source file: {}", e))?;
+            Ok(ImportedFile {
+                source,
+                path: format!("{}/{}", imported_root, relative),
+                byte_length: bytes.len() as u64,
+                sha256: sha256_hex(&bytes),
+            })
+        })
+        .collect()
+}
+
+/// Copy into a hidden staging folder, then rename it to the imported root in one step.
+/// 先复制到隐藏的暂存文件夹，再一次性重命名为导入根目录
+fn copy_import_files(
+    project_root: &Path,
+    imported_root: &str,
+    files: &[ImportedFile],
+) -> Result<(), String> {
+    let target = project_root.join(imported_root);
+    let staging = target.with_file_name(format!(
+        ".{}.importing",
+        target
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    ));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to clear asset staging folder: {}", e))?;
+    }
+
+    let copied = files.iter().try_for_each(|file| {
+        let relative = file
+            .path
+            .strip_prefix(imported_root)
+            .unwrap_or(&file.path)
+            .trim_start_matches('/');
+        let destination = staging.join(relative);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create asset folder: {}", e))?;
+        }
+        fs::copy(&file.source, &destination)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy asset file: {}", e))
+    });
+    let renamed = copied.and_then(|_| {
+        fs::rename(&staging, &target).map_err(|e| format!("Failed to move imported asset: {}", e))
+    });
+    if renamed.is_err() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    renamed
+}
+
+/// Model roles: top-level glTF/GLB is `primary`, glTF files under `lod1/` and `lod2/` become LOD roles.
+/// 模型角色：顶层 glTF/GLB 为 `primary`，`lod1/` 与 `lod2/` 下的 glTF 文件为 LOD 角色
+fn model_file_roles(files: &[ImportedFile]) -> Result<Map<String, Value>, String> {
+    let mut roles = Map::new();
+    for file in files {
+        let path = Path::new(&file.path);
+        let is_model = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                extension.eq_ignore_ascii_case("gltf") || extension.eq_ignore_ascii_case("glb")
+            });
+        if !is_model {
+            continue;
+        }
+
+        let parent = path
+            .parent()
+            .and_then(|parent| parent.file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let role = match parent {
+            "lod1" | "lod2" => parent.to_string(),
+            _ if !roles.contains_key("primary") => "primary".to_string(),
+            _ => file_stem(&file.path),
+        };
+        if !roles.contains_key(&role) {
+            roles.insert(role, Value::String(file.path.clone()));
+        }
+    }
+
+    if !roles.contains_key("primary") {
+        return Err("Model source folder must contain a top-level .gltf or .glb file".to_string());
+    }
+    Ok(roles)
+}
+
+/// Material roles from file name suffixes such as `_diff`, `_nor_gl` and `_arm`; other files use their stem.
+/// 根据 `_diff`、`_nor_gl`、`_arm` 等文件名后缀确定材质角色；其他文件使用文件名主干
+fn material_file_roles(files: &[ImportedFile]) -> Map<String, Value> {
+    let mut roles = Map::new();
+    for file in files {
+        let stem = file_stem(&file.path);
+        let lowercase = stem.to_ascii_lowercase();
+        let role = MATERIAL_ROLE_SUFFIXES
+            .iter()
+            .find(|(role, suffixes)| {
+                !roles.contains_key(*role)
+                    && suffixes.iter().any(|suffix| {
+                        let marker = format!("_{}", suffix);
+                        lowercase.match_indices(&marker).any(|(index, _)| {
+                            let rest = &lowercase[index + marker.len()..];
+                            rest.is_empty() || rest.starts_with('_')
+                        })
+                    })
+            })
+            .map(|(role, _)| role.to_string())
+            .unwrap_or(stem);
+        if !roles.contains_key(&role) {
+            roles.insert(role, Value::String(file.path.clone()));
+        }
+    }
+    roles
+}
+
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// "snow_03" -> "Snow 03", matching the registry's display names.
+/// "snow_03" -> "Snow 03"，与注册表中的显示名称一致
+fn display_name_from_slug(slug: &str) -> String {
+    slug.split(['_', '-'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut characters = word.chars();
+            characters
+                .next()
+                .map(|first| first.to_uppercase().chain(characters).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
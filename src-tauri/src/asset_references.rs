@@ -0,0 +1,333 @@
+// Asset reference tracking across map manifests and generation graphs.
+// 跨地图清单与生成图的资源引用追踪
+//
+// EN: Map files mix project-relative ("assets/...") and map-relative ("../../assets/...") paths; both are normalized to project-relative before matching.
+// 中文: 地图文件混用项目相对路径（"assets/..."）与地图相对路径（"../../assets/..."），匹配前统一规范化为项目相对路径。
+
+use crate::asset_registry::AssetRegistry;
+use crate::commands::{MAP_FILE, MAPS_DIR, validate_cook_project_path};
+use crate::map_layout::{
+    GENERATION_GRAPH_PATH, PAINT_MANIFEST_PATH, VEGETATION_MODELS_PATH, WORLD_OBJECTS_PATH,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Component, Path};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindAssetReferencesRequest {
+    project_path: String,
+    asset_id_or_path: String,
+}
+
+/// One JSON string in a map file that points at a project asset path.
+/// 地图文件中指向项目资源路径的一个 JSON 字符串
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetReference {
+    map_id: String,
+    file: String,
+    pointer: String,
+    value: String,
+    path: String,
+    asset_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetReferenceResult {
+    asset_id: Option<String>,
+    matched_paths: Vec<String>,
+    references: Vec<AssetReference>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetReferenceCheck {
+    scanned_files: Vec<String>,
+    reference_count: usize,
+    unregistered: Vec<AssetReference>,
+    missing_files: Vec<AssetReference>,
+}
+
+/// Registry ownership of imported paths: (asset id, imported root, imported files).
+/// 导入路径的注册表归属：（资源 id、导入根目录、导入文件）
+struct RegisteredAssetPaths {
+    asset_id: String,
+    root: String,
+    files: Vec<String>,
+}
+
+// --- Asset reference commands / 资源引用命令 ---
+
+/// Find every map file reference to a registry asset (by id) or to a project-relative path or directory.
+/// 查找地图文件中对某个注册表资源（按 id）或项目相对路径/目录的所有引用
+#[tauri::command]
+pub async fn find_asset_references(
+    request: FindAssetReferencesRequest,
+) -> Result<AssetReferenceResult, String> {
+    tauri::async_runtime::spawn_blocking(move || find_asset_references_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join asset reference task: {}", e))?
+}
+
+/// Flag references to imported assets that no registry entry covers, and references to missing files.
+/// 标记未被任何注册表条目覆盖的导入资源引用，以及指向缺失文件的引用
+#[tauri::command]
+pub async fn check_asset_references(project_path: String) -> Result<AssetReferenceCheck, String> {
+    tauri::async_runtime::spawn_blocking(move || check_asset_references_blocking(&project_path))
+        .await
+        .map_err(|e| format!("Failed to join asset reference check task: {}", e))?
+}
+
+fn find_asset_references_blocking(
+    request: FindAssetReferencesRequest,
+) -> Result<AssetReferenceResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    find_references_to(&project_root, &request.asset_id_or_path)
+}
+
+/// References to a registry asset id, or to a project-relative path or directory.
+/// 对注册表资源 id 或项目相对路径/目录的引用
+pub(crate) fn find_references_to(
+    project_root: &Path,
+    asset_id_or_path: &str,
+) -> Result<AssetReferenceResult, String> {
+    let registered = read_registered_asset_paths(project_root)?;
+    let target = asset_id_or_path.trim();
+    if target.is_empty() {
+        return Err("Asset id or path cannot be empty".to_string());
+    }
+
+    let (asset_id, matched_paths) = match registered.iter().find(|asset| asset.asset_id == target) {
+        Some(asset) => {
+            let mut paths = vec![asset.root.clone()];
+            paths.extend(asset.files.iter().cloned());
+            (Some(asset.asset_id.clone()), paths)
+        }
+        None => {
+            let path = normalize_project_path(Path::new(""), target).ok_or_else(|| {
+                format!(
+                    "'{}' is neither a registry asset id nor a project-relative path",
+                    target
+                )
+            })?;
+            (None, vec![path])
+        }
+    };
+
+    let (references, _) = scan_asset_references(project_root, &registered)?;
+    let references = references
+        .into_iter()
+        .filter(|reference| {
+            matched_paths
+                .iter()
+                .any(|path| is_same_or_inside(&reference.path, path))
+        })
+        .collect();
+
+    Ok(AssetReferenceResult {
+        asset_id,
+        matched_paths,
+        references,
+    })
+}
+
+impl AssetReferenceResult {
+    /// Distinct map files holding at least one reference.
+    /// 至少包含一个引用的不同地图文件
+    pub(crate) fn referencing_files(&self) -> Vec<&str> {
+        let mut files: Vec<&str> = self
+            .references
+            .iter()
+            .map(|reference| reference.file.as_str())
+            .collect();
+        files.dedup();
+        files
+    }
+}
+
+fn check_asset_references_blocking(project_path: &str) -> Result<AssetReferenceCheck, String> {
+    let project_root = validate_cook_project_path(project_path)?;
+    let registry = AssetRegistry::read(&project_root)?;
+    let imported_roots: Vec<String> = ["importedModels", "importedMaterials"]
+        .into_iter()
+        .filter_map(|name| registry.root(name).map(str::to_string))
+        .collect();
+    let registered = read_registered_asset_paths(&project_root)?;
+    let (references, scanned_files) = scan_asset_references(&project_root, &registered)?;
+
+    let unregistered = references
+        .iter()
+        .filter(|reference| {
+            reference.asset_id.is_none()
+                && imported_roots
+                    .iter()
+                    .any(|root| is_same_or_inside(&reference.path, root))
+        })
+        .cloned()
+        .collect();
+    let missing_files = references
+        .iter()
+        .filter(|reference| !project_root.join(&reference.path).exists())
+        .cloned()
+        .collect();
+
+    Ok(AssetReferenceCheck {
+        scanned_files,
+        reference_count: references.len(),
+        unregistered,
+        missing_files,
+    })
+}
+
+fn read_registered_asset_paths(project_root: &Path) -> Result<Vec<RegisteredAssetPaths>, String> {
+    let registry = AssetRegistry::read(project_root)?;
+    Ok(registry
+        .assets()
+        .into_iter()
+        .filter_map(|(asset_id, entry)| {
+            let imported = entry.get("imported")?;
+            let root = imported.get("root")?.as_str()?.to_string();
+            let files = imported
+                .get("files")
+                .and_then(Value::as_object)
+                .map(|files| {
+                    files
+                        .values()
+                        .filter_map(Value::as_str)
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            Some(RegisteredAssetPaths {
+                asset_id,
+                root,
+                files,
+            })
+        })
+        .collect())
+}
+
+/// Collect asset path references from every map's manifests and generation graph.
+/// 从每张地图的清单与生成图中收集资源路径引用
+fn scan_asset_references(
+    project_root: &Path,
+    registered: &[RegisteredAssetPaths],
+) -> Result<(Vec<AssetReference>, Vec<String>), String> {
+    let maps_root = project_root.join(MAPS_DIR);
+    let mut map_ids: Vec<String> = match fs::read_dir(&maps_root) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().join(MAP_FILE).is_file())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    map_ids.sort();
+
+    let mut references = Vec::new();
+    let mut scanned_files = Vec::new();
+    for map_id in &map_ids {
+        let map_directory = format!("{}/{}", MAPS_DIR, map_id);
+        for relative in [
+            MAP_FILE,
+            GENERATION_GRAPH_PATH,
+            PAINT_MANIFEST_PATH,
+            VEGETATION_MODELS_PATH,
+            WORLD_OBJECTS_PATH,
+        ] {
+            let file = format!("{}/{}", map_directory, relative);
+            let Ok(text) = fs::read_to_string(project_root.join(&file)) else {
+                continue;
+            };
+            let document: Value = serde_json::from_str(&text)
+                .map_err(|e| format!("Failed to parse {}: {}", file, e))?;
+            scanned_files.push(file.clone());
+
+            let mut strings = Vec::new();
+            collect_json_strings(&document, String::new(), &mut strings);
+            for (pointer, value) in strings {
+                let Some(path) = normalize_project_path(Path::new(&map_directory), &value) else {
+                    continue;
+                };
+                if !path.starts_with("assets/") {
+                    continue;
+                }
+
+                let asset_id = registered
+                    .iter()
+                    .find(|asset| is_same_or_inside(&path, &asset.root))
+                    .map(|asset| asset.asset_id.clone());
+                references.push(AssetReference {
+                    map_id: map_id.clone(),
+                    file: file.clone(),
+                    pointer,
+                    value,
+                    path,
+                    asset_id,
+                });
+            }
+        }
+    }
+
+    Ok((references, scanned_files))
+}
+
+/// Walk a JSON document and collect (JSON pointer, string) pairs.
+/// 遍历 JSON 文档并收集（JSON 指针，字符串）对
+fn collect_json_strings(value: &Value, pointer: String, out: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(text) => out.push((pointer, text.clone())),
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect_json_strings(item, format!("{}/{}", pointer, index), out);
+            }
+        }
+        Value::Object(fields) => {
+            for (key, item) in fields {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                collect_json_strings(item, format!("{}/{}", pointer, escaped), out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Resolve a map-relative ("../..") or project-relative path lexically; `None` when it leaves the project.
+/// 按词法解析地图相对（"../.."）或项目相对路径；越出项目时返回 `None`
+pub(crate) fn normalize_project_path(map_directory: &Path, value: &str) -> Option<String> {
+    if value.is_empty() || value.contains("://") || value.contains('\\') {
+        return None;
+    }
+
+    let base = if value.starts_with("./") || value.starts_with("../") {
+        map_directory.to_path_buf()
+    } else {
+        Path::new("").to_path_buf()
+    };
+    let mut parts: Vec<String> = base
+        .components()
+        .filter_map(|component| component.as_os_str().to_str().map(str::to_string))
+        .collect();
+    for component in Path::new(value).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?.to_string()),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                parts.pop()?;
+            }
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+pub(crate) fn is_same_or_inside(path: &str, root: &str) -> bool {
+    path == root
+        || path
+            .strip_prefix(root)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
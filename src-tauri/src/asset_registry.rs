@@ -0,0 +1,498 @@
+// Asset registry management for `assets/registry.json`.
+// `assets/registry.json` 资源注册表管理
+//
+// EN: Every write is checked against the registry's own policy and roots, mirroring scripts/validate-map-assets.mjs.
+// 中文: 每次写入都按注册表自身的策略与根目录校验，与 scripts/validate-map-assets.mjs 保持一致。
+
+use crate::asset_references::find_references_to;
+use crate::commands::{recover_safe_write, safe_write, validate_cook_project_path};
+use crate::map_layout::ASSET_REGISTRY_PATH;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+const ASSET_REGISTRY_FORMAT: &str = "open-fps-asset-registry-v1";
+const ASSET_REGISTRY_VERSION: u64 = 1;
+
+/// Serializes read-modify-write updates of the registry file.
+/// 串行化注册表文件的读-改-写更新
+static ASSET_REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetRegistryEntryRequest {
+    project_path: String,
+    entry: Value,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveAssetRegistryEntryRequest {
+    project_path: String,
+    asset_id: String,
+}
+
+/// One registry entry with the policy issues found when listing.
+/// 单个注册表条目，以及列出时发现的策略问题
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetRegistryListEntry {
+    entry: Value,
+    issues: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetRegistryListing {
+    project_id: Value,
+    policy: Value,
+    roots: Value,
+    assets: Vec<AssetRegistryListEntry>,
+}
+
+/// Parsed registry document; unknown fields are preserved on write.
+/// 已解析的注册表文档；写入时保留未知字段
+pub(crate) struct AssetRegistry {
+    document: Map<String, Value>,
+}
+
+// --- Asset registry commands / 资源注册表命令 ---
+
+/// List registry entries sorted by id, each with its current policy issues.
+/// 按 id 排序列出注册表条目，并附带各自当前的策略问题
+#[tauri::command]
+pub async fn list_asset_registry(project_path: String) -> Result<AssetRegistryListing, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let project_root = validate_cook_project_path(&project_path)?;
+        let registry = AssetRegistry::read(&project_root)?;
+        let assets = registry
+            .assets()
+            .iter()
+            .map(|(asset_id, entry)| AssetRegistryListEntry {
+                entry: entry.clone(),
+                issues: registry.entry_issues(&project_root, asset_id, entry),
+            })
+            .collect();
+
+        Ok(AssetRegistryListing {
+            project_id: registry
+                .document
+                .get("projectId")
+                .cloned()
+                .unwrap_or_default(),
+            policy: registry.document.get("policy").cloned().unwrap_or_default(),
+            roots: registry.document.get("roots").cloned().unwrap_or_default(),
+            assets,
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to join asset registry list task: {}", e))?
+}
+
+/// Add a new registry entry after checking it against the registry policy.
+/// 按注册表策略校验后添加新的注册表条目
+#[tauri::command]
+pub async fn add_asset_registry_entry(request: AssetRegistryEntryRequest) -> Result<Value, String> {
+    tauri::async_runtime::spawn_blocking(move || write_asset_registry_entry(request, false))
+        .await
+        .map_err(|e| format!("Failed to join asset registry add task: {}", e))?
+}
+
+/// Replace an existing registry entry after checking it against the registry policy.
+/// 按注册表策略校验后替换已有的注册表条目
+#[tauri::command]
+pub async fn update_asset_registry_entry(
+    request: AssetRegistryEntryRequest,
+) -> Result<Value, String> {
+    tauri::async_runtime::spawn_blocking(move || write_asset_registry_entry(request, true))
+        .await
+        .map_err(|e| format!("Failed to join asset registry update task: {}", e))?
+}
+
+/// Remove a registry entry together with its imported files and source metadata.
+/// Refuses while any map still references the asset.
+/// 移除注册表条目及其导入文件与源元数据；仍有地图引用该资源时拒绝移除
+#[tauri::command]
+pub async fn remove_asset_registry_entry(
+    request: RemoveAssetRegistryEntryRequest,
+) -> Result<Value, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let project_root = validate_cook_project_path(&request.project_path)?;
+        let _guard = ASSET_REGISTRY_LOCK
+            .lock()
+            .map_err(|_| "Asset registry lock is poisoned".to_string())?;
+        let mut registry = AssetRegistry::read(&project_root)?;
+        let entry = registry
+            .assets()
+            .get(&request.asset_id)
+            .cloned()
+            .ok_or_else(|| format!("Asset '{}' is not in the registry", request.asset_id))?;
+
+        let references = find_references_to(&project_root, &request.asset_id)?;
+        let referencing_files = references.referencing_files();
+        if !referencing_files.is_empty() {
+            return Err(format!(
+                "Asset '{}' is still referenced by {}; remove those references first",
+                request.asset_id,
+                referencing_files.join(", ")
+            ));
+        }
+
+        // EN: Files are moved aside before the registry write and restored if it fails, so the registry and assets/imported never disagree.
+        // 中文: 写入注册表前先将文件移到一旁，写入失败时再还原，使注册表与 assets/imported 始终一致。
+        let staged = stage_asset_removal(&project_root, &registry, &entry)?;
+        registry.assets_mut()?.shift_remove(&request.asset_id);
+        if let Err(error) = registry.write(&project_root) {
+            restore_staged_removal(&staged);
+            return Err(error);
+        }
+        finish_staged_removal(&staged);
+        Ok(entry)
+    })
+    .await
+    .map_err(|e| format!("Failed to join asset registry remove task: {}", e))?
+}
+
+fn write_asset_registry_entry(
+    request: AssetRegistryEntryRequest,
+    replace: bool,
+) -> Result<Value, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    store_asset_registry_entry(&project_root, request.entry, replace)
+}
+
+/// Add (or with `replace`, update) a registry entry after checking it against the registry policy.
+/// 按注册表策略校验后添加（`replace` 时更新）注册表条目
+pub(crate) fn store_asset_registry_entry(
+    project_root: &Path,
+    entry: Value,
+    replace: bool,
+) -> Result<Value, String> {
+    let asset_id = entry
+        .get("id")
+        .and_then(Value::as_str)
+        .filter(|asset_id| !asset_id.is_empty())
+        .ok_or_else(|| "Asset registry entry must declare an id".to_string())?
+        .to_string();
+
+    let _guard = ASSET_REGISTRY_LOCK
+        .lock()
+        .map_err(|_| "Asset registry lock is poisoned".to_string())?;
+    let mut registry = AssetRegistry::read(project_root)?;
+    let exists = registry.assets().contains_key(&asset_id);
+    if replace && !exists {
+        return Err(format!("Asset '{}' is not in the registry", asset_id));
+    }
+    if !replace && exists {
+        return Err(format!("Asset '{}' is already in the registry", asset_id));
+    }
+
+    let issues = registry.entry_issues(project_root, &asset_id, &entry);
+    if !issues.is_empty() {
+        return Err(format!(
+            "Asset '{}' does not satisfy the registry policy: {}",
+            asset_id,
+            issues.join("; ")
+        ));
+    }
+
+    // EN: Updates keep the entry's position; new entries are appended like hand-edited registries.
+    // 中文: 更新保留条目原有位置；新条目像手工编辑的注册表一样追加到末尾。
+    registry.assets_mut()?.insert(asset_id, entry.clone());
+    registry.write(project_root)?;
+    Ok(entry)
+}
+
+impl AssetRegistry {
+    pub(crate) fn read(project_root: &Path) -> Result<Self, String> {
+        let path = project_root.join(ASSET_REGISTRY_PATH);
+        recover_safe_write(&path)?;
+        let text = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read asset registry: {}", e))?;
+        let document: Map<String, Value> = serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse asset registry: {}", e))?;
+        if document.get("format").and_then(Value::as_str) != Some(ASSET_REGISTRY_FORMAT) {
+            return Err(format!(
+                "Asset registry '{}' must use format {}",
+                ASSET_REGISTRY_PATH, ASSET_REGISTRY_FORMAT
+            ));
+        }
+        if document.get("version").and_then(Value::as_u64) != Some(ASSET_REGISTRY_VERSION) {
+            return Err(format!(
+                "Asset registry must use version {}",
+                ASSET_REGISTRY_VERSION
+            ));
+        }
+
+        Ok(Self { document })
+    }
+
+    fn write(&self, project_root: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(&self.document)
+            .map_err(|e| format!("Failed to serialize asset registry: {}", e))?;
+        safe_write(
+            &project_root.join(ASSET_REGISTRY_PATH),
+            format!("{}\n", text).as_bytes(),
+        )
+        .map_err(|e| format!("Failed to write asset registry: {}", e))
+    }
+
+    /// Registry entries sorted by asset id.
+    /// 按资源 id 排序的注册表条目
+    pub(crate) fn assets(&self) -> Map<String, Value> {
+        let mut assets: Vec<(String, Value)> = self
+            .document
+            .get("assets")
+            .and_then(Value::as_object)
+            .map(|assets| {
+                assets
+                    .iter()
+                    .map(|(asset_id, entry)| (asset_id.clone(), entry.clone()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        assets.sort_by(|left, right| left.0.cmp(&right.0));
+        assets.into_iter().collect()
+    }
+
+    fn assets_mut(&mut self) -> Result<&mut Map<String, Value>, String> {
+        self.document
+            .entry("assets")
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .ok_or_else(|| "Asset registry assets must be an object".to_string())
+    }
+
+    /// Licenses listed in `policy.acceptedLicenses`.
+    /// `policy.acceptedLicenses` 中列出的许可证
+    pub(crate) fn accepted_licenses(&self) -> Vec<&str> {
+        self.document
+            .get("policy")
+            .and_then(|policy| policy.get("acceptedLicenses"))
+            .and_then(Value::as_array)
+            .map(|licenses| licenses.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn root(&self, name: &str) -> Option<&str> {
+        self.document
+            .get("roots")
+            .and_then(|roots| roots.get(name))
+            .and_then(Value::as_str)
+    }
+
+    /// Policy problems with an entry: required fields, accepted license, source metadata and imported files.
+    /// 条目的策略问题：必填字段、许可证、源元数据与导入文件
+    pub(crate) fn entry_issues(
+        &self,
+        project_root: &Path,
+        asset_id: &str,
+        entry: &Value,
+    ) -> Vec<String> {
+        let mut issues = Vec::new();
+        let text = |field: &str| entry.get(field).and_then(Value::as_str).unwrap_or_default();
+
+        if text("id") != asset_id {
+            issues.push(format!("id must be '{}'", asset_id));
+        }
+        let asset_type = text("type");
+        if asset_type != "model" && asset_type != "material" {
+            issues.push("type must be model or material".to_string());
+        }
+        for field in ["name", "provider"] {
+            if text(field).is_empty() {
+                issues.push(format!("{} must not be empty", field));
+            }
+        }
+        for field in ["sourceUrl", "licenseUrl"] {
+            let url = text(field);
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                issues.push(format!("{} must be an http(s) URL", field));
+            }
+        }
+
+        let accepted_licenses = self.accepted_licenses();
+        if !accepted_licenses.contains(&text("license")) {
+            issues.push(format!(
+                "license '{}' is not accepted; allowed: {}",
+                text("license"),
+                accepted_licenses.join(", ")
+            ));
+        }
+
+        let imported_root_name = if asset_type == "model" {
+            "importedModels"
+        } else {
+            "importedMaterials"
+        };
+        let imported = entry.get("imported");
+        let imported_root = imported
+            .and_then(|imported| imported.get("root"))
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        match self.root(imported_root_name) {
+            Some(root) if is_inside_root(imported_root, root) => {}
+            Some(root) => issues.push(format!("imported.root must be inside {}", root)),
+            None => issues.push(format!("registry roots.{} is missing", imported_root_name)),
+        }
+
+        match imported
+            .and_then(|imported| imported.get("files"))
+            .and_then(Value::as_object)
+        {
+            Some(files) if !files.is_empty() => {
+                for (role, file) in files {
+                    let file = file.as_str().unwrap_or_default();
+                    if !is_inside_root(file, imported_root) {
+                        issues.push(format!(
+                            "imported file '{}' must be inside its imported root",
+                            role
+                        ));
+                    } else if !project_root.join(file).is_file() {
+                        issues.push(format!("imported file '{}' is missing: {}", role, file));
+                    }
+                }
+            }
+            _ => issues.push("imported.files must list at least one file".to_string()),
+        }
+
+        self.source_metadata_issues(project_root, entry, imported_root, &mut issues);
+        issues
+    }
+
+    /// Source metadata must exist under `roots.sourceMetadata` and agree with the entry.
+    /// 源元数据必须位于 `roots.sourceMetadata` 下，并与条目一致
+    fn source_metadata_issues(
+        &self,
+        project_root: &Path,
+        entry: &Value,
+        imported_root: &str,
+        issues: &mut Vec<String>,
+    ) {
+        let source_metadata_path = entry
+            .get("sourceMetadataPath")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let Some(root) = self.root("sourceMetadata") else {
+            issues.push("registry roots.sourceMetadata is missing".to_string());
+            return;
+        };
+        if !is_inside_root(source_metadata_path, root) {
+            issues.push(format!("sourceMetadataPath must be inside {}", root));
+            return;
+        }
+
+        let source: Value = match fs::read_to_string(project_root.join(source_metadata_path)) {
+            Ok(text) => match serde_json::from_str(&text) {
+                Ok(source) => source,
+                Err(e) => {
+                    issues.push(format!("source metadata is not valid JSON: {}", e));
+                    return;
+                }
+            },
+            Err(_) => {
+                issues.push(format!(
+                    "source metadata is missing: {}",
+                    source_metadata_path
+                ));
+                return;
+            }
+        };
+        for field in ["id", "provider", "sourceUrl", "license", "licenseUrl"] {
+            if source.get(field) != entry.get(field) {
+                issues.push(format!(
+                    "source metadata {} does not match the entry",
+                    field
+                ));
+            }
+        }
+        if source.get("importedRoot").and_then(Value::as_str) != Some(imported_root) {
+            issues.push("source metadata importedRoot does not match imported.root".to_string());
+        }
+    }
+}
+
+/// Paths an entry owns on disk, renamed to `<path>.removing` until the registry write succeeds.
+/// Only paths inside the registry's own roots are touched.
+/// 条目在磁盘上拥有的路径，在注册表写入成功前重命名为 `<path>.removing`；只处理注册表自身根目录内的路径
+fn stage_asset_removal(
+    project_root: &Path,
+    registry: &AssetRegistry,
+    entry: &Value,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let imported_root_name = if entry.get("type").and_then(Value::as_str) == Some("model") {
+        "importedModels"
+    } else {
+        "importedMaterials"
+    };
+    let owned = [
+        (
+            entry
+                .get("imported")
+                .and_then(|imported| imported.get("root"))
+                .and_then(Value::as_str),
+            registry.root(imported_root_name),
+        ),
+        (
+            entry.get("sourceMetadataPath").and_then(Value::as_str),
+            registry.root("sourceMetadata"),
+        ),
+    ];
+
+    let mut staged = Vec::new();
+    for (path, root) in owned {
+        let (Some(path), Some(root)) = (path, root) else {
+            continue;
+        };
+        let original = project_root.join(path);
+        if !is_inside_root(path, root) || !original.exists() {
+            continue;
+        }
+        let mut aside = original.clone().into_os_string();
+        aside.push(".removing");
+        let aside = PathBuf::from(aside);
+        if let Err(e) = fs::rename(&original, &aside) {
+            restore_staged_removal(&staged);
+            return Err(format!("Failed to remove imported asset '{}': {}", path, e));
+        }
+        staged.push((original, aside));
+    }
+    Ok(staged)
+}
+
+fn restore_staged_removal(staged: &[(PathBuf, PathBuf)]) {
+    for (original, aside) in staged.iter().rev() {
+        let _ = fs::rename(aside, original);
+    }
+}
+
+/// Delete staged paths, then the source metadata directory if it is left empty.
+/// 删除已暂存的路径；源元数据目录因此变空时一并删除
+fn finish_staged_removal(staged: &[(PathBuf, PathBuf)]) {
+    for (original, aside) in staged {
+        if aside.is_dir() {
+            let _ = fs::remove_dir_all(aside);
+        } else {
+            let _ = fs::remove_file(aside);
+            if let Some(parent) = original.parent() {
+                // EN: remove_dir only succeeds on an empty directory.
+                // 中文: remove_dir 只会删除空目录。
+                let _ = fs::remove_dir(parent);
+            }
+        }
+    }
+}
+
+/// Whether a project-relative path is a safe path strictly inside `root`.
+/// 项目相对路径是否为严格位于 `root` 内的安全路径
+fn is_inside_root(path: &str, root: &str) -> bool {
+    let path = Path::new(path);
+    !root.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        && path.starts_with(root)
+        && path != Path::new(root)
+}
@@ -1,6 +1,14 @@
 // Tauri commands for project management.
 // Tauri 项目管理命令
 
+use crate::cook_cache::read_cooked_input_signature;
+use crate::cook_history::{CookHistoryEntry, record_cook_history_entry};
+use crate::cook_report::{
+    CookReport, create_cook_run_files, project_relative_path, read_cook_report, write_cook_log,
+};
+use crate::tool_runner::{
+    COOK_MAP_SCRIPT, ToolRunner, WORLD_GENERATION_GRAPH_SCRIPT, resolve_tool_runner,
+};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
 use std::io::Write;
@@ -11,9 +19,9 @@ use tauri::Manager;
 
 /// Project file names.
 /// 项目文件名
-const PROJECT_FILE: &str = "project.json";
-const MAP_FILE: &str = "map.json";
-const MAPS_DIR: &str = "maps";
+pub(crate) const PROJECT_FILE: &str = "project.json";
+pub(crate) const MAP_FILE: &str = "map.json";
+pub(crate) const MAPS_DIR: &str = "maps";
 const SETTINGS_FILE: &str = "settings.json";
 const RECENT_PROJECTS_FILE: &str = "recent_projects.json";
 const COOK_MAP_MAX_STAGE_COUNT: usize = 16;
@@ -30,34 +38,42 @@ const COOK_MAP_ALLOWED_STAGES: &[&str] = &[
     "nav",
 ];
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CookMapScopes {
-    terrain_regions: Vec<String>,
-    paint_regions: Vec<String>,
-    vegetation_regions: Vec<String>,
-    partition_cells: Vec<String>,
+    pub(crate) terrain_regions: Vec<String>,
+    pub(crate) paint_regions: Vec<String>,
+    pub(crate) vegetation_regions: Vec<String>,
+    pub(crate) partition_cells: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CookMapRequest {
-    project_path: String,
-    map_id: String,
-    dry_run: bool,
-    full: bool,
-    changed_stages: Vec<String>,
-    scopes: CookMapScopes,
+    pub(crate) project_path: String,
+    pub(crate) map_id: String,
+    pub(crate) dry_run: bool,
+    pub(crate) full: bool,
+    pub(crate) changed_stages: Vec<String>,
+    pub(crate) scopes: CookMapScopes,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CookMapResult {
+    history_id: String,
     command: Vec<String>,
     exit_code: i32,
     stdout: String,
     stderr: String,
     duration_ms: u64,
+    report: Option<CookReport>,
+    report_path: String,
+    log_path: String,
+    /// Problems recording a cook that already ran; the cook result itself stands.
+    /// 记录已完成 cook 时遇到的问题；cook 结果本身仍然有效
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
 }
 
 /// Ensure project folder exists, create if not.
@@ -69,17 +85,17 @@ fn ensure_project_folder(path: &PathBuf) -> Result<(), String> {
     Ok(())
 }
 
-fn ensure_parent_directory(path: &PathBuf) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        if !parent.exists() {
-            fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-        }
+pub(crate) fn ensure_parent_directory(path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent()
+        && !parent.exists()
+    {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
     }
 
     Ok(())
 }
 
-fn safe_write_temp_path(path: &PathBuf) -> Result<PathBuf, String> {
+fn safe_write_temp_path(path: &Path) -> Result<PathBuf, String> {
     let file_name = path
         .file_name()
         .ok_or_else(|| "Path must include a file name".to_string())?
@@ -97,7 +113,7 @@ fn safe_write_temp_path(path: &PathBuf) -> Result<PathBuf, String> {
     )))
 }
 
-fn safe_write_backup_path(path: &PathBuf) -> Result<PathBuf, String> {
+fn safe_write_backup_path(path: &Path) -> Result<PathBuf, String> {
     let file_name = path
         .file_name()
         .ok_or_else(|| "Path must include a file name".to_string())?
@@ -106,7 +122,7 @@ fn safe_write_backup_path(path: &PathBuf) -> Result<PathBuf, String> {
     Ok(path.with_file_name(format!(".{}.bak", file_name)))
 }
 
-fn recover_safe_write(path: &PathBuf) -> Result<(), String> {
+pub(crate) fn recover_safe_write(path: &Path) -> Result<(), String> {
     let backup_path = safe_write_backup_path(path)?;
     if path.exists() {
         if backup_path.exists() {
@@ -126,7 +142,7 @@ fn recover_safe_write(path: &PathBuf) -> Result<(), String> {
     Ok(())
 }
 
-fn write_temp_file(path: &PathBuf, bytes: &[u8]) -> Result<(), String> {
+fn write_temp_file(path: &Path, bytes: &[u8]) -> Result<(), String> {
     let mut file = File::create(path).map_err(|e| format!("Failed to create temp file: {}", e))?;
     file.write_all(bytes)
         .map_err(|e| format!("Failed to write temp file: {}", e))?;
@@ -134,7 +150,7 @@ fn write_temp_file(path: &PathBuf, bytes: &[u8]) -> Result<(), String> {
         .map_err(|e| format!("Failed to sync temp file: {}", e))
 }
 
-fn safe_write(path: &PathBuf, bytes: &[u8]) -> Result<(), String> {
+pub(crate) fn safe_write(path: &Path, bytes: &[u8]) -> Result<(), String> {
     // EN: Stage through temp + backup so interrupted saves recover the previous complete file on next access.
     // 中文: 通过临时文件与备份文件分阶段写入，使中断保存能在下次访问时恢复旧完整文件。
     ensure_parent_directory(path)?;
@@ -172,7 +188,7 @@ fn safe_write(path: &PathBuf, bytes: &[u8]) -> Result<(), String> {
     Ok(())
 }
 
-fn validate_single_path_segment(value: &str, field_name: &str) -> Result<(), String> {
+pub(crate) fn validate_single_path_segment(value: &str, field_name: &str) -> Result<(), String> {
     if value.is_empty() {
         return Err(format!("{} cannot be empty", field_name));
     }
@@ -193,7 +209,22 @@ fn project_map_manifest_path(project_path: &str, map_id: &str) -> Result<PathBuf
         .join(MAP_FILE))
 }
 
-fn validate_relative_file_path(value: &str, field_name: &str) -> Result<(), String> {
+/// Read the world size in meters from a map's map.json.
+/// 从地图的 map.json 读取以米为单位的世界尺寸
+pub(crate) fn read_map_world_size(map_root: &Path) -> Result<f64, String> {
+    let path = map_root.join(MAP_FILE);
+    recover_safe_write(&path)?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read map manifest: {}", e))?;
+    let map: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse map manifest: {}", e))?;
+    map["world"]["sizeMeters"]
+        .as_f64()
+        .filter(|size| size.is_finite() && *size > 0.0)
+        .ok_or_else(|| "Map manifest world size must be a positive number".to_string())
+}
+
+pub(crate) fn validate_relative_file_path(value: &str, field_name: &str) -> Result<(), String> {
     if value.is_empty() {
         return Err(format!("{} cannot be empty", field_name));
     }
@@ -236,7 +267,7 @@ fn recent_projects_file(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     Ok(app_data_dir.join(RECENT_PROJECTS_FILE))
 }
 
-fn load_recent_project_paths(path: &PathBuf) -> Result<Vec<String>, String> {
+fn load_recent_project_paths(path: &Path) -> Result<Vec<String>, String> {
     recover_safe_write(path)?;
 
     if !path.exists() {
@@ -248,7 +279,7 @@ fn load_recent_project_paths(path: &PathBuf) -> Result<Vec<String>, String> {
     serde_json::from_str(&content).map_err(|e| format!("Failed to parse recent projects: {}", e))
 }
 
-fn save_recent_project_paths(path: &PathBuf, paths: &[String]) -> Result<(), String> {
+fn save_recent_project_paths(path: &Path, paths: &[String]) -> Result<(), String> {
     let content = serde_json::to_string_pretty(paths)
         .map_err(|e| format!("Failed to serialize recent projects: {}", e))?;
     safe_write(path, content.as_bytes())
@@ -493,8 +524,12 @@ pub async fn remove_recent_project(
 /// Run the whitelisted map cook workflow for the editor.
 /// 为编辑器运行白名单地图 cook 工作流。
 #[tauri::command]
-pub async fn run_cook_map(request: CookMapRequest) -> Result<CookMapResult, String> {
-    tauri::async_runtime::spawn_blocking(move || run_cook_map_blocking(request))
+pub async fn run_cook_map(
+    app: tauri::AppHandle,
+    request: CookMapRequest,
+) -> Result<CookMapResult, String> {
+    let tool_runner = resolve_tool_runner(&app)?;
+    tauri::async_runtime::spawn_blocking(move || run_cook_map_blocking(&tool_runner, request))
         .await
         .map_err(|e| format!("Failed to join cook command task: {}", e))?
 }
@@ -502,105 +537,147 @@ pub async fn run_cook_map(request: CookMapRequest) -> Result<CookMapResult, Stri
 /// Run the whitelisted world generation graph workflow for the editor.
 /// 为编辑器运行白名单世界生成图工作流。
 #[tauri::command]
-pub async fn run_world_generation_graph(request: CookMapRequest) -> Result<CookMapResult, String> {
-    tauri::async_runtime::spawn_blocking(move || run_world_generation_graph_blocking(request))
-        .await
-        .map_err(|e| format!("Failed to join graph command task: {}", e))?
+pub async fn run_world_generation_graph(
+    app: tauri::AppHandle,
+    request: CookMapRequest,
+) -> Result<CookMapResult, String> {
+    let tool_runner = resolve_tool_runner(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        run_world_generation_graph_blocking(&tool_runner, request)
+    })
+    .await
+    .map_err(|e| format!("Failed to join graph command task: {}", e))?
 }
 
-fn run_cook_map_blocking(request: CookMapRequest) -> Result<CookMapResult, String> {
+fn run_cook_map_blocking(
+    tool_runner: &ToolRunner,
+    request: CookMapRequest,
+) -> Result<CookMapResult, String> {
     // EN: Build argv from structured fields only; never pass user text through a shell.
     // 中文: 只从结构化字段构造 argv；绝不把用户文本交给 shell 解释。
     let args = create_cook_map_args(&request)?;
-    let repository_root = repository_root()?;
-    let script_path = repository_root.join("scripts").join("cook-map-assets.mjs");
-    if !script_path.exists() {
-        return Err("Cook map script is not available in this build".to_string());
-    }
+    let script_path = tool_runner.script_path(COOK_MAP_SCRIPT)?;
 
-    let executable = pnpm_executable();
-    let mut command_display = Vec::with_capacity(args.len() + 1);
-    command_display.push(executable.to_string());
-    command_display.extend(args.iter().cloned());
-
-    let started_at = Instant::now();
-    let output = Command::new(executable)
-        .current_dir(&repository_root)
-        .args(&args)
-        .stdin(Stdio::null())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .output()
-        .map_err(|e| format!("Failed to run cook map command: {}", e))?;
-
-    Ok(CookMapResult {
-        command: command_display,
-        exit_code: output
-            .status
-            .code()
-            .unwrap_or_else(|| if output.status.success() { 0 } else { -1 }),
-        stdout: truncate_command_output(&output.stdout),
-        stderr: truncate_command_output(&output.stderr),
-        duration_ms: started_at.elapsed().as_millis().min(u128::from(u64::MAX)) as u64,
-    })
+    run_cook_workflow_command(tool_runner, &script_path, &request, "cook", args)
+        .map_err(|e| format!("Failed to run cook map command: {}", e))
 }
 
-fn run_world_generation_graph_blocking(request: CookMapRequest) -> Result<CookMapResult, String> {
+fn run_world_generation_graph_blocking(
+    tool_runner: &ToolRunner,
+    request: CookMapRequest,
+) -> Result<CookMapResult, String> {
     let args = create_world_generation_graph_args(&request)?;
-    let repository_root = repository_root()?;
-    let script_path = repository_root
-        .join("scripts")
-        .join("execute-world-generation-graph.mjs");
-    if !script_path.exists() {
-        return Err("World generation graph script is not available in this build".to_string());
-    }
+    let script_path = tool_runner.script_path(WORLD_GENERATION_GRAPH_SCRIPT)?;
+
+    run_cook_workflow_command(tool_runner, &script_path, &request, "graph", args)
+        .map_err(|e| format!("Failed to run world generation graph command: {}", e))
+}
 
-    let executable = pnpm_executable();
-    let mut command_display = Vec::with_capacity(args.len() + 1);
-    command_display.push(executable.to_string());
+/// Run a cook workflow script, saving its full log and reading back its structured report.
+/// 运行 cook 工作流脚本，保存完整日志并读回结构化报告
+fn run_cook_workflow_command(
+    tool_runner: &ToolRunner,
+    script_path: &Path,
+    request: &CookMapRequest,
+    workflow: &str,
+    mut args: Vec<String>,
+) -> Result<CookMapResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let run_files = create_cook_run_files(&project_root, workflow, &request.map_id)?;
+    args.push("--report".to_string());
+    args.push(run_files.report_path.to_string_lossy().to_string());
+
+    let input_signature_before = read_cooked_input_signature(&project_root, &request.map_id)?;
+
+    let mut command_display = Vec::with_capacity(args.len() + 2);
+    command_display.push(tool_runner.node_executable.clone());
+    command_display.push(script_path.to_string_lossy().to_string());
     command_display.extend(args.iter().cloned());
 
     let started_at = Instant::now();
-    let output = Command::new(executable)
-        .current_dir(&repository_root)
+    let output = Command::new(&tool_runner.node_executable)
+        .current_dir(&project_root)
+        .arg(script_path)
         .args(&args)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .map_err(|e| format!("Failed to run world generation graph command: {}", e))?;
+        .map_err(|e| e.to_string())?;
+    let exit_code = output
+        .status
+        .code()
+        .unwrap_or_else(|| if output.status.success() { 0 } else { -1 });
+    let duration_ms = started_at.elapsed().as_millis().min(u128::from(u64::MAX)) as u64;
+
+    // EN: The cook has already run at this point, so bookkeeping failures become warnings instead of turning a finished cook into an error.
+    // 中文: 此时 cook 已经执行完毕，记录失败只作为警告返回，而不会把已完成的 cook 变成错误。
+    let mut warnings = Vec::new();
+    if let Err(error) = write_cook_log(
+        &run_files.log_path,
+        &command_display,
+        exit_code,
+        duration_ms,
+        &output.stdout,
+        &output.stderr,
+    ) {
+        warnings.push(error);
+    }
+
+    let history = read_cooked_input_signature(&project_root, &request.map_id).and_then(
+        |input_signature_after| {
+            record_cook_history_entry(
+                &project_root,
+                CookHistoryEntry {
+                    id: run_files.run_id.clone(),
+                    workflow: workflow.to_string(),
+                    map_id: request.map_id.clone(),
+                    status: if exit_code == 0 {
+                        "succeeded"
+                    } else {
+                        "failed"
+                    }
+                    .to_string(),
+                    request: request.clone(),
+                    command: command_display.clone(),
+                    exit_code,
+                    started_at_ms: run_files.started_at_ms,
+                    duration_ms,
+                    input_signature_before,
+                    input_signature_after,
+                    report_path: project_relative_path(&project_root, &run_files.report_path),
+                    log_path: project_relative_path(&project_root, &run_files.log_path),
+                },
+            )
+        },
+    );
+    if let Err(error) = history {
+        warnings.push(error);
+    }
+
+    let report = read_cook_report(&run_files.report_path).unwrap_or_else(|error| {
+        warnings.push(error);
+        None
+    });
 
     Ok(CookMapResult {
+        history_id: run_files.run_id,
         command: command_display,
-        exit_code: output
-            .status
-            .code()
-            .unwrap_or_else(|| if output.status.success() { 0 } else { -1 }),
+        exit_code,
         stdout: truncate_command_output(&output.stdout),
         stderr: truncate_command_output(&output.stderr),
-        duration_ms: started_at.elapsed().as_millis().min(u128::from(u64::MAX)) as u64,
+        duration_ms,
+        report,
+        report_path: run_files.report_path.to_string_lossy().to_string(),
+        log_path: run_files.log_path.to_string_lossy().to_string(),
+        warnings,
     })
 }
 
 fn create_cook_map_args(request: &CookMapRequest) -> Result<Vec<String>, String> {
-    let project_path = validate_cook_project_path(&request.project_path)?;
-    validate_single_path_segment(&request.map_id, "map_id")?;
-    validate_cook_stages(&request.changed_stages)?;
-    validate_cook_scopes(&request.scopes)?;
-
-    if request.full && (has_cook_stage_input(request) || has_cook_scope_input(&request.scopes)) {
-        return Err("Full cook request cannot include changed stages or local scopes".to_string());
-    }
-
-    if !request.full && !has_cook_stage_input(request) && !has_cook_scope_input(&request.scopes) {
-        return Err(
-            "Cook request must include a full rebuild or at least one local change".to_string(),
-        );
-    }
+    let project_path = validate_cook_map_request(request, "Cook")?;
 
     let mut args = vec![
-        "cook:map".to_string(),
-        "--".to_string(),
         project_path.to_string_lossy().to_string(),
         "--map".to_string(),
         request.map_id.clone(),
@@ -636,24 +713,9 @@ fn create_cook_map_args(request: &CookMapRequest) -> Result<Vec<String>, String>
 }
 
 fn create_world_generation_graph_args(request: &CookMapRequest) -> Result<Vec<String>, String> {
-    let project_path = validate_cook_project_path(&request.project_path)?;
-    validate_single_path_segment(&request.map_id, "map_id")?;
-    validate_cook_stages(&request.changed_stages)?;
-    validate_cook_scopes(&request.scopes)?;
-
-    if request.full && (has_cook_stage_input(request) || has_cook_scope_input(&request.scopes)) {
-        return Err("Full graph request cannot include changed stages or local scopes".to_string());
-    }
-
-    if !request.full && !has_cook_stage_input(request) && !has_cook_scope_input(&request.scopes) {
-        return Err(
-            "Graph request must include a full rebuild or at least one local change".to_string(),
-        );
-    }
+    let project_path = validate_cook_map_request(request, "Graph")?;
 
     let mut args = vec![
-        "gen:graph".to_string(),
-        "--".to_string(),
         project_path.to_string_lossy().to_string(),
         "--map".to_string(),
         request.map_id.clone(),
@@ -688,7 +750,35 @@ fn create_world_generation_graph_args(request: &CookMapRequest) -> Result<Vec<St
     Ok(args)
 }
 
-fn validate_cook_project_path(value: &str) -> Result<PathBuf, String> {
+/// Validate a structured cook/graph/plan request and resolve its project folder.
+/// 校验结构化的 cook/图/计划请求并解析其项目文件夹
+pub(crate) fn validate_cook_map_request(
+    request: &CookMapRequest,
+    label: &str,
+) -> Result<PathBuf, String> {
+    let project_path = validate_cook_project_path(&request.project_path)?;
+    validate_single_path_segment(&request.map_id, "map_id")?;
+    validate_cook_stages(&request.changed_stages)?;
+    validate_cook_scopes(&request.scopes)?;
+
+    if request.full && (has_cook_stage_input(request) || has_cook_scope_input(&request.scopes)) {
+        return Err(format!(
+            "Full {} request cannot include changed stages or local scopes",
+            label.to_lowercase()
+        ));
+    }
+
+    if !request.full && !has_cook_stage_input(request) && !has_cook_scope_input(&request.scopes) {
+        return Err(format!(
+            "{} request must include a full rebuild or at least one local change",
+            label
+        ));
+    }
+
+    Ok(project_path)
+}
+
+pub(crate) fn validate_cook_project_path(value: &str) -> Result<PathBuf, String> {
     if value.trim().is_empty() {
         return Err("project_path cannot be empty".to_string());
     }
@@ -705,7 +795,7 @@ fn validate_cook_project_path(value: &str) -> Result<PathBuf, String> {
     Ok(path)
 }
 
-fn validate_cook_stages(stages: &[String]) -> Result<(), String> {
+pub(crate) fn validate_cook_stages(stages: &[String]) -> Result<(), String> {
     if stages.len() > COOK_MAP_MAX_STAGE_COUNT {
         return Err(format!(
             "Cook request has too many changed stages: {}",
@@ -722,7 +812,7 @@ fn validate_cook_stages(stages: &[String]) -> Result<(), String> {
     Ok(())
 }
 
-fn validate_cook_scopes(scopes: &CookMapScopes) -> Result<(), String> {
+pub(crate) fn validate_cook_scopes(scopes: &CookMapScopes) -> Result<(), String> {
     validate_grid_key_list("terrain region", &scopes.terrain_regions)?;
     validate_grid_key_list("paint region", &scopes.paint_regions)?;
     validate_grid_key_list("vegetation region", &scopes.vegetation_regions)?;
@@ -776,7 +866,7 @@ fn has_cook_stage_input(request: &CookMapRequest) -> bool {
     !request.changed_stages.is_empty()
 }
 
-fn has_cook_scope_input(scopes: &CookMapScopes) -> bool {
+pub(crate) fn has_cook_scope_input(scopes: &CookMapScopes) -> bool {
     !scopes.terrain_regions.is_empty()
         || !scopes.paint_regions.is_empty()
         || !scopes.vegetation_regions.is_empty()
@@ -790,17 +880,6 @@ fn append_cook_scope_args(args: &mut Vec<String>, flag: &str, values: &[String])
     }
 }
 
-fn repository_root() -> Result<PathBuf, String> {
-    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .parent()
-        .map(Path::to_path_buf)
-        .ok_or_else(|| "Failed to resolve repository root".to_string())
-}
-
-fn pnpm_executable() -> &'static str {
-    if cfg!(windows) { "pnpm.cmd" } else { "pnpm" }
-}
-
 fn truncate_command_output(bytes: &[u8]) -> String {
     let text = String::from_utf8_lossy(bytes);
     if text.chars().count() <= COOK_MAP_MAX_OUTPUT_CHARS {
@@ -883,20 +962,90 @@ pub async fn write_binary_file_base64(path: String, base64: String) -> Result<()
 /// 读取 PNG 文件并返回原始 RGBA 像素（base64）+ 尺寸
 /// This bypasses browser's premultiplied alpha issue.
 /// 这绕过了浏览器的预乘 alpha 问题
+/// With `bitDepth: 16` the pixels are RGBA16 little-endian at full precision.
+/// 传入 `bitDepth: 16` 时，像素为全精度的小端 RGBA16
 #[tauri::command]
-pub async fn read_png_rgba(path: String) -> Result<(String, u32, u32), String> {
+pub async fn read_png_rgba(
+    path: String,
+    bit_depth: Option<u8>,
+) -> Result<(String, u32, u32), String> {
     use base64::{Engine as _, engine::general_purpose::STANDARD};
-    use png::Decoder;
-    use std::io::BufReader;
 
-    let path = PathBuf::from(&path);
-    recover_safe_write(&path)?;
-    let file = std::fs::File::open(&path).map_err(|e| format!("Failed to open PNG: {}", e))?;
-    let decoder = Decoder::new(BufReader::new(file));
-    let mut reader = decoder
-        .read_info()
-        .map_err(|e| format!("Failed to read PNG info: {}", e))?;
+    let sixteen_bit = match bit_depth.unwrap_or(8) {
+        8 => false,
+        16 => true,
+        other => return Err(format!("Unsupported PNG output bit depth: {}", other)),
+    };
+    let (rgba_pixels, width, height) = decode_png_rgba_samples(Path::new(&path), sixteen_bit)?;
+    Ok((STANDARD.encode(&rgba_pixels), width, height))
+}
 
+/// Decode a PNG file into RGBA8 pixels.
+/// 将 PNG 文件解码为 RGBA8 像素
+pub(crate) fn decode_png_rgba(path: &Path) -> Result<(Vec<u8>, u32, u32), String> {
+    decode_png_rgba_samples(path, false)
+}
+
+/// Decode a PNG or JPEG file into RGBA8, choosing the decoder by extension.
+/// 按扩展名选择解码器，将 PNG 或 JPEG 文件解码为 RGBA8
+pub(crate) fn decode_image_rgba(path: &Path) -> Result<(Vec<u8>, u32, u32), String> {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => decode_png_rgba(path),
+        "jpg" | "jpeg" => decode_jpeg_rgba(path),
+        _ => Err(format!(
+            "Unsupported image type '{}'; expected PNG or JPEG",
+            extension
+        )),
+    }
+}
+
+fn decode_jpeg_rgba(path: &Path) -> Result<(Vec<u8>, u32, u32), String> {
+    let reader = File::open(path).map_err(|e| format!("Failed to open JPEG: {}", e))?;
+    let mut decoder = jpeg_decoder::Decoder::new(std::io::BufReader::new(reader));
+    let decoded = decoder
+        .decode()
+        .map_err(|e| format!("Failed to decode JPEG: {}", e))?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| "JPEG has no image info".to_string())?;
+    let pixels: Vec<u8> = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => decoded
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::L8 => decoded
+            .iter()
+            .flat_map(|luma| [*luma, *luma, *luma, 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::L16 => decoded
+            .chunks_exact(2)
+            .flat_map(|luma| [luma[0], luma[0], luma[0], 255])
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => decoded
+            .chunks_exact(4)
+            .flat_map(|cmyk| {
+                let k = 255 - cmyk[3] as u32;
+                let channel = |index: usize| ((255 - cmyk[index] as u32) * k / 255) as u8;
+                [channel(0), channel(1), channel(2), 255]
+            })
+            .collect(),
+    };
+
+    Ok((pixels, u32::from(info.width), u32::from(info.height)))
+}
+
+/// Decode any PNG color type and bit depth into RGBA8, or RGBA16 little-endian when `sixteen_bit` is set.
+/// 将任意颜色类型与位深的 PNG 解码为 RGBA8；设置 `sixteen_bit` 时解码为小端 RGBA16
+pub(crate) fn decode_png_rgba_samples(
+    path: &Path,
+    sixteen_bit: bool,
+) -> Result<(Vec<u8>, u32, u32), String> {
+    let mut reader = open_png_reader(path)?;
     let output_size = reader
         .output_buffer_size()
         .ok_or_else(|| "Failed to determine PNG output buffer size".to_string())?;
@@ -907,72 +1056,147 @@ pub async fn read_png_rgba(path: String) -> Result<(String, u32, u32), String> {
 
     let width = info.width;
     let height = info.height;
+    let format = PngSampleFormat::new(info.color_type, info.bit_depth)?;
+    let samples = &buf[..info.buffer_size()];
+
+    let pixel_count = (width * height) as usize;
+    let bytes_per_sample = if sixteen_bit { 2 } else { 1 };
+    let mut rgba_pixels = Vec::with_capacity(pixel_count * 4 * bytes_per_sample);
+    for pixel in 0..pixel_count {
+        push_rgba_samples(&mut rgba_pixels, format.rgba16(samples, pixel), sixteen_bit);
+    }
 
-    // Convert to RGBA if needed.
-    // 如有需要，转换为 RGBA
-    let rgba_pixels = match info.color_type {
-        png::ColorType::Rgba => buf[..info.buffer_size()].to_vec(),
-        png::ColorType::Rgb => {
-            // Convert RGB to RGBA (add A=255).
-            // 将 RGB 转换为 RGBA（添加 A=255）
-            let rgb = &buf[..info.buffer_size()];
-            let mut rgba = Vec::with_capacity((width * height * 4) as usize);
-            for chunk in rgb.chunks(3) {
-                rgba.push(chunk[0]);
-                rgba.push(chunk[1]);
-                rgba.push(chunk[2]);
-                rgba.push(255);
-            }
-            rgba
+    Ok((rgba_pixels, width, height))
+}
+
+/// Open a PNG with palette, tRNS and sub-byte expansion enabled.
+/// 打开 PNG，并启用调色板、tRNS 与低位深展开
+pub(crate) fn open_png_reader(
+    path: &Path,
+) -> Result<png::Reader<std::io::BufReader<File>>, String> {
+    use png::{Decoder, Transformations};
+    use std::io::BufReader;
+
+    recover_safe_write(path)?;
+    let file = File::open(path).map_err(|e| format!("Failed to open PNG: {}", e))?;
+    let mut decoder = Decoder::new(BufReader::new(file));
+    // EN: EXPAND resolves palettes, tRNS transparency and sub-byte grayscale into 8/16-bit channels.
+    // 中文: EXPAND 将调色板、tRNS 透明度与低位灰度展开为 8/16 位通道。
+    decoder.set_transformations(Transformations::EXPAND);
+    decoder
+        .read_info()
+        .map_err(|e| format!("Failed to read PNG info: {}", e))
+}
+
+/// Channel count and sample width of expanded PNG output.
+/// 展开后 PNG 输出的通道数与样本宽度
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PngSampleFormat {
+    channels: usize,
+    sixteen_bit: bool,
+}
+
+impl PngSampleFormat {
+    pub(crate) fn new(
+        color_type: png::ColorType,
+        bit_depth: png::BitDepth,
+    ) -> Result<Self, String> {
+        let channels = color_type.samples();
+        if !matches!(channels, 1..=4) {
+            return Err(format!("Unsupported PNG color type: {:?}", color_type));
         }
-        png::ColorType::GrayscaleAlpha => {
-            // Convert GA to RGBA.
-            // 将 GA 转换为 RGBA
-            let ga = &buf[..info.buffer_size()];
-            let mut rgba = Vec::with_capacity((width * height * 4) as usize);
-            for chunk in ga.chunks(2) {
-                let gray = chunk[0];
-                let alpha = chunk[1];
-                rgba.push(gray);
-                rgba.push(gray);
-                rgba.push(gray);
-                rgba.push(alpha);
+
+        Ok(Self {
+            channels,
+            sixteen_bit: bit_depth == png::BitDepth::Sixteen,
+        })
+    }
+
+    /// Read one pixel as RGBA16; 8-bit sources are widened exactly (v * 257).
+    /// 以 RGBA16 读取单个像素；8 位源精确扩展（v * 257）
+    pub(crate) fn rgba16(&self, samples: &[u8], pixel: usize) -> [u16; 4] {
+        let sample = |index: usize| -> u16 {
+            if self.sixteen_bit {
+                u16::from_be_bytes([samples[index * 2], samples[index * 2 + 1]])
+            } else {
+                samples[index] as u16 * 257
             }
-            rgba
+        };
+
+        let base = pixel * self.channels;
+        // Convert G, GA and RGB to RGBA.
+        // 将 G、GA 与 RGB 转换为 RGBA
+        match self.channels {
+            1 => [sample(base), sample(base), sample(base), u16::MAX],
+            2 => [sample(base), sample(base), sample(base), sample(base + 1)],
+            3 => [sample(base), sample(base + 1), sample(base + 2), u16::MAX],
+            _ => [
+                sample(base),
+                sample(base + 1),
+                sample(base + 2),
+                sample(base + 3),
+            ],
         }
-        png::ColorType::Grayscale => {
-            // Convert G to RGBA.
-            // 将 G 转换为 RGBA
-            let g = &buf[..info.buffer_size()];
-            let mut rgba = Vec::with_capacity((width * height * 4) as usize);
-            for &gray in g {
-                rgba.push(gray);
-                rgba.push(gray);
-                rgba.push(gray);
-                rgba.push(255);
-            }
-            rgba
+    }
+}
+
+/// Append RGBA16 values as little-endian RGBA16 or rounded RGBA8.
+/// 将 RGBA16 值追加为小端 RGBA16 或四舍五入后的 RGBA8
+pub(crate) fn push_rgba_samples(out: &mut Vec<u8>, rgba: [u16; 4], sixteen_bit: bool) {
+    for value in rgba {
+        if sixteen_bit {
+            out.extend_from_slice(&value.to_le_bytes());
+        } else {
+            // Round to nearest instead of dropping the low byte.
+            // 四舍五入，而不是直接丢弃低字节
+            out.push(((value as u32 * 255 + 32767) / 65535) as u8);
         }
-        _ => return Err(format!("Unsupported PNG color type: {:?}", info.color_type)),
-    };
+    }
+}
 
-    Ok((STANDARD.encode(&rgba_pixels), width, height))
+/// Channel layout of pixels passed to `write_png_rgba`.
+/// 传给 `write_png_rgba` 的像素通道布局
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PngChannelLayout {
+    #[default]
+    Rgba,
+    Rgb,
+    GrayscaleAlpha,
+    Grayscale,
+}
+
+/// PNG compression level for `write_png_rgba`.
+/// `write_png_rgba` 的 PNG 压缩级别
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PngCompressionLevel {
+    None,
+    Fastest,
+    #[default]
+    Fast,
+    Balanced,
+    High,
 }
 
 /// Write raw RGBA pixels to a PNG file.
 /// 将原始 RGBA 像素写入 PNG 文件
 /// This bypasses browser's premultiplied alpha issue.
 /// 这绕过了浏览器的预乘 alpha 问题
+/// Optional `channels`, `bitDepth` (16-bit samples are little-endian) and `compression` default to RGBA8 with fast compression.
+/// 可选的 `channels`、`bitDepth`（16 位样本为小端）与 `compression` 默认为 RGBA8 快速压缩
 #[tauri::command]
 pub async fn write_png_rgba(
     path: String,
     base64_pixels: String,
     width: u32,
     height: u32,
+    channels: Option<PngChannelLayout>,
+    bit_depth: Option<u8>,
+    compression: Option<PngCompressionLevel>,
 ) -> Result<(), String> {
     use base64::{Engine as _, engine::general_purpose::STANDARD};
-    use png::{BitDepth, ColorType, Encoder};
-    use std::io::BufWriter;
+    use png::{BitDepth, ColorType, Compression};
 
     let path = PathBuf::from(&path);
 
@@ -980,11 +1204,23 @@ pub async fn write_png_rgba(
     // 确保父目录存在
     ensure_parent_directory(&path)?;
 
-    let pixels = STANDARD
+    let (color_type, channel_count) = match channels.unwrap_or_default() {
+        PngChannelLayout::Rgba => (ColorType::Rgba, 4),
+        PngChannelLayout::Rgb => (ColorType::Rgb, 3),
+        PngChannelLayout::GrayscaleAlpha => (ColorType::GrayscaleAlpha, 2),
+        PngChannelLayout::Grayscale => (ColorType::Grayscale, 1),
+    };
+    let (depth, bytes_per_sample) = match bit_depth.unwrap_or(8) {
+        8 => (BitDepth::Eight, 1),
+        16 => (BitDepth::Sixteen, 2),
+        other => return Err(format!("Unsupported PNG bit depth: {}", other)),
+    };
+
+    let mut pixels = STANDARD
         .decode(&base64_pixels)
         .map_err(|e| format!("Failed to decode base64: {}", e))?;
 
-    let expected_len = (width * height * 4) as usize;
+    let expected_len = width as usize * height as usize * channel_count * bytes_per_sample;
     if pixels.len() != expected_len {
         return Err(format!(
             "Pixel data length mismatch: expected {}, got {}",
@@ -993,24 +1229,55 @@ pub async fn write_png_rgba(
         ));
     }
 
+    // PNG stores 16-bit samples big-endian; callers pass little-endian like read_png_rgba returns.
+    // PNG 以大端存储 16 位样本；调用方按 read_png_rgba 的返回格式传入小端数据
+    if depth == BitDepth::Sixteen {
+        for sample in pixels.chunks_exact_mut(2) {
+            sample.swap(0, 1);
+        }
+    }
+
+    // Fast compression stays the default for better save speed.
+    // 默认仍使用快速压缩以提高保存速度
+    let compression = match compression.unwrap_or_default() {
+        PngCompressionLevel::None => Compression::NoCompression,
+        PngCompressionLevel::Fastest => Compression::Fastest,
+        PngCompressionLevel::Fast => Compression::Fast,
+        PngCompressionLevel::Balanced => Compression::Balanced,
+        PngCompressionLevel::High => Compression::High,
+    };
+    let encoded = encode_png(&pixels, width, height, color_type, depth, compression)?;
+
+    safe_write(&path, &encoded).map_err(|e| format!("Failed to write PNG file: {}", e))
+}
+
+/// Encode raw big-endian samples as a PNG image in memory.
+/// 将原始大端样本在内存中编码为 PNG 图像
+pub(crate) fn encode_png(
+    samples: &[u8],
+    width: u32,
+    height: u32,
+    color: png::ColorType,
+    depth: png::BitDepth,
+    compression: png::Compression,
+) -> Result<Vec<u8>, String> {
     let mut encoded = Vec::new();
     {
-        let w = BufWriter::new(&mut encoded);
-        let mut encoder = Encoder::new(w, width, height);
-        encoder.set_color(ColorType::Rgba);
-        encoder.set_depth(BitDepth::Eight);
-        // Use fast compression for better save speed.
-        // 使用快速压缩以提高保存速度
-        encoder.set_compression(png::Compression::Fast);
+        let mut encoder = png::Encoder::new(&mut encoded, width, height);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        encoder.set_compression(compression);
 
         let mut writer = encoder
             .write_header()
             .map_err(|e| format!("Failed to write PNG header: {}", e))?;
 
         writer
-            .write_image_data(&pixels)
+            .write_image_data(samples)
             .map_err(|e| format!("Failed to write PNG data: {}", e))?;
+        writer
+            .finish()
+            .map_err(|e| format!("Failed to finish PNG: {}", e))?;
     }
-
-    safe_write(&path, &encoded).map_err(|e| format!("Failed to write PNG file: {}", e))
+    Ok(encoded)
 }
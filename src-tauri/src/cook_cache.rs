@@ -0,0 +1,822 @@
+// Tauri commands for inspecting and invalidating the incremental cook cache.
+// 增量 cook 缓存检查与失效的 Tauri 命令
+
+use crate::commands::{
+    CookMapRequest, CookMapScopes, MAP_FILE, MAPS_DIR, PROJECT_FILE, has_cook_scope_input,
+    recover_safe_write, safe_write, validate_cook_project_path, validate_cook_scopes,
+    validate_cook_stages, validate_single_path_segment,
+};
+use crate::map_layout::{
+    ASSET_REGISTRY_PATH, COOK_CACHE_FORMAT, COOKED_BUILD_CACHE_DIRECTORY,
+    COOKED_COLLISION_CELL_FORMAT, COOKED_MAP_FORMAT, COOKED_MAP_MANIFEST_FILE, COOKED_MAP_VERSION,
+    COOKED_MAPS_DIRECTORY, COOKED_NAV_CELL_FORMAT, COOKED_WORLD_PARTITION_CELL_SIZE_PAGES,
+    COOKED_WORLD_PARTITION_DEPENDENCY_KINDS, GENERATION_GRAPH_PATH, PAINT_MANIFEST_PATH,
+    TERRAIN_HEIGHT_PATH, VEGETATION_MODELS_PATH, WORLD_OBJECT_CELL_FORMAT, WORLD_OBJECTS_PATH,
+    compare_grid_keys, grid_key_from_file_name, sha256_hex, sort_grid_keys,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Cooked artifact kinds grouped by the cook stage that produces them.
+/// 按生成它们的 cook 阶段分组的 cooked 产物类型
+const COOK_STAGE_ARTIFACT_KINDS: &[(&str, &[&str])] = &[
+    ("terrain", &["terrain-region"]),
+    ("paint", &["paint-region", "terrain-texture"]),
+    ("vegetation", &["vegetation-region", "vegetation-model"]),
+    ("objects", &["world-object-cell", "world-object-model"]),
+    ("collision", &["world-collision-cell"]),
+    ("nav", &["world-nav-cell"]),
+];
+const COOK_CACHE_FILE_EXTENSION: &str = "json";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CookCacheFile {
+    map_id: String,
+    manifest_path: String,
+    input_signature: Option<String>,
+    generated_at: Option<String>,
+    artifact_count: Option<usize>,
+    #[serde(default)]
+    artifacts: Vec<String>,
+    #[serde(default)]
+    invalidations: Vec<CookCacheInvalidation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CookedManifestSummary {
+    build: CookedManifestBuild,
+    #[serde(default)]
+    source: BTreeMap<String, CookSourceRef>,
+    package: CookedManifestPackage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CookedManifestBuild {
+    input_signature: Option<String>,
+    previous_input_signature: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CookedManifestPackage {
+    #[serde(default)]
+    artifacts: BTreeMap<String, CookedArtifact>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CookedArtifact {
+    path: String,
+    blob_path: Option<String>,
+    kind: String,
+    byte_length: u64,
+    compression: Option<CookedArtifactCompression>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CookedArtifactCompression {
+    blob_path: String,
+    byte_length: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+struct CookSourceRef {
+    path: String,
+    sha256: String,
+}
+
+/// Source manifests hashed into the cook input signature, in Node key order.
+/// 参与 cook 输入签名哈希的源清单，字段顺序与 Node 一致
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CookSources {
+    project: CookSourceRef,
+    asset_registry: CookSourceRef,
+    map: CookSourceRef,
+    generation_graph: CookSourceRef,
+    terrain: CookSourceRef,
+    paint: CookSourceRef,
+    vegetation: CookSourceRef,
+    objects: CookSourceRef,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CookInputSignaturePayload<'a> {
+    format: &'a str,
+    version: u32,
+    map_id: &'a str,
+    source: &'a CookSources,
+    partition: CookInputSignaturePartition,
+    generated_assets: CookInputSignatureGeneratedAssets,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CookInputSignaturePartition {
+    cell_size_pages: i32,
+    dependency_kinds: &'static [&'static str],
+}
+
+#[derive(Serialize)]
+struct CookInputSignatureGeneratedAssets {
+    objects: &'static str,
+    collision: &'static str,
+    nav: &'static str,
+}
+
+/// A pending invalidation recorded in the cook cache until a cook covers it.
+/// 记录在 cook 缓存中的待处理失效项，直到某次 cook 覆盖它
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookCacheInvalidation {
+    stages: Vec<String>,
+    scopes: CookMapScopes,
+    invalidated_at_ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookCacheInvalidationRequest {
+    project_path: String,
+    map_id: String,
+    #[serde(default)]
+    stages: Vec<String>,
+    #[serde(default)]
+    scopes: CookMapScopes,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookCacheInvalidationResult {
+    map_id: String,
+    invalidation: CookCacheInvalidation,
+    invalidated_entry_count: usize,
+    pending_invalidation_count: usize,
+    cook_request: CookMapRequest,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookCacheEntry {
+    path: String,
+    kind: String,
+    key: Option<String>,
+    byte_length: u64,
+    compressed_byte_length: Option<u64>,
+    disk_bytes: u64,
+    present: bool,
+    last_used_ms: Option<u64>,
+    invalidated: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookCacheStage {
+    stage: String,
+    entry_count: usize,
+    invalidated_count: usize,
+    missing_count: usize,
+    disk_bytes: u64,
+    last_used_ms: Option<u64>,
+    entries: Vec<CookCacheEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookCacheMap {
+    map_id: String,
+    cache_path: String,
+    manifest_path: String,
+    manifest_present: bool,
+    input_signature: Option<String>,
+    generated_at: Option<String>,
+    artifact_count: usize,
+    invalidations: Vec<CookCacheInvalidation>,
+    stages: Vec<CookCacheStage>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookStalenessReport {
+    map_id: String,
+    stale: bool,
+    reasons: Vec<String>,
+    current_input_signature: String,
+    cached_input_signature: Option<String>,
+    manifest_input_signature: Option<String>,
+    previous_input_signature: Option<String>,
+    changed_sources: Vec<String>,
+    missing_artifact_count: usize,
+    pending_invalidation_count: usize,
+}
+
+// --- Cook cache commands / Cook 缓存命令 ---
+
+/// List cook cache entries per map and stage with size and last-used time.
+/// 按地图和阶段列出 cook 缓存条目及其大小和最近使用时间
+#[tauri::command]
+pub async fn list_cook_cache_entries(
+    project_path: String,
+    map_id: Option<String>,
+) -> Result<Vec<CookCacheMap>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        list_cook_cache_entries_blocking(&project_path, map_id.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Failed to join cook cache task: {}", e))?
+}
+
+/// Recompute the cook input signature and report whether the cooked map is stale.
+/// 重新计算 cook 输入签名并报告 cooked 地图是否过期
+#[tauri::command]
+pub async fn check_cook_staleness(
+    project_path: String,
+    map_id: String,
+) -> Result<CookStalenessReport, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        check_cook_staleness_blocking(&project_path, &map_id)
+    })
+    .await
+    .map_err(|e| format!("Failed to join cook staleness task: {}", e))?
+}
+
+/// Invalidate chosen cook stages or regions and return the scoped cook that rebuilds them.
+/// 使选定的 cook 阶段或区域失效，并返回重建它们的局部 cook 请求
+#[tauri::command]
+pub async fn invalidate_cook_cache(
+    request: CookCacheInvalidationRequest,
+) -> Result<CookCacheInvalidationResult, String> {
+    tauri::async_runtime::spawn_blocking(move || invalidate_cook_cache_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join cook invalidation task: {}", e))?
+}
+
+fn list_cook_cache_entries_blocking(
+    project_path: &str,
+    map_id: Option<&str>,
+) -> Result<Vec<CookCacheMap>, String> {
+    let project_root = validate_cook_project_path(project_path)?;
+    let map_ids = match map_id {
+        Some(map_id) => {
+            validate_single_path_segment(map_id, "map_id")?;
+            vec![map_id.to_string()]
+        }
+        None => list_cached_map_ids(&project_root)?,
+    };
+
+    map_ids
+        .iter()
+        .map(|map_id| read_cook_cache_map(&project_root, map_id))
+        .collect()
+}
+
+fn check_cook_staleness_blocking(
+    project_path: &str,
+    map_id: &str,
+) -> Result<CookStalenessReport, String> {
+    let project_root = validate_cook_project_path(project_path)?;
+    validate_single_path_segment(map_id, "map_id")?;
+
+    let sources = read_cook_sources(&project_root, map_id)?;
+    let current_input_signature = create_cook_input_signature(map_id, &sources)?;
+    let cache = read_cook_cache_file(&project_root, map_id)?;
+    let manifest = read_cooked_manifest_summary(&project_root, map_id)?;
+
+    let mut reasons = Vec::new();
+    let cached_input_signature = cache
+        .as_ref()
+        .and_then(|cache| cache.input_signature.clone());
+    match &cached_input_signature {
+        None => reasons.push("cook cache is missing".to_string()),
+        Some(signature) if signature != &current_input_signature => {
+            reasons.push("cook cache input signature does not match current sources".to_string())
+        }
+        Some(_) => {}
+    }
+
+    let manifest_input_signature = manifest
+        .as_ref()
+        .and_then(|manifest| manifest.build.input_signature.clone());
+    let previous_input_signature = manifest
+        .as_ref()
+        .and_then(|manifest| manifest.build.previous_input_signature.clone());
+    let changed_sources = match &manifest {
+        Some(manifest) => changed_source_names(&sources, &manifest.source),
+        None => {
+            reasons.push("cooked manifest is missing".to_string());
+            Vec::new()
+        }
+    };
+    if !changed_sources.is_empty() {
+        reasons.push(format!("changed sources: {}", changed_sources.join(", ")));
+    }
+
+    let missing_artifact_count = cache
+        .as_ref()
+        .map(|cache| {
+            cache
+                .artifacts
+                .iter()
+                .filter(|artifact| !project_root.join(artifact).exists())
+                .count()
+        })
+        .unwrap_or(0);
+    if missing_artifact_count > 0 {
+        reasons.push(format!(
+            "{} cached artifact file(s) are missing",
+            missing_artifact_count
+        ));
+    }
+
+    let pending_invalidation_count = cache
+        .as_ref()
+        .map(|cache| cache.invalidations.len())
+        .unwrap_or(0);
+    if pending_invalidation_count > 0 {
+        reasons.push(format!(
+            "{} pending cache invalidation(s)",
+            pending_invalidation_count
+        ));
+    }
+
+    Ok(CookStalenessReport {
+        map_id: map_id.to_string(),
+        stale: !reasons.is_empty(),
+        reasons,
+        current_input_signature,
+        cached_input_signature,
+        manifest_input_signature,
+        previous_input_signature,
+        changed_sources,
+        missing_artifact_count,
+        pending_invalidation_count,
+    })
+}
+
+fn invalidate_cook_cache_blocking(
+    request: CookCacheInvalidationRequest,
+) -> Result<CookCacheInvalidationResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    validate_single_path_segment(&request.map_id, "map_id")?;
+    validate_cook_stages(&request.stages)?;
+    validate_cook_scopes(&request.scopes)?;
+    if request.stages.is_empty() && !has_cook_scope_input(&request.scopes) {
+        return Err("Cache invalidation must name at least one stage or scope key".to_string());
+    }
+
+    let cache_path = cook_cache_path(&project_root, &request.map_id);
+    recover_safe_write(&cache_path)?;
+    if !cache_path.exists() {
+        return Err(format!(
+            "Cook cache for map '{}' does not exist",
+            request.map_id
+        ));
+    }
+
+    let content =
+        fs::read_to_string(&cache_path).map_err(|e| format!("Failed to read cook cache: {}", e))?;
+    let mut cache_json: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse cook cache: {}", e))?;
+    let cache_object = cache_json
+        .as_object_mut()
+        .ok_or_else(|| "Cook cache must be a JSON object".to_string())?;
+    if cache_object.get("format").and_then(|value| value.as_str()) != Some(COOK_CACHE_FORMAT) {
+        return Err(format!("Cook cache must use {}", COOK_CACHE_FORMAT));
+    }
+
+    let invalidation = CookCacheInvalidation {
+        stages: sorted_unique_strings(&request.stages),
+        scopes: sorted_scopes(&request.scopes),
+        invalidated_at_ms: unix_time_ms(SystemTime::now()).unwrap_or(0),
+    };
+
+    let mut invalidations: Vec<CookCacheInvalidation> = match cache_object.get("invalidations") {
+        Some(value) => serde_json::from_value(value.clone())
+            .map_err(|e| format!("Failed to parse cook cache invalidations: {}", e))?,
+        None => Vec::new(),
+    };
+    invalidations.push(invalidation.clone());
+    let pending_invalidation_count = invalidations.len();
+    cache_object.insert(
+        "invalidations".to_string(),
+        serde_json::to_value(&invalidations)
+            .map_err(|e| format!("Failed to serialize cook cache invalidations: {}", e))?,
+    );
+
+    // EN: Keep the Node cache format (2-space JSON + newline) so the cook reads its own file back unchanged.
+    // 中文: 保持 Node 缓存格式（2 空格 JSON + 换行），使 cook 读回的文件格式不变。
+    let mut serialized = serde_json::to_string_pretty(&cache_json)
+        .map_err(|e| format!("Failed to serialize cook cache: {}", e))?;
+    serialized.push('\n');
+    safe_write(&cache_path, serialized.as_bytes())
+        .map_err(|e| format!("Failed to save cook cache: {}", e))?;
+
+    let invalidated_entry_count =
+        match read_cooked_manifest_summary(&project_root, &request.map_id)? {
+            Some(manifest) => manifest
+                .package
+                .artifacts
+                .values()
+                .filter(|artifact| {
+                    let stage = stage_for_artifact_kind(&artifact.kind);
+                    let key = artifact_grid_key(&artifact.path);
+                    invalidation_matches(&invalidation, stage, key.as_deref())
+                })
+                .count(),
+            None => 0,
+        };
+
+    Ok(CookCacheInvalidationResult {
+        map_id: request.map_id.clone(),
+        cook_request: CookMapRequest {
+            project_path: request.project_path,
+            map_id: request.map_id,
+            dry_run: false,
+            full: false,
+            changed_stages: invalidation.stages.clone(),
+            scopes: invalidation.scopes.clone(),
+        },
+        invalidation,
+        invalidated_entry_count,
+        pending_invalidation_count,
+    })
+}
+
+// --- Cache file access / 缓存文件访问 ---
+
+fn cook_cache_path(project_root: &Path, map_id: &str) -> PathBuf {
+    project_root
+        .join(COOKED_BUILD_CACHE_DIRECTORY)
+        .join(format!("{}.{}", map_id, COOK_CACHE_FILE_EXTENSION))
+}
+
+fn cooked_manifest_path(project_root: &Path, map_id: &str) -> PathBuf {
+    project_root
+        .join(COOKED_MAPS_DIRECTORY)
+        .join(map_id)
+        .join(COOKED_MAP_MANIFEST_FILE)
+}
+
+fn list_cached_map_ids(project_root: &Path) -> Result<Vec<String>, String> {
+    let cache_dir = project_root.join(COOKED_BUILD_CACHE_DIRECTORY);
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut map_ids = Vec::new();
+    let entries =
+        fs::read_dir(&cache_dir).map_err(|e| format!("Failed to read cook cache folder: {}", e))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| format!("Failed to read cook cache entry: {}", e))?
+            .path();
+        if path.extension().and_then(|value| value.to_str()) != Some(COOK_CACHE_FILE_EXTENSION) {
+            continue;
+        }
+        if let Some(stem) = path.file_stem().and_then(|value| value.to_str())
+            && !stem.starts_with('.')
+        {
+            map_ids.push(stem.to_string());
+        }
+    }
+
+    map_ids.sort();
+    Ok(map_ids)
+}
+
+fn read_cook_cache_file(
+    project_root: &Path,
+    map_id: &str,
+) -> Result<Option<CookCacheFile>, String> {
+    let path = cook_cache_path(project_root, map_id);
+    recover_safe_write(&path)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read cook cache: {}", e))?;
+    let cache: CookCacheFile =
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse cook cache: {}", e))?;
+    if cache.map_id != map_id {
+        return Err(format!(
+            "Cook cache '{}' belongs to map '{}'",
+            map_id, cache.map_id
+        ));
+    }
+
+    Ok(Some(cache))
+}
+
+fn read_cooked_manifest_summary(
+    project_root: &Path,
+    map_id: &str,
+) -> Result<Option<CookedManifestSummary>, String> {
+    let path = cooked_manifest_path(project_root, map_id);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read cooked manifest: {}", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse cooked manifest: {}", e))
+}
+
+/// Read the input signature of the last successful cook, if the map has been cooked.
+/// 读取最近一次成功 cook 的输入签名（如果地图已 cook）
+pub(crate) fn read_cooked_input_signature(
+    project_root: &Path,
+    map_id: &str,
+) -> Result<Option<String>, String> {
+    Ok(read_cooked_manifest_summary(project_root, map_id)?
+        .and_then(|manifest| manifest.build.input_signature))
+}
+
+fn read_cook_cache_map(project_root: &Path, map_id: &str) -> Result<CookCacheMap, String> {
+    let cache = read_cook_cache_file(project_root, map_id)?
+        .ok_or_else(|| format!("Cook cache for map '{}' does not exist", map_id))?;
+    let manifest = read_cooked_manifest_summary(project_root, map_id)?;
+
+    let mut stages: BTreeMap<usize, CookCacheStage> = BTreeMap::new();
+    if let Some(manifest) = &manifest {
+        for artifact in manifest.package.artifacts.values() {
+            let stage = stage_for_artifact_kind(&artifact.kind);
+            let entry =
+                create_cook_cache_entry(project_root, artifact, stage, &cache.invalidations);
+            let stage_summary =
+                stages
+                    .entry(stage_sort_index(stage))
+                    .or_insert_with(|| CookCacheStage {
+                        stage: stage.to_string(),
+                        entry_count: 0,
+                        invalidated_count: 0,
+                        missing_count: 0,
+                        disk_bytes: 0,
+                        last_used_ms: None,
+                        entries: Vec::new(),
+                    });
+            stage_summary.entry_count += 1;
+            stage_summary.invalidated_count += usize::from(entry.invalidated);
+            stage_summary.missing_count += usize::from(!entry.present);
+            stage_summary.disk_bytes += entry.disk_bytes;
+            stage_summary.last_used_ms = stage_summary.last_used_ms.max(entry.last_used_ms);
+            stage_summary.entries.push(entry);
+        }
+    }
+
+    let stages = stages
+        .into_values()
+        .map(|mut stage| {
+            stage.entries.sort_by(|left, right| {
+                match (&left.key, &right.key) {
+                    (Some(left_key), Some(right_key)) => compare_grid_keys(left_key, right_key),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => std::cmp::Ordering::Equal,
+                }
+                .then_with(|| left.path.cmp(&right.path))
+            });
+            stage
+        })
+        .collect();
+
+    Ok(CookCacheMap {
+        map_id: map_id.to_string(),
+        cache_path: format!(
+            "{}/{}.{}",
+            COOKED_BUILD_CACHE_DIRECTORY, map_id, COOK_CACHE_FILE_EXTENSION
+        ),
+        manifest_present: manifest.is_some(),
+        manifest_path: cache.manifest_path,
+        input_signature: cache.input_signature,
+        generated_at: cache.generated_at,
+        artifact_count: cache.artifact_count.unwrap_or(cache.artifacts.len()),
+        invalidations: cache.invalidations,
+        stages,
+    })
+}
+
+fn create_cook_cache_entry(
+    project_root: &Path,
+    artifact: &CookedArtifact,
+    stage: &str,
+    invalidations: &[CookCacheInvalidation],
+) -> CookCacheEntry {
+    let files = [
+        Some(artifact.path.as_str()),
+        artifact.blob_path.as_deref(),
+        artifact
+            .compression
+            .as_ref()
+            .map(|compression| compression.blob_path.as_str()),
+    ];
+
+    let mut present = true;
+    let mut disk_bytes = 0;
+    let mut last_used_ms = None;
+    for relative_path in files.into_iter().flatten() {
+        match fs::metadata(project_root.join(relative_path)) {
+            Ok(metadata) => {
+                disk_bytes += metadata.len();
+                last_used_ms = last_used_ms.max(file_last_used_ms(&metadata));
+            }
+            Err(_) => present = false,
+        }
+    }
+
+    let key = artifact_grid_key(&artifact.path);
+    let invalidated = invalidations
+        .iter()
+        .any(|invalidation| invalidation_matches(invalidation, stage, key.as_deref()));
+
+    CookCacheEntry {
+        path: artifact.path.clone(),
+        kind: artifact.kind.clone(),
+        key,
+        byte_length: artifact.byte_length,
+        compressed_byte_length: artifact
+            .compression
+            .as_ref()
+            .map(|compression| compression.byte_length),
+        disk_bytes,
+        present,
+        last_used_ms,
+        invalidated,
+    }
+}
+
+// --- Input signature / 输入签名 ---
+
+fn read_cook_sources(project_root: &Path, map_id: &str) -> Result<CookSources, String> {
+    let map_dir = format!("{}/{}", MAPS_DIR, map_id);
+    let read = |relative_path: String| read_cook_source_ref(project_root, relative_path);
+
+    Ok(CookSources {
+        project: read(PROJECT_FILE.to_string())?,
+        asset_registry: read(ASSET_REGISTRY_PATH.to_string())?,
+        map: read(format!("{}/{}", map_dir, MAP_FILE))?,
+        generation_graph: read(format!("{}/{}", map_dir, GENERATION_GRAPH_PATH))?,
+        terrain: read(format!("{}/{}", map_dir, TERRAIN_HEIGHT_PATH))?,
+        paint: read(format!("{}/{}", map_dir, PAINT_MANIFEST_PATH))?,
+        vegetation: read(format!("{}/{}", map_dir, VEGETATION_MODELS_PATH))?,
+        objects: read(format!("{}/{}", map_dir, WORLD_OBJECTS_PATH))?,
+    })
+}
+
+fn read_cook_source_ref(
+    project_root: &Path,
+    relative_path: String,
+) -> Result<CookSourceRef, String> {
+    let path = project_root.join(&relative_path);
+    recover_safe_write(&path)?;
+    let bytes = fs::read(&path)
+        .map_err(|e| format!("Failed to read cook source '{}': {}", relative_path, e))?;
+
+    Ok(CookSourceRef {
+        path: relative_path,
+        sha256: sha256_hex(&bytes),
+    })
+}
+
+fn create_cook_input_signature(map_id: &str, sources: &CookSources) -> Result<String, String> {
+    // EN: Serialize compactly in the same key order as JSON.stringify so signatures match the Node cook byte for byte.
+    // 中文: 按与 JSON.stringify 相同的键顺序紧凑序列化，使签名与 Node cook 逐字节一致。
+    let payload = CookInputSignaturePayload {
+        format: COOKED_MAP_FORMAT,
+        version: COOKED_MAP_VERSION,
+        map_id,
+        source: sources,
+        partition: CookInputSignaturePartition {
+            cell_size_pages: COOKED_WORLD_PARTITION_CELL_SIZE_PAGES,
+            dependency_kinds: COOKED_WORLD_PARTITION_DEPENDENCY_KINDS,
+        },
+        generated_assets: CookInputSignatureGeneratedAssets {
+            objects: WORLD_OBJECT_CELL_FORMAT,
+            collision: COOKED_COLLISION_CELL_FORMAT,
+            nav: COOKED_NAV_CELL_FORMAT,
+        },
+    };
+    let json = serde_json::to_string(&payload)
+        .map_err(|e| format!("Failed to serialize cook input signature: {}", e))?;
+    Ok(sha256_hex(json.as_bytes()))
+}
+
+fn changed_source_names(
+    sources: &CookSources,
+    manifest_sources: &BTreeMap<String, CookSourceRef>,
+) -> Vec<String> {
+    let current = [
+        ("project", &sources.project),
+        ("assetRegistry", &sources.asset_registry),
+        ("map", &sources.map),
+        ("generationGraph", &sources.generation_graph),
+        ("terrain", &sources.terrain),
+        ("paint", &sources.paint),
+        ("vegetation", &sources.vegetation),
+        ("objects", &sources.objects),
+    ];
+
+    current
+        .into_iter()
+        .filter(|(name, source)| manifest_sources.get(*name) != Some(*source))
+        .map(|(name, _)| name.to_string())
+        .collect()
+}
+
+// --- Stage and scope matching / 阶段与范围匹配 ---
+
+fn stage_for_artifact_kind(kind: &str) -> &str {
+    COOK_STAGE_ARTIFACT_KINDS
+        .iter()
+        .find(|(_, kinds)| kinds.contains(&kind))
+        .map(|(stage, _)| *stage)
+        .unwrap_or(kind)
+}
+
+fn stage_sort_index(stage: &str) -> usize {
+    COOK_STAGE_ARTIFACT_KINDS
+        .iter()
+        .position(|(name, _)| *name == stage)
+        .unwrap_or(COOK_STAGE_ARTIFACT_KINDS.len())
+}
+
+fn artifact_grid_key(runtime_path: &str) -> Option<String> {
+    let file_name = runtime_path.rsplit('/').next()?;
+    grid_key_from_file_name(file_name)
+}
+
+fn scope_keys_for_stage<'a>(scopes: &'a CookMapScopes, stage: &str) -> &'a [String] {
+    match stage {
+        "terrain" => &scopes.terrain_regions,
+        "paint" => &scopes.paint_regions,
+        "vegetation" => &scopes.vegetation_regions,
+        "objects" | "collision" | "nav" => &scopes.partition_cells,
+        _ => &[],
+    }
+}
+
+fn invalidation_matches(
+    invalidation: &CookCacheInvalidation,
+    stage: &str,
+    key: Option<&str>,
+) -> bool {
+    let stage_selected =
+        invalidation.stages.is_empty() || invalidation.stages.iter().any(|name| name == stage);
+    if !stage_selected {
+        return false;
+    }
+    if !has_cook_scope_input(&invalidation.scopes) {
+        return true;
+    }
+
+    // EN: Scoped invalidation only hits keyed region/cell artifacts; shared models and textures stay valid.
+    // 中文: 局部失效只命中带键的区域/单元产物；共享模型与纹理保持有效。
+    key.is_some_and(|key| {
+        scope_keys_for_stage(&invalidation.scopes, stage)
+            .iter()
+            .any(|scope_key| scope_key == key)
+    })
+}
+
+fn sorted_unique_strings(values: &[String]) -> Vec<String> {
+    let mut values = values.to_vec();
+    values.sort();
+    values.dedup();
+    values
+}
+
+fn sorted_scopes(scopes: &CookMapScopes) -> CookMapScopes {
+    let sort = |keys: &[String]| {
+        let mut keys = keys.to_vec();
+        sort_grid_keys(&mut keys);
+        keys
+    };
+
+    CookMapScopes {
+        terrain_regions: sort(&scopes.terrain_regions),
+        paint_regions: sort(&scopes.paint_regions),
+        vegetation_regions: sort(&scopes.vegetation_regions),
+        partition_cells: sort(&scopes.partition_cells),
+    }
+}
+
+fn file_last_used_ms(metadata: &fs::Metadata) -> Option<u64> {
+    let accessed = metadata.accessed().ok().and_then(unix_time_ms);
+    let modified = metadata.modified().ok().and_then(unix_time_ms);
+    accessed.max(modified)
+}
+
+fn unix_time_ms(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|duration| duration.as_millis().min(u128::from(u64::MAX)) as u64)
+}
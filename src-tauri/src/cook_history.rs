@@ -0,0 +1,228 @@
+// Persistent cook and graph run history per project.
+// 每个项目持久化的 cook 与图运行历史
+//
+// EN: Every editor-triggered run is appended to cooked/reports/history.json so regressions can be traced to a run.
+// 中文: 每次编辑器触发的运行都会追加到 cooked/reports/history.json，便于将回归追溯到具体运行。
+
+use crate::commands::{
+    CookMapRequest, recover_safe_write, safe_write, validate_cook_project_path,
+    validate_single_path_segment,
+};
+use crate::cook_report::remove_cook_run_files;
+use crate::map_layout::{COOK_HISTORY_FORMAT, COOK_REPORTS_DIRECTORY};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const COOK_HISTORY_FILE: &str = "history.json";
+const COOK_HISTORY_VERSION: u32 = 1;
+
+/// Serializes read-modify-write updates of history files across concurrent runs.
+/// 在并发运行之间串行化历史文件的读-改-写更新
+static COOK_HISTORY_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CookHistoryFile {
+    version: u32,
+    format: String,
+    #[serde(default)]
+    entries: Vec<CookHistoryEntry>,
+}
+
+/// One recorded cook or graph run.
+/// 一次已记录的 cook 或图运行
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookHistoryEntry {
+    pub(crate) id: String,
+    pub(crate) workflow: String,
+    pub(crate) map_id: String,
+    pub(crate) status: String,
+    pub(crate) request: CookMapRequest,
+    pub(crate) command: Vec<String>,
+    pub(crate) exit_code: i32,
+    pub(crate) started_at_ms: u64,
+    pub(crate) duration_ms: u64,
+    pub(crate) input_signature_before: Option<String>,
+    pub(crate) input_signature_after: Option<String>,
+    pub(crate) report_path: String,
+    pub(crate) log_path: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CookHistoryFilter {
+    map_id: Option<String>,
+    workflow: Option<String>,
+    status: Option<String>,
+    since_ms: Option<u64>,
+    until_ms: Option<u64>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookHistoryDeleteResult {
+    deleted_count: usize,
+    remaining_count: usize,
+}
+
+// --- Cook history commands / Cook 历史命令 ---
+
+/// List recorded cook runs for a project, newest first.
+/// 列出项目已记录的 cook 运行，最新的在前
+#[tauri::command]
+pub async fn list_cook_history(
+    project_path: String,
+    filter: Option<CookHistoryFilter>,
+) -> Result<Vec<CookHistoryEntry>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        list_cook_history_blocking(&project_path, &filter.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Failed to join cook history task: {}", e))?
+}
+
+/// Delete cook history entries together with their saved reports and logs.
+/// 删除 cook 历史条目及其保存的报告和日志
+#[tauri::command]
+pub async fn delete_cook_history_entries(
+    project_path: String,
+    entry_ids: Vec<String>,
+) -> Result<CookHistoryDeleteResult, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        delete_cook_history_entries_blocking(&project_path, &entry_ids)
+    })
+    .await
+    .map_err(|e| format!("Failed to join cook history task: {}", e))?
+}
+
+fn list_cook_history_blocking(
+    project_path: &str,
+    filter: &CookHistoryFilter,
+) -> Result<Vec<CookHistoryEntry>, String> {
+    let project_root = validate_cook_project_path(project_path)?;
+    let history = read_cook_history_file(&project_root)?;
+
+    let entries = history
+        .entries
+        .into_iter()
+        .rev()
+        .filter(|entry| matches_cook_history_filter(entry, filter));
+    Ok(match filter.limit {
+        Some(limit) => entries.take(limit).collect(),
+        None => entries.collect(),
+    })
+}
+
+fn delete_cook_history_entries_blocking(
+    project_path: &str,
+    entry_ids: &[String],
+) -> Result<CookHistoryDeleteResult, String> {
+    let project_root = validate_cook_project_path(project_path)?;
+    for entry_id in entry_ids {
+        validate_single_path_segment(entry_id, "entry_id")?;
+    }
+
+    let entry_ids: BTreeSet<&str> = entry_ids.iter().map(String::as_str).collect();
+    let _guard = COOK_HISTORY_LOCK
+        .lock()
+        .map_err(|_| "Cook history lock is poisoned".to_string())?;
+    let mut history = read_cook_history_file(&project_root)?;
+    let (deleted, remaining): (Vec<_>, Vec<_>) = history
+        .entries
+        .into_iter()
+        .partition(|entry| entry_ids.contains(entry.id.as_str()));
+    history.entries = remaining;
+
+    if !deleted.is_empty() {
+        write_cook_history_file(&project_root, &history)?;
+        for entry in &deleted {
+            remove_cook_run_files(&project_root, &entry.id)?;
+        }
+    }
+
+    Ok(CookHistoryDeleteResult {
+        deleted_count: deleted.len(),
+        remaining_count: history.entries.len(),
+    })
+}
+
+/// Append a finished run to the project's cook history.
+/// 将已完成的运行追加到项目 cook 历史
+pub(crate) fn record_cook_history_entry(
+    project_root: &Path,
+    entry: CookHistoryEntry,
+) -> Result<(), String> {
+    let _guard = COOK_HISTORY_LOCK
+        .lock()
+        .map_err(|_| "Cook history lock is poisoned".to_string())?;
+    let mut history = read_cook_history_file(project_root)?;
+    history.entries.push(entry);
+    write_cook_history_file(project_root, &history)
+}
+
+// --- History file access / 历史文件访问 ---
+
+fn cook_history_path(project_root: &Path) -> PathBuf {
+    project_root
+        .join(COOK_REPORTS_DIRECTORY)
+        .join(COOK_HISTORY_FILE)
+}
+
+fn read_cook_history_file(project_root: &Path) -> Result<CookHistoryFile, String> {
+    let path = cook_history_path(project_root);
+    recover_safe_write(&path)?;
+    if !path.exists() {
+        return Ok(CookHistoryFile {
+            version: COOK_HISTORY_VERSION,
+            format: COOK_HISTORY_FORMAT.to_string(),
+            entries: Vec::new(),
+        });
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read cook history: {}", e))?;
+    let history: CookHistoryFile = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse cook history: {}", e))?;
+    if history.format != COOK_HISTORY_FORMAT {
+        return Err(format!(
+            "Unsupported cook history format '{}'",
+            history.format
+        ));
+    }
+
+    Ok(history)
+}
+
+fn write_cook_history_file(project_root: &Path, history: &CookHistoryFile) -> Result<(), String> {
+    let mut content = serde_json::to_string_pretty(history)
+        .map_err(|e| format!("Failed to serialize cook history: {}", e))?;
+    content.push('\n');
+    safe_write(&cook_history_path(project_root), content.as_bytes())
+        .map_err(|e| format!("Failed to save cook history: {}", e))
+}
+
+fn matches_cook_history_filter(entry: &CookHistoryEntry, filter: &CookHistoryFilter) -> bool {
+    filter
+        .map_id
+        .as_ref()
+        .is_none_or(|map_id| &entry.map_id == map_id)
+        && filter
+            .workflow
+            .as_ref()
+            .is_none_or(|workflow| &entry.workflow == workflow)
+        && filter
+            .status
+            .as_ref()
+            .is_none_or(|status| &entry.status == status)
+        && filter
+            .since_ms
+            .is_none_or(|since_ms| entry.started_at_ms >= since_ms)
+        && filter
+            .until_ms
+            .is_none_or(|until_ms| entry.started_at_ms <= until_ms)
+}
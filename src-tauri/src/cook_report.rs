@@ -0,0 +1,178 @@
+// Structured cook reports and raw cook logs.
+// 结构化 cook 报告与原始 cook 日志
+//
+// EN: Cook scripts write an open-fps-cook-report-v1 file via --report; the editor reads it instead of scraping stdout.
+// 中文: cook 脚本通过 --report 写出 open-fps-cook-report-v1 文件；编辑器读取该文件而不是解析 stdout。
+
+use crate::map_layout::{COOK_REPORT_FORMAT, COOK_REPORTS_DIRECTORY};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const COOK_REPORT_FILE_SUFFIX: &str = ".report.json";
+const COOK_LOG_FILE_SUFFIX: &str = ".log";
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CookReport {
+    version: u32,
+    format: String,
+    workflow: String,
+    status: String,
+    dry_run: bool,
+    project: String,
+    started_at: Option<String>,
+    finished_at: Option<String>,
+    maps: Vec<CookReportMap>,
+    warnings: Vec<String>,
+    errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CookReportMap {
+    map_id: String,
+    plan_id: Option<String>,
+    mode: String,
+    blocked: bool,
+    stages: Vec<CookReportStage>,
+    cache_hit: Option<bool>,
+    manifest_path: Option<String>,
+    artifacts: CookReportArtifacts,
+    cells: Vec<CookReportCell>,
+    warnings: Vec<String>,
+    errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CookReportStage {
+    stage: String,
+    executor: String,
+    key_count: usize,
+    status: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CookReportArtifacts {
+    written: Vec<String>,
+    reused: Vec<String>,
+}
+
+/// Per-cell performance budget copied from the cooked partition.
+/// 从 cooked 分区复制的单元性能预算
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CookReportCell {
+    key: String,
+    object_count: u64,
+    collision_shape_count: u64,
+    nav_node_count: u64,
+    nav_link_count: u64,
+    raw_bytes: u64,
+    compressed_bytes: u64,
+    estimated_cost: f64,
+    rating: String,
+}
+
+/// Report and log locations reserved for a single cook run.
+/// 为单次 cook 运行预留的报告与日志路径
+pub(crate) struct CookRunFiles {
+    pub(crate) run_id: String,
+    pub(crate) started_at_ms: u64,
+    pub(crate) report_path: PathBuf,
+    pub(crate) log_path: PathBuf,
+}
+
+pub(crate) fn create_cook_run_files(
+    project_root: &Path,
+    workflow: &str,
+    map_id: &str,
+) -> Result<CookRunFiles, String> {
+    let directory = project_root.join(COOK_REPORTS_DIRECTORY);
+    fs::create_dir_all(&directory)
+        .map_err(|e| format!("Failed to create cook report directory: {}", e))?;
+
+    let started_at_ms = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis().min(u128::from(u64::MAX)) as u64)
+        .unwrap_or_default();
+    let run_id = format!("{}-{}-{}", workflow, map_id, started_at_ms);
+    Ok(CookRunFiles {
+        report_path: directory.join(format!("{}{}", run_id, COOK_REPORT_FILE_SUFFIX)),
+        log_path: directory.join(format!("{}{}", run_id, COOK_LOG_FILE_SUFFIX)),
+        run_id,
+        started_at_ms,
+    })
+}
+
+/// Delete the report and log saved for a run; files that are already gone are ignored.
+/// 删除某次运行保存的报告和日志；已不存在的文件会被忽略
+pub(crate) fn remove_cook_run_files(project_root: &Path, run_id: &str) -> Result<(), String> {
+    let directory = project_root.join(COOK_REPORTS_DIRECTORY);
+    for suffix in [COOK_REPORT_FILE_SUFFIX, COOK_LOG_FILE_SUFFIX] {
+        let path = directory.join(format!("{}{}", run_id, suffix));
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to delete cook run file: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Express a file under the project as a `/`-separated project-relative path.
+/// 将项目内文件表示为以 `/` 分隔的项目相对路径
+pub(crate) fn project_relative_path(project_root: &Path, path: &Path) -> String {
+    path.strip_prefix(project_root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Save the untruncated command output next to the report.
+/// 将未截断的命令输出保存在报告旁边
+pub(crate) fn write_cook_log(
+    path: &Path,
+    command: &[String],
+    exit_code: i32,
+    duration_ms: u64,
+    stdout: &[u8],
+    stderr: &[u8],
+) -> Result<(), String> {
+    let mut content = format!(
+        "$ {}\nexit {} in {}ms\n\n--- stdout ---\n",
+        command.join(" "),
+        exit_code,
+        duration_ms
+    )
+    .into_bytes();
+    content.extend_from_slice(stdout);
+    content.extend_from_slice(b"\n--- stderr ---\n");
+    content.extend_from_slice(stderr);
+    fs::write(path, content).map_err(|e| format!("Failed to write cook log: {}", e))
+}
+
+/// Read the report a cook script wrote; `None` when the script exited before writing one.
+/// 读取 cook 脚本写出的报告；脚本在写出前退出时返回 `None`
+pub(crate) fn read_cook_report(path: &Path) -> Result<Option<CookReport>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read cook report: {}", e))?;
+    let report: CookReport = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse cook report: {}", e))?;
+    if report.format != COOK_REPORT_FORMAT {
+        return Err(format!(
+            "Unsupported cook report format '{}'",
+            report.format
+        ));
+    }
+
+    Ok(Some(report))
+}
@@ -0,0 +1,272 @@
+// Read-only access to a cooked map: manifest, partition cell packs and the cooked terrain heightfield.
+// 已 cook 地图的只读访问：清单、分区单元包与 cooked 地形高度场
+//
+// EN: Mirrors how the game runtime streams cooked/maps/<map>: every pack is addressed by a manifest entry with path, byteLength and sha256, and is rejected when the bytes no longer match.
+// 中文: 与游戏运行时读取 cooked/maps/<map> 的方式一致：每个数据包由带有 path、byteLength 与 sha256 的清单条目定位，字节不匹配时拒绝读取。
+
+use crate::commands::{validate_relative_file_path, validate_single_path_segment};
+use crate::height_pack::{HeightPackLayout, decode_region_pages, parse_region_mask};
+use crate::map_layout::{
+    COOKED_MAP_FORMAT, COOKED_MAP_MANIFEST_FILE, COOKED_MAPS_DIRECTORY, HEIGHT_REGION_FORMAT,
+    format_grid_key, parse_grid_key, sha256_hex,
+};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cooked map manifest plus the project root its artifact paths are relative to.
+/// 已 cook 地图清单，以及其产物路径所基于的项目根目录
+pub(crate) struct CookedMap {
+    project_root: PathBuf,
+    manifest: Value,
+}
+
+/// Partition cell grid of one cooked cell asset such as collision or nav.
+/// 单类 cooked 单元资产（如 collision 或 nav）的分区网格
+pub(crate) struct CookedCellGrid {
+    pub(crate) cell_size_meters: f64,
+    cells: BTreeMap<String, Value>,
+}
+
+/// Cooked terrain regions, decoded on first use.
+/// 按需解码的 cooked 地形区域
+pub(crate) struct CookedTerrain {
+    pub(crate) layout: HeightPackLayout,
+    regions: BTreeMap<String, (u64, Value)>,
+    pages: BTreeMap<String, BTreeMap<u32, Vec<f32>>>,
+}
+
+impl CookedMap {
+    pub(crate) fn open(project_root: &Path, map_id: &str) -> Result<Self, String> {
+        validate_single_path_segment(map_id, "map_id")?;
+        let manifest_path = project_root
+            .join(COOKED_MAPS_DIRECTORY)
+            .join(map_id)
+            .join(COOKED_MAP_MANIFEST_FILE);
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read cooked manifest for map '{}': {}", map_id, e))?;
+        let manifest: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse cooked manifest: {}", e))?;
+        if manifest["format"].as_str() != Some(COOKED_MAP_FORMAT) {
+            return Err(format!(
+                "Cooked map '{}' must use the {} format; cook the map again",
+                map_id, COOKED_MAP_FORMAT
+            ));
+        }
+        if manifest["mapId"].as_str() != Some(map_id) {
+            return Err(format!(
+                "Cooked manifest does not belong to map '{}'",
+                map_id
+            ));
+        }
+
+        Ok(Self {
+            project_root: project_root.to_path_buf(),
+            manifest,
+        })
+    }
+
+    /// Raw manifest entry of a cooked asset such as `objects`.
+    /// cooked 资产（如 `objects`）的原始清单条目
+    pub(crate) fn asset(&self, asset: &str) -> &Value {
+        &self.manifest["assets"][asset]
+    }
+
+    /// Cell grid of a cooked cell asset, checked against the expected pack format.
+    /// cooked 单元资产的网格，并校验其包格式
+    pub(crate) fn cell_grid(&self, asset: &str, format: &str) -> Result<CookedCellGrid, String> {
+        let entry = &self.manifest["assets"][asset];
+        if entry["format"].as_str() != Some(format) {
+            return Err(format!(
+                "Cooked {} cells must use the {} format",
+                asset, format
+            ));
+        }
+        let cell_size_meters = entry["cellSizeMeters"]
+            .as_f64()
+            .filter(|size| size.is_finite() && *size > 0.0)
+            .ok_or_else(|| format!("Cooked {} cell size must be a positive number", asset))?;
+        let cells: BTreeMap<String, Value> = entry["cells"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, cell)| (key.clone(), cell.clone()))
+            .collect();
+        if let Some(key) = cells.keys().find(|key| parse_grid_key(key).is_none()) {
+            return Err(format!("Invalid cooked {} cell key '{}'", asset, key));
+        }
+
+        Ok(CookedCellGrid {
+            cell_size_meters,
+            cells,
+        })
+    }
+
+    /// Read a cell pack listed in `grid`; returns `None` when the cell was not cooked.
+    /// 读取 `grid` 中列出的单元包；该单元未 cook 时返回 `None`
+    pub(crate) fn read_cell_pack(
+        &self,
+        grid: &CookedCellGrid,
+        cell_key: &str,
+        format: &str,
+    ) -> Result<Option<Value>, String> {
+        let Some(entry) = grid.cells.get(cell_key) else {
+            return Ok(None);
+        };
+        let bytes = self.read_artifact(entry, &format!("cooked cell '{}'", cell_key))?;
+        let pack: Value = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Failed to parse cooked cell '{}': {}", cell_key, e))?;
+        if pack["format"].as_str() != Some(format) || pack["cell"]["key"].as_str() != Some(cell_key)
+        {
+            return Err(format!(
+                "Cooked cell '{}' is not a valid {} pack",
+                cell_key, format
+            ));
+        }
+
+        Ok(Some(pack))
+    }
+
+    /// Cooked terrain heightfield described by the manifest's terrain asset.
+    /// 由清单 terrain 资产描述的 cooked 地形高度场
+    pub(crate) fn terrain(&self) -> Result<CookedTerrain, String> {
+        let entry = &self.manifest["assets"]["terrain"];
+        if entry["format"].as_str() != Some(HEIGHT_REGION_FORMAT) {
+            return Err("Cooked terrain has invalid region pack format".to_string());
+        }
+        let layout = HeightPackLayout {
+            page_resolution: entry["pageResolution"]
+                .as_u64()
+                .filter(|resolution| *resolution >= 2)
+                .ok_or_else(|| "Cooked terrain page resolution must be at least 2".to_string())?
+                as usize,
+            page_size_meters: entry["pageSizeMeters"]
+                .as_f64()
+                .filter(|size| size.is_finite() && *size > 0.0)
+                .ok_or_else(|| "Cooked terrain page size must be a positive number".to_string())?,
+            region_size_pages: entry["regionSizePages"]
+                .as_u64()
+                .filter(|size| (1..=8).contains(size))
+                .ok_or_else(|| "Cooked terrain region size must be between 1 and 8".to_string())?
+                as i32,
+        };
+
+        let mut regions = BTreeMap::new();
+        for (key, region) in entry["regions"].as_object().into_iter().flatten() {
+            if parse_grid_key(key).is_none() {
+                return Err(format!("Invalid cooked terrain region key '{}'", key));
+            }
+            let mask = parse_region_mask(&region["mask"])
+                .ok_or_else(|| format!("Cooked terrain region '{}' has an invalid mask", key))?;
+            regions.insert(key.clone(), (mask, region.clone()));
+        }
+
+        Ok(CookedTerrain {
+            layout,
+            regions,
+            pages: BTreeMap::new(),
+        })
+    }
+
+    fn read_artifact(&self, entry: &Value, label: &str) -> Result<Vec<u8>, String> {
+        let path = entry["path"]
+            .as_str()
+            .ok_or_else(|| format!("The {} has no path", label))?;
+        validate_relative_file_path(path, label)?;
+        let bytes = fs::read(self.project_root.join(path))
+            .map_err(|e| format!("Failed to read {}: {}", label, e))?;
+        let matches_manifest = entry["byteLength"].as_u64() == Some(bytes.len() as u64)
+            && entry["sha256"].as_str() == Some(sha256_hex(&bytes).as_str());
+        if !matches_manifest {
+            return Err(format!(
+                "The {} does not match its cooked manifest entry; cook the map again",
+                label
+            ));
+        }
+
+        Ok(bytes)
+    }
+}
+
+impl CookedCellGrid {
+    pub(crate) fn contains(&self, cell_key: &str) -> bool {
+        self.cells.contains_key(cell_key)
+    }
+
+    /// Cooked cell keys.
+    /// 所有 cooked 单元键
+    pub(crate) fn keys(&self) -> impl Iterator<Item = &String> {
+        self.cells.keys()
+    }
+
+    /// Cell key containing a world position.
+    /// 包含世界坐标的单元键
+    pub(crate) fn cell_key_for_position(&self, x: f64, z: f64) -> String {
+        format_grid_key(
+            (x / self.cell_size_meters).floor() as i32,
+            (z / self.cell_size_meters).floor() as i32,
+        )
+    }
+
+    /// Cooked cell keys touching a world rectangle, in grid order.
+    /// 与世界矩形相交的 cooked 单元键（按网格顺序）
+    pub(crate) fn cell_keys_in_rect(
+        &self,
+        min_x: f64,
+        min_z: f64,
+        max_x: f64,
+        max_z: f64,
+    ) -> Vec<String> {
+        let size = self.cell_size_meters;
+        let mut keys = Vec::new();
+        for cz in (min_z / size).floor() as i32..=(max_z / size).floor() as i32 {
+            for cx in (min_x / size).floor() as i32..=(max_x / size).floor() as i32 {
+                let key = format_grid_key(cx, cz);
+                if self.cells.contains_key(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+        keys
+    }
+}
+
+impl CookedTerrain {
+    /// Decode the listed regions so `height_at` can sample them.
+    /// 解码所列区域，使 `height_at` 可以采样
+    pub(crate) fn load_regions(
+        &mut self,
+        map: &CookedMap,
+        region_keys: &[&str],
+    ) -> Result<(), String> {
+        for region_key in region_keys {
+            if self.pages.contains_key(*region_key) {
+                continue;
+            }
+            let Some((mask, entry)) = self.regions.get(*region_key) else {
+                continue;
+            };
+            let bytes =
+                map.read_artifact(entry, &format!("cooked terrain region '{}'", region_key))?;
+            let pages = decode_region_pages(&self.layout, region_key, *mask, &bytes)?;
+            self.pages.insert(region_key.to_string(), pages);
+        }
+
+        Ok(())
+    }
+
+    /// Bilinear height from loaded regions, or `None` outside them.
+    /// 基于已加载区域的双线性高度；区域之外返回 `None`
+    pub(crate) fn height_at(&self, x: f64, z: f64) -> Option<f64> {
+        let page_size = self.layout.page_size_meters;
+        let px = (x / page_size).floor() as i32;
+        let pz = (z / page_size).floor() as i32;
+        let (region_x, region_z) = self.layout.region_coords_for_page(px, pz);
+        let heights = self
+            .pages
+            .get(&format_grid_key(region_x, region_z))?
+            .get(&self.layout.local_page_index(px, pz))?;
+        Some(f64::from(self.layout.sample_page(heights, px, pz, x, z)))
+    }
+}
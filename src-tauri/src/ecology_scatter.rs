@@ -0,0 +1,1057 @@
+// Deterministic ecology scatter executor that rebuilds vegetation region packs.
+// 确定性的生态散布执行器，用于重建植被区域包
+//
+// EN: Ports the vegetation rules of scripts/map-generation/vegetation-assets.mjs onto height packs, paint splats and world object semantics. Candidates sit on a seed-hashed jittered grid and spacing uses a Matérn hard-core rule, so each keep/reject decision depends only on nearby candidates; regions therefore run in parallel and agree along their borders.
+// 中文: 将 scripts/map-generation/vegetation-assets.mjs 的植被规则移植到高度包、绘制 splat 与世界物体语义之上。候选点位于按种子哈希抖动的网格上，间距采用 Matérn 硬核规则，因此每个保留/剔除决定只依赖附近的候选点；各区域可以并行运行且边界结果一致。
+
+use crate::commands::{
+    MAP_FILE, MAPS_DIR, read_map_world_size, recover_safe_write, validate_cook_project_path,
+    validate_single_path_segment,
+};
+use crate::height_pack::HeightPackSession;
+use crate::map_layout::{
+    GENERATION_GRAPH_PATH, WORLD_OBJECTS_PATH, WorldRect, compare_grid_keys, format_grid_key,
+    parse_grid_key, segment_projection, smoothstep, splitmix64,
+};
+use crate::paint_pack::PaintPackSession;
+use crate::vegetation_pack::{VegetationInstance, VegetationPackLayout, VegetationPackSession};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::f64::consts::TAU;
+use std::fs;
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+const SLOPE_SAMPLE_METERS: f64 = 6.0;
+const CLUSTER_EDGE_WIDTH: f64 = 0.18;
+const CLUSTER_DETAIL_WEIGHT: f64 = 0.5;
+const UNDERSTORY_HOST_BOOST: f64 = 0.6;
+const UNDERSTORY_OPEN_GROUND_FACTOR: f64 = 0.3;
+const POI_FALLBACK_RADIUS_METERS: f64 = 16.0;
+const PROP_CLEARANCE_INNER_METERS: f64 = 6.0;
+const PROP_CLEARANCE_OUTER_METERS: f64 = 24.0;
+const SCATTER_POSITION_PRECISION: f64 = 1000.0;
+
+const CHANNEL_JITTER_X: u64 = 0;
+const CHANNEL_JITTER_Z: u64 = 1;
+const CHANNEL_ROLL: u64 = 2;
+const CHANNEL_PRIORITY: u64 = 3;
+const CHANNEL_ROTATION: u64 = 4;
+const CHANNEL_SCALE: u64 = 5;
+const CHANNEL_CLUSTER: u64 = 6;
+const CHANNEL_CLUSTER_DETAIL: u64 = 7;
+const CHANNELS_PER_SPECIES: u64 = 16;
+
+const RULE_SLOPE_EXCLUSION: &str = "slope-exclusion";
+const RULE_SNOWLINE_EXCLUSION: &str = "snowline-exclusion";
+const RULE_CLUSTERS: &str = "basin-grass-clusters";
+const RULE_EDGE_FALLOFF: &str = "forest-edge-falloff";
+const RULE_WATER_BANK_BOOST: &str = "water-bank-boost";
+const RULE_ROAD_CLEARANCE: &str = "road-clearance";
+const RULE_POI_CLEARANCE: &str = "poi-clearance";
+const RULE_MANUAL_PROTECTED_ZONE: &str = "manual-protected-zone";
+const RULE_UNDERSTORY: &str = "tree-cluster-understory";
+const SCATTER_RULES: &[&str] = &[
+    RULE_SLOPE_EXCLUSION,
+    RULE_SNOWLINE_EXCLUSION,
+    RULE_CLUSTERS,
+    RULE_EDGE_FALLOFF,
+    RULE_WATER_BANK_BOOST,
+    RULE_ROAD_CLEARANCE,
+    RULE_POI_CLEARANCE,
+    RULE_MANUAL_PROTECTED_ZONE,
+    RULE_UNDERSTORY,
+];
+
+/// Ecology profile of one vegetation species, read from `stages.vegetation.species` in the generation graph.
+/// Canopy species must come before their understory.
+/// 单个植被物种的生态参数，读取自生成图的 `stages.vegetation.species`；冠层物种必须排在其林下物种之前
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EcologySpecies {
+    model_id: String,
+    salt: u64,
+    spacing_meters: f64,
+    max_density: f64,
+    cluster_scale_meters: f64,
+    cluster_coverage: f64,
+    slope_degrees: [f64; 2],
+    snowline_meters: [f64; 2],
+    wetland_boost: f64,
+    shoulder_penalty: f64,
+    #[serde(default)]
+    edge_regrowth_boost: f64,
+    scale_range: [f64; 2],
+    #[serde(default)]
+    paint_affinity: Vec<EcologyPaintAffinity>,
+    #[serde(default)]
+    understory_of: Option<EcologyUnderstory>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EcologyPaintAffinity {
+    layer: String,
+    affinity: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EcologyUnderstory {
+    model_id: String,
+    radius_meters: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunEcologyScatterRequest {
+    project_path: String,
+    map_id: String,
+    #[serde(default)]
+    regions: Option<Vec<String>>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcologySpeciesCount {
+    model_id: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EcologyScatterResult {
+    seed: u64,
+    rules: Vec<String>,
+    region_count: usize,
+    protected_regions: Vec<String>,
+    instance_count: usize,
+    species: Vec<EcologySpeciesCount>,
+    /// Vegetation rules listed in the graph that this executor does not apply.
+    /// 生成图中列出但本执行器未应用的植被规则
+    skipped_rules: Vec<String>,
+    updated_regions: Vec<String>,
+    removed_regions: Vec<String>,
+    dry_run: bool,
+}
+
+/// Graph rules the executor honours; other rule names are reported as skipped.
+/// 执行器遵循的图规则；其他规则名会被报告为已跳过
+struct ScatterRules {
+    slope_exclusion: bool,
+    snowline_exclusion: bool,
+    clusters: bool,
+    edge_falloff: bool,
+    water_bank_boost: bool,
+    road_clearance: bool,
+    poi_clearance: bool,
+    manual_protection: bool,
+    understory: bool,
+}
+
+/// Vegetation stage settings read from the generation graph.
+/// 从生成图读取的植被阶段设置
+struct ScatterGraph {
+    rules: ScatterRules,
+    applied_rules: Vec<String>,
+    skipped_rules: Vec<String>,
+    species: Vec<EcologySpecies>,
+}
+
+/// A species profile bound to its record index and paint slots for this map.
+/// 绑定到当前地图记录索引与绘制槽位的物种参数
+struct ActiveSpecies {
+    profile: EcologySpecies,
+    model_index: u16,
+    paint_slots: Vec<(usize, f64)>,
+    host: Option<(usize, f64)>,
+}
+
+/// Road or water centerline segment with its path width.
+/// 带路径宽度的道路或水体中心线段
+#[derive(Clone)]
+struct SemanticSegment {
+    width_meters: f64,
+    start: [f64; 2],
+    end: [f64; 2],
+}
+
+/// Object semantics that influence vegetation, gathered from the world object cells.
+/// 从世界物体 cell 中收集的、影响植被的物体语义
+#[derive(Default)]
+struct WorldSemantics {
+    roads: Vec<SemanticSegment>,
+    water: Vec<SemanticSegment>,
+    pois: Vec<([f64; 2], f64)>,
+    props: Vec<[f64; 4]>,
+}
+
+/// Semantic weights at one position, matching sampleWorldSemantics in world-semantics.mjs.
+/// 单个位置的语义权重，与 world-semantics.mjs 中的 sampleWorldSemantics 一致
+struct SemanticSample {
+    road_core: f64,
+    road_shoulder: f64,
+    water_core: f64,
+    water_bank: f64,
+    poi_clearance: f64,
+    prop_clearance: f64,
+}
+
+/// A candidate that passed its density roll.
+/// 通过密度判定的候选点
+#[derive(Clone, Copy)]
+struct ScatterCandidate {
+    x: f64,
+    z: f64,
+    height: f64,
+    rank: (f64, i64, i64),
+}
+
+/// Read-only inputs shared by every scatter worker.
+/// 所有散布工作线程共享的只读输入
+#[derive(Clone, Copy)]
+struct ScatterContext<'a> {
+    seed: u64,
+    world_half_size: f64,
+    margin_meters: f64,
+    rules: &'a ScatterRules,
+    terrain: &'a HeightPackSession,
+    paint: &'a PaintPackSession,
+    semantics: &'a WorldSemantics,
+    species: &'a [ActiveSpecies],
+    layout: &'a VegetationPackLayout,
+}
+
+// --- Ecology scatter command / 生态散布命令 ---
+
+/// Regenerate procedural vegetation for whole map or selected regions from the map seed.
+/// 根据地图种子为整张地图或所选区域重新生成程序化植被
+#[tauri::command]
+pub async fn run_ecology_scatter(
+    request: RunEcologyScatterRequest,
+) -> Result<EcologyScatterResult, String> {
+    tauri::async_runtime::spawn_blocking(move || run_ecology_scatter_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join ecology scatter task: {}", e))?
+}
+
+fn run_ecology_scatter_blocking(
+    request: RunEcologyScatterRequest,
+) -> Result<EcologyScatterResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    validate_single_path_segment(&request.map_id, "map_id")?;
+    let map_root = project_root.join(MAPS_DIR).join(&request.map_id);
+    let seed = read_map_seed(&map_root)?;
+    let world_size = read_map_world_size(&map_root)?;
+    let graph = read_scatter_graph(&map_root)?;
+    let rules = graph.rules;
+    let semantics = WorldSemantics::load(&map_root)?;
+
+    let mut vegetation = VegetationPackSession::open(&project_root, &request.map_id)?;
+    let layout = VegetationPackLayout {
+        cell_size_meters: vegetation.layout.cell_size_meters,
+        region_size_cells: vegetation.layout.region_size_cells,
+    };
+    let mut paint = PaintPackSession::open(&project_root, &request.map_id)?;
+    let species = resolve_active_species(graph.species, &vegetation, &paint)?;
+    let species_indices: BTreeSet<u16> =
+        species.iter().map(|species| species.model_index).collect();
+
+    let mut targets = match &request.regions {
+        Some(keys) => {
+            if keys.is_empty() {
+                return Err("Ecology scatter region list cannot be empty".to_string());
+            }
+            keys.iter()
+                .map(|key| {
+                    parse_grid_key(key)
+                        .ok_or_else(|| format!("Invalid vegetation region key '{}'", key))
+                })
+                .collect::<Result<BTreeSet<_>, _>>()?
+        }
+        None => world_region_coords(&layout, world_size / 2.0)
+            .into_iter()
+            .chain(
+                vegetation
+                    .region_keys()
+                    .iter()
+                    .filter_map(|key| parse_grid_key(key)),
+            )
+            .collect(),
+    };
+
+    // EN: Regions a designer pinned in a manual patch layer keep their instances.
+    // 中文: 设计师在手动 patch 层中固定的区域保留其实例。
+    let mut protected_regions = Vec::new();
+    if rules.manual_protection {
+        let manual_regions = vegetation.manual_regions();
+        targets.retain(|(region_x, region_z)| {
+            let key = format_grid_key(*region_x, *region_z);
+            let protected = manual_regions.contains(&key);
+            if protected {
+                protected_regions.push(key);
+            }
+            !protected
+        });
+    }
+    let mut targets: Vec<(i32, i32)> = targets.into_iter().collect();
+    targets.sort_by(|left, right| left.1.cmp(&right.1).then(left.0.cmp(&right.0)));
+    protected_regions.sort_by(|left, right| compare_grid_keys(left, right));
+
+    let mut terrain = HeightPackSession::open(&project_root, &request.map_id)?;
+    if let Some([min_x, min_z, max_x, max_z]) = targets_bounds(&layout, &targets) {
+        let margin = species_margin(&species) + SLOPE_SAMPLE_METERS;
+        terrain.load_regions_in_rect(
+            min_x - margin,
+            min_z - margin,
+            max_x + margin,
+            max_z + margin,
+        )?;
+    }
+    paint.load_all_regions()?;
+
+    let context = ScatterContext {
+        seed,
+        world_half_size: world_size / 2.0,
+        margin_meters: species_margin(&species),
+        rules: &rules,
+        terrain: &terrain,
+        paint: &paint,
+        semantics: &semantics,
+        species: &species,
+        layout: &layout,
+    };
+    let region_instances = context.scatter_regions(&targets)?;
+
+    let mut counts: BTreeMap<u16, usize> = BTreeMap::new();
+    for instance in region_instances.iter().flatten() {
+        *counts.entry(instance.model_index).or_default() += 1;
+    }
+    let instance_count = counts.values().sum();
+    let species_counts = species
+        .iter()
+        .map(|species| EcologySpeciesCount {
+            model_id: species.profile.model_id.clone(),
+            count: counts.get(&species.model_index).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let (updated_regions, removed_regions) = if request.dry_run {
+        (Vec::new(), Vec::new())
+    } else {
+        for ((region_x, region_z), instances) in targets.iter().zip(region_instances) {
+            vegetation.replace_region_species(
+                &format_grid_key(*region_x, *region_z),
+                &species_indices,
+                instances,
+            )?;
+        }
+        vegetation.save()?
+    };
+
+    Ok(EcologyScatterResult {
+        seed,
+        rules: graph.applied_rules,
+        region_count: targets.len(),
+        protected_regions,
+        instance_count,
+        species: species_counts,
+        skipped_rules: graph.skipped_rules,
+        updated_regions,
+        removed_regions,
+        dry_run: request.dry_run,
+    })
+}
+
+fn read_map_seed(map_root: &Path) -> Result<u64, String> {
+    let path = map_root.join(MAP_FILE);
+    recover_safe_write(&path)?;
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read map manifest: {}", e))?;
+    let map: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse map manifest: {}", e))?;
+    map["seed"]
+        .as_u64()
+        .or_else(|| map["seed"].as_i64().map(|seed| seed as u64))
+        .ok_or_else(|| "Map manifest seed must be an integer".to_string())
+}
+
+fn read_scatter_graph(map_root: &Path) -> Result<ScatterGraph, String> {
+    let content = fs::read_to_string(map_root.join(GENERATION_GRAPH_PATH))
+        .map_err(|e| format!("Failed to read generation graph: {}", e))?;
+    let graph: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse generation graph: {}", e))?;
+    let stage = &graph["stages"]["vegetation"];
+    let (applied_rules, skipped_rules): (Vec<String>, Vec<String>) = stage["rules"]
+        .as_array()
+        .ok_or_else(|| "Generation graph has no vegetation rules".to_string())?
+        .iter()
+        .filter_map(|rule| rule.as_str())
+        .map(str::to_string)
+        .partition(|rule| SCATTER_RULES.contains(&rule.as_str()));
+    let enabled = |name: &str| applied_rules.iter().any(|rule| rule == name);
+    let rules = ScatterRules {
+        slope_exclusion: enabled(RULE_SLOPE_EXCLUSION),
+        snowline_exclusion: enabled(RULE_SNOWLINE_EXCLUSION),
+        clusters: enabled(RULE_CLUSTERS),
+        edge_falloff: enabled(RULE_EDGE_FALLOFF),
+        water_bank_boost: enabled(RULE_WATER_BANK_BOOST),
+        road_clearance: enabled(RULE_ROAD_CLEARANCE),
+        poi_clearance: enabled(RULE_POI_CLEARANCE),
+        manual_protection: enabled(RULE_MANUAL_PROTECTED_ZONE),
+        understory: enabled(RULE_UNDERSTORY),
+    };
+
+    // EN: Species profiles come only from the graph, which world-generation-graph.mjs writes from the same tables the Node cook uses.
+    // 中文: 物种参数只从生成图读取，生成图由 world-generation-graph.mjs 按 Node cook 使用的同一份表写出。
+    let species: Vec<EcologySpecies> = serde_json::from_value(
+        stage
+            .get("species")
+            .cloned()
+            .ok_or_else(|| "Generation graph has no vegetation species profiles".to_string())?,
+    )
+    .map_err(|e| format!("Failed to parse vegetation species profiles: {}", e))?;
+    for profile in &species {
+        if !(profile.spacing_meters > 0.0 && profile.cluster_scale_meters > 0.0) {
+            return Err(format!(
+                "Vegetation species '{}' spacingMeters and clusterScaleMeters must be positive",
+                profile.model_id
+            ));
+        }
+    }
+
+    Ok(ScatterGraph {
+        rules,
+        applied_rules,
+        skipped_rules,
+        species,
+    })
+}
+
+fn resolve_active_species(
+    profiles: Vec<EcologySpecies>,
+    vegetation: &VegetationPackSession,
+    paint: &PaintPackSession,
+) -> Result<Vec<ActiveSpecies>, String> {
+    let mut species: Vec<ActiveSpecies> = Vec::new();
+    for profile in profiles {
+        let Some(model_index) = vegetation
+            .model_ids
+            .iter()
+            .position(|id| *id == profile.model_id)
+        else {
+            continue;
+        };
+
+        // EN: Maps without a given paint layer simply skip that affinity.
+        // 中文: 地图缺少某个绘制层时直接跳过该亲和项。
+        let paint_slots = profile
+            .paint_affinity
+            .iter()
+            .filter_map(|entry| {
+                let slot = paint.layer(&entry.layer).ok()?.storage_slot().ok()?;
+                Some((slot, entry.affinity))
+            })
+            .collect();
+        let host = profile.understory_of.as_ref().and_then(|understory| {
+            species
+                .iter()
+                .position(|active| active.profile.model_id == understory.model_id)
+                .map(|host_index| (host_index, understory.radius_meters))
+        });
+        species.push(ActiveSpecies {
+            profile,
+            model_index: model_index as u16,
+            paint_slots,
+            host,
+        });
+    }
+
+    if species.is_empty() {
+        return Err("No vegetation model has an ecology scatter profile".to_string());
+    }
+
+    Ok(species)
+}
+
+/// Every region overlapping the world square centered on the origin.
+/// 与以原点为中心的世界正方形相交的所有区域
+fn world_region_coords(layout: &VegetationPackLayout, half_size: f64) -> Vec<(i32, i32)> {
+    let (min_cell, _) = layout.cell_for_position(-half_size, -half_size);
+    let max_cell = (half_size / layout.cell_size_meters).ceil() as i32 - 1;
+    let (min_region, _) = layout.region_coords_for_cell(min_cell, min_cell);
+    let (max_region, _) = layout.region_coords_for_cell(max_cell, max_cell);
+    (min_region..=max_region)
+        .flat_map(|region_z| (min_region..=max_region).map(move |region_x| (region_x, region_z)))
+        .collect()
+}
+
+fn region_rect(layout: &VegetationPackLayout, region_x: i32, region_z: i32) -> [f64; 4] {
+    let size = layout.cell_size_meters * layout.region_size_cells as f64;
+    [
+        region_x as f64 * size,
+        region_z as f64 * size,
+        (region_x + 1) as f64 * size,
+        (region_z + 1) as f64 * size,
+    ]
+}
+
+fn targets_bounds(layout: &VegetationPackLayout, targets: &[(i32, i32)]) -> Option<[f64; 4]> {
+    targets
+        .iter()
+        .map(|(region_x, region_z)| region_rect(layout, *region_x, *region_z))
+        .reduce(|left, right| {
+            [
+                left[0].min(right[0]),
+                left[1].min(right[1]),
+                left[2].max(right[2]),
+                left[3].max(right[3]),
+            ]
+        })
+}
+
+/// Distance beyond a region that scatter decisions inside it can look at.
+/// 区域内散布决策可能查看的区域外距离
+fn species_margin(species: &[ActiveSpecies]) -> f64 {
+    (0..species.len())
+        .map(|index| species_reach(species, index))
+        .fold(0.0, f64::max)
+}
+
+/// Reach of one species: its evaluated grid ring plus, for understory, the host scatter around it.
+/// 单个物种的查看范围：其评估网格外环，林下物种还要加上周围的宿主散布范围
+fn species_reach(species: &[ActiveSpecies], index: usize) -> f64 {
+    let active = &species[index];
+    active.profile.spacing_meters
+        + active
+            .host
+            .map_or(0.0, |(host, radius)| radius + species_reach(species, host))
+}
+
+fn expand_rect(rect: [f64; 4], margin: f64) -> [f64; 4] {
+    [
+        rect[0] - margin,
+        rect[1] - margin,
+        rect[2] + margin,
+        rect[3] + margin,
+    ]
+}
+
+impl ScatterContext<'_> {
+    /// Scatter every target region on scoped worker threads; results keep target order.
+    /// 在作用域工作线程上散布所有目标区域；结果保持目标顺序
+    fn scatter_regions(
+        &self,
+        targets: &[(i32, i32)],
+    ) -> Result<Vec<Vec<VegetationInstance>>, String> {
+        let worker_count = thread::available_parallelism()
+            .map_or(1, NonZeroUsize::get)
+            .min(targets.len())
+            .max(1);
+        let next_target = AtomicUsize::new(0);
+        let mut results: Vec<Vec<VegetationInstance>> = vec![Vec::new(); targets.len()];
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..worker_count)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut produced = Vec::new();
+                        loop {
+                            let index = next_target.fetch_add(1, Ordering::Relaxed);
+                            let Some(&(region_x, region_z)) = targets.get(index) else {
+                                break;
+                            };
+                            produced.push((index, self.scatter_region(region_x, region_z)));
+                        }
+                        produced
+                    })
+                })
+                .collect();
+            for worker in workers {
+                let produced = worker
+                    .join()
+                    .map_err(|_| "Ecology scatter worker panicked".to_string())?;
+                for (index, instances) in produced {
+                    results[index] = instances;
+                }
+            }
+            Ok::<(), String>(())
+        })?;
+
+        Ok(results)
+    }
+
+    fn scatter_region(&self, region_x: i32, region_z: i32) -> Vec<VegetationInstance> {
+        let rect = region_rect(self.layout, region_x, region_z);
+        let nearby = self.semantics.near(expand_rect(rect, self.margin_meters));
+        let context = ScatterContext {
+            semantics: &nearby,
+            ..*self
+        };
+        let mut instances = Vec::new();
+        for (species_index, species) in self.species.iter().enumerate() {
+            for candidate in context.scatter_species(species_index, rect) {
+                let instance = context.instance_for(species, &candidate);
+                // EN: f32 rounding can push a point across the region edge; it is dropped here and never claimed by the neighbour either.
+                // 中文: f32 舍入可能把点推过区域边界；此处将其丢弃，相邻区域也不会认领它。
+                let (cx, cz) = self
+                    .layout
+                    .cell_for_position(instance.x as f64, instance.z as f64);
+                if self.layout.region_coords_for_cell(cx, cz) == (region_x, region_z) {
+                    instances.push(instance);
+                }
+            }
+        }
+
+        instances
+    }
+
+    /// Accepted candidates of one species inside `rect`, in grid row order.
+    /// 单个物种在 `rect` 内被接受的候选点，按网格行顺序排列
+    fn scatter_species(&self, species_index: usize, rect: [f64; 4]) -> Vec<ScatterCandidate> {
+        let species = &self.species[species_index];
+        let profile = &species.profile;
+        let spacing = profile.spacing_meters;
+        let grid_size = spacing * 0.5;
+        // EN: Candidates are evaluated up to one spacing outside `rect`, so hosts must cover that ring plus the understory radius.
+        // 中文: 候选点的评估范围延伸到 `rect` 外一个间距，因此宿主需覆盖该外环再加上林下半径。
+        let hosts = match (self.rules.understory, species.host) {
+            (true, Some((host_index, radius))) => {
+                self.scatter_species(host_index, expand_rect(rect, radius + spacing))
+            }
+            _ => Vec::new(),
+        };
+
+        let evaluated = expand_rect(rect, spacing);
+        let min_gx = (evaluated[0] / grid_size).floor() as i64;
+        let min_gz = (evaluated[1] / grid_size).floor() as i64;
+        let columns = ((evaluated[2] / grid_size).floor() as i64 - min_gx + 1) as usize;
+        let rows = ((evaluated[3] / grid_size).floor() as i64 - min_gz + 1) as usize;
+        let salt = profile.salt * CHANNELS_PER_SPECIES;
+        let mut grid: Vec<Option<ScatterCandidate>> = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                let gx = min_gx + column as i64;
+                let gz = min_gz + row as i64;
+                let x = (gx as f64 + self.hash(salt + CHANNEL_JITTER_X, gx, gz)) * grid_size;
+                let z = (gz as f64 + self.hash(salt + CHANNEL_JITTER_Z, gx, gz)) * grid_size;
+                let candidate =
+                    self.density_at(species, &hosts, x, z)
+                        .and_then(|(density, height)| {
+                            (self.hash(salt + CHANNEL_ROLL, gx, gz) < density).then(|| {
+                                ScatterCandidate {
+                                    x,
+                                    z,
+                                    height,
+                                    rank: (self.hash(salt + CHANNEL_PRIORITY, gx, gz), gz, gx),
+                                }
+                            })
+                        });
+                grid.push(candidate);
+            }
+        }
+
+        // EN: Matérn type-II thinning: a point survives when no passing neighbour within the spacing outranks it; ties fall back to global grid coordinates.
+        // 中文: Matérn II 型稀疏化：间距内没有优先级更高的通过点时，该点才会保留；优先级相同时按全局网格坐标决定。
+        let reach = (spacing / grid_size).ceil() as usize + 1;
+        let mut accepted = Vec::new();
+        for row in 0..rows {
+            for column in 0..columns {
+                let Some(candidate) = grid[row * columns + column] else {
+                    continue;
+                };
+                if candidate.x < rect[0]
+                    || candidate.x >= rect[2]
+                    || candidate.z < rect[1]
+                    || candidate.z >= rect[3]
+                {
+                    continue;
+                }
+
+                let outranked = (row.saturating_sub(reach)..(row + reach + 1).min(rows))
+                    .flat_map(|other_row| {
+                        (column.saturating_sub(reach)..(column + reach + 1).min(columns))
+                            .map(move |other_column| other_row * columns + other_column)
+                    })
+                    .filter_map(|other_index| grid[other_index])
+                    .any(|other| {
+                        other.rank > candidate.rank
+                            && (other.x - candidate.x).hypot(other.z - candidate.z) < spacing
+                    });
+                if !outranked {
+                    accepted.push(candidate);
+                }
+            }
+        }
+
+        accepted
+    }
+
+    /// Acceptance probability and terrain height at a point, or `None` off the world or terrain.
+    /// 某点的接受概率与地形高度；位于世界或地形之外时返回 `None`
+    fn density_at(
+        &self,
+        species: &ActiveSpecies,
+        hosts: &[ScatterCandidate],
+        x: f64,
+        z: f64,
+    ) -> Option<(f64, f64)> {
+        if x.abs() >= self.world_half_size || z.abs() >= self.world_half_size {
+            return None;
+        }
+
+        let height = self.terrain.loaded_height_at(x, z)? as f64;
+        let profile = &species.profile;
+        let rules = self.rules;
+        let mut density = profile.max_density;
+        if rules.slope_exclusion {
+            let slope = self.slope_degrees(x, z)?;
+            density *= 1.0 - smoothstep(profile.slope_degrees[0], profile.slope_degrees[1], slope);
+        }
+        if rules.snowline_exclusion {
+            density *= 1.0
+                - smoothstep(
+                    profile.snowline_meters[0],
+                    profile.snowline_meters[1],
+                    height,
+                );
+        }
+        if density <= 0.0 {
+            return Some((0.0, height));
+        }
+
+        let semantics = self.semantics.sample(x, z);
+        let poi_clearance = if rules.poi_clearance {
+            semantics.poi_clearance.max(semantics.prop_clearance)
+        } else {
+            0.0
+        };
+        let path_clearance = if rules.road_clearance {
+            semantics.road_core.max(semantics.water_core)
+        } else {
+            0.0
+        };
+        let clearance = path_clearance.max(poi_clearance);
+        density *= 1.0 - clearance;
+        if rules.road_clearance {
+            density *= (1.0 - semantics.road_shoulder * profile.shoulder_penalty).max(0.0);
+        }
+        if rules.water_bank_boost {
+            let wetland = (semantics.water_bank * (1.0 - clearance * 0.6)).clamp(0.0, 1.0);
+            let edge_regrowth = (semantics.road_shoulder.max(semantics.water_bank)
+                * (1.0 - semantics.poi_clearance))
+                .clamp(0.0, 1.0);
+            density *=
+                1.0 + wetland * profile.wetland_boost + edge_regrowth * profile.edge_regrowth_boost;
+        }
+        if rules.clusters {
+            density *= self.cluster_factor(profile, x, z);
+        }
+
+        let affinity: f64 = species
+            .paint_slots
+            .iter()
+            .filter_map(|(slot, affinity)| {
+                self.paint
+                    .loaded_weight_at(*slot, x, z)
+                    .map(|weight| weight as f64 / 255.0 * affinity)
+            })
+            .sum();
+        density *= (1.0 + affinity).max(0.0);
+
+        if let Some((_, radius)) = species.host.filter(|_| rules.understory) {
+            let nearest = hosts
+                .iter()
+                .map(|host| (host.x - x).hypot(host.z - z))
+                .fold(f64::INFINITY, f64::min);
+            density *= if nearest < radius {
+                1.0 + UNDERSTORY_HOST_BOOST * (1.0 - nearest / radius)
+            } else {
+                UNDERSTORY_OPEN_GROUND_FACTOR
+            };
+        }
+
+        Some((density.clamp(0.0, 1.0), height))
+    }
+
+    /// Patchy cover from two octaves of value noise; the edge rule softens patch borders.
+    /// 由两层值噪声构成的斑块覆盖；边缘规则会柔化斑块边界
+    fn cluster_factor(&self, profile: &EcologySpecies, x: f64, z: f64) -> f64 {
+        let salt = profile.salt * CHANNELS_PER_SPECIES;
+        let scale = profile.cluster_scale_meters;
+        let noise = (self.value_noise(salt + CHANNEL_CLUSTER, x / scale, z / scale)
+            + self.value_noise(
+                salt + CHANNEL_CLUSTER_DETAIL,
+                x * 2.0 / scale,
+                z * 2.0 / scale,
+            ) * CLUSTER_DETAIL_WEIGHT)
+            / (1.0 + CLUSTER_DETAIL_WEIGHT);
+        let threshold = 1.0 - profile.cluster_coverage;
+        if self.rules.edge_falloff {
+            smoothstep(
+                threshold - CLUSTER_EDGE_WIDTH,
+                threshold + CLUSTER_EDGE_WIDTH,
+                noise,
+            )
+        } else if noise >= threshold {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn value_noise(&self, channel: u64, x: f64, z: f64) -> f64 {
+        let xi = x.floor();
+        let zi = z.floor();
+        let u = quintic(x - xi);
+        let v = quintic(z - zi);
+        let (xi, zi) = (xi as i64, zi as i64);
+        let corner = |dx: i64, dz: i64| self.hash(channel, xi + dx, zi + dz);
+        let top = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * u;
+        let bottom = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * u;
+        top + (bottom - top) * v
+    }
+
+    fn slope_degrees(&self, x: f64, z: f64) -> Option<f64> {
+        let distance = SLOPE_SAMPLE_METERS;
+        let left = self.terrain.loaded_height_at(x - distance, z)? as f64;
+        let right = self.terrain.loaded_height_at(x + distance, z)? as f64;
+        let back = self.terrain.loaded_height_at(x, z - distance)? as f64;
+        let forward = self.terrain.loaded_height_at(x, z + distance)? as f64;
+        let gradient_x = (right - left) / (distance * 2.0);
+        let gradient_z = (forward - back) / (distance * 2.0);
+        Some(gradient_x.hypot(gradient_z).atan().to_degrees())
+    }
+
+    fn instance_for(
+        &self,
+        species: &ActiveSpecies,
+        candidate: &ScatterCandidate,
+    ) -> VegetationInstance {
+        let salt = species.profile.salt * CHANNELS_PER_SPECIES;
+        let gx = (candidate.x * SCATTER_POSITION_PRECISION).round() as i64;
+        let gz = (candidate.z * SCATTER_POSITION_PRECISION).round() as i64;
+        let [min_scale, max_scale] = species.profile.scale_range;
+        VegetationInstance {
+            model_index: species.model_index,
+            x: round_position(candidate.x) as f32,
+            y: round_position(candidate.height) as f32,
+            z: round_position(candidate.z) as f32,
+            rotation_y: (self.hash(salt + CHANNEL_ROTATION, gx, gz) * TAU) as f32,
+            scale: round_position(
+                min_scale + self.hash(salt + CHANNEL_SCALE, gx, gz) * (max_scale - min_scale),
+            ) as f32,
+        }
+    }
+
+    /// Uniform value in [0, 1) from the map seed, a channel and integer coordinates.
+    /// 由地图种子、通道与整数坐标得到的 [0, 1) 均匀值
+    fn hash(&self, channel: u64, x: i64, z: i64) -> f64 {
+        let mut state = splitmix64(self.seed ^ channel.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        state = splitmix64(state ^ (x as u64).wrapping_mul(0xbf58_476d_1ce4_e5b9));
+        state = splitmix64(state ^ (z as u64).wrapping_mul(0x94d0_49bb_1331_11eb));
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl WorldSemantics {
+    /// Collect roads, water, POIs and collision props from every object cell, once per object id.
+    /// 从所有物体 cell 中收集道路、水体、兴趣点与碰撞道具，每个物体 id 只计一次
+    fn load(map_root: &Path) -> Result<Self, String> {
+        let manifest_path = map_root.join(WORLD_OBJECTS_PATH);
+        if !manifest_path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read world object manifest: {}", e))?;
+        let manifest: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse world object manifest: {}", e))?;
+        let mut semantics = Self::default();
+        let mut seen_ids = BTreeSet::new();
+        let Some(cells) = manifest["cells"].as_object() else {
+            return Ok(semantics);
+        };
+        for (cell_key, cell) in cells {
+            let Some(path) = cell["path"].as_str() else {
+                continue;
+            };
+            let content = fs::read_to_string(map_root.join(path))
+                .map_err(|e| format!("Failed to read world object cell '{}': {}", cell_key, e))?;
+            let pack: Value = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse world object cell '{}': {}", cell_key, e))?;
+            for object in pack["objects"].as_array().into_iter().flatten() {
+                let Some(id) = object["id"].as_str() else {
+                    continue;
+                };
+                if seen_ids.insert(id.to_string()) {
+                    semantics.add_object(object);
+                }
+            }
+        }
+
+        Ok(semantics)
+    }
+
+    /// Objects whose influence can reach `rect`; sampling inside it gives the same result as the full set.
+    /// 影响范围可达 `rect` 的物体；在其内部采样与使用完整集合结果相同
+    fn near(&self, rect: [f64; 4]) -> Self {
+        let reaches = |bounds: [f64; 4], reach: f64| {
+            bounds[0] - reach <= rect[2]
+                && bounds[2] + reach >= rect[0]
+                && bounds[1] - reach <= rect[3]
+                && bounds[3] + reach >= rect[1]
+        };
+        let segment_reaches = |segment: &&SemanticSegment, reach: f64| {
+            reaches(
+                [
+                    segment.start[0].min(segment.end[0]),
+                    segment.start[1].min(segment.end[1]),
+                    segment.start[0].max(segment.end[0]),
+                    segment.start[1].max(segment.end[1]),
+                ],
+                reach,
+            )
+        };
+
+        Self {
+            roads: self
+                .roads
+                .iter()
+                .filter(|segment| segment_reaches(segment, segment.width_meters * 5.0 + 18.0))
+                .cloned()
+                .collect(),
+            water: self
+                .water
+                .iter()
+                .filter(|segment| segment_reaches(segment, segment.width_meters * 4.5 + 24.0))
+                .cloned()
+                .collect(),
+            pois: self
+                .pois
+                .iter()
+                .filter(|([x, z], radius)| reaches([*x, *z, *x, *z], radius * 1.5))
+                .copied()
+                .collect(),
+            props: self
+                .props
+                .iter()
+                .filter(|bounds| reaches(**bounds, PROP_CLEARANCE_OUTER_METERS))
+                .copied()
+                .collect(),
+        }
+    }
+
+    fn add_object(&mut self, object: &Value) {
+        let bounds = WorldRect::from_value(&object["boundsMeters"])
+            .map(|rect| [rect.min_x, rect.min_z, rect.max_x, rect.max_z]);
+        match object["layer"].as_str() {
+            Some(layer @ ("road" | "water")) => {
+                let width_meters = object["spline"]["widthMeters"].as_f64().unwrap_or(0.0);
+                let points: Vec<[f64; 2]> = object["spline"]["points"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|point| Some([point["x"].as_f64()?, point["z"].as_f64()?]))
+                    .collect();
+                let segments = points.windows(2).map(|pair| SemanticSegment {
+                    width_meters,
+                    start: pair[0],
+                    end: pair[1],
+                });
+                if layer == "road" {
+                    self.roads.extend(segments);
+                } else {
+                    self.water.extend(segments);
+                }
+            }
+            Some("poi") => {
+                let position = &object["position"];
+                if let (Some(x), Some(z)) = (position["x"].as_f64(), position["z"].as_f64()) {
+                    let radius = object["radiusMeters"]
+                        .as_f64()
+                        .or_else(|| {
+                            bounds.map(|[min_x, min_z, max_x, max_z]| {
+                                (max_x - min_x).max(max_z - min_z) * 0.5
+                            })
+                        })
+                        .unwrap_or(POI_FALLBACK_RADIUS_METERS);
+                    self.pois.push(([x, z], radius));
+                }
+            }
+            _ => {}
+        }
+        if let Some(bounds) = bounds.filter(|_| object["collision"].is_object()) {
+            self.props.push(bounds);
+        }
+    }
+
+    // EN: Path weights take the strongest segment rather than the nearest one, so the value at a point never depends on which objects a caller filtered out.
+    // 中文: 路径权重取最强的线段而不是最近的线段，因此某点的取值不会因调用方过滤掉哪些物体而改变。
+    fn sample(&self, x: f64, z: f64) -> SemanticSample {
+        let mut sample = SemanticSample {
+            road_core: 0.0,
+            road_shoulder: 0.0,
+            water_core: 0.0,
+            water_bank: 0.0,
+            poi_clearance: 0.0,
+            prop_clearance: 0.0,
+        };
+        for segment in &self.roads {
+            let width = segment.width_meters;
+            let distance = segment.distance(x, z);
+            sample.road_core = sample
+                .road_core
+                .max(1.0 - smoothstep(width * 0.55, width * 1.35 + 4.0, distance));
+            sample.road_shoulder = sample
+                .road_shoulder
+                .max(1.0 - smoothstep(width * 1.25 + 4.0, width * 5.0 + 18.0, distance));
+        }
+        for segment in &self.water {
+            let width = segment.width_meters;
+            let distance = segment.distance(x, z);
+            sample.water_core = sample
+                .water_core
+                .max(1.0 - smoothstep(width * 0.55, width * 1.25 + 4.0, distance));
+            sample.water_bank = sample
+                .water_bank
+                .max(1.0 - smoothstep(width * 0.85 + 5.0, width * 4.5 + 24.0, distance));
+        }
+        for ([poi_x, poi_z], radius) in &self.pois {
+            let distance = (x - poi_x).hypot(z - poi_z);
+            sample.poi_clearance = sample
+                .poi_clearance
+                .max(1.0 - smoothstep(radius * 0.55, radius * 1.5, distance));
+        }
+        for [min_x, min_z, max_x, max_z] in &self.props {
+            let distance = (x - x.clamp(*min_x, *max_x)).hypot(z - z.clamp(*min_z, *max_z));
+            sample.prop_clearance = sample.prop_clearance.max(
+                1.0 - smoothstep(
+                    PROP_CLEARANCE_INNER_METERS,
+                    PROP_CLEARANCE_OUTER_METERS,
+                    distance,
+                ),
+            );
+        }
+
+        sample
+    }
+}
+
+impl SemanticSegment {
+    fn distance(&self, x: f64, z: f64) -> f64 {
+        segment_projection(self.start, self.end, x, z).0
+    }
+}
+
+fn quintic(value: f64) -> f64 {
+    value * value * value * (value * (value * 6.0 - 15.0) + 10.0)
+}
+
+fn round_position(value: f64) -> f64 {
+    (value * SCATTER_POSITION_PRECISION).round() / SCATTER_POSITION_PRECISION
+}
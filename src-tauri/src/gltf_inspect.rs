@@ -0,0 +1,937 @@
+// glTF model validation and statistics.
+// glTF 模型校验与统计
+//
+// EN: Bounds follow the runtime: each mesh's POSITION box is transformed by its node's world matrix, and every LOD is normalized by its own height.
+// 中文: 包围盒与运行时一致：每个网格的 POSITION 包围盒按节点世界矩阵变换，各级 LOD 分别按自身高度归一化。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const GLB_MAGIC: u32 = 0x4654_6c67;
+const GLB_CHUNK_JSON: u32 = 0x4e4f_534a;
+const GLB_CHUNK_BIN: u32 = 0x004e_4942;
+const GLTF_LOD1_MAX_TRIANGLE_RATIO: f64 = 0.6;
+const GLTF_LOD2_MAX_TRIANGLE_RATIO: f64 = 0.35;
+const GLTF_LOD_BOUNDS_TOLERANCE: f64 = 0.05;
+const GLTF_MIN_NORMALIZED_SCALE: f64 = 0.1;
+const GLTF_MAX_NORMALIZED_SCALE: f64 = 10.0;
+const GLTF_MIN_SOURCE_HEIGHT_METERS: f64 = 0.001;
+const GLTF_MAX_ZERO_ACCESSOR_ELEMENTS: usize = 1 << 24;
+const GLTF_TEXTURE_SLOTS: &[(&str, &[&str])] = &[
+    ("baseColor", &["pbrMetallicRoughness", "baseColorTexture"]),
+    (
+        "metallicRoughness",
+        &["pbrMetallicRoughness", "metallicRoughnessTexture"],
+    ),
+    ("normal", &["normalTexture"]),
+    ("occlusion", &["occlusionTexture"]),
+    ("emissive", &["emissiveTexture"]),
+];
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectGltfRequest {
+    path: String,
+    #[serde(default)]
+    lod1_path: Option<String>,
+    #[serde(default)]
+    lod2_path: Option<String>,
+    #[serde(default)]
+    target_height_meters: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GltfInspection {
+    levels: Vec<GltfLevelReport>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GltfLevelReport {
+    level: u8,
+    path: String,
+    generator: Option<String>,
+    triangle_count: u64,
+    vertex_count: u64,
+    draw_count: usize,
+    node_count: usize,
+    materials: Vec<GltfMaterialReport>,
+    images: Vec<GltfImageReport>,
+    missing_files: Vec<String>,
+    bounds_min: [f64; 3],
+    bounds_max: [f64; 3],
+    size: [f64; 3],
+    source_height_meters: f64,
+    normalized_scale: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GltfMaterialReport {
+    index: usize,
+    name: Option<String>,
+    alpha_mode: String,
+    double_sided: bool,
+    textures: Vec<GltfTextureSlot>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GltfTextureSlot {
+    slot: String,
+    texture: usize,
+    image: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GltfImageReport {
+    index: usize,
+    uri: Option<String>,
+    mime_type: Option<String>,
+    embedded: bool,
+    exists: bool,
+}
+
+/// A parsed glTF/GLB document with its binary buffers; external files that failed to load are listed in `missing_files`.
+/// 已解析的 glTF/GLB 文档及其二进制缓冲区；加载失败的外部文件列在 `missing_files` 中
+pub(crate) struct GltfAsset {
+    pub(crate) path: PathBuf,
+    pub(crate) document: Value,
+    pub(crate) buffers: Vec<Option<Vec<u8>>>,
+    pub(crate) missing_files: Vec<String>,
+}
+
+/// One mesh placed in the scene with its column-major world matrix.
+/// 场景中放置的一个网格及其列主序世界矩阵
+pub(crate) struct GltfMeshInstance {
+    pub(crate) mesh: usize,
+    pub(crate) matrix: [f64; 16],
+}
+
+impl GltfAsset {
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read glTF file: {}", e))?;
+        let (document, binary_chunk) = if read_u32(&bytes, 0) == Some(GLB_MAGIC) {
+            parse_glb(&bytes)?
+        } else {
+            let document = serde_json::from_slice(&bytes)
+                .map_err(|e| format!("Failed to parse glTF JSON: {}", e))?;
+            (document, None)
+        };
+        if document.pointer("/asset/version").and_then(Value::as_str) != Some("2.0") {
+            return Err("glTF asset.version must be \"2.0\"".to_string());
+        }
+
+        let mut asset = Self {
+            path: path.to_path_buf(),
+            document,
+            buffers: Vec::new(),
+            missing_files: Vec::new(),
+        };
+        let mut binary_chunk = binary_chunk;
+        for (index, buffer) in asset.array("buffers").to_vec().iter().enumerate() {
+            let data = match buffer.get("uri").and_then(Value::as_str) {
+                None if index == 0 => binary_chunk.take(),
+                None => None,
+                Some(uri) => match decode_data_uri(uri) {
+                    Some(data) => data,
+                    None => asset.resolve_uri(uri).and_then(|file| fs::read(file).ok()),
+                },
+            };
+            if data.is_none() {
+                let uri = buffer.get("uri").and_then(Value::as_str).unwrap_or("<glb>");
+                asset.missing_files.push(uri.to_string());
+            }
+            asset.buffers.push(data);
+        }
+        for image in asset.array("images").to_vec() {
+            let Some(uri) = image.get("uri").and_then(Value::as_str) else {
+                continue;
+            };
+            if uri.starts_with("data:") {
+                continue;
+            }
+            if !asset.resolve_uri(uri).is_some_and(|file| file.is_file()) {
+                asset.missing_files.push(uri.to_string());
+            }
+        }
+        Ok(asset)
+    }
+
+    pub(crate) fn array(&self, name: &str) -> &[Value] {
+        self.document
+            .get(name)
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Resolve a relative, percent-encoded URI against the glTF file's folder.
+    /// 将相对的百分号编码 URI 解析为相对 glTF 文件所在文件夹的路径
+    pub(crate) fn resolve_uri(&self, uri: &str) -> Option<PathBuf> {
+        if uri.contains("://") || uri.starts_with("data:") {
+            return None;
+        }
+        let decoded = percent_decode(uri)?;
+        Some(self.path.parent()?.join(decoded))
+    }
+
+    /// Read an accessor as f32 components, applying normalization for integer types.
+    /// 以 f32 分量读取访问器，整数类型按 normalized 标志归一化
+    pub(crate) fn read_accessor(&self, index: usize) -> Result<(Vec<f32>, usize), String> {
+        let accessor = self
+            .array("accessors")
+            .get(index)
+            .ok_or_else(|| format!("glTF accessor {} does not exist", index))?;
+        if accessor.get("sparse").is_some() {
+            return Err(format!("glTF accessor {} is sparse; not supported", index));
+        }
+        let count = accessor_count(accessor, index)?;
+        let components = match accessor.get("type").and_then(Value::as_str) {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") | Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            other => return Err(format!("glTF accessor {} has type {:?}", index, other)),
+        };
+        let component_type = accessor
+            .get("componentType")
+            .and_then(Value::as_u64)
+            .unwrap_or(0);
+        let component_size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            other => {
+                return Err(format!(
+                    "glTF accessor {} has componentType {}",
+                    index, other
+                ));
+            }
+        };
+        let normalized = accessor
+            .get("normalized")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        let Some(view_index) = accessor.get("bufferView").and_then(Value::as_u64) else {
+            // EN: Without a bufferView there is no data to bound the count, so zero-filled accessors get a fixed cap.
+            // 中文: 没有 bufferView 时无数据可约束 count，因此零填充访问器使用固定上限。
+            if count > GLTF_MAX_ZERO_ACCESSOR_ELEMENTS {
+                return Err(format!(
+                    "glTF accessor {} has {} zero-filled elements; at most {} are supported",
+                    index, count, GLTF_MAX_ZERO_ACCESSOR_ELEMENTS
+                ));
+            }
+            return Ok((vec![0.0; count * components], components));
+        };
+        let view_index = usize::try_from(view_index)
+            .map_err(|_| format!("glTF bufferView {} does not exist", view_index))?;
+        let data = self.buffer_view_bytes(view_index)?;
+        let element_size = component_size * components;
+        let stride = match self.array("bufferViews")[view_index]
+            .get("byteStride")
+            .and_then(Value::as_u64)
+        {
+            Some(stride) => usize::try_from(stride).unwrap_or(usize::MAX),
+            None => element_size,
+        };
+        if stride < element_size {
+            return Err(format!(
+                "glTF bufferView {} byteStride {} is smaller than accessor {} elements ({} bytes)",
+                view_index, stride, index, element_size
+            ));
+        }
+        let start = json_offset(accessor, "byteOffset")?;
+        // EN: The span is checked before allocating, so `count` is bounded by the bufferView length.
+        // 中文: 分配前先检查跨度，因此 `count` 受 bufferView 长度约束。
+        let end = match count.checked_sub(1) {
+            None => Some(start),
+            Some(last) => last
+                .checked_mul(stride)
+                .and_then(|span| span.checked_add(start))
+                .and_then(|span| span.checked_add(element_size)),
+        };
+        if end.is_none_or(|end| end > data.len()) {
+            return Err(format!(
+                "glTF accessor {} reads past the end of bufferView {}",
+                index, view_index
+            ));
+        }
+
+        let mut values = Vec::with_capacity(count * components);
+        for element in 0..count {
+            let base = start + element * stride;
+            for component in 0..components {
+                let at = base + component * component_size;
+                let bytes = &data[at..at + component_size];
+                let value = match component_type {
+                    5120 => component_value(bytes[0] as i8 as f32, 127.0, normalized),
+                    5121 => component_value(bytes[0] as f32, 255.0, normalized),
+                    5122 => component_value(
+                        i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+                        32767.0,
+                        normalized,
+                    ),
+                    5123 => component_value(
+                        u16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+                        65535.0,
+                        normalized,
+                    ),
+                    5125 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f32,
+                    _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+                };
+                values.push(value);
+            }
+        }
+        Ok((values, components))
+    }
+
+    /// Raw bytes of a bufferView.
+    /// bufferView 的原始字节
+    pub(crate) fn buffer_view_bytes(&self, index: usize) -> Result<&[u8], String> {
+        let view = self
+            .array("bufferViews")
+            .get(index)
+            .ok_or_else(|| format!("glTF bufferView {} does not exist", index))?;
+        let buffer_index = view.get("buffer").and_then(Value::as_u64).unwrap_or(0) as usize;
+        let buffer = self
+            .buffers
+            .get(buffer_index)
+            .and_then(Option::as_ref)
+            .ok_or_else(|| format!("glTF buffer {} is not loaded", buffer_index))?;
+        let offset = json_offset(view, "byteOffset")?;
+        let length = json_offset(view, "byteLength")?;
+        offset
+            .checked_add(length)
+            .and_then(|end| buffer.get(offset..end))
+            .ok_or_else(|| format!("glTF bufferView {} extends past its buffer", index))
+    }
+
+    /// Index list of a primitive; non-indexed primitives get a sequential list.
+    /// 图元的索引列表；无索引图元返回顺序索引
+    pub(crate) fn read_indices(&self, primitive: &Value) -> Result<Vec<u32>, String> {
+        if let Some(index) = primitive.get("indices").and_then(Value::as_u64) {
+            let accessor = self
+                .array("accessors")
+                .get(index as usize)
+                .ok_or_else(|| format!("glTF accessor {} does not exist", index))?;
+            if accessor.get("componentType").and_then(Value::as_u64) == Some(5125) {
+                return self.read_u32_accessor(index as usize);
+            }
+            let (values, _) = self.read_accessor(index as usize)?;
+            return Ok(values.into_iter().map(|value| value as u32).collect());
+        }
+        let count = primitive
+            .pointer("/attributes/POSITION")
+            .and_then(Value::as_u64)
+            .and_then(|position| {
+                let position = position as usize;
+                let accessor = self.array("accessors").get(position)?;
+                Some(accessor_count(accessor, position))
+            })
+            .transpose()?
+            .unwrap_or(0);
+        let count = u32::try_from(count)
+            .map_err(|_| format!("glTF primitive has {} vertices; too many to index", count))?;
+        Ok((0..count).collect())
+    }
+
+    /// Meshes reachable from the default scene (or every root node when there are no scenes).
+    /// Node graphs that revisit a node (cycles or shared children) are rejected.
+    /// 默认场景可达的网格（无场景时为所有根节点）；重复访问节点（循环或共享子节点）的节点图会被拒绝
+    pub(crate) fn mesh_instances(&self) -> Result<Vec<GltfMeshInstance>, String> {
+        let nodes = self.array("nodes");
+        let scene_index = self
+            .document
+            .get("scene")
+            .and_then(Value::as_u64)
+            .unwrap_or(0) as usize;
+        let roots: Vec<usize> = match self.array("scenes").get(scene_index) {
+            Some(scene) => scene
+                .get("nodes")
+                .and_then(Value::as_array)
+                .map(|roots| {
+                    roots
+                        .iter()
+                        .filter_map(Value::as_u64)
+                        .map(|root| root as usize)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            None => {
+                let mut is_child = vec![false; nodes.len()];
+                for node in nodes {
+                    for child in node_children(node) {
+                        if let Some(flag) = is_child.get_mut(child) {
+                            *flag = true;
+                        }
+                    }
+                }
+                (0..nodes.len()).filter(|index| !is_child[*index]).collect()
+            }
+        };
+
+        let mut instances = Vec::new();
+        let mut visited = vec![false; nodes.len()];
+        let mut pending: Vec<(usize, [f64; 16])> = roots
+            .into_iter()
+            .map(|root| (root, IDENTITY_MATRIX))
+            .collect();
+        while let Some((index, parent)) = pending.pop() {
+            let Some(node) = nodes.get(index) else {
+                continue;
+            };
+            // EN: glTF node hierarchies are disjoint trees; a second visit means a cycle or a shared child.
+            // 中文: glTF 节点层级是互不相交的树；再次访问意味着循环或共享子节点。
+            if std::mem::replace(&mut visited[index], true) {
+                return Err(format!(
+                    "glTF node {} is reachable more than once; node hierarchies must be trees",
+                    index
+                ));
+            }
+            let matrix = multiply_matrices(&parent, &node_local_matrix(node));
+            if let Some(mesh) = node.get("mesh").and_then(Value::as_u64) {
+                instances.push(GltfMeshInstance {
+                    mesh: mesh as usize,
+                    matrix,
+                });
+            }
+            for child in node_children(node) {
+                pending.push((child, matrix));
+            }
+        }
+        Ok(instances)
+    }
+
+    fn read_u32_accessor(&self, index: usize) -> Result<Vec<u32>, String> {
+        let accessor = &self.array("accessors")[index];
+        let count = accessor_count(accessor, index)?;
+        let view_index = accessor
+            .get("bufferView")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| format!("glTF index accessor {} has no bufferView", index))?;
+        let start = json_offset(accessor, "byteOffset")?;
+        let view = self.buffer_view_bytes(
+            usize::try_from(view_index)
+                .map_err(|_| format!("glTF bufferView {} does not exist", view_index))?,
+        )?;
+        let bytes = count
+            .checked_mul(4)
+            .and_then(|length| length.checked_add(start))
+            .and_then(|end| view.get(start..end))
+            .ok_or_else(|| {
+                format!(
+                    "glTF accessor {} reads past the end of its bufferView",
+                    index
+                )
+            })?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+}
+
+// --- glTF inspection commands / glTF 检查命令 ---
+
+/// Inspect a glTF model and its LOD variants: triangle/vertex counts, materials, textures, missing files and bounds.
+/// 检查 glTF 模型及其 LOD 变体：三角形/顶点数、材质、纹理、缺失文件与包围盒
+#[tauri::command]
+pub async fn inspect_gltf(request: InspectGltfRequest) -> Result<GltfInspection, String> {
+    tauri::async_runtime::spawn_blocking(move || inspect_gltf_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join glTF inspection task: {}", e))?
+}
+
+fn inspect_gltf_blocking(request: InspectGltfRequest) -> Result<GltfInspection, String> {
+    let path = PathBuf::from(&request.path);
+    let target_height = request
+        .target_height_meters
+        .filter(|height| height.is_finite() && *height > 0.0);
+    let mut warnings = Vec::new();
+    let mut levels = vec![inspect_level(0, &path, target_height)?];
+
+    for (level, explicit) in [(1u8, &request.lod1_path), (2u8, &request.lod2_path)] {
+        let lod_path = match explicit {
+            Some(lod_path) => Some(PathBuf::from(lod_path)),
+            None => discover_lod_path(&path, level),
+        };
+        let Some(lod_path) = lod_path else {
+            continue;
+        };
+        match inspect_level(level, &lod_path, target_height) {
+            Ok(report) => levels.push(report),
+            Err(error) => warnings.push(format!("LOD{} could not be read: {}", level, error)),
+        }
+    }
+
+    collect_warnings(&levels, target_height, &mut warnings);
+    Ok(GltfInspection { levels, warnings })
+}
+
+fn inspect_level(
+    level: u8,
+    path: &Path,
+    target_height: Option<f64>,
+) -> Result<GltfLevelReport, String> {
+    let asset = GltfAsset::load(path)?;
+    let accessors = asset.array("accessors");
+    let meshes = asset.array("meshes");
+
+    let mut triangle_count = 0u64;
+    let mut vertex_count = 0u64;
+    let mut draw_count = 0usize;
+    let mut bounds_min = [f64::INFINITY; 3];
+    let mut bounds_max = [f64::NEG_INFINITY; 3];
+    for instance in asset.mesh_instances()? {
+        let Some(mesh) = meshes.get(instance.mesh) else {
+            continue;
+        };
+        for primitive in mesh
+            .get("primitives")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+        {
+            draw_count += 1;
+            let Some(position) = primitive
+                .pointer("/attributes/POSITION")
+                .and_then(Value::as_u64)
+            else {
+                continue;
+            };
+            let position_count = accessors
+                .get(position as usize)
+                .and_then(|accessor| accessor.get("count"))
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let index_count = primitive
+                .get("indices")
+                .and_then(Value::as_u64)
+                .and_then(|index| accessors.get(index as usize))
+                .and_then(|accessor| accessor.get("count"))
+                .and_then(Value::as_u64)
+                .unwrap_or(position_count);
+            vertex_count += position_count;
+            triangle_count += match primitive.get("mode").and_then(Value::as_u64).unwrap_or(4) {
+                4 => index_count / 3,
+                5 | 6 => index_count.saturating_sub(2),
+                _ => 0,
+            };
+
+            let (local_min, local_max) = position_bounds(&asset, position as usize)?;
+            for corner in 0..8 {
+                let point = [
+                    if corner & 1 == 0 {
+                        local_min[0]
+                    } else {
+                        local_max[0]
+                    },
+                    if corner & 2 == 0 {
+                        local_min[1]
+                    } else {
+                        local_max[1]
+                    },
+                    if corner & 4 == 0 {
+                        local_min[2]
+                    } else {
+                        local_max[2]
+                    },
+                ];
+                let world = transform_point(&instance.matrix, point);
+                for axis in 0..3 {
+                    bounds_min[axis] = bounds_min[axis].min(world[axis]);
+                    bounds_max[axis] = bounds_max[axis].max(world[axis]);
+                }
+            }
+        }
+    }
+    if triangle_count == 0 {
+        bounds_min = [0.0; 3];
+        bounds_max = [0.0; 3];
+    }
+    let size = [
+        bounds_max[0] - bounds_min[0],
+        bounds_max[1] - bounds_min[1],
+        bounds_max[2] - bounds_min[2],
+    ];
+    let source_height_meters = size[1].max(GLTF_MIN_SOURCE_HEIGHT_METERS);
+
+    let textures = asset.array("textures");
+    let materials = asset
+        .array("materials")
+        .iter()
+        .enumerate()
+        .map(|(index, material)| GltfMaterialReport {
+            index,
+            name: material
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            alpha_mode: material
+                .get("alphaMode")
+                .and_then(Value::as_str)
+                .unwrap_or("OPAQUE")
+                .to_string(),
+            double_sided: material
+                .get("doubleSided")
+                .and_then(Value::as_bool)
+                .unwrap_or(false),
+            textures: GLTF_TEXTURE_SLOTS
+                .iter()
+                .filter_map(|(slot, keys)| {
+                    let texture = keys
+                        .iter()
+                        .try_fold(material, |value, key| value.get(*key))?
+                        .get("index")
+                        .and_then(Value::as_u64)? as usize;
+                    Some(GltfTextureSlot {
+                        slot: slot.to_string(),
+                        texture,
+                        image: textures
+                            .get(texture)
+                            .and_then(|texture| texture.get("source"))
+                            .and_then(Value::as_u64)
+                            .map(|image| image as usize),
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+    let images = asset
+        .array("images")
+        .iter()
+        .enumerate()
+        .map(|(index, image)| {
+            let uri = image.get("uri").and_then(Value::as_str);
+            let embedded = uri.is_none_or(|uri| uri.starts_with("data:"));
+            GltfImageReport {
+                index,
+                uri: uri.map(str::to_string),
+                mime_type: image
+                    .get("mimeType")
+                    .and_then(Value::as_str)
+                    .map(str::to_string),
+                embedded,
+                exists: embedded
+                    || uri
+                        .and_then(|uri| asset.resolve_uri(uri))
+                        .is_some_and(|file| file.is_file()),
+            }
+        })
+        .collect();
+
+    Ok(GltfLevelReport {
+        level,
+        path: path.to_string_lossy().to_string(),
+        generator: asset
+            .document
+            .pointer("/asset/generator")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        triangle_count,
+        vertex_count,
+        draw_count,
+        node_count: asset.array("nodes").len(),
+        materials,
+        images,
+        missing_files: asset.missing_files,
+        bounds_min,
+        bounds_max,
+        size,
+        source_height_meters,
+        normalized_scale: target_height.map(|height| height / source_height_meters),
+    })
+}
+
+fn collect_warnings(
+    levels: &[GltfLevelReport],
+    target_height: Option<f64>,
+    warnings: &mut Vec<String>,
+) {
+    let base = &levels[0];
+    for level in levels {
+        let label = format!("LOD{}", level.level);
+        if level.triangle_count == 0 {
+            warnings.push(format!("{} has no triangles", label));
+        }
+        for missing in &level.missing_files {
+            warnings.push(format!("{} references missing file '{}'", label, missing));
+        }
+        if let Some(scale) = level.normalized_scale
+            && !(GLTF_MIN_NORMALIZED_SCALE..=GLTF_MAX_NORMALIZED_SCALE).contains(&scale)
+        {
+            warnings.push(format!(
+                "{} is {:.3} m tall but targetHeightMeters is {:.3}; scale {:.3} suggests the model uses different units",
+                label,
+                level.source_height_meters,
+                target_height.unwrap_or_default(),
+                scale
+            ));
+        }
+        if level.level == 0 {
+            continue;
+        }
+
+        let max_ratio = if level.level == 1 {
+            GLTF_LOD1_MAX_TRIANGLE_RATIO
+        } else {
+            GLTF_LOD2_MAX_TRIANGLE_RATIO
+        };
+        let ratio = level.triangle_count as f64 / base.triangle_count.max(1) as f64;
+        if ratio > max_ratio {
+            warnings.push(format!(
+                "{} keeps {:.0}% of LOD0 triangles ({} of {}); expected at most {:.0}%",
+                label,
+                ratio * 100.0,
+                level.triangle_count,
+                base.triangle_count,
+                max_ratio * 100.0
+            ));
+        }
+        if let Some(previous) = levels.iter().find(|other| other.level + 1 == level.level)
+            && level.level > 1
+            && level.triangle_count >= previous.triangle_count
+        {
+            warnings.push(format!(
+                "{} has no fewer triangles than LOD{} ({} >= {})",
+                label, previous.level, level.triangle_count, previous.triangle_count
+            ));
+        }
+
+        // EN: Each LOD is scaled to targetHeightMeters by its own height, so width/depth drift shows up as popping.
+        // 中文: 每级 LOD 都按自身高度缩放到 targetHeightMeters，宽度/深度偏差会表现为切换跳变。
+        for (axis, name) in [(0, "width"), (1, "height"), (2, "depth")] {
+            let reference = base.size[axis].max(GLTF_MIN_SOURCE_HEIGHT_METERS);
+            let difference = (level.size[axis] - base.size[axis]).abs() / reference;
+            if difference > GLTF_LOD_BOUNDS_TOLERANCE {
+                warnings.push(format!(
+                    "{} bounds {} differs from LOD0 by {:.0}% ({:.3} vs {:.3})",
+                    label,
+                    name,
+                    difference * 100.0,
+                    level.size[axis],
+                    base.size[axis]
+                ));
+            }
+        }
+    }
+}
+
+/// LOD files follow the `lod1/` and `lod2/` folder convention next to the source model.
+/// LOD 文件遵循源模型旁 `lod1/` 与 `lod2/` 文件夹的约定
+pub(crate) fn discover_lod_path(path: &Path, level: u8) -> Option<PathBuf> {
+    let directory = path.parent()?.join(format!("lod{}", level));
+    let mut candidates: Vec<PathBuf> = fs::read_dir(directory)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|candidate| {
+            candidate
+                .extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    extension.eq_ignore_ascii_case("gltf") || extension.eq_ignore_ascii_case("glb")
+                })
+        })
+        .collect();
+    candidates.sort();
+    candidates.into_iter().next()
+}
+
+fn position_bounds(asset: &GltfAsset, accessor: usize) -> Result<([f64; 3], [f64; 3]), String> {
+    let declared = asset.array("accessors").get(accessor).and_then(|value| {
+        let read = |key: &str| -> Option<[f64; 3]> {
+            let values = value.get(key)?.as_array()?;
+            Some([
+                values.first()?.as_f64()?,
+                values.get(1)?.as_f64()?,
+                values.get(2)?.as_f64()?,
+            ])
+        };
+        Some((read("min")?, read("max")?))
+    });
+    if let Some(bounds) = declared {
+        return Ok(bounds);
+    }
+
+    let (values, components) = asset.read_accessor(accessor)?;
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for point in values.chunks_exact(components.max(1)) {
+        for axis in 0..3.min(components) {
+            min[axis] = min[axis].min(point[axis] as f64);
+            max[axis] = max[axis].max(point[axis] as f64);
+        }
+    }
+    Ok((min, max))
+}
+
+fn parse_glb(bytes: &[u8]) -> Result<(Value, Option<Vec<u8>>), String> {
+    if read_u32(bytes, 4) != Some(2) {
+        return Err("Unsupported GLB container version".to_string());
+    }
+    let total = (read_u32(bytes, 8).unwrap_or(0) as usize).min(bytes.len());
+    let mut offset = 12;
+    let mut document = None;
+    let mut binary = None;
+    while offset + 8 <= total {
+        let length = read_u32(bytes, offset).unwrap_or(0) as usize;
+        let kind = read_u32(bytes, offset + 4).unwrap_or(0);
+        let chunk = bytes
+            .get(offset + 8..offset + 8 + length)
+            .ok_or_else(|| "GLB chunk extends past the end of the file".to_string())?;
+        match kind {
+            GLB_CHUNK_JSON => {
+                document = Some(
+                    serde_json::from_slice(chunk)
+                        .map_err(|e| format!("Failed to parse GLB JSON chunk: {}", e))?,
+                )
+            }
+            GLB_CHUNK_BIN => binary = Some(chunk.to_vec()),
+            _ => {}
+        }
+        offset += 8 + length;
+    }
+    Ok((
+        document.ok_or_else(|| "GLB file has no JSON chunk".to_string())?,
+        binary,
+    ))
+}
+
+fn decode_data_uri(uri: &str) -> Option<Option<Vec<u8>>> {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+    let rest = uri.strip_prefix("data:")?;
+    let (_, payload) = rest.split_once(";base64,")?;
+    Some(STANDARD.decode(payload).ok())
+}
+
+fn percent_decode(uri: &str) -> Option<String> {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Accessor element count as `usize`.
+/// 访问器元素数量（`usize`）
+fn accessor_count(accessor: &Value, index: usize) -> Result<usize, String> {
+    let count = accessor.get("count").and_then(Value::as_u64).unwrap_or(0);
+    usize::try_from(count)
+        .map_err(|_| format!("glTF accessor {} count {} is too large", index, count))
+}
+
+/// Byte offset or length field as `usize`; absent fields are 0.
+/// 字节偏移或长度字段（`usize`）；缺省为 0
+fn json_offset(value: &Value, field: &str) -> Result<usize, String> {
+    let offset = value.get(field).and_then(Value::as_u64).unwrap_or(0);
+    usize::try_from(offset).map_err(|_| format!("glTF {} {} is too large", field, offset))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let slice = bytes.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]]))
+}
+
+fn component_value(value: f32, max: f32, normalized: bool) -> f32 {
+    if normalized {
+        (value / max).max(-1.0)
+    } else {
+        value
+    }
+}
+
+fn node_children(node: &Value) -> Vec<usize> {
+    node.get("children")
+        .and_then(Value::as_array)
+        .map(|children| {
+            children
+                .iter()
+                .filter_map(Value::as_u64)
+                .map(|child| child as usize)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+const IDENTITY_MATRIX: [f64; 16] = [
+    1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
+];
+
+/// Column-major local matrix from `matrix` or translation/rotation/scale.
+/// 由 `matrix` 或平移/旋转/缩放得到的列主序局部矩阵
+fn node_local_matrix(node: &Value) -> [f64; 16] {
+    let read = |key: &str, default: &[f64]| -> Vec<f64> {
+        node.get(key)
+            .and_then(Value::as_array)
+            .map(|values| values.iter().filter_map(Value::as_f64).collect::<Vec<_>>())
+            .filter(|values| values.len() == default.len())
+            .unwrap_or_else(|| default.to_vec())
+    };
+    let matrix = read("matrix", &IDENTITY_MATRIX);
+    if node.get("matrix").is_some() {
+        let mut out = IDENTITY_MATRIX;
+        out.copy_from_slice(&matrix);
+        return out;
+    }
+
+    let t = read("translation", &[0.0, 0.0, 0.0]);
+    let r = read("rotation", &[0.0, 0.0, 0.0, 1.0]);
+    let s = read("scale", &[1.0, 1.0, 1.0]);
+    let (x, y, z, w) = (r[0], r[1], r[2], r[3]);
+    [
+        (1.0 - 2.0 * (y * y + z * z)) * s[0],
+        (2.0 * (x * y + z * w)) * s[0],
+        (2.0 * (x * z - y * w)) * s[0],
+        0.0,
+        (2.0 * (x * y - z * w)) * s[1],
+        (1.0 - 2.0 * (x * x + z * z)) * s[1],
+        (2.0 * (y * z + x * w)) * s[1],
+        0.0,
+        (2.0 * (x * z + y * w)) * s[2],
+        (2.0 * (y * z - x * w)) * s[2],
+        (1.0 - 2.0 * (x * x + y * y)) * s[2],
+        0.0,
+        t[0],
+        t[1],
+        t[2],
+        1.0,
+    ]
+}
+
+fn multiply_matrices(a: &[f64; 16], b: &[f64; 16]) -> [f64; 16] {
+    let mut out = [0.0; 16];
+    for column in 0..4 {
+        for row in 0..4 {
+            out[column * 4 + row] = (0..4).map(|k| a[k * 4 + row] * b[column * 4 + k]).sum();
+        }
+    }
+    out
+}
+
+pub(crate) fn transform_point(matrix: &[f64; 16], point: [f64; 3]) -> [f64; 3] {
+    [
+        matrix[0] * point[0] + matrix[4] * point[1] + matrix[8] * point[2] + matrix[12],
+        matrix[1] * point[0] + matrix[5] * point[1] + matrix[9] * point[2] + matrix[13],
+        matrix[2] * point[0] + matrix[6] * point[1] + matrix[10] * point[2] + matrix[14],
+    ]
+}
@@ -0,0 +1,464 @@
+// Native height-region-pack-v1 codec with page creation.
+// 原生 height-region-pack-v1 编解码，支持创建页面
+//
+// EN: Mirrors src/workspace/TerrainHeightData.ts: a region stores float32le pages in mask bit order; sample (0, 0) sits at the page's minimum corner and edges are shared with neighbours.
+// 中文: 与 src/workspace/TerrainHeightData.ts 一致：区域按掩码位顺序存储 float32le 页面；样本 (0, 0) 位于页面最小角，边缘与相邻页面共享。
+
+use crate::commands::{MAPS_DIR, recover_safe_write, safe_write, validate_single_path_segment};
+use crate::map_layout::{
+    HEIGHT_REGION_FORMAT, HEIGHT_REGIONS_DIRECTORY, HEIGHT_SAMPLE_FORMAT, TERRAIN_HEIGHT_PATH,
+    compare_grid_keys, format_grid_key, parse_grid_key, region_file_name, sha256_hex,
+};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HEIGHT_REGION_EXTENSION: &str = "heightpack";
+const MAX_HEIGHT_REGION_SIZE_PAGES: i32 = 8;
+const HEIGHT_SAMPLE_BYTES: usize = 4;
+
+/// Storage layout shared by every height region pack of one map.
+/// 同一地图所有高度区域包共享的存储布局
+pub(crate) struct HeightPackLayout {
+    pub(crate) page_resolution: usize,
+    pub(crate) page_size_meters: f64,
+    pub(crate) region_size_pages: i32,
+}
+
+/// One decoded height region pack, keyed by local page index.
+/// 一个已解码的高度区域包，按局部页面索引存储
+struct HeightRegionPack {
+    pages: BTreeMap<u32, Vec<f32>>,
+}
+
+/// Height manifest plus lazily loaded region packs for one map.
+/// 单张地图的高度清单及按需加载的区域包
+pub(crate) struct HeightPackSession {
+    map_root: PathBuf,
+    document: Value,
+    pub(crate) layout: HeightPackLayout,
+    region_masks: BTreeMap<String, u64>,
+    packs: BTreeMap<String, HeightRegionPack>,
+    dirty_regions: BTreeSet<String>,
+}
+
+impl HeightPackLayout {
+    pub(crate) fn page_sample_count(&self) -> usize {
+        self.page_resolution * self.page_resolution
+    }
+
+    /// Distance in meters between neighbouring samples.
+    /// 相邻样本之间的距离（米）
+    pub(crate) fn sample_spacing_meters(&self) -> f64 {
+        self.page_size_meters / (self.page_resolution - 1) as f64
+    }
+
+    pub(crate) fn region_coords_for_page(&self, px: i32, pz: i32) -> (i32, i32) {
+        (
+            px.div_euclid(self.region_size_pages),
+            pz.div_euclid(self.region_size_pages),
+        )
+    }
+
+    pub(crate) fn local_page_index(&self, px: i32, pz: i32) -> u32 {
+        let size = self.region_size_pages;
+        (pz.rem_euclid(size) * size + px.rem_euclid(size)) as u32
+    }
+
+    /// Bilinear height inside page (`px`, `pz`) at a world position clamped to the page.
+    /// 在页面 (`px`, `pz`) 内按世界坐标（限制在页面范围内）计算双线性高度
+    pub(crate) fn sample_page(&self, heights: &[f32], px: i32, pz: i32, x: f64, z: f64) -> f32 {
+        let page_size = self.page_size_meters;
+        let spacing = self.sample_spacing_meters();
+        let resolution = self.page_resolution;
+
+        // EN: Edges are shared between pages, so one page always holds all four bilinear samples.
+        // 中文: 页面之间共享边缘，因此单个页面总是包含双线性所需的四个样本。
+        let last = (resolution - 1) as f64;
+        let local_x = ((x - px as f64 * page_size) / spacing).clamp(0.0, last);
+        let local_z = ((z - pz as f64 * page_size) / spacing).clamp(0.0, last);
+        let x0 = (local_x.floor() as usize).min(resolution - 2);
+        let z0 = (local_z.floor() as usize).min(resolution - 2);
+        let tx = (local_x - x0 as f64) as f32;
+        let tz = (local_z - z0 as f64) as f32;
+
+        let value = |column: usize, row: usize| heights[row * resolution + column];
+        let top = value(x0, z0) + (value(x0 + 1, z0) - value(x0, z0)) * tx;
+        let bottom = value(x0, z0 + 1) + (value(x0 + 1, z0 + 1) - value(x0, z0 + 1)) * tx;
+        top + (bottom - top) * tz
+    }
+}
+
+/// Page mask written as a `0x`-prefixed hexadecimal string.
+/// 以 `0x` 前缀十六进制字符串写出的页面掩码
+pub(crate) fn parse_region_mask(value: &Value) -> Option<u64> {
+    value
+        .as_str()
+        .and_then(|mask| mask.strip_prefix("0x"))
+        .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+}
+
+/// Decode region pack bytes into pages keyed by local page index.
+/// 将区域包字节解码为按局部页面索引存储的页面
+pub(crate) fn decode_region_pages(
+    layout: &HeightPackLayout,
+    region_key: &str,
+    mask: u64,
+    bytes: &[u8],
+) -> Result<BTreeMap<u32, Vec<f32>>, String> {
+    HeightRegionPack::decode(layout, region_key, mask, bytes).map(|pack| pack.pages)
+}
+
+impl HeightRegionPack {
+    fn decode(
+        layout: &HeightPackLayout,
+        region_key: &str,
+        mask: u64,
+        bytes: &[u8],
+    ) -> Result<Self, String> {
+        let page_byte_length = layout.page_sample_count() * HEIGHT_SAMPLE_BYTES;
+        let expected_byte_length = mask.count_ones() as usize * page_byte_length;
+        if bytes.len() != expected_byte_length {
+            return Err(format!(
+                "Height region pack '{}' requires {} bytes, got {}",
+                region_key,
+                expected_byte_length,
+                bytes.len()
+            ));
+        }
+
+        let pages = (0..64u32)
+            .filter(|local_index| mask & (1u64 << local_index) != 0)
+            .zip(bytes.chunks_exact(page_byte_length))
+            .map(|(local_index, page_bytes)| {
+                let heights = page_bytes
+                    .chunks_exact(HEIGHT_SAMPLE_BYTES)
+                    .map(|sample| f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]))
+                    .collect();
+                (local_index, heights)
+            })
+            .collect();
+
+        Ok(Self { pages })
+    }
+
+    fn mask(&self) -> u64 {
+        self.pages
+            .keys()
+            .fold(0u64, |mask, local_index| mask | (1u64 << local_index))
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        self.pages
+            .values()
+            .flat_map(|heights| heights.iter().flat_map(|height| height.to_le_bytes()))
+            .collect()
+    }
+}
+
+impl HeightPackSession {
+    pub(crate) fn open(project_root: &Path, map_id: &str) -> Result<Self, String> {
+        validate_single_path_segment(map_id, "map_id")?;
+        let map_root = project_root.join(MAPS_DIR).join(map_id);
+        let manifest_path = map_root.join(TERRAIN_HEIGHT_PATH);
+        recover_safe_write(&manifest_path)?;
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read terrain height manifest: {}", e))?;
+        let document: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse terrain height manifest: {}", e))?;
+
+        if document["format"].as_str() != Some(HEIGHT_REGION_FORMAT) {
+            return Err("Terrain height manifest has invalid region pack format".to_string());
+        }
+        if document["sampleFormat"].as_str() != Some(HEIGHT_SAMPLE_FORMAT) {
+            return Err("Terrain height manifest has invalid height sample format".to_string());
+        }
+
+        let layout = HeightPackLayout {
+            page_resolution: document["pageResolution"]
+                .as_u64()
+                .filter(|resolution| *resolution >= 2)
+                .ok_or_else(|| "Terrain height page resolution must be at least 2".to_string())?
+                as usize,
+            page_size_meters: document["pageSizeMeters"]
+                .as_f64()
+                .filter(|size| size.is_finite() && *size > 0.0)
+                .ok_or_else(|| "Terrain height page size must be a positive number".to_string())?,
+            region_size_pages: document["regionSizePages"]
+                .as_u64()
+                .filter(|size| *size > 0 && *size <= MAX_HEIGHT_REGION_SIZE_PAGES as u64)
+                .ok_or_else(|| "Terrain height region size must be between 1 and 8".to_string())?
+                as i32,
+        };
+
+        let mut region_masks = BTreeMap::new();
+        if let Some(regions) = document["regions"].as_object() {
+            for (key, mask) in regions {
+                if parse_grid_key(key).is_none() {
+                    return Err(format!("Invalid height region key '{}'", key));
+                }
+
+                let mask = parse_region_mask(mask)
+                    .ok_or_else(|| format!("Height region '{}' has an invalid mask", key))?;
+                region_masks.insert(key.clone(), mask);
+            }
+        }
+
+        Ok(Self {
+            map_root,
+            document,
+            layout,
+            region_masks,
+            packs: BTreeMap::new(),
+            dirty_regions: BTreeSet::new(),
+        })
+    }
+
+    /// Whether a page is stored, including pages created in this session.
+    /// 页面是否已存储，包括本次会话中创建的页面
+    pub(crate) fn has_page(&self, px: i32, pz: i32) -> bool {
+        let (region_x, region_z) = self.layout.region_coords_for_page(px, pz);
+        let region_key = format_grid_key(region_x, region_z);
+        let local_index = self.layout.local_page_index(px, pz);
+        match self.packs.get(&region_key) {
+            Some(pack) => pack.pages.contains_key(&local_index),
+            None => self
+                .region_masks
+                .get(&region_key)
+                .is_some_and(|mask| mask & (1u64 << local_index) != 0),
+        }
+    }
+
+    /// Heights of a stored page in row-major order, or `None` when the page does not exist.
+    /// 已存储页面的行优先高度；页面不存在时返回 `None`
+    pub(crate) fn page_heights(&mut self, px: i32, pz: i32) -> Result<Option<&[f32]>, String> {
+        if !self.has_page(px, pz) {
+            return Ok(None);
+        }
+
+        let region_key = self.load_region_for_page(px, pz)?;
+        let local_index = self.layout.local_page_index(px, pz);
+        Ok(self.packs[&region_key]
+            .pages
+            .get(&local_index)
+            .map(Vec::as_slice))
+    }
+
+    /// Bilinear terrain height at a world position, or `None` over a missing page.
+    /// 世界坐标处的双线性地形高度；位于缺失页面上时返回 `None`
+    pub(crate) fn height_at(&mut self, x: f64, z: f64) -> Result<Option<f32>, String> {
+        let page_size = self.layout.page_size_meters;
+        let px = (x / page_size).floor() as i32;
+        let pz = (z / page_size).floor() as i32;
+        if self.has_page(px, pz) {
+            self.load_region_for_page(px, pz)?;
+        }
+        Ok(self.loaded_height_at(x, z))
+    }
+
+    /// Load every stored region overlapping a world rectangle so `loaded_height_at` can sample it.
+    /// 加载与世界矩形相交的所有已存储区域，使 `loaded_height_at` 可以采样
+    pub(crate) fn load_regions_in_rect(
+        &mut self,
+        min_x: f64,
+        min_z: f64,
+        max_x: f64,
+        max_z: f64,
+    ) -> Result<(), String> {
+        let page_size = self.layout.page_size_meters;
+        let (min_region_x, min_region_z) = self.layout.region_coords_for_page(
+            (min_x / page_size).floor() as i32,
+            (min_z / page_size).floor() as i32,
+        );
+        let (max_region_x, max_region_z) = self.layout.region_coords_for_page(
+            (max_x / page_size).floor() as i32,
+            (max_z / page_size).floor() as i32,
+        );
+        let size = self.layout.region_size_pages;
+        for region_z in min_region_z..=max_region_z {
+            for region_x in min_region_x..=max_region_x {
+                if self
+                    .region_masks
+                    .contains_key(&format_grid_key(region_x, region_z))
+                {
+                    self.load_region_for_page(region_x * size, region_z * size)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Bilinear height from already loaded regions only; lets worker threads share one session.
+    /// 仅从已加载区域读取双线性高度；使工作线程可以共享同一个会话
+    pub(crate) fn loaded_height_at(&self, x: f64, z: f64) -> Option<f32> {
+        let page_size = self.layout.page_size_meters;
+        let px = (x / page_size).floor() as i32;
+        let pz = (z / page_size).floor() as i32;
+        let (region_x, region_z) = self.layout.region_coords_for_page(px, pz);
+        let heights = self
+            .packs
+            .get(&format_grid_key(region_x, region_z))?
+            .pages
+            .get(&self.layout.local_page_index(px, pz))?;
+        Some(self.layout.sample_page(heights, px, pz, x, z))
+    }
+
+    /// Mutable heights of a page, creating a zero-filled page when `create` is set.
+    /// The page's region is marked for saving.
+    /// 页面的可变高度；设置 `create` 时创建零填充页面。页面所在区域会被标记为待保存
+    pub(crate) fn page_heights_mut(
+        &mut self,
+        px: i32,
+        pz: i32,
+        create: bool,
+    ) -> Result<Option<&mut Vec<f32>>, String> {
+        if !create && !self.has_page(px, pz) {
+            return Ok(None);
+        }
+
+        let region_key = self.load_region_for_page(px, pz)?;
+        let local_index = self.layout.local_page_index(px, pz);
+        let sample_count = self.layout.page_sample_count();
+        self.dirty_regions.insert(region_key.clone());
+        let pack = self
+            .packs
+            .get_mut(&region_key)
+            .ok_or_else(|| format!("Height region pack '{}' is not loaded", region_key))?;
+        Ok(Some(
+            pack.pages
+                .entry(local_index)
+                .or_insert_with(|| vec![0.0; sample_count]),
+        ))
+    }
+
+    fn load_region_for_page(&mut self, px: i32, pz: i32) -> Result<String, String> {
+        let (region_x, region_z) = self.layout.region_coords_for_page(px, pz);
+        let region_key = format_grid_key(region_x, region_z);
+        if self.packs.contains_key(&region_key) {
+            return Ok(region_key);
+        }
+
+        let pack = match self.region_masks.get(&region_key) {
+            Some(&mask) => {
+                let path = self.region_path(region_x, region_z);
+                recover_safe_write(&path)?;
+                let bytes = fs::read(&path).map_err(|e| {
+                    format!("Failed to read height region pack '{}': {}", region_key, e)
+                })?;
+                HeightRegionPack::decode(&self.layout, &region_key, mask, &bytes)?
+            }
+            None => HeightRegionPack {
+                pages: BTreeMap::new(),
+            },
+        };
+        self.packs.insert(region_key.clone(), pack);
+        Ok(region_key)
+    }
+
+    /// Write changed packs and update masks, integrity and the base patch layer in the manifest.
+    /// 写入已修改的区域包，并更新清单中的掩码、完整性与基础 patch 层
+    pub(crate) fn save(mut self) -> Result<Vec<String>, String> {
+        let mut dirty_regions: Vec<String> = self.dirty_regions.iter().cloned().collect();
+        dirty_regions.sort_by(|left, right| compare_grid_keys(left, right));
+        if dirty_regions.is_empty() {
+            return Ok(dirty_regions);
+        }
+
+        let mut integrity = Map::new();
+        let mut new_regions = Vec::new();
+        for region_key in &dirty_regions {
+            let (region_x, region_z) = parse_grid_key(region_key)
+                .ok_or_else(|| format!("Invalid height region key '{}'", region_key))?;
+            let pack = &self.packs[region_key];
+            let bytes = pack.encode();
+            safe_write(&self.region_path(region_x, region_z), &bytes).map_err(|e| {
+                format!("Failed to save height region pack '{}': {}", region_key, e)
+            })?;
+
+            if self
+                .region_masks
+                .insert(region_key.clone(), pack.mask())
+                .is_none()
+            {
+                new_regions.push(region_key.clone());
+            }
+            integrity.insert(
+                region_key.clone(),
+                json!({ "byteLength": bytes.len(), "sha256": sha256_hex(&bytes) }),
+            );
+        }
+
+        self.update_manifest_regions(integrity, &new_regions);
+        let mut content = serde_json::to_string_pretty(&self.document)
+            .map_err(|e| format!("Failed to serialize terrain height manifest: {}", e))?;
+        content.push('\n');
+        safe_write(&self.map_root.join(TERRAIN_HEIGHT_PATH), content.as_bytes())
+            .map_err(|e| format!("Failed to save terrain height manifest: {}", e))?;
+
+        Ok(dirty_regions)
+    }
+
+    fn update_manifest_regions(&mut self, integrity: Map<String, Value>, new_regions: &[String]) {
+        // EN: Rebuild both maps in region order so new regions land where the Node cook would write them.
+        // 中文: 按区域顺序重建两个映射，使新区域的位置与 Node cook 写出的一致。
+        let mut region_keys: Vec<String> = self.region_masks.keys().cloned().collect();
+        region_keys.sort_by(|left, right| compare_grid_keys(left, right));
+        let previous_integrity = self.document["regionIntegrity"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+
+        let mut regions = Map::new();
+        let mut region_integrity = Map::new();
+        for key in &region_keys {
+            regions.insert(
+                key.clone(),
+                Value::String(format!("0x{:016x}", self.region_masks[key])),
+            );
+            if let Some(entry) = integrity.get(key).or_else(|| previous_integrity.get(key)) {
+                region_integrity.insert(key.clone(), entry.clone());
+            }
+        }
+        self.document["regions"] = Value::Object(regions);
+        self.document["regionIntegrity"] = Value::Object(region_integrity);
+
+        if new_regions.is_empty() {
+            return;
+        }
+        let Some(layers) = self.document["patchLayers"]["layers"].as_array_mut() else {
+            return;
+        };
+        let Some(base_layer) = layers
+            .iter_mut()
+            .find(|layer| layer["kind"].as_str() == Some("base"))
+        else {
+            return;
+        };
+
+        let mut base_regions: Vec<String> = base_layer["regions"]
+            .as_array()
+            .map(|regions| {
+                regions
+                    .iter()
+                    .filter_map(|region| region.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        base_regions.extend(new_regions.iter().cloned());
+        base_regions.sort_by(|left, right| compare_grid_keys(left, right));
+        base_regions.dedup();
+        base_layer["regions"] = json!(base_regions);
+    }
+
+    fn region_path(&self, region_x: i32, region_z: i32) -> PathBuf {
+        self.map_root
+            .join(HEIGHT_REGIONS_DIRECTORY)
+            .join(region_file_name(
+                region_x,
+                region_z,
+                HEIGHT_REGION_EXTENSION,
+            ))
+    }
+}
@@ -0,0 +1,507 @@
+// Heightmap import and export between 16-bit PNG/RAW files and height region packs.
+// 在 16 位 PNG/RAW 文件与高度区域包之间导入和导出高度图
+//
+// EN: Image grids are corner-aligned: first and last samples sit exactly on the world rectangle edges, row 0 at minimum Z.
+// 中文: 图像网格按角点对齐：首尾样本正好位于世界矩形边缘，第 0 行位于最小 Z。
+
+use crate::commands::{
+    MAPS_DIR, decode_png_rgba_samples, encode_png, read_map_world_size, safe_write,
+    validate_cook_project_path,
+};
+use crate::height_pack::HeightPackSession;
+use crate::map_layout::{WorldRect, format_grid_key, parse_grid_key};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const HEIGHTMAP_IMPORT_MAX_PAGES: u64 = 4096;
+const HEIGHTMAP_EXPORT_MAX_RESOLUTION: u32 = 16385;
+const HEIGHTMAP_SIDECAR_FORMAT: &str = "heightmap-export-v1";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportHeightmapRequest {
+    project_path: String,
+    map_id: String,
+    source_path: String,
+    #[serde(default)]
+    raw_width: Option<u32>,
+    #[serde(default)]
+    raw_height: Option<u32>,
+    world_rect: WorldRect,
+    min_height: f64,
+    max_height: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeightmapImportResult {
+    changed_regions: Vec<String>,
+    updated_page_count: usize,
+    created_pages: Vec<String>,
+    source_width: usize,
+    source_height: usize,
+}
+
+/// Export encodings; all of them store heights normalized to the sidecar range.
+/// 导出编码；均以 sidecar 中的高度范围归一化存储高度
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HeightmapExportFormat {
+    Png16,
+    R16,
+    R32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportHeightmapRequest {
+    project_path: String,
+    map_id: String,
+    output_path: String,
+    world_rect: WorldRect,
+    format: HeightmapExportFormat,
+    resolution: u32,
+    #[serde(default)]
+    fill_height: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeightmapExportResult {
+    output_path: String,
+    sidecar_path: String,
+    min_height: f64,
+    max_height: f64,
+    filled_sample_count: usize,
+}
+
+/// Source height samples normalized to 0..1.
+/// 归一化到 0..1 的源高度样本
+struct HeightmapSource {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+// --- Heightmap commands / 高度图命令 ---
+
+/// Import a 16-bit grayscale PNG or r16/r32 RAW heightmap into a world rectangle.
+/// 将 16 位灰度 PNG 或 r16/r32 RAW 高度图导入到世界矩形
+#[tauri::command]
+pub async fn import_heightmap(
+    request: ImportHeightmapRequest,
+) -> Result<HeightmapImportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || import_heightmap_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join heightmap import task: {}", e))?
+}
+
+fn import_heightmap_blocking(
+    request: ImportHeightmapRequest,
+) -> Result<HeightmapImportResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let rect = request.world_rect;
+    rect.validate("Heightmap")?;
+    if !request.min_height.is_finite()
+        || !request.max_height.is_finite()
+        || request.min_height >= request.max_height
+    {
+        return Err("Heightmap height range must be finite with min below max".to_string());
+    }
+
+    let source = HeightmapSource::read(
+        Path::new(&request.source_path),
+        request.raw_width,
+        request.raw_height,
+    )?;
+    let mut session = HeightPackSession::open(&project_root, &request.map_id)?;
+    let world_size = read_map_world_size(&project_root.join(MAPS_DIR).join(&request.map_id))?;
+
+    let page_size = session.layout.page_size_meters;
+    let resolution = session.layout.page_resolution;
+    let spacing = session.layout.sample_spacing_meters();
+    // EN: Page math runs in i64 with checked spans so a huge but finite rectangle is rejected instead of overflowing.
+    // 中文: 页面计算使用 i64 与带检查的跨度，使巨大但有限的矩形被拒绝而不是溢出。
+    let (min_page_x, max_page_x) = heightmap_page_span(rect.min_x, rect.max_x, page_size);
+    let (min_page_z, max_page_z) = heightmap_page_span(rect.min_z, rect.max_z, page_size);
+    page_span_count(min_page_x, max_page_x)
+        .zip(page_span_count(min_page_z, max_page_z))
+        .and_then(|(columns, rows)| columns.checked_mul(rows))
+        .filter(|count| *count <= HEIGHTMAP_IMPORT_MAX_PAGES)
+        .ok_or_else(|| {
+            format!(
+                "Heightmap covers more than {} pages",
+                HEIGHTMAP_IMPORT_MAX_PAGES
+            )
+        })?;
+    let page_index = |value: i64| {
+        i32::try_from(value).map_err(|_| "Heightmap rectangle is outside the page grid".to_string())
+    };
+    let (min_page_x, max_page_x) = (page_index(min_page_x)?, page_index(max_page_x)?);
+    let (min_page_z, max_page_z) = (page_index(min_page_z)?, page_index(max_page_z)?);
+
+    let height_range = request.max_height - request.min_height;
+    let sample_height = |x: f64, z: f64| {
+        let u = (x - rect.min_x) / (rect.max_x - rect.min_x);
+        let v = (z - rect.min_z) / (rect.max_z - rect.min_z);
+        (request.min_height + source.sample(u, v) as f64 * height_range) as f32
+    };
+
+    let mut updated_page_count = 0;
+    let mut created_pages = Vec::new();
+    for pz in min_page_z..=max_page_z {
+        for px in min_page_x..=max_page_x {
+            let origin_x = px as f64 * page_size;
+            let origin_z = pz as f64 * page_size;
+            let exists = session.has_page(px, pz);
+            if !exists {
+                // EN: Only create pages that overlap the rectangle with area and lie inside the world.
+                // 中文: 只创建与矩形有面积重叠且位于世界范围内的页面。
+                let overlaps = origin_x < rect.max_x
+                    && origin_x + page_size > rect.min_x
+                    && origin_z < rect.max_z
+                    && origin_z + page_size > rect.min_z;
+                let center_x = origin_x + page_size / 2.0;
+                let center_z = origin_z + page_size / 2.0;
+                let inside_world =
+                    center_x.abs() <= world_size / 2.0 && center_z.abs() <= world_size / 2.0;
+                if !overlaps || !inside_world {
+                    continue;
+                }
+            }
+
+            let Some(heights) = session.page_heights_mut(px, pz, true)? else {
+                continue;
+            };
+            for row in 0..resolution {
+                let z = origin_z + row as f64 * spacing;
+                for column in 0..resolution {
+                    let x = origin_x + column as f64 * spacing;
+                    let inside =
+                        x >= rect.min_x && x <= rect.max_x && z >= rect.min_z && z <= rect.max_z;
+                    // EN: New pages take clamped source values everywhere; existing pages only change inside the rectangle.
+                    // 中文: 新页面全部使用钳制后的源值；已有页面只修改矩形内部。
+                    if inside || !exists {
+                        heights[row * resolution + column] = sample_height(x, z);
+                    }
+                }
+            }
+
+            if exists {
+                updated_page_count += 1;
+            } else {
+                created_pages.push(format_grid_key(px, pz));
+            }
+        }
+    }
+
+    stitch_created_page_edges(&mut session, &created_pages)?;
+
+    Ok(HeightmapImportResult {
+        changed_regions: session.save()?,
+        updated_page_count,
+        created_pages,
+        source_width: source.width,
+        source_height: source.height,
+    })
+}
+
+/// Stitch height pages over a world rectangle into a 16-bit PNG or r16/r32 RAW file with a JSON sidecar.
+/// 将世界矩形内的高度页面拼接为 16 位 PNG 或 r16/r32 RAW 文件，并附带 JSON 说明文件
+#[tauri::command]
+pub async fn export_heightmap(
+    request: ExportHeightmapRequest,
+) -> Result<HeightmapExportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || export_heightmap_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join heightmap export task: {}", e))?
+}
+
+fn export_heightmap_blocking(
+    request: ExportHeightmapRequest,
+) -> Result<HeightmapExportResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let rect = request.world_rect;
+    rect.validate("Heightmap")?;
+    if request.resolution < 2 || request.resolution > HEIGHTMAP_EXPORT_MAX_RESOLUTION {
+        return Err(format!(
+            "Heightmap export resolution must be between 2 and {}",
+            HEIGHTMAP_EXPORT_MAX_RESOLUTION
+        ));
+    }
+    if !request.fill_height.is_finite() {
+        return Err("Heightmap fill height must be finite".to_string());
+    }
+
+    let output_path = PathBuf::from(&request.output_path);
+    let sidecar_path = output_path.with_extension("json");
+    if sidecar_path == output_path {
+        return Err("Heightmap export path must not end in .json".to_string());
+    }
+
+    let mut session = HeightPackSession::open(&project_root, &request.map_id)?;
+    let resolution = request.resolution as usize;
+    let step_x = (rect.max_x - rect.min_x) / (resolution - 1) as f64;
+    let step_z = (rect.max_z - rect.min_z) / (resolution - 1) as f64;
+    let mut heights = Vec::with_capacity(resolution * resolution);
+    let mut filled_sample_count = 0;
+    for row in 0..resolution {
+        let z = rect.min_z + row as f64 * step_z;
+        for column in 0..resolution {
+            let x = rect.min_x + column as f64 * step_x;
+            match session.height_at(x, z)? {
+                Some(height) => heights.push(height as f64),
+                None => {
+                    heights.push(request.fill_height);
+                    filled_sample_count += 1;
+                }
+            }
+        }
+    }
+
+    let min_height = heights.iter().copied().fold(f64::INFINITY, f64::min);
+    let max_height = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let height_range = max_height - min_height;
+    let normalized = heights.iter().map(|height| {
+        if height_range > 0.0 {
+            (height - min_height) / height_range
+        } else {
+            0.0
+        }
+    });
+
+    let bytes = match request.format {
+        HeightmapExportFormat::Png16 => {
+            let samples: Vec<u8> = normalized
+                .flat_map(|value| ((value * 65535.0).round() as u16).to_be_bytes())
+                .collect();
+            encode_png(
+                &samples,
+                request.resolution,
+                request.resolution,
+                png::ColorType::Grayscale,
+                png::BitDepth::Sixteen,
+                png::Compression::Balanced,
+            )?
+        }
+        HeightmapExportFormat::R16 => normalized
+            .flat_map(|value| ((value * 65535.0).round() as u16).to_le_bytes())
+            .collect(),
+        HeightmapExportFormat::R32 => normalized
+            .flat_map(|value| (value as f32).to_le_bytes())
+            .collect(),
+    };
+    safe_write(&output_path, &bytes)
+        .map_err(|e| format!("Failed to write heightmap export: {}", e))?;
+
+    // EN: The sidecar carries everything needed to re-import the file with import_heightmap.
+    // 中文: 说明文件包含使用 import_heightmap 重新导入该文件所需的全部信息。
+    let sidecar = serde_json::json!({
+        "format": HEIGHTMAP_SIDECAR_FORMAT,
+        "encoding": request.format,
+        "width": request.resolution,
+        "height": request.resolution,
+        "minHeight": min_height,
+        "maxHeight": max_height,
+        "origin": [rect.min_x, rect.min_z],
+        "worldRect": {
+            "minX": rect.min_x,
+            "minZ": rect.min_z,
+            "maxX": rect.max_x,
+            "maxZ": rect.max_z,
+        },
+        "sampleSpacingMeters": [step_x, step_z],
+        "fillHeight": request.fill_height,
+        "filledSampleCount": filled_sample_count,
+    });
+    let sidecar_text = serde_json::to_string_pretty(&sidecar)
+        .map_err(|e| format!("Failed to serialize heightmap sidecar: {}", e))?;
+    safe_write(&sidecar_path, format!("{}\n", sidecar_text).as_bytes())
+        .map_err(|e| format!("Failed to write heightmap sidecar: {}", e))?;
+
+    Ok(HeightmapExportResult {
+        output_path: output_path.to_string_lossy().to_string(),
+        sidecar_path: sidecar_path.to_string_lossy().to_string(),
+        min_height,
+        max_height,
+        filled_sample_count,
+    })
+}
+
+/// Copy shared edges from pre-existing neighbours into newly created pages.
+/// 将已有相邻页面的共享边复制到新创建的页面
+fn stitch_created_page_edges(
+    session: &mut HeightPackSession,
+    created_pages: &[String],
+) -> Result<(), String> {
+    let created: BTreeSet<&str> = created_pages.iter().map(String::as_str).collect();
+    let resolution = session.layout.page_resolution;
+    let last = resolution - 1;
+    for key in created_pages {
+        let Some((px, pz)) = parse_grid_key(key) else {
+            continue;
+        };
+
+        for (dx, dz) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+            if created.contains(format_grid_key(px + dx, pz + dz).as_str()) {
+                continue;
+            }
+            let Some(neighbour_heights) = session.page_heights(px + dx, pz + dz)? else {
+                continue;
+            };
+
+            // EN: Map each edge sample to (neighbour index, own index) on the shared edge.
+            // 中文: 将每个边缘样本映射为共享边上的（相邻索引，自身索引）。
+            let edge: Vec<(usize, f32)> = (0..resolution)
+                .map(|i| {
+                    let (from, to) = match (dx, dz) {
+                        (-1, 0) => (i * resolution + last, i * resolution),
+                        (1, 0) => (i * resolution, i * resolution + last),
+                        (0, -1) => (last * resolution + i, i),
+                        _ => (i, last * resolution + i),
+                    };
+                    (to, neighbour_heights[from])
+                })
+                .collect();
+            if let Some(heights) = session.page_heights_mut(px, pz, false)? {
+                for (index, height) in edge {
+                    heights[index] = height;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl HeightmapSource {
+    /// Read a PNG (any bit depth) or RAW heightmap chosen by file extension.
+    /// 按扩展名读取 PNG（任意位深）或 RAW 高度图
+    fn read(path: &Path, raw_width: Option<u32>, raw_height: Option<u32>) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let source = match extension.as_str() {
+            "png" => Self::read_png(path)?,
+            "r16" | "raw" => Self::read_raw(path, 2, raw_width, raw_height)?,
+            "r32" => Self::read_raw(path, 4, raw_width, raw_height)?,
+            _ => {
+                return Err(format!(
+                    "Unsupported heightmap format '.{}'; use .png, .r16, .raw or .r32",
+                    extension
+                ));
+            }
+        };
+
+        if source.width < 2 || source.height < 2 {
+            return Err("Heightmap must be at least 2x2 samples".to_string());
+        }
+        Ok(source)
+    }
+
+    fn read_png(path: &Path) -> Result<Self, String> {
+        // EN: Use the red channel at full precision; grayscale heightmaps are the expected input.
+        // 中文: 以全精度使用红色通道；预期输入为灰度高度图。
+        let (pixels, width, height) = decode_png_rgba_samples(path, true)?;
+        let values = pixels
+            .chunks_exact(8)
+            .map(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]) as f32 / 65535.0)
+            .collect();
+
+        Ok(Self {
+            width: width as usize,
+            height: height as usize,
+            values,
+        })
+    }
+
+    /// Read little-endian r16 (unsigned) or r32 (float, normalized 0..1) samples.
+    /// 读取小端 r16（无符号）或 r32（浮点，归一化 0..1）样本
+    fn read_raw(
+        path: &Path,
+        sample_bytes: usize,
+        raw_width: Option<u32>,
+        raw_height: Option<u32>,
+    ) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read heightmap: {}", e))?;
+        if bytes.len() % sample_bytes != 0 {
+            return Err(format!(
+                "RAW heightmap size {} is not a multiple of {} bytes",
+                bytes.len(),
+                sample_bytes
+            ));
+        }
+
+        let sample_count = bytes.len() / sample_bytes;
+        let (width, height) = match (raw_width, raw_height) {
+            (Some(width), Some(height)) => (width as usize, height as usize),
+            (None, None) => {
+                let side = (sample_count as f64).sqrt().round() as usize;
+                (side, side)
+            }
+            _ => return Err("RAW heightmaps need both width and height, or neither".to_string()),
+        };
+        if width * height != sample_count {
+            return Err(format!(
+                "RAW heightmap has {} samples, expected {}x{}",
+                sample_count, width, height
+            ));
+        }
+
+        let values = if sample_bytes == 2 {
+            bytes
+                .chunks_exact(2)
+                .map(|sample| u16::from_le_bytes([sample[0], sample[1]]) as f32 / 65535.0)
+                .collect()
+        } else {
+            bytes
+                .chunks_exact(4)
+                .map(|sample| f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]))
+                .collect()
+        };
+
+        Ok(Self {
+            width,
+            height,
+            values,
+        })
+    }
+
+    /// Bilinear sample at normalized coordinates, clamped to the grid.
+    /// 在归一化坐标处双线性采样，并钳制到网格内
+    fn sample(&self, u: f64, v: f64) -> f32 {
+        let x = (u * (self.width - 1) as f64).clamp(0.0, (self.width - 1) as f64);
+        let y = (v * (self.height - 1) as f64).clamp(0.0, (self.height - 1) as f64);
+        let x0 = x.floor() as usize;
+        let y0 = y.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let tx = (x - x0 as f64) as f32;
+        let ty = (y - y0 as f64) as f32;
+
+        let value = |column: usize, row: usize| self.values[row * self.width + column];
+        let top = value(x0, y0) + (value(x1, y0) - value(x0, y0)) * tx;
+        let bottom = value(x0, y1) + (value(x1, y1) - value(x0, y1)) * tx;
+        top + (bottom - top) * ty
+    }
+}
+
+/// Pages touched by `[min, max]` on one axis, including pages whose shared edge lies on the boundary so seams stay identical.
+/// 单轴上 `[min, max]` 触及的页面，包括共享边恰好落在边界上的页面，使接缝保持一致
+fn heightmap_page_span(min: f64, max: f64, page_size: f64) -> (i64, i64) {
+    (
+        ((min / page_size).ceil() as i64).saturating_sub(1),
+        (max / page_size).floor() as i64,
+    )
+}
+
+fn page_span_count(min_page: i64, max_page: i64) -> Option<u64> {
+    max_page
+        .checked_sub(min_page)?
+        .checked_add(1)
+        .and_then(|count| u64::try_from(count).ok())
+}
@@ -0,0 +1,675 @@
+// CPU impostor atlas baking for distant vegetation.
+// 为远景植被在 CPU 上烘焙 impostor 图集
+//
+// EN: Views are orthographic and evenly spaced around the Y axis; each frame is a square centered on the model bounds so the runtime can draw one camera-facing quad per instance.
+// 中文: 视角为绕 Y 轴均匀分布的正交投影；每帧都是以模型包围盒为中心的正方形，运行时每个实例只需绘制一个朝向相机的四边形。
+
+use crate::asset_references::normalize_project_path;
+use crate::commands::{
+    MAPS_DIR, decode_image_rgba, encode_png, safe_write, validate_cook_project_path,
+    validate_single_path_segment,
+};
+use crate::gltf_inspect::{GltfAsset, transform_point};
+use crate::map_layout::{VEGETATION_IMPOSTOR_FORMAT, VEGETATION_MODELS_PATH};
+use crate::model_lod::{cross, dot, sub};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const IMPOSTOR_DEFAULT_VIEW_COUNT: u32 = 8;
+const IMPOSTOR_MAX_VIEW_COUNT: u32 = 64;
+const IMPOSTOR_DEFAULT_FRAME_SIZE: u32 = 256;
+const IMPOSTOR_MIN_FRAME_SIZE: u32 = 16;
+const IMPOSTOR_MAX_FRAME_SIZE: u32 = 2048;
+const IMPOSTOR_FRAME_MARGIN: f64 = 1.04;
+const IMPOSTOR_DILATION_PASSES: usize = 4;
+const IMPOSTOR_FAR_DISTANCE_FACTOR: f64 = 4.0;
+const IMPOSTOR_MAX_DISTANCE_METERS: f64 = 2000.0;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BakeVegetationImpostorRequest {
+    project_path: String,
+    map_id: String,
+    model_id: String,
+    #[serde(default)]
+    view_count: Option<u32>,
+    #[serde(default)]
+    frame_size: Option<u32>,
+    #[serde(default)]
+    max_visible_distance_meters: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationImpostorResult {
+    model_id: String,
+    impostor: Value,
+    covered_pixels: u64,
+    warnings: Vec<String>,
+}
+
+/// Material inputs the rasterizer needs: base color texture/factor and alpha handling.
+/// 光栅化所需的材质输入：基础色纹理/系数与 alpha 处理方式
+struct ImpostorMaterial {
+    texture: Option<usize>,
+    factor: [f32; 4],
+    alpha_cutoff: Option<f32>,
+    double_sided: bool,
+}
+
+/// Decoded RGBA8 texture sampled with wrapping nearest-neighbour lookups.
+/// 已解码的 RGBA8 纹理，按重复寻址最近邻采样
+struct ImpostorTexture {
+    pixels: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl ImpostorTexture {
+    fn sample(&self, uv: [f32; 2]) -> [f32; 4] {
+        let x = ((uv[0] - uv[0].floor()) * self.width as f32) as usize;
+        let y = ((uv[1] - uv[1].floor()) * self.height as f32) as usize;
+        let offset = (y.min(self.height - 1) * self.width + x.min(self.width - 1)) * 4;
+        [0, 1, 2, 3].map(|channel| self.pixels[offset + channel] as f32 / 255.0)
+    }
+}
+
+/// A world-space triangle with interpolated attributes.
+/// 带插值属性的世界空间三角形
+struct ImpostorTriangle {
+    positions: [[f64; 3]; 3],
+    normals: [[f64; 3]; 3],
+    uvs: [[f32; 2]; 3],
+    material: Option<usize>,
+}
+
+/// One orthographic view: camera axes, world-to-texel scale and the frame's atlas origin.
+/// 一个正交视角：相机轴、世界到纹素的缩放以及帧在图集中的起点
+///
+/// EN: The camera sits on +forward looking back at the center; +right and +Y span the frame.
+/// 中文: 相机位于 +forward 方向并朝向中心；+right 与 +Y 构成帧平面。
+#[derive(Clone, Copy)]
+struct ImpostorView {
+    forward: [f64; 3],
+    right: [f64; 3],
+    center: [f64; 3],
+    scale: f64,
+    frame_size: usize,
+    origin: (usize, usize),
+}
+
+/// Albedo and normal atlases sharing one RGBA8 layout.
+/// 共享同一 RGBA8 布局的反照率与法线图集
+struct ImpostorAtlas {
+    width: usize,
+    height: usize,
+    albedo: Vec<u8>,
+    normal: Vec<u8>,
+}
+
+impl ImpostorAtlas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            albedo: vec![0; width * height * 4],
+            normal: vec![0; width * height * 4],
+        }
+    }
+}
+
+// --- Vegetation impostor commands / 植被 impostor 命令 ---
+
+/// Rasterize a vegetation model from evenly spaced view angles into albedo/normal atlases and record them as its far tier.
+/// 从均匀分布的视角将植被模型光栅化为反照率/法线图集，并记录为其远景层级
+#[tauri::command]
+pub async fn bake_vegetation_impostor(
+    request: BakeVegetationImpostorRequest,
+) -> Result<VegetationImpostorResult, String> {
+    tauri::async_runtime::spawn_blocking(move || bake_vegetation_impostor_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join impostor bake task: {}", e))?
+}
+
+fn bake_vegetation_impostor_blocking(
+    request: BakeVegetationImpostorRequest,
+) -> Result<VegetationImpostorResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    validate_single_path_segment(&request.map_id, "map_id")?;
+    let view_count = request.view_count.unwrap_or(IMPOSTOR_DEFAULT_VIEW_COUNT);
+    if view_count == 0 || view_count > IMPOSTOR_MAX_VIEW_COUNT {
+        return Err(format!(
+            "Impostor view count must be between 1 and {}",
+            IMPOSTOR_MAX_VIEW_COUNT
+        ));
+    }
+    let frame_size = request.frame_size.unwrap_or(IMPOSTOR_DEFAULT_FRAME_SIZE);
+    if !(IMPOSTOR_MIN_FRAME_SIZE..=IMPOSTOR_MAX_FRAME_SIZE).contains(&frame_size) {
+        return Err(format!(
+            "Impostor frame size must be between {} and {}",
+            IMPOSTOR_MIN_FRAME_SIZE, IMPOSTOR_MAX_FRAME_SIZE
+        ));
+    }
+
+    let map_directory = format!("{}/{}", MAPS_DIR, request.map_id);
+    let models_path = project_root
+        .join(&map_directory)
+        .join(VEGETATION_MODELS_PATH);
+    let text = fs::read_to_string(&models_path)
+        .map_err(|e| format!("Failed to read vegetation models: {}", e))?;
+    let mut document: Value = serde_json::from_str(&text)
+        .map_err(|e| format!("Failed to parse vegetation models: {}", e))?;
+    let model = document
+        .get("models")
+        .and_then(|models| models.get(&request.model_id))
+        .ok_or_else(|| format!("Vegetation model '{}' does not exist", request.model_id))?;
+    let model_path = model
+        .get("path")
+        .and_then(Value::as_str)
+        .and_then(|path| normalize_project_path(Path::new(&map_directory), path))
+        .ok_or_else(|| {
+            format!(
+                "Vegetation model '{}' has no project path",
+                request.model_id
+            )
+        })?;
+    let mesh_max_distance = model
+        .get("maxVisibleDistanceMeters")
+        .and_then(Value::as_f64)
+        .unwrap_or(0.0);
+    let far_distance = request
+        .max_visible_distance_meters
+        .unwrap_or(mesh_max_distance * IMPOSTOR_FAR_DISTANCE_FACTOR)
+        .min(IMPOSTOR_MAX_DISTANCE_METERS);
+    if far_distance <= mesh_max_distance {
+        return Err(format!(
+            "Impostor max distance {:.0} m must exceed the mesh max distance {:.0} m",
+            far_distance, mesh_max_distance
+        ));
+    }
+
+    let asset = GltfAsset::load(&project_root.join(&model_path))?;
+    let mut warnings = Vec::new();
+    let materials = read_materials(&asset);
+    let textures = load_textures(&asset, &materials, &mut warnings);
+    let triangles = collect_triangles(&asset)?;
+    if triangles.is_empty() {
+        return Err("Vegetation model has no triangles to bake".to_string());
+    }
+
+    let mut bounds_min = [f64::INFINITY; 3];
+    let mut bounds_max = [f64::NEG_INFINITY; 3];
+    for point in triangles.iter().flat_map(|triangle| triangle.positions) {
+        for axis in 0..3 {
+            bounds_min[axis] = bounds_min[axis].min(point[axis]);
+            bounds_max[axis] = bounds_max[axis].max(point[axis]);
+        }
+    }
+    let center = [
+        (bounds_min[0] + bounds_max[0]) * 0.5,
+        (bounds_min[1] + bounds_max[1]) * 0.5,
+        (bounds_min[2] + bounds_max[2]) * 0.5,
+    ];
+    // EN: The frame must hold the model from every azimuth, so its half size covers the horizontal radius around the center axis.
+    // 中文: 帧需在任意方位角下容纳模型，因此半尺寸需覆盖绕中心轴的水平半径。
+    let horizontal_radius = triangles
+        .iter()
+        .flat_map(|triangle| triangle.positions)
+        .map(|point| (point[0] - center[0]).hypot(point[2] - center[2]))
+        .fold(0.0, f64::max);
+    let half_size = horizontal_radius
+        .max((bounds_max[1] - bounds_min[1]) * 0.5)
+        .max(f64::EPSILON)
+        * IMPOSTOR_FRAME_MARGIN;
+
+    let columns = (view_count as f64).sqrt().ceil() as u32;
+    let rows = view_count.div_ceil(columns);
+    let mut atlas = ImpostorAtlas::new(
+        (columns * frame_size) as usize,
+        (rows * frame_size) as usize,
+    );
+    let mut covered_pixels = 0u64;
+    for view in 0..view_count {
+        let angle = view as f64 / view_count as f64 * std::f64::consts::TAU;
+        let view = ImpostorView {
+            forward: [angle.sin(), 0.0, angle.cos()],
+            right: [angle.cos(), 0.0, -angle.sin()],
+            center,
+            scale: frame_size as f64 * 0.5 / half_size,
+            frame_size: frame_size as usize,
+            origin: (
+                (view % columns * frame_size) as usize,
+                (view / columns * frame_size) as usize,
+            ),
+        };
+        covered_pixels += rasterize_view(&triangles, &materials, &textures, &view, &mut atlas);
+    }
+    dilate_transparent_texels(&mut atlas.albedo, atlas.width, atlas.height);
+
+    let (model_directory, _) = model_path
+        .rsplit_once('/')
+        .ok_or_else(|| format!("Model path '{}' has no folder", model_path))?;
+    let albedo_path = format!(
+        "{}/impostor/{}_albedo.png",
+        model_directory, request.model_id
+    );
+    let normal_path = format!(
+        "{}/impostor/{}_normal.png",
+        model_directory, request.model_id
+    );
+    for (path, pixels) in [(&albedo_path, &atlas.albedo), (&normal_path, &atlas.normal)] {
+        let encoded = encode_png(
+            pixels,
+            atlas.width as u32,
+            atlas.height as u32,
+            png::ColorType::Rgba,
+            png::BitDepth::Eight,
+            png::Compression::Balanced,
+        )?;
+        safe_write(&project_root.join(path), &encoded)
+            .map_err(|e| format!("Failed to write impostor atlas: {}", e))?;
+    }
+
+    // EN: Paths are map-relative like the model's own LOD paths so the vegetation cook can copy them the same way.
+    // 中文: 路径与模型自身的 LOD 路径一样相对于地图目录，便于植被 cook 以相同方式复制。
+    let impostor = json!({
+        "format": VEGETATION_IMPOSTOR_FORMAT,
+        "albedoPath": format!("../../{}", albedo_path),
+        "normalPath": format!("../../{}", normal_path),
+        "frameCount": view_count,
+        "columns": columns,
+        "rows": rows,
+        "frameSize": frame_size,
+        "frameWorldSize": half_size * 2.0,
+        "frameCenter": center,
+        "sourceMinY": bounds_min[1],
+        "sourceHeightMeters": bounds_max[1] - bounds_min[1],
+        "startDistanceMeters": mesh_max_distance,
+        "maxVisibleDistanceMeters": far_distance,
+    });
+    let model = document
+        .get_mut("models")
+        .and_then(|models| models.get_mut(&request.model_id))
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| format!("Vegetation model '{}' does not exist", request.model_id))?;
+    model.insert("impostor".to_string(), impostor.clone());
+    let text = serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize vegetation models: {}", e))?;
+    safe_write(&models_path, format!("{}\n", text).as_bytes())
+        .map_err(|e| format!("Failed to write vegetation models: {}", e))?;
+
+    Ok(VegetationImpostorResult {
+        model_id: request.model_id,
+        impostor,
+        covered_pixels,
+        warnings,
+    })
+}
+
+fn read_materials(asset: &GltfAsset) -> Vec<ImpostorMaterial> {
+    asset
+        .array("materials")
+        .iter()
+        .map(|material| {
+            let base_color = material.pointer("/pbrMetallicRoughness/baseColorTexture");
+            let factor = material
+                .pointer("/pbrMetallicRoughness/baseColorFactor")
+                .and_then(Value::as_array)
+                .filter(|values| values.len() == 4)
+                .map(|values| {
+                    [0, 1, 2, 3].map(|index| values[index].as_f64().unwrap_or(1.0) as f32)
+                })
+                .unwrap_or([1.0; 4]);
+            let alpha_cutoff = match material.get("alphaMode").and_then(Value::as_str) {
+                Some("MASK") => Some(
+                    material
+                        .get("alphaCutoff")
+                        .and_then(Value::as_f64)
+                        .unwrap_or(0.5) as f32,
+                ),
+                Some("BLEND") => Some(0.5),
+                _ => None,
+            };
+            ImpostorMaterial {
+                texture: base_color
+                    .and_then(|texture| texture.get("index"))
+                    .and_then(Value::as_u64)
+                    .map(|index| index as usize),
+                factor,
+                alpha_cutoff,
+                double_sided: material
+                    .get("doubleSided")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+            }
+        })
+        .collect()
+}
+
+/// Decode every base color texture referenced by a material; PNG and JPEG files are supported.
+/// 解码材质引用的所有基础色纹理；支持 PNG 与 JPEG 文件
+fn load_textures(
+    asset: &GltfAsset,
+    materials: &[ImpostorMaterial],
+    warnings: &mut Vec<String>,
+) -> HashMap<usize, ImpostorTexture> {
+    let mut textures = HashMap::new();
+    for texture in materials.iter().filter_map(|material| material.texture) {
+        if textures.contains_key(&texture) {
+            continue;
+        }
+        let uri = asset
+            .array("textures")
+            .get(texture)
+            .and_then(|texture| texture.get("source"))
+            .and_then(Value::as_u64)
+            .and_then(|image| asset.array("images").get(image as usize))
+            .and_then(|image| image.get("uri"))
+            .and_then(Value::as_str);
+        let Some(file) = uri.and_then(|uri| asset.resolve_uri(uri)) else {
+            warnings.push(format!(
+                "Texture {} is embedded or missing; its base color factor is used instead",
+                texture
+            ));
+            continue;
+        };
+        match decode_texture(&file) {
+            Ok(decoded) => {
+                textures.insert(texture, decoded);
+            }
+            Err(error) => warnings.push(format!("Texture {} was not decoded: {}", texture, error)),
+        }
+    }
+    textures
+}
+
+fn decode_texture(file: &Path) -> Result<ImpostorTexture, String> {
+    let (pixels, width, height) = decode_image_rgba(file)?;
+    Ok(ImpostorTexture {
+        pixels,
+        width: width as usize,
+        height: height as usize,
+    })
+}
+
+fn collect_triangles(asset: &GltfAsset) -> Result<Vec<ImpostorTriangle>, String> {
+    let meshes = asset.array("meshes");
+    let mut triangles = Vec::new();
+    for instance in asset.mesh_instances()? {
+        let Some(primitives) = meshes
+            .get(instance.mesh)
+            .and_then(|mesh| mesh.get("primitives"))
+            .and_then(Value::as_array)
+        else {
+            continue;
+        };
+        let matrix = instance.matrix;
+        for primitive in primitives {
+            if primitive.get("mode").and_then(Value::as_u64).unwrap_or(4) != 4 {
+                continue;
+            }
+            let attribute = |name: &str| -> Result<Option<Vec<f32>>, String> {
+                match primitive
+                    .pointer(&format!("/attributes/{}", name))
+                    .and_then(Value::as_u64)
+                {
+                    Some(accessor) => Ok(Some(asset.read_accessor(accessor as usize)?.0)),
+                    None => Ok(None),
+                }
+            };
+            let material = primitive
+                .get("material")
+                .and_then(Value::as_u64)
+                .map(|material| material as usize);
+            let Some(positions) = attribute("POSITION")? else {
+                continue;
+            };
+            let normals = attribute("NORMAL")?;
+            let tex_coord = material
+                .and_then(|material| asset.array("materials").get(material))
+                .and_then(|material| {
+                    material.pointer("/pbrMetallicRoughness/baseColorTexture/texCoord")
+                })
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            let uvs = attribute(&format!("TEXCOORD_{}", tex_coord))?;
+
+            let indices = asset.read_indices(primitive)?;
+            for corners in indices.chunks_exact(3) {
+                let corners = [corners[0], corners[1], corners[2]].map(|index| index as usize);
+                if corners.iter().any(|index| index * 3 + 2 >= positions.len()) {
+                    continue;
+                }
+                let positions = corners.map(|index| {
+                    transform_point(
+                        &matrix,
+                        [0, 1, 2].map(|axis| positions[index * 3 + axis] as f64),
+                    )
+                });
+                let face_normal = normalize(cross(
+                    sub(positions[1], positions[0]),
+                    sub(positions[2], positions[0]),
+                ));
+                let normals = corners.map(|index| match &normals {
+                    Some(normals) if index * 3 + 2 < normals.len() => {
+                        // EN: Rotate normals by the node matrix (translation dropped); non-uniform scale is rare in vegetation exports.
+                        // 中文: 按节点矩阵旋转法线（忽略平移）；植被导出中很少出现非均匀缩放。
+                        let local = [0, 1, 2].map(|axis| normals[index * 3 + axis] as f64);
+                        let origin = transform_point(&matrix, [0.0; 3]);
+                        normalize(sub(transform_point(&matrix, local), origin))
+                    }
+                    _ => face_normal,
+                });
+                let uvs = corners.map(|index| match &uvs {
+                    Some(uvs) if index * 2 + 1 < uvs.len() => [uvs[index * 2], uvs[index * 2 + 1]],
+                    _ => [0.0, 0.0],
+                });
+                triangles.push(ImpostorTriangle {
+                    positions,
+                    normals,
+                    uvs,
+                    material,
+                });
+            }
+        }
+    }
+    Ok(triangles)
+}
+
+/// Rasterize one orthographic view into its atlas frame; returns the number of covered texels.
+/// 将一个正交视角光栅化到其图集帧中；返回被覆盖的纹素数量
+fn rasterize_view(
+    triangles: &[ImpostorTriangle],
+    materials: &[ImpostorMaterial],
+    textures: &HashMap<usize, ImpostorTexture>,
+    view: &ImpostorView,
+    atlas: &mut ImpostorAtlas,
+) -> u64 {
+    let ImpostorView {
+        forward,
+        right,
+        center,
+        scale,
+        frame_size,
+        origin,
+    } = *view;
+    let project = |point: [f64; 3]| -> [f64; 3] {
+        let offset = sub(point, center);
+        [
+            (dot(offset, right) * scale) + frame_size as f64 * 0.5,
+            frame_size as f64 * 0.5 - offset[1] * scale,
+            dot(offset, forward),
+        ]
+    };
+
+    let mut depth = vec![f64::NEG_INFINITY; frame_size * frame_size];
+    let mut covered = 0u64;
+    for triangle in triangles {
+        let screen = triangle.positions.map(project);
+        let area = edge(screen[0], screen[1], screen[2]);
+        if area.abs() <= f64::EPSILON {
+            continue;
+        }
+        let min_x = screen
+            .iter()
+            .map(|point| point[0])
+            .fold(f64::INFINITY, f64::min)
+            .floor()
+            .max(0.0) as usize;
+        let max_x = screen
+            .iter()
+            .map(|point| point[0])
+            .fold(f64::NEG_INFINITY, f64::max)
+            .ceil()
+            .min(frame_size as f64) as usize;
+        let min_y = screen
+            .iter()
+            .map(|point| point[1])
+            .fold(f64::INFINITY, f64::min)
+            .floor()
+            .max(0.0) as usize;
+        let max_y = screen
+            .iter()
+            .map(|point| point[1])
+            .fold(f64::NEG_INFINITY, f64::max)
+            .ceil()
+            .min(frame_size as f64) as usize;
+        let material = triangle
+            .material
+            .and_then(|material| materials.get(material));
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let pixel = [x as f64 + 0.5, y as f64 + 0.5, 0.0];
+                let weights = [
+                    edge(screen[1], screen[2], pixel) / area,
+                    edge(screen[2], screen[0], pixel) / area,
+                    edge(screen[0], screen[1], pixel) / area,
+                ];
+                if weights.iter().any(|weight| *weight < 0.0) {
+                    continue;
+                }
+                let z = (0..3)
+                    .map(|corner| screen[corner][2] * weights[corner])
+                    .sum::<f64>();
+                let slot = y * frame_size + x;
+                if z <= depth[slot] {
+                    continue;
+                }
+
+                let uv = [0, 1].map(|axis| {
+                    (0..3)
+                        .map(|corner| triangle.uvs[corner][axis] * weights[corner] as f32)
+                        .sum::<f32>()
+                });
+                let mut color = material.map_or([1.0; 4], |material| material.factor);
+                if let Some(texture) = material
+                    .and_then(|material| material.texture)
+                    .and_then(|texture| textures.get(&texture))
+                {
+                    let sample = texture.sample(uv);
+                    for (channel, value) in color.iter_mut().zip(sample) {
+                        *channel *= value;
+                    }
+                }
+                match material.and_then(|material| material.alpha_cutoff) {
+                    Some(cutoff) if color[3] < cutoff => continue,
+                    _ => color[3] = 1.0,
+                }
+
+                let world_normal = normalize(
+                    (0..3)
+                        .map(|corner| triangle.normals[corner].map(|value| value * weights[corner]))
+                        .fold([0.0; 3], |sum, value| {
+                            [sum[0] + value[0], sum[1] + value[1], sum[2] + value[2]]
+                        }),
+                );
+                let mut view_normal = [
+                    dot(world_normal, right),
+                    world_normal[1],
+                    dot(world_normal, forward),
+                ];
+                if view_normal[2] < 0.0 && material.is_none_or(|material| material.double_sided) {
+                    view_normal = view_normal.map(|value| -value);
+                }
+
+                depth[slot] = z;
+                let offset = ((origin.1 + y) * atlas.width + origin.0 + x) * 4;
+                let encoded_normal = view_normal
+                    .map(|value| ((value * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0).round() as u8);
+                atlas.albedo[offset..offset + 4].copy_from_slice(
+                    &color.map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8),
+                );
+                atlas.normal[offset..offset + 4].copy_from_slice(&[
+                    encoded_normal[0],
+                    encoded_normal[1],
+                    encoded_normal[2],
+                    255,
+                ]);
+            }
+        }
+    }
+    for value in &depth {
+        if value.is_finite() {
+            covered += 1;
+        }
+    }
+    covered
+}
+
+/// Bleed opaque colors into transparent neighbours (alpha stays 0) so mipmaps do not darken silhouettes.
+/// 将不透明颜色扩散到透明相邻纹素（alpha 保持为 0），避免 mipmap 使轮廓变暗
+fn dilate_transparent_texels(pixels: &mut [u8], width: usize, height: usize) {
+    let mut filled: Vec<bool> = pixels.chunks_exact(4).map(|pixel| pixel[3] > 0).collect();
+    for _ in 0..IMPOSTOR_DILATION_PASSES {
+        let mut updates = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if filled[y * width + x] {
+                    continue;
+                }
+                let mut sum = [0u32; 3];
+                let mut count = 0;
+                for (dx, dy) in [(-1i64, 0i64), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, ny) = (x as i64 + dx, y as i64 + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let neighbour = ny as usize * width + nx as usize;
+                    if filled[neighbour] {
+                        for (channel, total) in sum.iter_mut().enumerate() {
+                            *total += pixels[neighbour * 4 + channel] as u32;
+                        }
+                        count += 1;
+                    }
+                }
+                if count > 0 {
+                    updates.push((y * width + x, sum.map(|total| (total / count) as u8)));
+                }
+            }
+        }
+        if updates.is_empty() {
+            break;
+        }
+        for (index, color) in updates {
+            pixels[index * 4..index * 4 + 3].copy_from_slice(&color);
+            filled[index] = true;
+        }
+    }
+}
+
+fn edge(a: [f64; 3], b: [f64; 3], point: [f64; 3]) -> f64 {
+    (b[0] - a[0]) * (point[1] - a[1]) - (b[1] - a[1]) * (point[0] - a[0])
+}
+
+fn normalize(value: [f64; 3]) -> [f64; 3] {
+    let length = dot(value, value).sqrt();
+    if length <= f64::EPSILON {
+        [0.0, 1.0, 0.0]
+    } else {
+        value.map(|component| component / length)
+    }
+}
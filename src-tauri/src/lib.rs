@@ -1,4 +1,32 @@
+mod asset_import;
+mod asset_references;
+mod asset_registry;
 mod commands;
+mod cook_cache;
+mod cook_history;
+mod cook_report;
+mod cooked_map;
+mod ecology_scatter;
+mod gltf_inspect;
+mod height_pack;
+mod heightmap;
+mod impostor_bake;
+mod map_layout;
+mod model_lod;
+mod paint_import;
+mod paint_pack;
+mod png_region;
+mod rebuild_planner;
+mod spline;
+#[cfg(test)]
+mod test_fixtures;
+mod texture_import;
+mod tool_runner;
+mod vegetation_pack;
+mod world_collision;
+mod world_nav;
+mod world_objects;
+mod world_prefabs;
 
 use commands::*;
 
@@ -26,6 +54,70 @@ pub fn run_editor() {
             // Controlled editor workflows / 受控编辑器工作流
             run_cook_map,
             run_world_generation_graph,
+            // World script tool runner / 世界脚本工具运行器
+            tool_runner::read_tool_runner_settings,
+            tool_runner::save_tool_runner_settings,
+            tool_runner::check_toolchain,
+            // Paint region packs / 绘制区域包
+            paint_pack::read_paint_layer_weights,
+            paint_pack::write_paint_layer_weights,
+            paint_pack::renormalize_paint_weights,
+            paint_import::import_paint_mask,
+            // Vegetation region packs / 植被区域包
+            vegetation_pack::query_vegetation_instances,
+            vegetation_pack::summarize_vegetation_instances,
+            vegetation_pack::delete_vegetation_instances,
+            vegetation_pack::move_vegetation_instances,
+            // Ecology scatter / 生态散布
+            ecology_scatter::run_ecology_scatter,
+            // World object cells / 世界物体单元
+            world_objects::list_world_objects,
+            world_objects::read_world_object_cell,
+            world_objects::insert_world_objects,
+            world_objects::update_world_objects,
+            world_objects::remove_world_objects,
+            // World splines / 世界样条
+            spline::edit_world_spline,
+            // World prefabs / 世界预制体
+            world_prefabs::list_world_prefabs,
+            world_prefabs::save_world_prefab,
+            world_prefabs::expand_world_prefab,
+            world_prefabs::reexpand_world_prefab,
+            // World collision / 世界碰撞
+            world_collision::raycast_world_collision,
+            world_collision::sweep_world_collision_sphere,
+            world_collision::overlap_world_collision_sphere,
+            world_collision::sample_world_ground_heights,
+            // World navigation / 世界导航
+            world_nav::find_world_nav_path,
+            world_nav::find_nearest_world_nav_points,
+            world_nav::check_world_nav_reachability,
+            // Terrain heightmaps / 地形高度图
+            heightmap::import_heightmap,
+            heightmap::export_heightmap,
+            // Asset registry / 资源注册表
+            asset_registry::list_asset_registry,
+            asset_registry::add_asset_registry_entry,
+            asset_registry::update_asset_registry_entry,
+            asset_registry::remove_asset_registry_entry,
+            asset_import::import_asset,
+            asset_references::find_asset_references,
+            asset_references::check_asset_references,
+            // Texture import / 纹理导入
+            texture_import::import_texture,
+            // Model inspection and LODs / 模型检查与 LOD
+            gltf_inspect::inspect_gltf,
+            model_lod::generate_model_lods,
+            impostor_bake::bake_vegetation_impostor,
+            // Incremental cook cache / 增量 cook 缓存
+            cook_cache::list_cook_cache_entries,
+            cook_cache::check_cook_staleness,
+            cook_cache::invalidate_cook_cache,
+            // Cook run history / Cook 运行历史
+            cook_history::list_cook_history,
+            cook_history::delete_cook_history_entries,
+            // Native world rebuild planning / 原生世界重建规划
+            rebuild_planner::plan_world_rebuild,
             // Generic file operations / 通用文件操作
             read_text_file,
             write_text_file,
@@ -35,6 +127,7 @@ pub fn run_editor() {
             // PNG operations (bypass browser premultiplied alpha) / PNG 操作（绕过浏览器预乘 alpha）
             read_png_rgba,
             write_png_rgba,
+            png_region::read_png_region,
         ])
         .run(tauri::generate_context!())
         .expect("error while running open-fps editor");
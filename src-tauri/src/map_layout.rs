@@ -0,0 +1,227 @@
+// Shared project map layout constants, grid key helpers, planar geometry and seed hashing helpers.
+// 共享的项目地图布局常量、网格键工具、平面几何与种子哈希工具
+//
+// EN: Mirrors scripts/map-generation/shared.mjs so native tools agree with the Node cook on paths and formats.
+// 中文: 与 scripts/map-generation/shared.mjs 保持一致，使原生工具与 Node cook 的路径和格式约定相同。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+
+pub(crate) const PAGE_SIZE_METERS: f64 = 64.0;
+pub(crate) const HEIGHT_REGION_SIZE_PAGES: i32 = 8;
+pub(crate) const PAINT_REGION_SIZE_PAGES: i32 = 8;
+pub(crate) const VEGETATION_CELL_SIZE_METERS: f64 = 32.0;
+pub(crate) const VEGETATION_REGION_SIZE_CELLS: i32 = 8;
+
+pub(crate) const GENERATION_GRAPH_PATH: &str = "generation/graph.json";
+pub(crate) const TERRAIN_HEIGHT_PATH: &str = "terrain/height/manifest.json";
+pub(crate) const HEIGHT_REGIONS_DIRECTORY: &str = "terrain/height/regions";
+pub(crate) const HEIGHT_REGION_FORMAT: &str = "height-region-pack-v1";
+pub(crate) const HEIGHT_SAMPLE_FORMAT: &str = "float32le";
+pub(crate) const SPLINE_TERRAIN_BASELINE_DIRECTORY: &str = "terrain/height/spline-baseline";
+pub(crate) const PAINT_MANIFEST_PATH: &str = "paint/layers.json";
+pub(crate) const PAINT_REGIONS_DIRECTORY: &str = "paint/regions";
+pub(crate) const PAINT_REGION_FORMAT: &str = "rgba8-splat-region-pack-v1";
+pub(crate) const VEGETATION_MODELS_PATH: &str = "vegetation/models.json";
+pub(crate) const VEGETATION_REGIONS_DIRECTORY: &str = "vegetation/regions";
+pub(crate) const VEGETATION_REGION_FORMAT: &str = "vegetation-region-pack-v1";
+pub(crate) const VEGETATION_INSTANCE_FORMAT: &str = "instanced-f32le-v1";
+pub(crate) const VEGETATION_IMPOSTOR_FORMAT: &str = "vegetation-impostor-atlas-v1";
+pub(crate) const WORLD_OBJECTS_PATH: &str = "objects/manifest.json";
+pub(crate) const WORLD_OBJECT_CELLS_DIRECTORY: &str = "objects/cells";
+pub(crate) const WORLD_OBJECT_MANIFEST_FORMAT: &str = "world-object-manifest-v1";
+pub(crate) const ASSET_REGISTRY_PATH: &str = "assets/registry.json";
+pub(crate) const WORLD_PREFABS_DIRECTORY: &str = "prefabs";
+pub(crate) const WORLD_PREFAB_FORMAT: &str = "world-object-prefab-v1";
+
+pub(crate) const COOKED_MAPS_DIRECTORY: &str = "cooked/maps";
+pub(crate) const COOKED_MAP_MANIFEST_FILE: &str = "manifest.json";
+pub(crate) const COOKED_MAP_FORMAT: &str = "open-fps-cooked-map-v4";
+pub(crate) const COOKED_MAP_VERSION: u32 = 4;
+pub(crate) const COOKED_BUILD_CACHE_DIRECTORY: &str = "cooked/cache/maps";
+pub(crate) const COOK_CACHE_FORMAT: &str = "open-fps-cook-cache-v1";
+pub(crate) const COOK_REPORTS_DIRECTORY: &str = "cooked/reports";
+pub(crate) const COOK_REPORT_FORMAT: &str = "open-fps-cook-report-v1";
+pub(crate) const COOK_HISTORY_FORMAT: &str = "open-fps-cook-history-v1";
+pub(crate) const COOKED_BLOB_DIRECTORY: &str = "cooked/blobs/sha256";
+pub(crate) const COOKED_TEXTURE_MANIFEST_PATH: &str = "cooked/textures/manifest.json";
+pub(crate) const COOKED_TEXTURE_MANIFEST_FORMAT: &str = "open-fps-cooked-textures-v1";
+pub(crate) const COOKED_WORLD_PARTITION_CELL_SIZE_PAGES: i32 = 8;
+pub(crate) const COOKED_WORLD_PARTITION_DEPENDENCY_KINDS: &[&str] = &[
+    "terrain",
+    "paint",
+    "vegetation",
+    "objects",
+    "collision",
+    "nav",
+];
+pub(crate) const WORLD_OBJECT_CELL_FORMAT: &str = "world-object-cell-pack-v1";
+pub(crate) const COOKED_COLLISION_CELL_FORMAT: &str = "world-collision-cell-pack-v1";
+pub(crate) const COOKED_NAV_CELL_FORMAT: &str = "world-nav-cell-pack-v1";
+
+/// World-space rectangle in meters.
+/// 以米为单位的世界空间矩形
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldRect {
+    pub(crate) min_x: f64,
+    pub(crate) min_z: f64,
+    pub(crate) max_x: f64,
+    pub(crate) max_z: f64,
+}
+
+impl WorldRect {
+    pub(crate) fn validate(&self, label: &str) -> Result<(), String> {
+        if ![self.min_x, self.min_z, self.max_x, self.max_z]
+            .iter()
+            .all(|value| value.is_finite())
+            || self.min_x >= self.max_x
+            || self.min_z >= self.max_z
+        {
+            return Err(format!(
+                "{} world rectangle must be finite with min below max",
+                label
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Read a `{ minX, minZ, maxX, maxZ }` object, rejecting missing or non-finite edges.
+    /// 读取 `{ minX, minZ, maxX, maxZ }` 对象，拒绝缺失或非有限的边界
+    pub(crate) fn from_value(value: &Value) -> Option<Self> {
+        let rect = Self {
+            min_x: value["minX"].as_f64()?,
+            min_z: value["minZ"].as_f64()?,
+            max_x: value["maxX"].as_f64()?,
+            max_z: value["maxZ"].as_f64()?,
+        };
+        [rect.min_x, rect.min_z, rect.max_x, rect.max_z]
+            .iter()
+            .all(|value| value.is_finite())
+            .then_some(rect)
+    }
+}
+
+pub(crate) fn smoothstep(edge0: f64, edge1: f64, value: f64) -> f64 {
+    let t = ((value - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Horizontal distance from a point to a segment and the clamped parameter of the closest point.
+/// 点到线段的水平距离，以及最近点处被夹取的参数
+pub(crate) fn segment_projection(start: [f64; 2], end: [f64; 2], x: f64, z: f64) -> (f64, f64) {
+    let (dx, dz) = (end[0] - start[0], end[1] - start[1]);
+    let length_squared = dx * dx + dz * dz;
+    let t = if length_squared > 0.0 {
+        (((x - start[0]) * dx + (z - start[1]) * dz) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((start[0] + dx * t - x).hypot(start[1] + dz * t - z), t)
+}
+
+/// Horizontal distance from a point to a polyline; infinite when the polyline is empty.
+/// 点到折线的水平距离；折线为空时为无穷大
+pub(crate) fn distance_to_polyline(points: &[[f64; 2]], x: f64, z: f64) -> f64 {
+    let mut nearest = points
+        .first()
+        .map_or(f64::INFINITY, |[px, pz]| (px - x).hypot(pz - z));
+    for segment in points.windows(2) {
+        nearest = nearest.min(segment_projection(segment[0], segment[1], x, z).0);
+    }
+    nearest
+}
+
+/// SplitMix64 finalizer used to derive stable per-candidate seeds.
+/// 用于派生稳定逐候选种子的 SplitMix64 终混函数
+pub(crate) fn splitmix64(value: u64) -> u64 {
+    let mut state = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    state = (state ^ (state >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    state = (state ^ (state >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    state ^ (state >> 31)
+}
+
+/// Parse a `<x>,<z>` grid key.
+/// 解析 `<x>,<z>` 网格键
+pub(crate) fn parse_grid_key(key: &str) -> Option<(i32, i32)> {
+    let (x, z) = key.split_once(',')?;
+    Some((x.trim().parse().ok()?, z.trim().parse().ok()?))
+}
+
+pub(crate) fn format_grid_key(x: i32, z: i32) -> String {
+    format!("{},{}", x, z)
+}
+
+/// Order grid keys row-major (z first, then x), matching the Node cook.
+/// 按行优先（先 z 后 x）排序网格键，与 Node cook 一致
+pub(crate) fn compare_grid_keys(left: &str, right: &str) -> Ordering {
+    let (left_x, left_z) = parse_grid_key(left).unwrap_or((0, 0));
+    let (right_x, right_z) = parse_grid_key(right).unwrap_or((0, 0));
+    left_z.cmp(&right_z).then(left_x.cmp(&right_x))
+}
+
+pub(crate) fn sort_grid_keys(keys: &mut Vec<String>) {
+    keys.sort_by(|left, right| compare_grid_keys(left, right));
+    keys.dedup();
+}
+
+pub(crate) fn format_grid_coordinate(value: i32) -> String {
+    if value < 0 {
+        format!("m{}", value.unsigned_abs())
+    } else {
+        value.to_string()
+    }
+}
+
+/// Build the `r_X_Z.<extension>` file name of a region pack.
+/// 构造区域包的 `r_X_Z.<extension>` 文件名
+pub(crate) fn region_file_name(x: i32, z: i32, extension: &str) -> String {
+    format!(
+        "r_{}_{}.{}",
+        format_grid_coordinate(x),
+        format_grid_coordinate(z),
+        extension
+    )
+}
+
+/// Build the `c_X_Z.<extension>` file name of a world partition cell pack.
+/// 构造世界分区单元包的 `c_X_Z.<extension>` 文件名
+pub(crate) fn cell_file_name(x: i32, z: i32, extension: &str) -> String {
+    format!(
+        "c_{}_{}.{}",
+        format_grid_coordinate(x),
+        format_grid_coordinate(z),
+        extension
+    )
+}
+
+fn parse_grid_coordinate(value: &str) -> Option<i32> {
+    match value.strip_prefix('m') {
+        Some(digits) => digits.parse::<i32>().ok().map(|value| -value),
+        None => value.parse().ok(),
+    }
+}
+
+/// Read the grid key from a `r_X_Z.*` region or `c_X_Z.*` cell file name.
+/// 从 `r_X_Z.*` 区域或 `c_X_Z.*` 单元文件名读取网格键
+pub(crate) fn grid_key_from_file_name(file_name: &str) -> Option<String> {
+    let stem = file_name.split('.').next()?;
+    let rest = stem
+        .strip_prefix("r_")
+        .or_else(|| stem.strip_prefix("c_"))?;
+    let (x, z) = rest.split_once('_')?;
+    Some(format_grid_key(
+        parse_grid_coordinate(x)?,
+        parse_grid_coordinate(z)?,
+    ))
+}
+
+pub(crate) fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
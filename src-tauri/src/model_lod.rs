@@ -0,0 +1,831 @@
+// Automatic LOD generation for registered glTF models.
+// 为已注册 glTF 模型自动生成 LOD
+//
+// EN: Quadric edge collapse onto existing vertices; a vertex only collapses when each of its UV/normal wedges continues into a wedge of the target, so seams survive.
+// 中文: 基于二次误差的边折叠，仅折叠到已有顶点；只有当顶点的每个 UV/法线楔都能延续到目标顶点的楔时才允许折叠，从而保留接缝。
+
+use crate::asset_references::normalize_project_path;
+use crate::asset_registry::{AssetRegistry, store_asset_registry_entry};
+use crate::commands::{MAP_FILE, MAPS_DIR, safe_write, validate_cook_project_path};
+use crate::gltf_inspect::GltfAsset;
+use crate::map_layout::VEGETATION_MODELS_PATH;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const MODEL_LOD_DEFAULT_RATIOS: [f64; 2] = [0.5, 0.2];
+const MODEL_LOD_MIN_TRIANGLES: usize = 4;
+const MODEL_LOD_MAX_PASSES: usize = 96;
+const MODEL_LOD_BORDER_WEIGHT: f64 = 10.0;
+const MODEL_LOD_MIN_FLIP_COSINE: f64 = 0.01;
+const GLTF_ARRAY_BUFFER: u64 = 34962;
+const GLTF_ELEMENT_ARRAY_BUFFER: u64 = 34963;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateModelLodsRequest {
+    project_path: String,
+    asset_id: String,
+    #[serde(default)]
+    lod1_ratio: Option<f64>,
+    #[serde(default)]
+    lod2_ratio: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenerateModelLodsResult {
+    asset_id: String,
+    source_triangles: usize,
+    levels: Vec<GeneratedModelLod>,
+    updated_maps: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeneratedModelLod {
+    level: u8,
+    path: String,
+    target_ratio: f64,
+    triangle_count: usize,
+    achieved_ratio: f64,
+}
+
+/// Symmetric 4x4 plane quadric stored as its upper triangle.
+/// 以上三角形式存储的对称 4x4 平面二次误差矩阵
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+    fn from_plane(normal: [f64; 3], distance: f64, weight: f64) -> Self {
+        let [a, b, c] = normal;
+        let d = distance;
+        Self(
+            [
+                a * a,
+                a * b,
+                a * c,
+                a * d,
+                b * b,
+                b * c,
+                b * d,
+                c * c,
+                c * d,
+                d * d,
+            ]
+            .map(|value| value * weight),
+        )
+    }
+
+    fn add(&mut self, other: &Quadric) {
+        for (value, added) in self.0.iter_mut().zip(other.0) {
+            *value += added;
+        }
+    }
+
+    fn error(&self, point: [f64; 3]) -> f64 {
+        let [x, y, z] = point;
+        let q = &self.0;
+        (q[0] * x * x
+            + q[4] * y * y
+            + q[7] * z * z
+            + q[9]
+            + 2.0 * (q[1] * x * y + q[2] * x * z + q[3] * x + q[5] * y * z + q[6] * y + q[8] * z))
+            .abs()
+    }
+}
+
+/// Accumulates vertex and index data into one binary buffer with matching views and accessors.
+/// 将顶点与索引数据累积到同一个二进制缓冲区，并生成对应的 bufferView 与访问器
+#[derive(Default)]
+struct GltfBinaryWriter {
+    bytes: Vec<u8>,
+    views: Vec<Value>,
+    accessors: Vec<Value>,
+}
+
+impl GltfBinaryWriter {
+    fn push_view(&mut self, data: &[u8], target: Option<u64>) -> usize {
+        while !self.bytes.len().is_multiple_of(4) {
+            self.bytes.push(0);
+        }
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.bytes.len(),
+            "byteLength": data.len(),
+        });
+        if let Some(target) = target {
+            view["target"] = json!(target);
+        }
+        self.bytes.extend_from_slice(data);
+        self.views.push(view);
+        self.views.len() - 1
+    }
+
+    fn push_attribute(&mut self, values: &[f32], components: usize, with_bounds: bool) -> usize {
+        let data: Vec<u8> = values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let view = self.push_view(&data, Some(GLTF_ARRAY_BUFFER));
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": 5126,
+            "count": values.len() / components.max(1),
+            "type": match components {
+                1 => "SCALAR",
+                2 => "VEC2",
+                3 => "VEC3",
+                _ => "VEC4",
+            },
+        });
+        if with_bounds {
+            let mut min = vec![f32::INFINITY; components];
+            let mut max = vec![f32::NEG_INFINITY; components];
+            for element in values.chunks_exact(components) {
+                for (axis, value) in element.iter().enumerate() {
+                    min[axis] = min[axis].min(*value);
+                    max[axis] = max[axis].max(*value);
+                }
+            }
+            accessor["min"] = json!(min);
+            accessor["max"] = json!(max);
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u32], vertex_count: usize) -> usize {
+        let (data, component_type): (Vec<u8>, u64) = if vertex_count <= u16::MAX as usize {
+            (
+                indices
+                    .iter()
+                    .flat_map(|index| (*index as u16).to_le_bytes())
+                    .collect(),
+                5123,
+            )
+        } else {
+            (
+                indices
+                    .iter()
+                    .flat_map(|index| index.to_le_bytes())
+                    .collect(),
+                5125,
+            )
+        };
+        let view = self.push_view(&data, Some(GLTF_ELEMENT_ARRAY_BUFFER));
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": component_type,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+}
+
+// --- Model LOD commands / 模型 LOD 命令 ---
+
+/// Generate LOD1/LOD2 glTFs for a registered model and point the registry and vegetation models at them.
+/// 为已注册模型生成 LOD1/LOD2 glTF，并让注册表与植被模型指向它们
+#[tauri::command]
+pub async fn generate_model_lods(
+    request: GenerateModelLodsRequest,
+) -> Result<GenerateModelLodsResult, String> {
+    tauri::async_runtime::spawn_blocking(move || generate_model_lods_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join model LOD generation task: {}", e))?
+}
+
+fn generate_model_lods_blocking(
+    request: GenerateModelLodsRequest,
+) -> Result<GenerateModelLodsResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let ratios = [
+        request.lod1_ratio.unwrap_or(MODEL_LOD_DEFAULT_RATIOS[0]),
+        request.lod2_ratio.unwrap_or(MODEL_LOD_DEFAULT_RATIOS[1]),
+    ];
+    if ratios
+        .iter()
+        .any(|ratio| !ratio.is_finite() || *ratio <= 0.0 || *ratio >= 1.0)
+        || ratios[1] >= ratios[0]
+    {
+        return Err("LOD ratios must satisfy 0 < lod2Ratio < lod1Ratio < 1".to_string());
+    }
+
+    let registry = AssetRegistry::read(&project_root)?;
+    let mut entry = registry
+        .assets()
+        .remove(&request.asset_id)
+        .ok_or_else(|| format!("Asset '{}' is not in the registry", request.asset_id))?;
+    if entry.get("type").and_then(Value::as_str) != Some("model") {
+        return Err(format!("Asset '{}' is not a model", request.asset_id));
+    }
+    let primary = entry
+        .pointer("/imported/files/primary")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("Asset '{}' has no imported.files.primary", request.asset_id))?;
+    let source = GltfAsset::load(&project_root.join(&primary))?;
+    if let Some(missing) = source.missing_files.first() {
+        return Err(format!("Model references missing file '{}'", missing));
+    }
+    validate_simplifiable(&source)?;
+
+    let (primary_directory, _) = primary
+        .rsplit_once('/')
+        .ok_or_else(|| format!("Model path '{}' has no folder", primary))?;
+    let stem = request
+        .asset_id
+        .rsplit(':')
+        .next()
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or("model");
+    let source_triangles = triangle_count(&source)?;
+
+    let mut levels = Vec::new();
+    for (level, ratio) in [(1u8, ratios[0]), (2u8, ratios[1])] {
+        let directory = format!("{}/lod{}", primary_directory, level);
+        let file_name = format!("{}_lod{}", stem, level);
+        let (document, binary, triangles) = build_lod_document(&source, ratio, &file_name)?;
+        let text = serde_json::to_string_pretty(&document)
+            .map_err(|e| format!("Failed to serialize LOD glTF: {}", e))?;
+        safe_write(
+            &project_root.join(format!("{}/{}.bin", directory, file_name)),
+            &binary,
+        )
+        .map_err(|e| format!("Failed to write LOD buffer: {}", e))?;
+        let path = format!("{}/{}.gltf", directory, file_name);
+        safe_write(&project_root.join(&path), format!("{}\n", text).as_bytes())
+            .map_err(|e| format!("Failed to write LOD glTF: {}", e))?;
+
+        entry["imported"]["files"][format!("lod{}", level)] = Value::String(path.clone());
+        levels.push(GeneratedModelLod {
+            level,
+            path,
+            target_ratio: ratio,
+            triangle_count: triangles,
+            achieved_ratio: triangles as f64 / source_triangles.max(1) as f64,
+        });
+    }
+
+    store_asset_registry_entry(&project_root, entry, true)?;
+    let updated_maps = update_vegetation_models(&project_root, &primary, &levels)?;
+
+    Ok(GenerateModelLodsResult {
+        asset_id: request.asset_id,
+        source_triangles,
+        levels,
+        updated_maps,
+    })
+}
+
+/// Skinned, morphed or compressed geometry is rejected instead of being silently broken.
+/// 蒙皮、变形或压缩几何会被拒绝，而不是被静默破坏
+fn validate_simplifiable(asset: &GltfAsset) -> Result<(), String> {
+    for unsupported in ["skins", "animations"] {
+        if !asset.array(unsupported).is_empty() {
+            return Err(format!(
+                "Models with {} are not supported by LOD generation",
+                unsupported
+            ));
+        }
+    }
+    if !asset.array("extensionsRequired").is_empty() {
+        return Err("Models with required glTF extensions are not supported".to_string());
+    }
+    let has_unsupported_primitive = asset
+        .array("meshes")
+        .iter()
+        .filter_map(|mesh| mesh.get("primitives").and_then(Value::as_array))
+        .flatten()
+        .any(|primitive| {
+            primitive.get("targets").is_some() || primitive.get("extensions").is_some()
+        });
+    if has_unsupported_primitive {
+        return Err("Morph targets and compressed primitives are not supported".to_string());
+    }
+    Ok(())
+}
+
+fn triangle_count(asset: &GltfAsset) -> Result<usize, String> {
+    let mut total = 0;
+    for mesh in asset.array("meshes") {
+        for primitive in mesh
+            .get("primitives")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+        {
+            if primitive.get("mode").and_then(Value::as_u64).unwrap_or(4) == 4 {
+                total += asset.read_indices(primitive)?.len() / 3;
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Rebuild the glTF with simplified primitives in a fresh buffer; materials, nodes and textures are kept.
+/// 用新缓冲区中的简化图元重建 glTF；保留材质、节点与纹理
+fn build_lod_document(
+    source: &GltfAsset,
+    ratio: f64,
+    file_name: &str,
+) -> Result<(Value, Vec<u8>, usize), String> {
+    let mut document = source.document.clone();
+    let mut writer = GltfBinaryWriter::default();
+    let mut triangles = 0;
+    let materials = source.array("materials");
+
+    let mut meshes = source.array("meshes").to_vec();
+    for mesh in &mut meshes {
+        let Some(primitives) = mesh.get_mut("primitives").and_then(Value::as_array_mut) else {
+            continue;
+        };
+        for primitive in primitives {
+            let attributes: Vec<(String, usize)> = primitive
+                .get("attributes")
+                .and_then(Value::as_object)
+                .map(|attributes| {
+                    attributes
+                        .iter()
+                        .filter_map(|(name, index)| Some((name.clone(), index.as_u64()? as usize)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let mut attribute_values = Vec::with_capacity(attributes.len());
+            for (name, accessor) in &attributes {
+                let (values, components) = source.read_accessor(*accessor)?;
+                attribute_values.push((name.clone(), values, components));
+            }
+            let mut indices = source.read_indices(primitive)?;
+
+            // EN: Indices and attributes are validated up front; the simplifier and the remap below index them unchecked.
+            // 中文: 预先校验索引与属性；下方的简化器与重映射直接按索引访问。
+            let vertex_count = attribute_values
+                .iter()
+                .find(|(name, _, _)| name == "POSITION")
+                .or(attribute_values.first())
+                .map_or(0, |(_, values, components)| values.len() / components);
+            if let Some((name, values, components)) = attribute_values
+                .iter()
+                .find(|(_, values, components)| values.len() / components != vertex_count)
+            {
+                return Err(format!(
+                    "glTF attribute {} has {} elements but POSITION has {}",
+                    name,
+                    values.len() / components,
+                    vertex_count
+                ));
+            }
+            if let Some(index) = indices
+                .iter()
+                .find(|index| **index as usize >= vertex_count)
+            {
+                return Err(format!(
+                    "glTF primitive index {} is out of range for {} vertices",
+                    index, vertex_count
+                ));
+            }
+
+            if primitive.get("mode").and_then(Value::as_u64).unwrap_or(4) == 4 {
+                let positions: Vec<[f32; 3]> = attribute_values
+                    .iter()
+                    .find(|(name, _, _)| name == "POSITION")
+                    .map(|(_, values, _)| {
+                        values
+                            .chunks_exact(3)
+                            .map(|point| [point[0], point[1], point[2]])
+                            .collect()
+                    })
+                    .ok_or_else(|| "glTF primitive has no POSITION attribute".to_string())?;
+                // EN: Alpha-tested cards get their open borders locked so leaf silhouettes do not shrink.
+                // 中文: 透明测试面片的开放边界被锁定，避免叶片轮廓收缩。
+                let alpha_mode = primitive
+                    .get("material")
+                    .and_then(Value::as_u64)
+                    .and_then(|material| materials.get(material as usize))
+                    .and_then(|material| material.get("alphaMode"))
+                    .and_then(Value::as_str)
+                    .unwrap_or("OPAQUE");
+                let source_triangles = indices.len() / 3;
+                let target = ((source_triangles as f64 * ratio).round() as usize)
+                    .max(MODEL_LOD_MIN_TRIANGLES)
+                    .min(source_triangles);
+                indices = simplify_triangles(&positions, &indices, target, alpha_mode != "OPAQUE");
+                triangles += indices.len() / 3;
+            }
+
+            // EN: Drop vertices no longer referenced so the LOD buffer shrinks with its triangles.
+            // 中文: 删除不再被引用的顶点，使 LOD 缓冲区随三角形一同缩小。
+            let mut remap = vec![u32::MAX; vertex_count];
+            let mut kept = Vec::new();
+            for index in &mut indices {
+                let slot = &mut remap[*index as usize];
+                if *slot == u32::MAX {
+                    *slot = kept.len() as u32;
+                    kept.push(*index as usize);
+                }
+                *index = *slot;
+            }
+
+            let mut new_attributes = serde_json::Map::new();
+            for (name, values, components) in &attribute_values {
+                let compacted: Vec<f32> = kept
+                    .iter()
+                    .flat_map(|vertex| &values[vertex * components..(vertex + 1) * components])
+                    .copied()
+                    .collect();
+                let accessor = writer.push_attribute(&compacted, *components, name == "POSITION");
+                new_attributes.insert(name.clone(), json!(accessor));
+            }
+            primitive["attributes"] = Value::Object(new_attributes);
+            primitive["indices"] = json!(writer.push_indices(&indices, kept.len()));
+        }
+    }
+    document["meshes"] = Value::Array(meshes);
+
+    let mut images = source.array("images").to_vec();
+    for image in &mut images {
+        if let Some(view) = image.get("bufferView").and_then(Value::as_u64) {
+            let data = source.buffer_view_bytes(view as usize)?.to_vec();
+            image["bufferView"] = json!(writer.push_view(&data, None));
+        } else if let Some(uri) = image.get("uri").and_then(Value::as_str)
+            && !uri.starts_with("data:")
+            && !uri.contains("://")
+        {
+            // EN: LODs live one folder below the source, so relative texture URIs gain a "../".
+            // 中文: LOD 位于源模型下一级文件夹，因此相对纹理 URI 需加上 "../"。
+            image["uri"] = Value::String(format!("../{}", uri));
+        }
+    }
+    if !images.is_empty() {
+        document["images"] = Value::Array(images);
+    }
+
+    let object = document
+        .as_object_mut()
+        .ok_or_else(|| "glTF document must be an object".to_string())?;
+    object.insert(
+        "buffers".to_string(),
+        json!([{ "uri": format!("{}.bin", file_name), "byteLength": writer.bytes.len() }]),
+    );
+    object.insert("bufferViews".to_string(), Value::Array(writer.views));
+    object.insert("accessors".to_string(), Value::Array(writer.accessors));
+    document["asset"]["generator"] = json!("open-fps model LOD generator");
+    Ok((document, writer.bytes, triangles))
+}
+
+/// Simplify a triangle list to roughly `target` triangles, returning indices into the original vertices.
+/// 将三角形列表简化到约 `target` 个三角形，返回指向原始顶点的索引
+fn simplify_triangles(
+    positions: &[[f32; 3]],
+    indices: &[u32],
+    target: usize,
+    lock_border: bool,
+) -> Vec<u32> {
+    // EN: Weld wedges that share a position so seams are recognised as one topological vertex.
+    // 中文: 合并位置相同的楔，使接缝被识别为同一个拓扑顶点。
+    let mut position_ids: HashMap<[u32; 3], u32> = HashMap::new();
+    let mut points: Vec<[f64; 3]> = Vec::new();
+    let wedge_position: Vec<u32> = positions
+        .iter()
+        .map(|point| {
+            *position_ids
+                .entry(point.map(f32::to_bits))
+                .or_insert_with(|| {
+                    points.push(point.map(|value| value as f64));
+                    points.len() as u32 - 1
+                })
+        })
+        .collect();
+    let mut triangles: Vec<[u32; 3]> = indices
+        .chunks_exact(3)
+        .map(|corners| [corners[0], corners[1], corners[2]])
+        .filter(|corners| {
+            corners
+                .iter()
+                .all(|corner| (*corner as usize) < wedge_position.len())
+        })
+        .filter(|corners| !is_degenerate(&wedge_position, corners))
+        .collect();
+
+    let mut quadrics = vec![Quadric::default(); points.len()];
+    for corners in &triangles {
+        let [a, b, c] = corners.map(|corner| points[wedge_position[corner as usize] as usize]);
+        let normal = cross(sub(b, a), sub(c, a));
+        let length = dot(normal, normal).sqrt();
+        if length <= f64::EPSILON {
+            continue;
+        }
+        let unit = normal.map(|value| value / length);
+        let quadric = Quadric::from_plane(unit, -dot(unit, a), length * 0.5);
+        for corner in corners {
+            quadrics[wedge_position[*corner as usize] as usize].add(&quadric);
+        }
+    }
+    if !lock_border {
+        // EN: Border planes perpendicular to open edges keep outlines from drifting inward.
+        // 中文: 垂直于开放边的边界平面防止轮廓向内漂移。
+        let counts = edge_counts(&triangles, &wedge_position);
+        for corners in &triangles {
+            let ids = corners.map(|corner| wedge_position[corner as usize]);
+            let [a, b, c] = ids.map(|id| points[id as usize]);
+            let normal = cross(sub(b, a), sub(c, a));
+            for edge in 0..3 {
+                let (from, to) = (ids[edge], ids[(edge + 1) % 3]);
+                if counts.get(&edge_key(from, to)) != Some(&1) {
+                    continue;
+                }
+                let direction = sub(points[to as usize], points[from as usize]);
+                let perpendicular = cross(direction, normal);
+                let length = dot(perpendicular, perpendicular).sqrt();
+                if length <= f64::EPSILON {
+                    continue;
+                }
+                let unit = perpendicular.map(|value| value / length);
+                let quadric = Quadric::from_plane(
+                    unit,
+                    -dot(unit, points[from as usize]),
+                    dot(direction, direction) * MODEL_LOD_BORDER_WEIGHT,
+                );
+                quadrics[from as usize].add(&quadric);
+                quadrics[to as usize].add(&quadric);
+            }
+        }
+    }
+
+    for _ in 0..MODEL_LOD_MAX_PASSES {
+        if triangles.len() <= target {
+            break;
+        }
+        let counts = edge_counts(&triangles, &wedge_position);
+        let mut border = vec![false; points.len()];
+        let mut locked = vec![false; points.len()];
+        for (&(a, b), &count) in &counts {
+            if count == 1 {
+                border[a as usize] = true;
+                border[b as usize] = true;
+            } else if count > 2 {
+                locked[a as usize] = true;
+                locked[b as usize] = true;
+            }
+        }
+        if lock_border {
+            for (lock, is_border) in locked.iter_mut().zip(&border) {
+                *lock |= *is_border;
+            }
+        }
+
+        let mut adjacency = vec![Vec::new(); points.len()];
+        for (triangle, corners) in triangles.iter().enumerate() {
+            for corner in corners {
+                let id = wedge_position[*corner as usize] as usize;
+                if adjacency[id].last() != Some(&triangle) {
+                    adjacency[id].push(triangle);
+                }
+            }
+        }
+
+        let mut candidates = Vec::new();
+        for (&(a, b), &count) in &counts {
+            for (from, to) in [(a, b), (b, a)] {
+                if locked[from as usize] || (border[from as usize] && count != 1) {
+                    continue;
+                }
+                let mut quadric = quadrics[from as usize];
+                quadric.add(&quadrics[to as usize]);
+                candidates.push((quadric.error(points[to as usize]), from, to));
+            }
+        }
+        candidates.sort_by(|left, right| left.0.total_cmp(&right.0));
+
+        // EN: Each collapse removes about two triangles; one pass only applies the cheapest third so costs stay current.
+        // 中文: 每次折叠约移除两个三角形；每轮只执行最便宜的三分之一，使误差保持最新。
+        let max_collapses = ((triangles.len() - target) / 2)
+            .max(1)
+            .min(candidates.len() / 3 + 1);
+        let mut touched = vec![false; points.len()];
+        let mut wedge_remap: Vec<u32> = (0..wedge_position.len() as u32).collect();
+        let mut collapsed = 0;
+        for (_, from, to) in candidates {
+            if collapsed >= max_collapses {
+                break;
+            }
+            if touched[from as usize] || touched[to as usize] {
+                continue;
+            }
+            let triangles_around = &adjacency[from as usize];
+            let Some(mapping) =
+                wedge_mapping(&triangles, triangles_around, &wedge_position, from, to)
+            else {
+                continue;
+            };
+            if collapse_flips(
+                &triangles,
+                triangles_around,
+                &wedge_position,
+                &points,
+                from,
+                to,
+            ) {
+                continue;
+            }
+
+            for (wedge, target_wedge) in mapping {
+                wedge_remap[wedge as usize] = target_wedge;
+            }
+            let merged = quadrics[from as usize];
+            quadrics[to as usize].add(&merged);
+            for triangle in triangles_around {
+                for corner in triangles[*triangle] {
+                    touched[wedge_position[corner as usize] as usize] = true;
+                }
+            }
+            collapsed += 1;
+        }
+        if collapsed == 0 {
+            break;
+        }
+
+        triangles = triangles
+            .into_iter()
+            .map(|corners| corners.map(|corner| wedge_remap[corner as usize]))
+            .filter(|corners| !is_degenerate(&wedge_position, corners))
+            .collect();
+    }
+
+    triangles.into_iter().flatten().collect()
+}
+
+/// Map every wedge of `from` to the wedge of `to` it shares a triangle with; `None` when a seam would be welded or broken.
+/// 将 `from` 的每个楔映射到与其共享三角形的 `to` 的楔；会合并或破坏接缝时返回 `None`
+fn wedge_mapping(
+    triangles: &[[u32; 3]],
+    triangles_around: &[usize],
+    wedge_position: &[u32],
+    from: u32,
+    to: u32,
+) -> Option<Vec<(u32, u32)>> {
+    let mut mapping: Vec<(u32, Option<u32>)> = Vec::new();
+    for triangle in triangles_around {
+        let corners = triangles[*triangle];
+        let Some(from_wedge) = corners
+            .iter()
+            .copied()
+            .find(|corner| wedge_position[*corner as usize] == from)
+        else {
+            continue;
+        };
+        let to_wedge = corners
+            .iter()
+            .copied()
+            .find(|corner| wedge_position[*corner as usize] == to);
+        match mapping.iter_mut().find(|(wedge, _)| *wedge == from_wedge) {
+            Some((_, existing)) => match (*existing, to_wedge) {
+                (Some(current), Some(next)) if current != next => return None,
+                (None, Some(next)) => *existing = Some(next),
+                _ => {}
+            },
+            None => mapping.push((from_wedge, to_wedge)),
+        }
+    }
+
+    let mapping: Vec<(u32, u32)> = mapping
+        .into_iter()
+        .map(|(wedge, target)| Some((wedge, target?)))
+        .collect::<Option<_>>()?;
+    let mut targets: Vec<u32> = mapping.iter().map(|(_, target)| *target).collect();
+    targets.sort_unstable();
+    targets.dedup();
+    (targets.len() == mapping.len()).then_some(mapping)
+}
+
+/// True when moving `from` onto `to` would flip or collapse a surviving triangle.
+/// 当把 `from` 移到 `to` 会使保留的三角形翻转或退化时返回 true
+fn collapse_flips(
+    triangles: &[[u32; 3]],
+    triangles_around: &[usize],
+    wedge_position: &[u32],
+    points: &[[f64; 3]],
+    from: u32,
+    to: u32,
+) -> bool {
+    triangles_around.iter().any(|triangle| {
+        let ids = triangles[*triangle].map(|corner| wedge_position[corner as usize]);
+        if ids.contains(&to) {
+            return false;
+        }
+        let before = ids.map(|id| points[id as usize]);
+        let after = ids.map(|id| points[if id == from { to } else { id } as usize]);
+        let normal_before = cross(sub(before[1], before[0]), sub(before[2], before[0]));
+        let normal_after = cross(sub(after[1], after[0]), sub(after[2], after[0]));
+        let scale = (dot(normal_before, normal_before) * dot(normal_after, normal_after)).sqrt();
+        scale <= f64::EPSILON
+            || dot(normal_before, normal_after) <= scale * MODEL_LOD_MIN_FLIP_COSINE
+    })
+}
+
+/// Undirected edge use counts over welded positions.
+/// 基于合并位置的无向边使用次数
+fn edge_counts(triangles: &[[u32; 3]], wedge_position: &[u32]) -> HashMap<(u32, u32), u32> {
+    let mut counts = HashMap::new();
+    for corners in triangles {
+        let ids = corners.map(|corner| wedge_position[corner as usize]);
+        for edge in 0..3 {
+            *counts
+                .entry(edge_key(ids[edge], ids[(edge + 1) % 3]))
+                .or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+fn edge_key(a: u32, b: u32) -> (u32, u32) {
+    (a.min(b), a.max(b))
+}
+
+fn is_degenerate(wedge_position: &[u32], corners: &[u32; 3]) -> bool {
+    let [a, b, c] = corners.map(|corner| wedge_position[corner as usize]);
+    a == b || b == c || a == c
+}
+
+pub(crate) fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub(crate) fn cross(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub(crate) fn dot(a: [f64; 3], b: [f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Point every map's vegetation model that uses `primary` at the generated LODs.
+/// 让每张地图中使用 `primary` 的植被模型指向生成的 LOD
+fn update_vegetation_models(
+    project_root: &Path,
+    primary: &str,
+    levels: &[GeneratedModelLod],
+) -> Result<Vec<String>, String> {
+    let mut map_ids: Vec<String> = match fs::read_dir(project_root.join(MAPS_DIR)) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .filter(|entry| entry.path().join(MAP_FILE).is_file())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    map_ids.sort();
+
+    let mut updated_maps = Vec::new();
+    for map_id in map_ids {
+        let map_directory = format!("{}/{}", MAPS_DIR, map_id);
+        let models_path = project_root
+            .join(&map_directory)
+            .join(VEGETATION_MODELS_PATH);
+        let Ok(text) = fs::read_to_string(&models_path) else {
+            continue;
+        };
+        let mut document: Value = serde_json::from_str(&text)
+            .map_err(|e| format!("Failed to parse vegetation models: {}", e))?;
+        let Some(models) = document.get_mut("models").and_then(Value::as_object_mut) else {
+            continue;
+        };
+
+        let mut changed = false;
+        for model in models.values_mut() {
+            let uses_primary = model
+                .get("path")
+                .and_then(Value::as_str)
+                .and_then(|path| normalize_project_path(Path::new(&map_directory), path))
+                .is_some_and(|path| path == primary);
+            if !uses_primary {
+                continue;
+            }
+            for level in levels {
+                // EN: Map files reference assets relative to maps/<id>/, matching the existing "../../assets" paths.
+                // 中文: 地图文件以 maps/<id>/ 为基准引用资源，与现有的 "../../assets" 路径一致。
+                model[format!("lod{}Path", level.level)] =
+                    Value::String(format!("../../{}", level.path));
+            }
+            changed = true;
+        }
+
+        if changed {
+            let text = serde_json::to_string_pretty(&document)
+                .map_err(|e| format!("Failed to serialize vegetation models: {}", e))?;
+            safe_write(&models_path, format!("{}\n", text).as_bytes())
+                .map_err(|e| format!("Failed to write vegetation models: {}", e))?;
+            updated_maps.push(map_id);
+        }
+    }
+    Ok(updated_maps)
+}
@@ -0,0 +1,140 @@
+// Paint mask import from externally authored PNG files.
+// 从外部制作的 PNG 文件导入绘制遮罩
+//
+// EN: The mask covers a world rectangle with image row 0 at the minimum Z edge, matching splat map row order.
+// 中文: 遮罩覆盖一个世界矩形，图像第 0 行位于最小 Z 边，与 splat map 的行顺序一致。
+
+use crate::commands::{decode_png_rgba, validate_cook_project_path};
+use crate::map_layout::{WorldRect, format_grid_key};
+use crate::paint_pack::{PaintPackSession, PaintPackWriteResult, PaintPageRect};
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPaintMaskRequest {
+    project_path: String,
+    map_id: String,
+    layer_id: String,
+    png_path: String,
+    world_rect: WorldRect,
+}
+
+/// Single-channel mask weights decoded from a PNG.
+/// 从 PNG 解码的单通道遮罩权重
+struct PaintMask {
+    width: usize,
+    height: usize,
+    values: Vec<f32>,
+}
+
+// --- Paint mask import commands / 绘制遮罩导入命令 ---
+
+/// Resample a grayscale or RGBA PNG mask into one paint layer and renormalize the others.
+/// 将灰度或 RGBA PNG 遮罩重采样到某个绘制层，并重新归一化其他层
+#[tauri::command]
+pub async fn import_paint_mask(
+    request: ImportPaintMaskRequest,
+) -> Result<PaintPackWriteResult, String> {
+    tauri::async_runtime::spawn_blocking(move || import_paint_mask_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join paint mask import task: {}", e))?
+}
+
+fn import_paint_mask_blocking(
+    request: ImportPaintMaskRequest,
+) -> Result<PaintPackWriteResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let rect = request.world_rect;
+    rect.validate("Paint mask")?;
+
+    let mask = PaintMask::read(Path::new(&request.png_path))?;
+    let mut session = PaintPackSession::open(&project_root, &request.map_id)?;
+    let layer_index = session.layer_index(&request.layer_id)?;
+
+    let layout = &session.layout;
+    let page_rect = PaintPageRect::new(
+        layout.page_for_world_coordinate(rect.min_x),
+        layout.page_for_world_coordinate(rect.min_z),
+        layout.page_for_world_coordinate(rect.max_x),
+        layout.page_for_world_coordinate(rect.max_z),
+    );
+    page_rect.validate()?;
+
+    let texel_size = layout.page_size_meters / layout.page_resolution as f64;
+    let mut updated_page_count = 0;
+    let mut missing_pages = Vec::new();
+    for (px, pz) in page_rect.pages() {
+        let origin_x = session.layout.page_origin_meters(px);
+        let origin_z = session.layout.page_origin_meters(pz);
+        let written_texels =
+            session.write_page_layer_weights(px, pz, layer_index, true, |row, column| {
+                // EN: Sample at texel centers; texels outside the rectangle keep their weights.
+                // 中文: 在纹素中心采样；矩形外的纹素保留原有权重。
+                let x = origin_x + (column as f64 + 0.5) * texel_size;
+                let z = origin_z + (row as f64 + 0.5) * texel_size;
+                if x < rect.min_x || x >= rect.max_x || z < rect.min_z || z >= rect.max_z {
+                    return None;
+                }
+
+                let u = (x - rect.min_x) / (rect.max_x - rect.min_x);
+                let v = (z - rect.min_z) / (rect.max_z - rect.min_z);
+                Some(mask.sample(u, v).round().clamp(0.0, 255.0) as u8)
+            })?;
+        match written_texels {
+            Some(0) => {}
+            Some(_) => updated_page_count += 1,
+            None => missing_pages.push(format_grid_key(px, pz)),
+        }
+    }
+
+    Ok(PaintPackWriteResult {
+        updated_regions: session.save()?,
+        updated_page_count,
+        missing_pages,
+    })
+}
+
+impl PaintMask {
+    /// Decode a PNG mask; weight is luminance scaled by alpha.
+    /// 解码 PNG 遮罩；权重为亮度乘以 alpha
+    fn read(path: &Path) -> Result<Self, String> {
+        let (pixels, width, height) = decode_png_rgba(path)?;
+        if width == 0 || height == 0 {
+            return Err("Paint mask image is empty".to_string());
+        }
+
+        let values = pixels
+            .chunks_exact(4)
+            .map(|pixel| {
+                let luminance =
+                    0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32;
+                luminance * pixel[3] as f32 / 255.0
+            })
+            .collect();
+
+        Ok(Self {
+            width: width as usize,
+            height: height as usize,
+            values,
+        })
+    }
+
+    /// Bilinear sample at normalized image coordinates.
+    /// 在归一化图像坐标处双线性采样
+    fn sample(&self, u: f64, v: f64) -> f32 {
+        let x = (u * self.width as f64 - 0.5).clamp(0.0, (self.width - 1) as f64);
+        let y = (v * self.height as f64 - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let x0 = x.floor() as usize;
+        let y0 = y.floor() as usize;
+        let x1 = (x0 + 1).min(self.width - 1);
+        let y1 = (y0 + 1).min(self.height - 1);
+        let tx = (x - x0 as f64) as f32;
+        let ty = (y - y0 as f64) as f32;
+
+        let value = |column: usize, row: usize| self.values[row * self.width + column];
+        let top = value(x0, y0) + (value(x1, y0) - value(x0, y0)) * tx;
+        let bottom = value(x0, y1) + (value(x1, y1) - value(x0, y1)) * tx;
+        top + (bottom - top) * ty
+    }
+}
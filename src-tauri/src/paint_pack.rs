@@ -0,0 +1,843 @@
+// Native rgba8-splat-region-pack-v1 codec with per-layer weight access.
+// 原生 rgba8-splat-region-pack-v1 编解码，支持按材质层访问权重
+//
+// EN: Mirrors src/workspace/PaintData.ts: a region stores its occupied pages in mask bit order, and each page stores one RGBA8 tile per declared splat map.
+// 中文: 与 src/workspace/PaintData.ts 一致：区域按掩码位顺序存储已占用页面，每个页面按声明的 splat map 顺序各存一块 RGBA8 瓦片。
+
+use crate::commands::{
+    MAPS_DIR, read_map_world_size, recover_safe_write, safe_write, validate_cook_project_path,
+    validate_single_path_segment,
+};
+use crate::map_layout::{
+    PAGE_SIZE_METERS, PAINT_MANIFEST_PATH, PAINT_REGION_FORMAT, PAINT_REGIONS_DIRECTORY,
+    format_grid_key, parse_grid_key, region_file_name, sha256_hex,
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PAINT_REGION_EXTENSION: &str = "paintpack";
+const LAYERS_PER_SPLAT_MAP: usize = 4;
+const MAX_SPLAT_MAPS: u32 = 4;
+const MAX_PAINT_REGION_SIZE_PAGES: i32 = 8;
+const PAINT_RECT_MAX_PAGES: u64 = 4096;
+const PAINT_WEIGHT_TOTAL: u32 = 255;
+
+/// Inclusive page rectangle in paint page coordinates.
+/// 以绘制页面坐标表示的闭区间页面矩形
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaintPageRect {
+    min_page_x: i32,
+    min_page_z: i32,
+    max_page_x: i32,
+    max_page_z: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaintLayerWeightsRequest {
+    project_path: String,
+    map_id: String,
+    layer: String,
+    rect: PaintPageRect,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WritePaintLayerWeightsRequest {
+    project_path: String,
+    map_id: String,
+    layer: String,
+    rect: PaintPageRect,
+    weights_base64: String,
+    #[serde(default = "default_renormalize")]
+    renormalize: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenormalizePaintWeightsRequest {
+    project_path: String,
+    map_id: String,
+    rect: PaintPageRect,
+}
+
+/// One layer's weights for a page rectangle, one byte per texel in row-major order.
+/// 页面矩形内单个材质层的权重，每个纹素一个字节，按行优先排列
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaintLayerWeights {
+    layer: String,
+    splat_map_index: u32,
+    channel: usize,
+    page_resolution: usize,
+    width: usize,
+    height: usize,
+    rect: PaintPageRect,
+    weights_base64: String,
+    missing_pages: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaintPackWriteResult {
+    pub(crate) updated_regions: Vec<String>,
+    pub(crate) updated_page_count: usize,
+    pub(crate) missing_pages: Vec<String>,
+}
+
+/// Storage layout shared by every region pack of one paint manifest.
+/// 同一绘制清单下所有区域包共享的存储布局
+pub(crate) struct PaintPackLayout {
+    pub(crate) world_size_meters: f64,
+    pub(crate) page_size_meters: f64,
+    pub(crate) page_resolution: usize,
+    pub(crate) region_size_pages: i32,
+    pub(crate) splat_map_indices: Vec<u32>,
+}
+
+/// Which splat map and channel a material layer is stored in.
+/// 材质层存储所在的 splat map 与通道
+#[derive(Debug, Clone)]
+pub(crate) struct PaintLayerAssignment {
+    pub(crate) name: String,
+    pub(crate) splat_map_index: u32,
+    pub(crate) channel: usize,
+    pub(crate) slot: Option<usize>,
+}
+
+/// One decoded region pack; bytes stay in their on-disk layout.
+/// 一个已解码的区域包；字节保持磁盘布局
+pub(crate) struct PaintRegionPack {
+    mask: u64,
+    bytes: Vec<u8>,
+}
+
+/// Paint manifest plus lazily loaded region packs for one map.
+/// 单张地图的绘制清单及按需加载的区域包
+pub(crate) struct PaintPackSession {
+    map_root: PathBuf,
+    document: Value,
+    pub(crate) layout: PaintPackLayout,
+    pub(crate) layers: Vec<PaintLayerAssignment>,
+    region_masks: BTreeMap<String, u64>,
+    packs: BTreeMap<String, PaintRegionPack>,
+    dirty_regions: BTreeSet<String>,
+}
+
+// --- Paint pack commands / 绘制区域包命令 ---
+
+/// Read one material layer's weight channel for a page rectangle.
+/// 读取页面矩形内某个材质层的权重通道
+#[tauri::command]
+pub async fn read_paint_layer_weights(
+    request: PaintLayerWeightsRequest,
+) -> Result<PaintLayerWeights, String> {
+    tauri::async_runtime::spawn_blocking(move || read_paint_layer_weights_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join paint weights task: {}", e))?
+}
+
+/// Write one material layer's weight channel for a page rectangle, renormalizing by default.
+/// 写入页面矩形内某个材质层的权重通道，默认重新归一化
+#[tauri::command]
+pub async fn write_paint_layer_weights(
+    request: WritePaintLayerWeightsRequest,
+) -> Result<PaintPackWriteResult, String> {
+    tauri::async_runtime::spawn_blocking(move || write_paint_layer_weights_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join paint weights task: {}", e))?
+}
+
+/// Renormalize layer weights across all splat maps so every texel sums to 255.
+/// 在所有 splat map 之间重新归一化层权重，使每个纹素的总和为 255
+#[tauri::command]
+pub async fn renormalize_paint_weights(
+    request: RenormalizePaintWeightsRequest,
+) -> Result<PaintPackWriteResult, String> {
+    tauri::async_runtime::spawn_blocking(move || renormalize_paint_weights_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join paint weights task: {}", e))?
+}
+
+fn read_paint_layer_weights_blocking(
+    request: PaintLayerWeightsRequest,
+) -> Result<PaintLayerWeights, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    request.rect.validate()?;
+    let mut session = PaintPackSession::open(&project_root, &request.map_id)?;
+    let layer = session.layer(&request.layer)?;
+    let slot = layer.storage_slot()?;
+
+    let page_resolution = session.layout.page_resolution;
+    let width = request.rect.width_pages() * page_resolution;
+    let height = request.rect.height_pages() * page_resolution;
+    let mut weights = vec![0u8; width * height];
+    let mut missing_pages = Vec::new();
+    for (px, pz) in request.rect.pages() {
+        let Some((region_key, page_offset)) = session.load_page(px, pz)? else {
+            missing_pages.push(format_grid_key(px, pz));
+            continue;
+        };
+
+        let pack = &session.packs[&region_key];
+        let origin_x = (px - request.rect.min_page_x) as usize * page_resolution;
+        let origin_z = (pz - request.rect.min_page_z) as usize * page_resolution;
+        for row in 0..page_resolution {
+            for column in 0..page_resolution {
+                let offset = session.layout.texel_offset(page_offset, slot, row, column);
+                weights[(origin_z + row) * width + origin_x + column] = pack.bytes[offset];
+            }
+        }
+    }
+
+    Ok(PaintLayerWeights {
+        layer: layer.name,
+        splat_map_index: layer.splat_map_index,
+        channel: layer.channel,
+        page_resolution,
+        width,
+        height,
+        rect: request.rect,
+        weights_base64: STANDARD.encode(&weights),
+        missing_pages,
+    })
+}
+
+fn write_paint_layer_weights_blocking(
+    request: WritePaintLayerWeightsRequest,
+) -> Result<PaintPackWriteResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    request.rect.validate()?;
+    let mut session = PaintPackSession::open(&project_root, &request.map_id)?;
+    let layer_index = session.layer_index(&request.layer)?;
+
+    let page_resolution = session.layout.page_resolution;
+    let width = request.rect.width_pages() * page_resolution;
+    let height = request.rect.height_pages() * page_resolution;
+    let weights = STANDARD
+        .decode(&request.weights_base64)
+        .map_err(|e| format!("Failed to decode paint weights: {}", e))?;
+    if weights.len() != width * height {
+        return Err(format!(
+            "Paint weights for a {}x{} texel rectangle require {} bytes, got {}",
+            width,
+            height,
+            width * height,
+            weights.len()
+        ));
+    }
+
+    let mut updated_page_count = 0;
+    let mut missing_pages = Vec::new();
+    for (px, pz) in request.rect.pages() {
+        let origin_x = (px - request.rect.min_page_x) as usize * page_resolution;
+        let origin_z = (pz - request.rect.min_page_z) as usize * page_resolution;
+        match session.write_page_layer_weights(
+            px,
+            pz,
+            layer_index,
+            request.renormalize,
+            |row, column| Some(weights[(origin_z + row) * width + origin_x + column]),
+        )? {
+            Some(_) => updated_page_count += 1,
+            None => missing_pages.push(format_grid_key(px, pz)),
+        }
+    }
+
+    Ok(PaintPackWriteResult {
+        updated_regions: session.save()?,
+        updated_page_count,
+        missing_pages,
+    })
+}
+
+fn renormalize_paint_weights_blocking(
+    request: RenormalizePaintWeightsRequest,
+) -> Result<PaintPackWriteResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    request.rect.validate()?;
+    let mut session = PaintPackSession::open(&project_root, &request.map_id)?;
+    if session.layers.is_empty() {
+        return Err("Paint manifest does not declare any layers".to_string());
+    }
+
+    let page_resolution = session.layout.page_resolution;
+    let mut updated_page_count = 0;
+    let mut missing_pages = Vec::new();
+    for (px, pz) in request.rect.pages() {
+        let Some((region_key, page_offset)) = session.load_page(px, pz)? else {
+            missing_pages.push(format_grid_key(px, pz));
+            continue;
+        };
+
+        let pack = session
+            .packs
+            .get_mut(&region_key)
+            .ok_or_else(|| format!("Paint region pack '{}' is not loaded", region_key))?;
+        for row in 0..page_resolution {
+            for column in 0..page_resolution {
+                renormalize_texel(
+                    &session.layout,
+                    &session.layers,
+                    &mut pack.bytes,
+                    page_offset,
+                    row,
+                    column,
+                    None,
+                );
+            }
+        }
+        session.dirty_regions.insert(region_key);
+        updated_page_count += 1;
+    }
+
+    Ok(PaintPackWriteResult {
+        updated_regions: session.save()?,
+        updated_page_count,
+        missing_pages,
+    })
+}
+
+fn default_renormalize() -> bool {
+    true
+}
+
+// --- Codec / 编解码 ---
+
+impl PaintPageRect {
+    pub(crate) fn new(min_page_x: i32, min_page_z: i32, max_page_x: i32, max_page_z: i32) -> Self {
+        Self {
+            min_page_x,
+            min_page_z,
+            max_page_x,
+            max_page_z,
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.min_page_x > self.max_page_x || self.min_page_z > self.max_page_z {
+            return Err("Paint page rectangle min must not exceed max".to_string());
+        }
+
+        let page_count = self.width_pages() as u64 * self.height_pages() as u64;
+        if page_count > PAINT_RECT_MAX_PAGES {
+            return Err(format!(
+                "Paint page rectangle covers {} pages; the limit is {}",
+                page_count, PAINT_RECT_MAX_PAGES
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn width_pages(&self) -> usize {
+        (self.max_page_x as i64 - self.min_page_x as i64 + 1) as usize
+    }
+
+    fn height_pages(&self) -> usize {
+        (self.max_page_z as i64 - self.min_page_z as i64 + 1) as usize
+    }
+
+    /// Pages in row-major order (z first, then x).
+    /// 按行优先顺序（先 z 后 x）遍历页面
+    pub(crate) fn pages(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        (self.min_page_z..=self.max_page_z)
+            .flat_map(move |pz| (self.min_page_x..=self.max_page_x).map(move |px| (px, pz)))
+    }
+}
+
+impl PaintPackLayout {
+    pub(crate) fn tile_byte_length(&self) -> usize {
+        self.page_resolution * self.page_resolution * 4
+    }
+
+    pub(crate) fn page_byte_length(&self) -> usize {
+        self.tile_byte_length() * self.splat_map_indices.len()
+    }
+
+    pub(crate) fn region_coords_for_page(&self, px: i32, pz: i32) -> (i32, i32) {
+        (
+            px.div_euclid(self.region_size_pages),
+            pz.div_euclid(self.region_size_pages),
+        )
+    }
+
+    fn world_min_page(&self) -> i32 {
+        let page_count = (self.world_size_meters / self.page_size_meters).round() as i32;
+        -(page_count / 2)
+    }
+
+    /// World-space minimum corner of a page along one axis, matching PaintData.ts.
+    /// 页面在单个轴上的世界空间最小角坐标，与 PaintData.ts 一致
+    pub(crate) fn page_origin_meters(&self, page: i32) -> f64 {
+        (page - self.world_min_page()) as f64 * self.page_size_meters - self.world_size_meters / 2.0
+    }
+
+    /// Page containing a world coordinate, clamped to the world bounds.
+    /// 包含世界坐标的页面，限制在世界范围内
+    pub(crate) fn page_for_world_coordinate(&self, coordinate: f64) -> i32 {
+        let min_page = self.world_min_page();
+        let page_count = (self.world_size_meters / self.page_size_meters).round() as i32;
+        let page = ((coordinate + self.world_size_meters / 2.0) / self.page_size_meters).floor()
+            as i32
+            + min_page;
+        page.clamp(min_page, min_page + page_count - 1)
+    }
+
+    fn local_page_index(&self, px: i32, pz: i32) -> u32 {
+        let size = self.region_size_pages;
+        (pz.rem_euclid(size) * size + px.rem_euclid(size)) as u32
+    }
+
+    /// Byte offset of one weight inside a region pack.
+    /// `slot` is `splat map order * 4 + channel`.
+    /// 区域包内单个权重的字节偏移；`slot` 为 `splat map 顺序 * 4 + 通道`
+    pub(crate) fn texel_offset(
+        &self,
+        page_offset: usize,
+        slot: usize,
+        row: usize,
+        column: usize,
+    ) -> usize {
+        page_offset
+            + (slot / LAYERS_PER_SPLAT_MAP) * self.tile_byte_length()
+            + (row * self.page_resolution + column) * 4
+            + slot % LAYERS_PER_SPLAT_MAP
+    }
+}
+
+impl PaintLayerAssignment {
+    pub(crate) fn storage_slot(&self) -> Result<usize, String> {
+        self.slot.ok_or_else(|| {
+            format!(
+                "Paint layer '{}' uses splat map {} which is not declared in the paint manifest",
+                self.name, self.splat_map_index
+            )
+        })
+    }
+}
+
+impl PaintRegionPack {
+    /// Validate raw pack bytes against the manifest mask and layout.
+    /// 按清单掩码与布局校验原始区域包字节
+    pub(crate) fn decode(
+        layout: &PaintPackLayout,
+        region_key: &str,
+        mask: u64,
+        bytes: Vec<u8>,
+    ) -> Result<Self, String> {
+        let expected_byte_length = mask.count_ones() as usize * layout.page_byte_length();
+        if bytes.len() != expected_byte_length {
+            return Err(format!(
+                "Paint region pack '{}' requires {} bytes, got {}",
+                region_key,
+                expected_byte_length,
+                bytes.len()
+            ));
+        }
+
+        Ok(Self { mask, bytes })
+    }
+
+    pub(crate) fn encode(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Byte offset of a page inside this pack, or `None` when the mask does not include it.
+    /// 页面在此区域包内的字节偏移；掩码不包含该页面时返回 `None`
+    pub(crate) fn page_offset(&self, layout: &PaintPackLayout, px: i32, pz: i32) -> Option<usize> {
+        let local_index = layout.local_page_index(px, pz);
+        let bit = 1u64 << local_index;
+        if self.mask & bit == 0 {
+            return None;
+        }
+
+        Some((self.mask & (bit - 1)).count_ones() as usize * layout.page_byte_length())
+    }
+}
+
+impl PaintPackSession {
+    pub(crate) fn open(project_root: &Path, map_id: &str) -> Result<Self, String> {
+        validate_single_path_segment(map_id, "map_id")?;
+        let map_root = project_root.join(MAPS_DIR).join(map_id);
+        let manifest_path = map_root.join(PAINT_MANIFEST_PATH);
+        recover_safe_write(&manifest_path)?;
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read paint manifest: {}", e))?;
+        let document: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse paint manifest: {}", e))?;
+
+        let splat_maps = &document["splatMaps"];
+        if splat_maps["format"].as_str() != Some(PAINT_REGION_FORMAT) {
+            return Err(format!(
+                "Paint manifest must use the {} format",
+                PAINT_REGION_FORMAT
+            ));
+        }
+
+        let layout = PaintPackLayout {
+            world_size_meters: read_map_world_size(&map_root)?,
+            page_size_meters: splat_maps["pageSizeMeters"]
+                .as_f64()
+                .filter(|size| size.is_finite() && *size > 0.0)
+                .unwrap_or(PAGE_SIZE_METERS),
+            page_resolution: read_positive_integer(
+                &splat_maps["pageResolution"],
+                "paint page resolution",
+            )?,
+            region_size_pages: read_positive_integer(
+                &splat_maps["regionSizePages"],
+                "paint region size",
+            )? as i32,
+            splat_map_indices: splat_maps["indices"]
+                .as_array()
+                .ok_or_else(|| "Paint manifest splat map indices must be an array".to_string())?
+                .iter()
+                .map(|index| {
+                    index
+                        .as_u64()
+                        .filter(|index| *index < MAX_SPLAT_MAPS as u64)
+                        .map(|index| index as u32)
+                        .ok_or_else(|| format!("Invalid paint splat map index {}", index))
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        if layout.region_size_pages > MAX_PAINT_REGION_SIZE_PAGES {
+            return Err(
+                "Paint region size cannot exceed 8 pages because masks are stored as 64-bit values"
+                    .to_string(),
+            );
+        }
+
+        let mut region_masks = BTreeMap::new();
+        if let Some(regions) = splat_maps["regions"].as_object() {
+            for (key, mask) in regions {
+                if parse_grid_key(key).is_none() {
+                    return Err(format!("Invalid paint region key '{}'", key));
+                }
+
+                let mask = mask
+                    .as_str()
+                    .and_then(|mask| mask.strip_prefix("0x"))
+                    .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+                    .ok_or_else(|| format!("Paint region '{}' has an invalid mask", key))?;
+                region_masks.insert(key.clone(), mask);
+            }
+        }
+
+        let layers = compute_layer_assignments(&document, &layout.splat_map_indices);
+        Ok(Self {
+            map_root,
+            document,
+            layout,
+            layers,
+            region_masks,
+            packs: BTreeMap::new(),
+            dirty_regions: BTreeSet::new(),
+        })
+    }
+
+    pub(crate) fn layer(&self, name: &str) -> Result<PaintLayerAssignment, String> {
+        self.layers
+            .iter()
+            .find(|layer| layer.name == name)
+            .cloned()
+            .ok_or_else(|| format!("Paint layer '{}' not found", name))
+    }
+
+    /// Index of a layer that has storage in the declared splat maps.
+    /// 在已声明 splat map 中有存储的层的索引
+    pub(crate) fn layer_index(&self, name: &str) -> Result<usize, String> {
+        let index = self
+            .layers
+            .iter()
+            .position(|layer| layer.name == name)
+            .ok_or_else(|| format!("Paint layer '{}' not found", name))?;
+        self.layers[index].storage_slot()?;
+        Ok(index)
+    }
+
+    /// Set one layer's weights on a page; `weight_at` returns `None` for texels to leave untouched.
+    /// Returns the number of texels written, or `None` when the page has no stored paint data.
+    /// 设置页面上某层的权重；`weight_at` 对不修改的纹素返回 `None`。返回写入的纹素数，页面没有绘制数据时返回 `None`
+    pub(crate) fn write_page_layer_weights(
+        &mut self,
+        px: i32,
+        pz: i32,
+        layer_index: usize,
+        renormalize: bool,
+        mut weight_at: impl FnMut(usize, usize) -> Option<u8>,
+    ) -> Result<Option<usize>, String> {
+        let slot = self.layers[layer_index].storage_slot()?;
+        let Some((region_key, page_offset)) = self.load_page(px, pz)? else {
+            return Ok(None);
+        };
+
+        let pack = self
+            .packs
+            .get_mut(&region_key)
+            .ok_or_else(|| format!("Paint region pack '{}' is not loaded", region_key))?;
+        let page_resolution = self.layout.page_resolution;
+        let mut written_texels = 0;
+        for row in 0..page_resolution {
+            for column in 0..page_resolution {
+                let Some(weight) = weight_at(row, column) else {
+                    continue;
+                };
+                written_texels += 1;
+
+                let offset = self.layout.texel_offset(page_offset, slot, row, column);
+                pack.bytes[offset] = weight;
+                if renormalize {
+                    renormalize_texel(
+                        &self.layout,
+                        &self.layers,
+                        &mut pack.bytes,
+                        page_offset,
+                        row,
+                        column,
+                        Some(layer_index),
+                    );
+                }
+            }
+        }
+        if written_texels > 0 {
+            self.dirty_regions.insert(region_key);
+        }
+        Ok(Some(written_texels))
+    }
+
+    /// Load the pack holding a page and return its region key and page offset.
+    /// 加载包含指定页面的区域包，返回其区域键与页面偏移
+    pub(crate) fn load_page(
+        &mut self,
+        px: i32,
+        pz: i32,
+    ) -> Result<Option<(String, usize)>, String> {
+        let (region_x, region_z) = self.layout.region_coords_for_page(px, pz);
+        let region_key = format_grid_key(region_x, region_z);
+        let Some(&mask) = self.region_masks.get(&region_key) else {
+            return Ok(None);
+        };
+
+        self.load_region(&region_key, mask)?;
+        Ok(self.packs[&region_key]
+            .page_offset(&self.layout, px, pz)
+            .map(|page_offset| (region_key, page_offset)))
+    }
+
+    /// Load every region listed in the manifest so `loaded_weight_at` can sample any page.
+    /// 加载清单中的所有区域，使 `loaded_weight_at` 可以采样任意页面
+    pub(crate) fn load_all_regions(&mut self) -> Result<(), String> {
+        let regions: Vec<(String, u64)> = self
+            .region_masks
+            .iter()
+            .map(|(key, mask)| (key.clone(), *mask))
+            .collect();
+        for (region_key, mask) in regions {
+            self.load_region(&region_key, mask)?;
+        }
+
+        Ok(())
+    }
+
+    /// Nearest-texel weight (0-255) of a storage slot from already loaded regions.
+    /// 从已加载区域读取存储槽位的最近纹素权重（0-255）
+    pub(crate) fn loaded_weight_at(&self, slot: usize, x: f64, z: f64) -> Option<u8> {
+        let px = self.layout.page_for_world_coordinate(x);
+        let pz = self.layout.page_for_world_coordinate(z);
+        let (region_x, region_z) = self.layout.region_coords_for_page(px, pz);
+        let pack = self.packs.get(&format_grid_key(region_x, region_z))?;
+        let page_offset = pack.page_offset(&self.layout, px, pz)?;
+        let resolution = self.layout.page_resolution;
+        let texel = |coordinate: f64, page: i32| {
+            let local =
+                (coordinate - self.layout.page_origin_meters(page)) / self.layout.page_size_meters;
+            ((local * resolution as f64).floor().max(0.0) as usize).min(resolution - 1)
+        };
+        let offset = self
+            .layout
+            .texel_offset(page_offset, slot, texel(z, pz), texel(x, px));
+        Some(pack.bytes[offset])
+    }
+
+    fn load_region(&mut self, region_key: &str, mask: u64) -> Result<(), String> {
+        if self.packs.contains_key(region_key) {
+            return Ok(());
+        }
+
+        let (region_x, region_z) = parse_grid_key(region_key)
+            .ok_or_else(|| format!("Invalid paint region key '{}'", region_key))?;
+        let path = self.region_path(region_x, region_z);
+        recover_safe_write(&path)?;
+        let bytes = fs::read(&path)
+            .map_err(|e| format!("Failed to read paint region pack '{}': {}", region_key, e))?;
+        let pack = PaintRegionPack::decode(&self.layout, region_key, mask, bytes)?;
+        self.packs.insert(region_key.to_string(), pack);
+        Ok(())
+    }
+
+    /// Write changed packs and refresh their integrity entries in the paint manifest.
+    /// 写入已修改的区域包，并刷新绘制清单中的完整性条目
+    pub(crate) fn save(mut self) -> Result<Vec<String>, String> {
+        let dirty_regions: Vec<String> = self.dirty_regions.iter().cloned().collect();
+        if dirty_regions.is_empty() {
+            return Ok(dirty_regions);
+        }
+
+        for region_key in &dirty_regions {
+            let (region_x, region_z) = parse_grid_key(region_key)
+                .ok_or_else(|| format!("Invalid paint region key '{}'", region_key))?;
+            let bytes = self.packs[region_key].encode();
+            safe_write(&self.region_path(region_x, region_z), bytes)
+                .map_err(|e| format!("Failed to save paint region pack '{}': {}", region_key, e))?;
+
+            if let Some(integrity) = self.document["splatMaps"]["regionIntegrity"].as_object_mut() {
+                integrity.insert(
+                    region_key.clone(),
+                    json!({ "byteLength": bytes.len(), "sha256": sha256_hex(bytes) }),
+                );
+            }
+        }
+
+        let mut content = serde_json::to_string_pretty(&self.document)
+            .map_err(|e| format!("Failed to serialize paint manifest: {}", e))?;
+        content.push('\n');
+        safe_write(&self.map_root.join(PAINT_MANIFEST_PATH), content.as_bytes())
+            .map_err(|e| format!("Failed to save paint manifest: {}", e))?;
+
+        Ok(dirty_regions)
+    }
+
+    fn region_path(&self, region_x: i32, region_z: i32) -> PathBuf {
+        self.map_root
+            .join(PAINT_REGIONS_DIRECTORY)
+            .join(region_file_name(region_x, region_z, PAINT_REGION_EXTENSION))
+    }
+}
+
+/// Assign layers to splat maps the way TextureData.ts does: channel follows key order, splat map is explicit or every four layers.
+/// 按 TextureData.ts 的方式分配层：通道按键顺序，splat map 为显式值或每四层一张
+fn compute_layer_assignments(
+    document: &Value,
+    splat_map_indices: &[u32],
+) -> Vec<PaintLayerAssignment> {
+    let Some(layers) = document["layers"].as_object() else {
+        return Vec::new();
+    };
+
+    layers
+        .iter()
+        .enumerate()
+        .map(|(index, (name, definition))| {
+            let splat_map_index = definition["splatMapIndex"]
+                .as_u64()
+                .unwrap_or((index / LAYERS_PER_SPLAT_MAP) as u64)
+                .min(MAX_SPLAT_MAPS as u64 - 1) as u32;
+            let channel = index % LAYERS_PER_SPLAT_MAP;
+            let slot = splat_map_indices
+                .iter()
+                .position(|candidate| *candidate == splat_map_index)
+                .map(|order| order * LAYERS_PER_SPLAT_MAP + channel);
+
+            PaintLayerAssignment {
+                name: name.clone(),
+                splat_map_index,
+                channel,
+                slot,
+            }
+        })
+        .collect()
+}
+
+// --- Weight normalization / 权重归一化 ---
+
+/// Renormalize one texel so the weights of all stored layers sum to 255.
+/// 重新归一化单个纹素，使所有已存储层的权重之和为 255
+fn renormalize_texel(
+    layout: &PaintPackLayout,
+    layers: &[PaintLayerAssignment],
+    bytes: &mut [u8],
+    page_offset: usize,
+    row: usize,
+    column: usize,
+    locked_layer: Option<usize>,
+) {
+    let mut slots: Vec<usize> = Vec::with_capacity(layers.len());
+    let mut locked = None;
+    for (index, layer) in layers.iter().enumerate() {
+        let Some(slot) = layer.slot else {
+            continue;
+        };
+        if slots.contains(&slot) {
+            continue;
+        }
+        if locked_layer == Some(index) {
+            locked = Some(slots.len());
+        }
+        slots.push(slot);
+    }
+
+    let mut weights: Vec<u8> = slots
+        .iter()
+        .map(|slot| bytes[layout.texel_offset(page_offset, *slot, row, column)])
+        .collect();
+    renormalize_weights(&mut weights, locked);
+
+    // EN: Channels without a layer never render, so they are cleared instead of absorbing weight.
+    // 中文: 没有对应层的通道永远不会渲染，因此直接清零而不是分摊权重。
+    for slot in 0..layout.splat_map_indices.len() * LAYERS_PER_SPLAT_MAP {
+        let weight = slots
+            .iter()
+            .position(|candidate| *candidate == slot)
+            .map_or(0, |index| weights[index]);
+        bytes[layout.texel_offset(page_offset, slot, row, column)] = weight;
+    }
+}
+
+/// Scale weights to sum to 255 with largest-remainder rounding, keeping the locked weight fixed.
+/// 以最大余数法将权重缩放到总和 255，保持锁定权重不变
+fn renormalize_weights(weights: &mut [u8], locked: Option<usize>) {
+    let budget = PAINT_WEIGHT_TOTAL - locked.map_or(0, |index| weights[index] as u32);
+    let free: Vec<usize> = (0..weights.len())
+        .filter(|index| Some(*index) != locked)
+        .collect();
+    let Some(&first_free) = free.first() else {
+        return;
+    };
+
+    let sum: u32 = free.iter().map(|index| weights[*index] as u32).sum();
+    if sum == 0 {
+        // EN: With nothing left to scale, the remainder goes to the first free layer, like a default splat map.
+        // 中文: 没有可缩放的权重时，剩余部分交给第一个自由层，与默认 splat map 一致。
+        weights[first_free] = budget as u8;
+        return;
+    }
+
+    let mut assigned = 0;
+    let mut remainders = Vec::with_capacity(free.len());
+    for index in free {
+        let scaled = weights[index] as u32 * budget;
+        weights[index] = (scaled / sum) as u8;
+        assigned += scaled / sum;
+        remainders.push((scaled % sum, index));
+    }
+
+    remainders.sort_by(|left, right| right.0.cmp(&left.0).then(left.1.cmp(&right.1)));
+    for (_, index) in remainders.into_iter().take((budget - assigned) as usize) {
+        weights[index] += 1;
+    }
+}
+
+fn read_positive_integer(value: &Value, label: &str) -> Result<usize, String> {
+    value
+        .as_u64()
+        .filter(|value| *value > 0)
+        .map(|value| value as usize)
+        .ok_or_else(|| format!("Paint manifest {} must be a positive integer", label))
+}
@@ -0,0 +1,308 @@
+// Rectangle reads from large PNG files without decoding the whole image.
+// 从大型 PNG 文件读取矩形区域，无需解码整张图像
+//
+// EN: Rows are decoded as they stream and kept in fixed-height bands per file, so nearby reads crop and box-downsample from cached rows instead of decoding again.
+// 中文: 行在流式读取时解码，并按文件以固定高度的行带缓存，相邻的读取直接从缓存行中裁剪和盒式降采样，而无需重新解码。
+
+use crate::commands::{PngSampleFormat, open_png_reader, push_rgba_samples};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+const PNG_REGION_MAX_OUTPUT_PIXELS: u64 = 4096 * 4096;
+const PNG_REGION_MAX_DOWNSAMPLE: u32 = 64;
+const PNG_REGION_BAND_ROWS: u32 = 256;
+const PNG_REGION_CACHE_MAX_BYTES: usize = 256 * 1024 * 1024;
+
+/// Recently decoded row bands, most recent last; keyed by file identity so edits invalidate entries.
+/// 最近解码的行带，最新的在末尾；以文件标识为键，文件修改后条目自动失效
+static PNG_BAND_CACHE: Mutex<VecDeque<CachedPngBand>> = Mutex::new(VecDeque::new());
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadPngRegionRequest {
+    path: String,
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    downsample: Option<u32>,
+    #[serde(default)]
+    bit_depth: Option<u8>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PngRegionResult {
+    base64_pixels: String,
+    width: u32,
+    height: u32,
+    source_width: u32,
+    source_height: u32,
+    bit_depth: u8,
+}
+
+/// Decoded rows of one band, shared between the cache and in-flight reads.
+/// 单个行带的解码行，由缓存与进行中的读取共享
+type BandRows = Arc<Vec<u8>>;
+
+#[derive(Debug, Clone, PartialEq)]
+struct PngFileKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    byte_length: u64,
+}
+
+/// Decoded row geometry shared by every band of one file.
+/// 同一文件所有行带共享的解码行几何信息
+#[derive(Debug, Clone, Copy)]
+struct PngRowLayout {
+    width: u32,
+    height: u32,
+    line_size: usize,
+    format: PngSampleFormat,
+}
+
+struct CachedPngBand {
+    file: PngFileKey,
+    layout: PngRowLayout,
+    band: u32,
+    rows: BandRows,
+}
+
+// --- PNG region commands / PNG 区域命令 ---
+
+/// Read a pixel rectangle from a PNG as RGBA8 (or RGBA16 little-endian), box-downsampled by an integer factor.
+/// 以 RGBA8（或小端 RGBA16）读取 PNG 中的像素矩形，并按整数倍盒式降采样
+#[tauri::command]
+pub async fn read_png_region(request: ReadPngRegionRequest) -> Result<PngRegionResult, String> {
+    tauri::async_runtime::spawn_blocking(move || read_png_region_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join PNG region read task: {}", e))?
+}
+
+fn read_png_region_blocking(request: ReadPngRegionRequest) -> Result<PngRegionResult, String> {
+    use base64::{Engine as _, engine::general_purpose::STANDARD};
+
+    let downsample = request.downsample.unwrap_or(1);
+    if downsample == 0 || downsample > PNG_REGION_MAX_DOWNSAMPLE {
+        return Err(format!(
+            "PNG region downsample must be between 1 and {}",
+            PNG_REGION_MAX_DOWNSAMPLE
+        ));
+    }
+    let sixteen_bit = match request.bit_depth.unwrap_or(8) {
+        8 => false,
+        16 => true,
+        other => return Err(format!("Unsupported PNG output bit depth: {}", other)),
+    };
+    if request.width == 0 || request.height == 0 {
+        return Err("PNG region must not be empty".to_string());
+    }
+    let output_width = request.width.div_ceil(downsample);
+    let output_height = request.height.div_ceil(downsample);
+    if output_width as u64 * output_height as u64 > PNG_REGION_MAX_OUTPUT_PIXELS {
+        return Err(format!(
+            "PNG region output {}x{} is too large; increase downsample",
+            output_width, output_height
+        ));
+    }
+
+    let path = PathBuf::from(&request.path);
+    let metadata =
+        fs::metadata(&path).map_err(|e| format!("Failed to read PNG metadata: {}", e))?;
+    let file = PngFileKey {
+        path,
+        modified: metadata.modified().ok(),
+        byte_length: metadata.len(),
+    };
+    let first_band = request.y / PNG_REGION_BAND_ROWS;
+    let last_band = ((request.y as u64 + request.height as u64 - 1) / PNG_REGION_BAND_ROWS as u64)
+        .min(u32::MAX as u64) as u32;
+    let (layout, bands) = match cached_bands(&file, first_band, last_band) {
+        Some(cached) => cached,
+        None => decode_png_bands(&file, &request, first_band, last_band)?,
+    };
+    check_region_bounds(&request, &layout)?;
+
+    // EN: Box filter: sum each output pixel's source block, then emit the output row once its last source row is visited.
+    // 中文: 盒式滤波：累加每个输出像素对应的源块，在访问到最后一个源行后输出该行。
+    let factor = downsample as usize;
+    let first_column = request.x as usize;
+    let column_count = request.width as usize;
+    let first_row = request.y as usize;
+    let row_end = first_row + request.height as usize;
+    let band_start = (first_band * PNG_REGION_BAND_ROWS) as usize;
+    let band_rows = PNG_REGION_BAND_ROWS as usize;
+    let bytes_per_sample = if sixteen_bit { 2 } else { 1 };
+    let mut pixels =
+        Vec::with_capacity(output_width as usize * output_height as usize * 4 * bytes_per_sample);
+    let mut sums = vec![[0u32; 4]; output_width as usize];
+    let mut counts = vec![0u32; output_width as usize];
+    for row in first_row..row_end {
+        let local = row - band_start;
+        let offset = (local % band_rows) * layout.line_size;
+        let samples = &bands[local / band_rows][offset..offset + layout.line_size];
+        for column in 0..column_count {
+            let rgba = layout.format.rgba16(samples, first_column + column);
+            let slot = column / factor;
+            for (sum, value) in sums[slot].iter_mut().zip(rgba) {
+                *sum += value as u32;
+            }
+            counts[slot] += 1;
+        }
+
+        if (row - first_row + 1).is_multiple_of(factor) || row + 1 == row_end {
+            for (sum, count) in sums.iter_mut().zip(counts.iter_mut()) {
+                let average = sum.map(|total| ((total + *count / 2) / *count) as u16);
+                push_rgba_samples(&mut pixels, average, sixteen_bit);
+                *sum = [0; 4];
+                *count = 0;
+            }
+        }
+    }
+
+    Ok(PngRegionResult {
+        base64_pixels: STANDARD.encode(&pixels),
+        width: output_width,
+        height: output_height,
+        source_width: layout.width,
+        source_height: layout.height,
+        bit_depth: if sixteen_bit { 16 } else { 8 },
+    })
+}
+
+fn check_region_bounds(
+    request: &ReadPngRegionRequest,
+    layout: &PngRowLayout,
+) -> Result<(), String> {
+    if request.x as u64 + request.width as u64 > layout.width as u64
+        || request.y as u64 + request.height as u64 > layout.height as u64
+    {
+        return Err(format!(
+            "PNG region {}x{} at ({}, {}) exceeds image size {}x{}",
+            request.width, request.height, request.x, request.y, layout.width, layout.height
+        ));
+    }
+    Ok(())
+}
+
+/// Decode the row bands covering a region and add them to the cache.
+/// 解码覆盖区域的行带并加入缓存
+fn decode_png_bands(
+    file: &PngFileKey,
+    request: &ReadPngRegionRequest,
+    first_band: u32,
+    last_band: u32,
+) -> Result<(PngRowLayout, Vec<BandRows>), String> {
+    let mut reader = open_png_reader(&file.path)?;
+    let (width, height, interlaced) = {
+        let info = reader.info();
+        (info.width, info.height, info.interlaced)
+    };
+    let (color_type, bit_depth) = reader.output_color_type();
+    let layout = PngRowLayout {
+        width,
+        height,
+        line_size: reader
+            .output_line_size(width)
+            .ok_or_else(|| "Failed to determine PNG row size".to_string())?,
+        format: PngSampleFormat::new(color_type, bit_depth)?,
+    };
+    check_region_bounds(request, &layout)?;
+
+    let band_rows = PNG_REGION_BAND_ROWS as usize;
+    let row_start = first_band as usize * band_rows;
+    let row_end = ((last_band as usize + 1) * band_rows).min(height as usize);
+    let mut rows = Vec::with_capacity((row_end - row_start) * layout.line_size);
+    if interlaced {
+        // EN: Adam7 rows arrive out of order, so interlaced files fall back to a full-frame decode.
+        // 中文: Adam7 行按交错顺序到达，因此交错文件退回到整帧解码。
+        let output_size = reader
+            .output_buffer_size()
+            .ok_or_else(|| "Failed to determine PNG output buffer size".to_string())?;
+        let mut buf = vec![0; output_size];
+        reader
+            .next_frame(&mut buf)
+            .map_err(|e| format!("Failed to decode PNG frame: {}", e))?;
+        rows.extend_from_slice(&buf[row_start * layout.line_size..row_end * layout.line_size]);
+    } else {
+        for row in 0..row_end {
+            let decoded = reader
+                .next_row()
+                .map_err(|e| format!("Failed to decode PNG row: {}", e))?
+                .ok_or_else(|| format!("PNG ended before row {}", row))?;
+            if row >= row_start {
+                rows.extend_from_slice(decoded.data());
+            }
+        }
+    }
+
+    let bands: Vec<BandRows> = rows
+        .chunks(band_rows * layout.line_size)
+        .map(|chunk| Arc::new(chunk.to_vec()))
+        .collect();
+    store_cached_bands(file, layout, first_band, &bands);
+    Ok((layout, bands))
+}
+
+fn cached_bands(
+    file: &PngFileKey,
+    first_band: u32,
+    last_band: u32,
+) -> Option<(PngRowLayout, Vec<BandRows>)> {
+    let mut cache = PNG_BAND_CACHE.lock().ok()?;
+    let mut layout = None;
+    let mut bands = Vec::new();
+    for band in first_band..=last_band {
+        let index = cache
+            .iter()
+            .position(|entry| entry.file == *file && entry.band == band)?;
+        let entry = cache.remove(index)?;
+        layout = Some(entry.layout);
+        bands.push(Arc::clone(&entry.rows));
+        cache.push_back(entry);
+    }
+    Some((layout?, bands))
+}
+
+fn store_cached_bands(
+    file: &PngFileKey,
+    layout: PngRowLayout,
+    first_band: u32,
+    bands: &[BandRows],
+) {
+    let Ok(mut cache) = PNG_BAND_CACHE.lock() else {
+        return;
+    };
+
+    // EN: Drop bands of older versions of the same file, then evict least recently used bands.
+    // 中文: 移除同一文件旧版本的行带，再淘汰最近最少使用的行带。
+    cache.retain(|entry| entry.file.path != file.path || entry.file == *file);
+    for (band, rows) in (first_band..).zip(bands) {
+        if rows.len() > PNG_REGION_CACHE_MAX_BYTES
+            || cache
+                .iter()
+                .any(|entry| entry.file == *file && entry.band == band)
+        {
+            continue;
+        }
+        cache.push_back(CachedPngBand {
+            file: file.clone(),
+            layout,
+            band,
+            rows: Arc::clone(rows),
+        });
+    }
+    let mut total_bytes: usize = cache.iter().map(|entry| entry.rows.len()).sum();
+    while total_bytes > PNG_REGION_CACHE_MAX_BYTES {
+        let Some(evicted) = cache.pop_front() else {
+            break;
+        };
+        total_bytes -= evicted.rows.len();
+    }
+}
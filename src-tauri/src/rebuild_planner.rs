@@ -0,0 +1,782 @@
+// Native world rebuild planner (open-fps-world-rebuild-plan-v1).
+// 原生世界重建规划器 (open-fps-world-rebuild-plan-v1)
+//
+// EN: Port of scripts/map-generation/world-rebuild-planner.mjs; plan ids must stay identical to the Node planner.
+// 中文: scripts/map-generation/world-rebuild-planner.mjs 的移植；计划 ID 必须与 Node 规划器保持一致。
+
+use crate::commands::{CookMapRequest, CookMapScopes, MAPS_DIR, validate_cook_map_request};
+use crate::map_layout::{
+    COOKED_WORLD_PARTITION_CELL_SIZE_PAGES, GENERATION_GRAPH_PATH, HEIGHT_REGION_SIZE_PAGES,
+    PAGE_SIZE_METERS, PAINT_REGION_SIZE_PAGES, VEGETATION_CELL_SIZE_METERS,
+    VEGETATION_REGION_SIZE_CELLS, WorldRect, format_grid_key, parse_grid_key, sha256_hex,
+    sort_grid_keys,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+pub(crate) const WORLD_REBUILD_PLAN_FORMAT: &str = "open-fps-world-rebuild-plan-v1";
+const WORLD_REBUILD_PLAN_VERSION: u32 = 1;
+const EXTERNAL_STAGE_DEPENDENCIES: &[&str] = &["assetRegistry"];
+
+/// Normalized rebuild request, serialized in the Node planner's key order.
+/// 归一化的重建请求，按 Node 规划器的键顺序序列化
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldRebuildRequest {
+    dry_run: bool,
+    full: bool,
+    allow_budget_overrun: bool,
+    target_stages: Vec<String>,
+    changed_stages: Vec<String>,
+    scopes: CookMapScopes,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldRebuildAction {
+    stage: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    executor: String,
+    scope: String,
+    keys: Vec<String>,
+    invalidates: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldRebuildBudget {
+    estimated_artifacts: usize,
+    terrain_region_count: usize,
+    paint_region_count: usize,
+    vegetation_region_count: usize,
+    partition_cell_count: usize,
+    max_partition_cells_per_scoped_cook: Option<u64>,
+    max_estimated_artifacts_per_scoped_cook: Option<u64>,
+    exceeded: bool,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldRebuildPlan {
+    version: u32,
+    format: &'static str,
+    mode: &'static str,
+    map_id: String,
+    plan_id: String,
+    request: WorldRebuildRequest,
+    stages: Vec<String>,
+    scopes: CookMapScopes,
+    actions: Vec<WorldRebuildAction>,
+    budget: WorldRebuildBudget,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct WorldRebuildPlanIdPayload<'a> {
+    map_id: &'a str,
+    mode: &'a str,
+    request: &'a WorldRebuildRequest,
+    stages: &'a [String],
+    scopes: &'a CookMapScopes,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PageRect {
+    min_x: i32,
+    max_x: i32,
+    min_z: i32,
+    max_z: i32,
+}
+
+impl PageRect {
+    fn intersects(&self, other: &PageRect) -> bool {
+        self.min_x <= other.max_x
+            && self.max_x >= other.min_x
+            && self.min_z <= other.max_z
+            && self.max_z >= other.min_z
+    }
+
+    fn clamp_to(&self, world: &PageRect) -> Option<PageRect> {
+        let clamped = PageRect {
+            min_x: self.min_x.max(world.min_x),
+            max_x: self.max_x.min(world.max_x),
+            min_z: self.min_z.max(world.min_z),
+            max_z: self.max_z.min(world.max_z),
+        };
+
+        (clamped.min_x <= clamped.max_x && clamped.min_z <= clamped.max_z).then_some(clamped)
+    }
+}
+
+/// Scope families a rebuild plan tracks.
+/// 重建计划跟踪的范围类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScopeField {
+    TerrainRegions,
+    PaintRegions,
+    VegetationRegions,
+    PartitionCells,
+}
+
+impl ScopeField {
+    const ALL: [ScopeField; 4] = [
+        ScopeField::TerrainRegions,
+        ScopeField::PaintRegions,
+        ScopeField::VegetationRegions,
+        ScopeField::PartitionCells,
+    ];
+
+    fn for_stage(stage_name: &str) -> Option<ScopeField> {
+        match stage_name {
+            "semantics" | "objects" | "collision" | "nav" => Some(ScopeField::PartitionCells),
+            "terrain" => Some(ScopeField::TerrainRegions),
+            "paint" => Some(ScopeField::PaintRegions),
+            "vegetation" => Some(ScopeField::VegetationRegions),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ScopeField::TerrainRegions => "terrainRegions",
+            ScopeField::PaintRegions => "paintRegions",
+            ScopeField::VegetationRegions => "vegetationRegions",
+            ScopeField::PartitionCells => "partitionCells",
+        }
+    }
+
+    fn keys(self, scopes: &CookMapScopes) -> &[String] {
+        match self {
+            ScopeField::TerrainRegions => &scopes.terrain_regions,
+            ScopeField::PaintRegions => &scopes.paint_regions,
+            ScopeField::VegetationRegions => &scopes.vegetation_regions,
+            ScopeField::PartitionCells => &scopes.partition_cells,
+        }
+    }
+
+    fn keys_mut(self, scopes: &mut CookMapScopes) -> &mut Vec<String> {
+        match self {
+            ScopeField::TerrainRegions => &mut scopes.terrain_regions,
+            ScopeField::PaintRegions => &mut scopes.paint_regions,
+            ScopeField::VegetationRegions => &mut scopes.vegetation_regions,
+            ScopeField::PartitionCells => &mut scopes.partition_cells,
+        }
+    }
+
+    fn key_to_page_rect(self, key: &str) -> PageRect {
+        match self {
+            ScopeField::TerrainRegions => region_key_to_page_rect(key, HEIGHT_REGION_SIZE_PAGES),
+            ScopeField::PaintRegions => region_key_to_page_rect(key, PAINT_REGION_SIZE_PAGES),
+            ScopeField::VegetationRegions => vegetation_region_key_to_page_rect(key),
+            ScopeField::PartitionCells => {
+                region_key_to_page_rect(key, COOKED_WORLD_PARTITION_CELL_SIZE_PAGES)
+            }
+        }
+    }
+}
+
+impl WorldRebuildRequest {
+    /// Build a planner request from the editor's structured cook request.
+    /// 从编辑器结构化 cook 请求构造规划器请求
+    pub(crate) fn from_cook_request(request: &CookMapRequest) -> Self {
+        normalize_request(&WorldRebuildRequest {
+            dry_run: request.dry_run,
+            full: request.full,
+            allow_budget_overrun: false,
+            target_stages: Vec::new(),
+            changed_stages: request.changed_stages.clone(),
+            scopes: request.scopes.clone(),
+        })
+    }
+}
+
+// --- World rebuild planning / 世界重建规划 ---
+
+/// Plan a world rebuild natively, returning stages, affected scopes and budget.
+/// 原生规划世界重建，返回阶段、受影响范围与预算
+#[tauri::command]
+pub async fn plan_world_rebuild(request: CookMapRequest) -> Result<WorldRebuildPlan, String> {
+    tauri::async_runtime::spawn_blocking(move || plan_world_rebuild_blocking(&request))
+        .await
+        .map_err(|e| format!("Failed to join rebuild plan task: {}", e))?
+}
+
+pub(crate) fn plan_world_rebuild_blocking(
+    request: &CookMapRequest,
+) -> Result<WorldRebuildPlan, String> {
+    let project_root = validate_cook_map_request(request, "Plan")?;
+    let graph = read_world_generation_graph(&project_root, &request.map_id)?;
+    create_world_rebuild_plan(&graph, &WorldRebuildRequest::from_cook_request(request))
+}
+
+pub(crate) fn read_world_generation_graph(
+    project_root: &Path,
+    map_id: &str,
+) -> Result<Value, String> {
+    let path = project_root
+        .join(MAPS_DIR)
+        .join(map_id)
+        .join(GENERATION_GRAPH_PATH);
+    if !path.exists() {
+        return Err(format!(
+            "World generation graph is missing for map '{}'. Run pnpm gen:all -- --map {} first.",
+            map_id, map_id
+        ));
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read world generation graph: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse world generation graph: {}", e))
+}
+
+pub(crate) fn create_world_rebuild_plan(
+    graph: &Value,
+    request: &WorldRebuildRequest,
+) -> Result<WorldRebuildPlan, String> {
+    let request = normalize_request(request);
+    let stage_names = get_stage_names(graph)?;
+    let topology = sort_stages_by_dependencies(graph, &stage_names)?;
+    let world_rect = get_world_page_rect(graph)?;
+    let all_scopes = create_all_scopes(&world_rect);
+    let scope_input_provided = has_scope_input(&request.scopes);
+    let stage_input_provided =
+        !request.target_stages.is_empty() || !request.changed_stages.is_empty();
+    let full = request.full || (!scope_input_provided && !stage_input_provided);
+
+    let stages = if full {
+        topology
+    } else {
+        resolve_selected_stages(graph, &topology, &request)?
+    };
+    let scopes = if full {
+        all_scopes
+    } else {
+        resolve_selected_scopes(&world_rect, &all_scopes, &request)
+    };
+    let mode = if full { "full" } else { "scoped" };
+    let actions = stages
+        .iter()
+        .map(|stage_name| create_rebuild_action(graph, stage_name, &scopes))
+        .collect();
+    let budget = create_plan_budget(graph, mode, &stages, &scopes);
+    let map_id = graph
+        .get("mapId")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    let plan_id = create_plan_id(&map_id, mode, &request, &stages, &scopes)?;
+    Ok(WorldRebuildPlan {
+        version: WORLD_REBUILD_PLAN_VERSION,
+        format: WORLD_REBUILD_PLAN_FORMAT,
+        mode,
+        map_id,
+        plan_id,
+        request,
+        stages,
+        scopes,
+        actions,
+        budget,
+    })
+}
+
+fn normalize_request(request: &WorldRebuildRequest) -> WorldRebuildRequest {
+    WorldRebuildRequest {
+        dry_run: request.dry_run,
+        full: request.full,
+        allow_budget_overrun: request.allow_budget_overrun,
+        target_stages: unique_strings_sorted(&request.target_stages),
+        changed_stages: unique_strings_sorted(&request.changed_stages),
+        scopes: normalize_scopes(&request.scopes),
+    }
+}
+
+fn normalize_scopes(scopes: &CookMapScopes) -> CookMapScopes {
+    let mut normalized = CookMapScopes::default();
+    for field in ScopeField::ALL {
+        let keys = field.keys_mut(&mut normalized);
+        keys.extend(field.keys(scopes).iter().cloned());
+        sort_grid_keys(keys);
+    }
+    normalized
+}
+
+fn unique_strings_sorted(values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn has_scope_input(scopes: &CookMapScopes) -> bool {
+    ScopeField::ALL
+        .iter()
+        .any(|field| !field.keys(scopes).is_empty())
+}
+
+// --- Stage graph / 阶段图 ---
+
+fn graph_stage<'a>(graph: &'a Value, stage_name: &str) -> Option<&'a Value> {
+    graph.get("stages")?.get(stage_name)
+}
+
+fn get_stage_names(graph: &Value) -> Result<Vec<String>, String> {
+    graph
+        .get("stages")
+        .and_then(Value::as_object)
+        .map(|stages| stages.keys().cloned().collect())
+        .ok_or_else(|| {
+            "World generation graph must contain stage metadata before planning a rebuild."
+                .to_string()
+        })
+}
+
+fn get_raw_stage_dependencies(graph: &Value, stage_name: &str) -> Vec<String> {
+    graph_stage(graph, stage_name)
+        .and_then(|stage| stage.get("dependencies"))
+        .and_then(Value::as_array)
+        .map(|dependencies| {
+            dependencies
+                .iter()
+                .filter_map(|dependency| dependency.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn get_stage_dependencies(graph: &Value, stage_name: &str) -> Vec<String> {
+    get_raw_stage_dependencies(graph, stage_name)
+        .into_iter()
+        .filter(|dependency| !EXTERNAL_STAGE_DEPENDENCIES.contains(&dependency.as_str()))
+        .collect()
+}
+
+fn sort_stages_by_dependencies(
+    graph: &Value,
+    stage_names: &[String],
+) -> Result<Vec<String>, String> {
+    let mut remaining: Vec<String> = stage_names.to_vec();
+    let mut sorted = Vec::with_capacity(stage_names.len());
+    while !remaining.is_empty() {
+        let ready: Vec<String> = remaining
+            .iter()
+            .filter(|stage_name| {
+                get_stage_dependencies(graph, stage_name)
+                    .iter()
+                    .all(|dependency| !remaining.contains(dependency))
+            })
+            .cloned()
+            .collect();
+        if ready.is_empty() {
+            return Err("World generation graph contains a dependency cycle.".to_string());
+        }
+
+        remaining.retain(|stage_name| !ready.contains(stage_name));
+        sorted.extend(ready);
+    }
+
+    Ok(sorted)
+}
+
+fn resolve_selected_stages(
+    graph: &Value,
+    topology: &[String],
+    request: &WorldRebuildRequest,
+) -> Result<Vec<String>, String> {
+    if !request.target_stages.is_empty() && request.changed_stages.is_empty() {
+        validate_stage_list(graph, &request.target_stages)?;
+        return Ok(topology
+            .iter()
+            .filter(|stage_name| request.target_stages.contains(stage_name))
+            .cloned()
+            .collect());
+    }
+
+    let seeds = if request.changed_stages.is_empty() {
+        infer_changed_stages_from_scopes(&request.scopes)
+    } else {
+        request.changed_stages.clone()
+    };
+    let internal_seeds: Vec<String> = seeds
+        .iter()
+        .filter(|stage_name| !EXTERNAL_STAGE_DEPENDENCIES.contains(&stage_name.as_str()))
+        .cloned()
+        .collect();
+    validate_stage_list(graph, &internal_seeds)?;
+    let affected_stages = collect_downstream_stages(graph, &seeds);
+    if !request.target_stages.is_empty() {
+        validate_stage_list(graph, &request.target_stages)?;
+    }
+
+    Ok(topology
+        .iter()
+        .filter(|stage_name| {
+            affected_stages.contains(*stage_name)
+                && (request.target_stages.is_empty() || request.target_stages.contains(stage_name))
+        })
+        .cloned()
+        .collect())
+}
+
+fn validate_stage_list(graph: &Value, stage_names: &[String]) -> Result<(), String> {
+    let known_stages: BTreeSet<String> = get_stage_names(graph)?.into_iter().collect();
+    for stage_name in stage_names {
+        if !known_stages.contains(stage_name) {
+            return Err(format!(
+                "Unknown world generation stage '{}'. Known stages: {}",
+                stage_name,
+                known_stages.iter().cloned().collect::<Vec<_>>().join(", ")
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn infer_changed_stages_from_scopes(scopes: &CookMapScopes) -> Vec<String> {
+    [
+        (&scopes.terrain_regions, "terrain"),
+        (&scopes.paint_regions, "paint"),
+        (&scopes.vegetation_regions, "vegetation"),
+        (&scopes.partition_cells, "objects"),
+    ]
+    .into_iter()
+    .filter(|(keys, _)| !keys.is_empty())
+    .map(|(_, stage_name)| stage_name.to_string())
+    .collect()
+}
+
+fn collect_downstream_stages(graph: &Value, seed_stages: &[String]) -> BTreeSet<String> {
+    let stage_names = get_stage_names(graph).unwrap_or_default();
+    let mut affected: BTreeSet<String> = seed_stages
+        .iter()
+        .filter(|stage_name| !EXTERNAL_STAGE_DEPENDENCIES.contains(&stage_name.as_str()))
+        .cloned()
+        .collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for stage_name in &stage_names {
+            if affected.contains(stage_name) {
+                continue;
+            }
+
+            let dependencies = get_raw_stage_dependencies(graph, stage_name);
+            if dependencies
+                .iter()
+                .any(|dependency| affected.contains(dependency) || seed_stages.contains(dependency))
+            {
+                affected.insert(stage_name.clone());
+                changed = true;
+            }
+        }
+    }
+
+    affected
+}
+
+fn create_rebuild_action(
+    graph: &Value,
+    stage_name: &str,
+    scopes: &CookMapScopes,
+) -> WorldRebuildAction {
+    let stage = graph_stage(graph, stage_name);
+    let scope_field = ScopeField::for_stage(stage_name).unwrap_or(ScopeField::PartitionCells);
+    let execution = stage.and_then(|stage| stage.get("execution"));
+
+    WorldRebuildAction {
+        stage: stage_name.to_string(),
+        kind: stage
+            .and_then(|stage| stage.get("kind"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        executor: execution
+            .and_then(|execution| execution.get("executor"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{}-executor", stage_name)),
+        scope: stage
+            .and_then(|stage| stage.get("rebuild"))
+            .and_then(|rebuild| rebuild.get("scope"))
+            .and_then(Value::as_str)
+            .unwrap_or(scope_field.name())
+            .to_string(),
+        keys: scope_field.keys(scopes).to_vec(),
+        invalidates: execution
+            .and_then(|execution| execution.get("invalidates"))
+            .and_then(Value::as_array)
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| entry.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+// --- Scopes / 范围 ---
+
+fn get_world_page_rect(graph: &Value) -> Result<PageRect, String> {
+    let page_bounds = graph
+        .get("world")
+        .and_then(|world| world.get("pageBounds"))
+        .ok_or_else(|| {
+            "World generation graph must contain page bounds before planning a rebuild.".to_string()
+        })?;
+    let read = |field: &str| {
+        page_bounds
+            .get(field)
+            .and_then(Value::as_i64)
+            .and_then(|value| i32::try_from(value).ok())
+            .ok_or_else(|| format!("World generation graph page bounds are missing '{}'", field))
+    };
+
+    Ok(PageRect {
+        min_x: read("minPageX")?,
+        max_x: read("maxPageX")?,
+        min_z: read("minPageZ")?,
+        max_z: read("maxPageZ")?,
+    })
+}
+
+fn create_all_scopes(world_rect: &PageRect) -> CookMapScopes {
+    CookMapScopes {
+        terrain_regions: collect_page_region_keys(world_rect, HEIGHT_REGION_SIZE_PAGES),
+        paint_regions: collect_page_region_keys(world_rect, PAINT_REGION_SIZE_PAGES),
+        vegetation_regions: collect_vegetation_region_keys(world_rect),
+        partition_cells: collect_page_region_keys(
+            world_rect,
+            COOKED_WORLD_PARTITION_CELL_SIZE_PAGES,
+        ),
+    }
+}
+
+/// Scope keys of every kind touching a world rectangle, clipped to the world page bounds.
+/// 与世界矩形相交的各类范围键，裁剪到世界页面范围内
+pub(crate) fn scopes_for_world_rect(
+    graph: &Value,
+    rect: &WorldRect,
+) -> Result<CookMapScopes, String> {
+    let world_rect = get_world_page_rect(graph)?;
+    let page_rect = PageRect {
+        min_x: (rect.min_x / PAGE_SIZE_METERS).floor() as i32,
+        max_x: (rect.max_x / PAGE_SIZE_METERS).floor() as i32,
+        min_z: (rect.min_z / PAGE_SIZE_METERS).floor() as i32,
+        max_z: (rect.max_z / PAGE_SIZE_METERS).floor() as i32,
+    };
+
+    Ok(page_rect
+        .clamp_to(&world_rect)
+        .map(|page_rect| create_all_scopes(&page_rect))
+        .unwrap_or_default())
+}
+
+fn resolve_selected_scopes(
+    world_rect: &PageRect,
+    all_scopes: &CookMapScopes,
+    request: &WorldRebuildRequest,
+) -> CookMapScopes {
+    if !has_scope_input(&request.scopes) {
+        return all_scopes.clone();
+    }
+
+    let page_rects: Vec<PageRect> = ScopeField::ALL
+        .iter()
+        .flat_map(|field| {
+            field
+                .keys(&request.scopes)
+                .iter()
+                .map(|key| field.key_to_page_rect(key))
+        })
+        .filter_map(|page_rect| page_rect.clamp_to(world_rect))
+        .collect();
+
+    let mut selected = request.scopes.clone();
+    for field in ScopeField::ALL {
+        let candidates = field.keys(all_scopes).to_vec();
+        let keys = field.keys_mut(&mut selected);
+        for key in candidates {
+            let key_rect = field.key_to_page_rect(&key);
+            if page_rects
+                .iter()
+                .any(|page_rect| key_rect.intersects(page_rect))
+            {
+                keys.push(key);
+            }
+        }
+        sort_grid_keys(keys);
+    }
+
+    selected
+}
+
+fn collect_page_region_keys(page_rect: &PageRect, region_size_pages: i32) -> Vec<String> {
+    collect_grid_keys(
+        page_rect.min_x.div_euclid(region_size_pages),
+        page_rect.max_x.div_euclid(region_size_pages),
+        page_rect.min_z.div_euclid(region_size_pages),
+        page_rect.max_z.div_euclid(region_size_pages),
+    )
+}
+
+fn vegetation_cells_per_page() -> i32 {
+    (PAGE_SIZE_METERS / VEGETATION_CELL_SIZE_METERS) as i32
+}
+
+fn collect_vegetation_region_keys(page_rect: &PageRect) -> Vec<String> {
+    let cells_per_page = vegetation_cells_per_page();
+    let min_cell_x = page_rect.min_x * cells_per_page;
+    let max_cell_x = (page_rect.max_x + 1) * cells_per_page - 1;
+    let min_cell_z = page_rect.min_z * cells_per_page;
+    let max_cell_z = (page_rect.max_z + 1) * cells_per_page - 1;
+    collect_grid_keys(
+        min_cell_x.div_euclid(VEGETATION_REGION_SIZE_CELLS),
+        max_cell_x.div_euclid(VEGETATION_REGION_SIZE_CELLS),
+        min_cell_z.div_euclid(VEGETATION_REGION_SIZE_CELLS),
+        max_cell_z.div_euclid(VEGETATION_REGION_SIZE_CELLS),
+    )
+}
+
+fn collect_grid_keys(min_x: i32, max_x: i32, min_z: i32, max_z: i32) -> Vec<String> {
+    let mut keys = Vec::new();
+    for z in min_z..=max_z {
+        for x in min_x..=max_x {
+            keys.push(format_grid_key(x, z));
+        }
+    }
+    keys
+}
+
+fn region_key_to_page_rect(key: &str, region_size_pages: i32) -> PageRect {
+    let (region_x, region_z) = parse_grid_key(key).unwrap_or((0, 0));
+    PageRect {
+        min_x: region_x * region_size_pages,
+        max_x: region_x * region_size_pages + region_size_pages - 1,
+        min_z: region_z * region_size_pages,
+        max_z: region_z * region_size_pages + region_size_pages - 1,
+    }
+}
+
+fn vegetation_region_key_to_page_rect(key: &str) -> PageRect {
+    let (region_x, region_z) = parse_grid_key(key).unwrap_or((0, 0));
+    let cells_per_page = vegetation_cells_per_page();
+    let min_cell_x = region_x * VEGETATION_REGION_SIZE_CELLS;
+    let min_cell_z = region_z * VEGETATION_REGION_SIZE_CELLS;
+    PageRect {
+        min_x: min_cell_x.div_euclid(cells_per_page),
+        max_x: (min_cell_x + VEGETATION_REGION_SIZE_CELLS - 1).div_euclid(cells_per_page),
+        min_z: min_cell_z.div_euclid(cells_per_page),
+        max_z: (min_cell_z + VEGETATION_REGION_SIZE_CELLS - 1).div_euclid(cells_per_page),
+    }
+}
+
+// --- Budget / 预算 ---
+
+fn create_plan_budget(
+    graph: &Value,
+    mode: &str,
+    stages: &[String],
+    scopes: &CookMapScopes,
+) -> WorldRebuildBudget {
+    let budgets = graph.get("budgets");
+    let budget_value = |field: &str| budgets.and_then(|budgets| budgets.get(field));
+    let mut warnings = Vec::new();
+    let mut errors = Vec::new();
+    let estimated_artifacts = stages
+        .iter()
+        .filter_map(|stage_name| ScopeField::for_stage(stage_name))
+        .map(|field| field.keys(scopes).len())
+        .sum();
+    let max_partition_cells = read_positive_integer(budget_value("maxPartitionCellsPerScopedCook"));
+    let max_estimated_artifacts =
+        read_positive_integer(budget_value("maxEstimatedArtifactsPerScopedCook"));
+
+    if equals_count(
+        budget_value("maxTerrainHeightRegionsPerFullRebuild"),
+        scopes.terrain_regions.len(),
+    ) {
+        warnings.push("terrain rebuild touches every height region".to_string());
+    }
+    if equals_count(
+        budget_value("maxPaintRegionsPerFullRebuild"),
+        scopes.paint_regions.len(),
+    ) {
+        warnings.push("paint rebuild touches every paint region".to_string());
+    }
+
+    if mode == "scoped"
+        && let Some(limit) = max_partition_cells
+        && scopes.partition_cells.len() as u64 > limit
+    {
+        errors.push(format!(
+            "partition cells {} exceeds scoped limit {}",
+            scopes.partition_cells.len(),
+            limit
+        ));
+    }
+
+    if mode == "scoped"
+        && let Some(limit) = max_estimated_artifacts
+        && estimated_artifacts as u64 > limit
+    {
+        errors.push(format!(
+            "estimated artifacts {} exceeds scoped limit {}",
+            estimated_artifacts, limit
+        ));
+    }
+
+    WorldRebuildBudget {
+        estimated_artifacts,
+        terrain_region_count: scopes.terrain_regions.len(),
+        paint_region_count: scopes.paint_regions.len(),
+        vegetation_region_count: scopes.vegetation_regions.len(),
+        partition_cell_count: scopes.partition_cells.len(),
+        max_partition_cells_per_scoped_cook: max_partition_cells,
+        max_estimated_artifacts_per_scoped_cook: max_estimated_artifacts,
+        exceeded: !errors.is_empty(),
+        errors,
+        warnings,
+    }
+}
+
+fn read_positive_integer(value: Option<&Value>) -> Option<u64> {
+    let number = value?.as_f64()?;
+    (number.fract() == 0.0 && number > 0.0).then_some(number as u64)
+}
+
+fn equals_count(value: Option<&Value>, count: usize) -> bool {
+    value
+        .and_then(Value::as_f64)
+        .is_some_and(|number| number == count as f64)
+}
+
+fn create_plan_id(
+    map_id: &str,
+    mode: &str,
+    request: &WorldRebuildRequest,
+    stages: &[String],
+    scopes: &CookMapScopes,
+) -> Result<String, String> {
+    let payload = serde_json::to_string(&WorldRebuildPlanIdPayload {
+        map_id,
+        mode,
+        request,
+        stages,
+        scopes,
+    })
+    .map_err(|e| format!("Failed to serialize rebuild plan id: {}", e))?;
+    let mut plan_id = sha256_hex(payload.as_bytes());
+    plan_id.truncate(16);
+    Ok(plan_id)
+}
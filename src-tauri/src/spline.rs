@@ -0,0 +1,1530 @@
+// Native spline model for road, river and fence objects and their terrain, paint and vegetation side effects.
+// 道路、河流与围栏对象的原生样条模型，及其对地形、绘制与植被的附带影响
+//
+// EN: Control points live in the object's `spline` entry. Existing packs carry no `interpolation` field and are read as polylines, matching findNearestWorldObjectPath in world-semantics.mjs.
+// 中文: 控制点存放在对象的 `spline` 条目中。现有数据包没有 `interpolation` 字段，按折线读取，与 world-semantics.mjs 中的 findNearestWorldObjectPath 一致。
+
+use crate::commands::{
+    CookMapScopes, MAPS_DIR, recover_safe_write, safe_write, validate_cook_project_path,
+};
+use crate::height_pack::HeightPackSession;
+use crate::map_layout::{
+    HEIGHT_SAMPLE_FORMAT, SPLINE_TERRAIN_BASELINE_DIRECTORY, WorldRect, compare_grid_keys,
+    format_grid_coordinate, format_grid_key, parse_grid_key, segment_projection, sha256_hex,
+    smoothstep, sort_grid_keys,
+};
+use crate::paint_pack::PaintPackSession;
+use crate::rebuild_planner::{read_world_generation_graph, scopes_for_world_rect};
+use crate::vegetation_pack::VegetationPackSession;
+use crate::world_objects::{WorldObjectSession, json_number, round_meters};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const ARC_LENGTH_SUBDIVISIONS: usize = 64;
+const SPLINE_SAMPLE_SPACING_METERS: f64 = 2.0;
+const MIN_CONTROL_POINT_SPACING_METERS: f64 = 0.001;
+const DEFAULT_SPLINE_WIDTH_METERS: f64 = 4.0;
+const TERRAIN_CHANGE_EPSILON_METERS: f32 = 0.0001;
+const VEGETATION_EXCLUSION_THRESHOLD: f64 = 0.5;
+const DECAL_CORE_WIDTH_FACTOR: f64 = 0.6;
+const CHANNEL_DEPTH_WIDTH_FACTOR: f64 = 0.25;
+const SPLINE_BASELINE_MANIFEST_FILE: &str = "manifest.json";
+const SPLINE_BASELINE_PAGE_EXTENSION: &str = "f32";
+
+// EN: Defaults of the road-grade and river-bed operations written by world-generation-graph.mjs, used when a graph omits them.
+// 中文: world-generation-graph.mjs 写入的 road-grade 与 river-bed 操作默认值，图中缺失时使用。
+const DEFAULT_ROAD_MAX_SLOPE_DEGREES: f64 = 18.0;
+const DEFAULT_ROAD_SHOULDER_METERS: f64 = 16.0;
+const DEFAULT_ROAD_BLEND_METERS: f64 = 22.0;
+const DEFAULT_WATER_BANK_BLEND_METERS: f64 = 28.0;
+const DEFAULT_WATER_MIN_DEPTH_METERS: f64 = 1.2;
+const DEFAULT_WATER_MAX_DEPTH_METERS: f64 = 3.8;
+
+// EN: Decal and surface materials mapped to paint layers; ruts favour gravel and banks favour sand or mud, as in resolvePaintWeights in paint-assets.mjs.
+// 中文: 贴花与表面材质到绘制层的映射；车辙偏向碎石，河岸偏向沙或泥，与 paint-assets.mjs 中的 resolvePaintWeights 一致。
+const SPLINE_PAINT_MATERIALS: &[(&str, &str, f64)] = &[
+    ("road-ruts", "gravelEmbeddedConcrete", 0.85),
+    ("rocky-road-ruts", "gravelEmbeddedConcrete", 1.0),
+    ("trampled-grass", "mudLeaves", 0.7),
+    ("river-water", "beachSand", 0.6),
+    ("stream-water", "mudLeaves", 0.6),
+];
+
+/// Spline control point; `y` is optional and, when every point has one, overrides the terrain grade.
+/// 样条控制点；`y` 可选，所有点都提供时会覆盖地形坡度线
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct SplineControlPoint {
+    x: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<f64>,
+    z: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SplineInterpolation {
+    Linear,
+    CatmullRom,
+    Bezier,
+}
+
+/// Control points plus interpolation, with a dense arc-length table.
+/// 控制点与插值方式，附带稠密的弧长表
+pub(crate) struct SplineCurve {
+    points: Vec<SplineControlPoint>,
+    interpolation: SplineInterpolation,
+    arc: Vec<([f64; 3], f64)>,
+}
+
+/// Point on the curve at a given arc length.
+/// 曲线上指定弧长处的点
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SplineSample {
+    pub(crate) distance: f64,
+    pub(crate) position: [f64; 3],
+}
+
+/// How a spline reshapes the terrain across its width.
+/// 样条在其宽度方向上改造地形的方式
+#[derive(Debug, Clone, Copy)]
+enum TerrainCut {
+    /// Cut to a smoothed, slope-limited grade line, blending out over the shoulder.
+    /// 切削到平滑且限坡的坡度线，在路肩范围内过渡
+    Grade {
+        shoulder_meters: f64,
+        blend_meters: f64,
+        max_slope_degrees: f64,
+    },
+    /// Carve a parabolic bed below the grade line, blending out over the bank.
+    /// 在坡度线下方开挖抛物线河床，在河岸范围内过渡
+    Channel {
+        bank_blend_meters: f64,
+        depth_meters: f64,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct SplineDecal {
+    material: String,
+    paint_layer: &'static str,
+    strength: f64,
+    edge_meters: f64,
+}
+
+/// Cross-section profile derived from the archetype and the terrain graph.
+/// 由原型与地形图推导出的横截面轮廓
+#[derive(Debug, Clone)]
+struct SplineProfile {
+    layer: String,
+    width_meters: f64,
+    terrain: Option<TerrainCut>,
+    decal: Option<SplineDecal>,
+    clears_vegetation: bool,
+}
+
+/// Terrain heights from before any spline edit, captured per page the first time an edit changes it, plus the splines whose cuts sit on top.
+/// Each stored page keeps the hash of the heights the last edit wrote, so pages changed by other tools are detected and re-captured.
+/// 样条编辑之前的地形高度（在编辑首次修改某页面时按页面捕获），以及叠加在其上的样条切削；
+/// 每个已存储页面记录最近一次编辑写入高度的哈希，从而检测并重新捕获被其他工具修改的页面
+struct SplineTerrainBaseline {
+    directory: PathBuf,
+    page_resolution: usize,
+    stored_pages: BTreeMap<(i32, i32), String>,
+    pages: BTreeMap<(i32, i32), Vec<f32>>,
+    captured_pages: BTreeSet<(i32, i32)>,
+    dropped_pages: BTreeSet<(i32, i32)>,
+    splines: BTreeSet<String>,
+}
+
+/// Sampled centerline with the grade height at every sample.
+/// 采样后的中心线，以及每个样本处的坡度线高度
+struct SplineFootprint {
+    samples: Vec<SplineSample>,
+    grade: Vec<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EditWorldSplineRequest {
+    project_path: String,
+    map_id: String,
+    object_id: String,
+    #[serde(default)]
+    points: Option<Vec<SplineControlPoint>>,
+    #[serde(default)]
+    width_meters: Option<f64>,
+    #[serde(default)]
+    interpolation: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplineTerrainPatch {
+    page: String,
+    changed_samples: usize,
+    max_delta_meters: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplinePaintMask {
+    page: String,
+    layer: String,
+    texel_count: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SplineVegetationMask {
+    region: String,
+    removed_instances: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldSplineEditResult {
+    object_id: String,
+    archetype: String,
+    interpolation: &'static str,
+    length_meters: f64,
+    sample_count: usize,
+    bounds_meters: WorldRect,
+    terrain_patches: Vec<SplineTerrainPatch>,
+    paint_masks: Vec<SplinePaintMask>,
+    vegetation_masks: Vec<SplineVegetationMask>,
+    updated_terrain_regions: Vec<String>,
+    updated_paint_regions: Vec<String>,
+    updated_vegetation_regions: Vec<String>,
+    updated_object_cells: Vec<String>,
+    changed_stages: Vec<String>,
+    scopes: CookMapScopes,
+    warnings: Vec<String>,
+    dry_run: bool,
+}
+
+// --- World spline commands / 世界样条命令 ---
+
+/// Edit a spline object and apply its terrain cut, paint decal and vegetation exclusion.
+/// Returns the affected regions and cells as cook scopes for a scoped rebuild.
+/// 编辑样条对象并应用其地形切削、绘制贴花与植被排除；以 cook 范围返回受影响的区域与单元，供局部重建使用
+#[tauri::command]
+pub async fn edit_world_spline(
+    request: EditWorldSplineRequest,
+) -> Result<WorldSplineEditResult, String> {
+    tauri::async_runtime::spawn_blocking(move || edit_world_spline_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join spline edit task: {}", e))?
+}
+
+// EN: Terrain is rebuilt from the spline baseline: pages under the old and new footprints are restored and every edited spline crossing them is cut again, so repeated edits do not deepen a cut and moving a spline reverts its earlier edits. Cuts baked by the map generator are part of the baseline, and pages changed since the last spline edit (heightmap imports, sculpting, regeneration) are re-captured from the live terrain instead of being reverted. Paint decals and vegetation clearing still stack on the current packs; the old footprint is included in the returned scopes so a rebuild can regenerate them.
+// 中文: 地形基于样条基线重建：新旧覆盖范围下的页面先恢复，再重新应用穿过它们的所有已编辑样条的切削，因此重复编辑不会加深切削，移动样条会还原其先前的编辑。地图生成器烘焙的切削属于基线的一部分；自上次样条编辑以来被修改的页面（高度图导入、雕刻、重新生成）会从当前地形重新捕获，而不会被还原。绘制贴花与植被清除仍叠加在当前数据包上；旧覆盖范围会包含在返回的范围中，供重建时重新生成。
+fn edit_world_spline_blocking(
+    request: EditWorldSplineRequest,
+) -> Result<WorldSplineEditResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let mut objects = WorldObjectSession::open(&project_root, &request.map_id)?;
+    let graph = read_world_generation_graph(&project_root, &request.map_id)?;
+    objects.load_all_cells()?;
+    let mut object = objects
+        .take_object(&request.object_id)?
+        .ok_or_else(|| format!("Unknown world object '{}'", request.object_id))?;
+    let archetype_id = object["archetype"].as_str().unwrap_or_default().to_string();
+    let archetype = objects
+        .archetype(&archetype_id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown world object archetype '{}'", archetype_id))?;
+    if archetype["editor"]["placement"].as_str() != Some("spline") {
+        return Err(format!(
+            "World object '{}' does not use a spline archetype",
+            request.object_id
+        ));
+    }
+
+    let previous_rect = SplineCurve::from_object(&object).ok().map(|curve| {
+        let profile = SplineProfile::resolve(&object, &archetype, &graph);
+        curve.footprint_rect(profile.reach())
+    });
+
+    if let Some(width_meters) = request.width_meters {
+        if !width_meters.is_finite() || width_meters <= 0.0 {
+            return Err("Spline width must be a positive number".to_string());
+        }
+        object["spline"]["widthMeters"] = json_number(round_meters(width_meters));
+    }
+    if let Some(points) = &request.points {
+        object["spline"]["points"] = points
+            .iter()
+            .map(|point| {
+                let mut entry = json!({ "x": json_number(round_meters(point.x)) });
+                if let Some(y) = point.y {
+                    entry["y"] = json_number(round_meters(y));
+                }
+                entry["z"] = json_number(round_meters(point.z));
+                entry
+            })
+            .collect();
+    }
+    if let Some(interpolation) = &request.interpolation {
+        let interpolation = SplineInterpolation::parse(Some(interpolation))?;
+        if let Some(spline) = object["spline"].as_object_mut() {
+            if interpolation == SplineInterpolation::Linear {
+                spline.remove("interpolation");
+            } else {
+                spline.insert("interpolation".to_string(), json!(interpolation.as_str()));
+            }
+        }
+    }
+
+    let curve = SplineCurve::from_object(&object)?;
+    let profile = SplineProfile::resolve(&object, &archetype, &graph);
+    let effect_rect = curve.footprint_rect(profile.reach());
+    let rebuild_rect = previous_rect.map_or(effect_rect, |rect| union_rect(&rect, &effect_rect));
+    let samples = curve.sample_by_arc_length(SPLINE_SAMPLE_SPACING_METERS);
+    let mut warnings = Vec::new();
+
+    let mut heights = HeightPackSession::open(&project_root, &request.map_id)?;
+    let mut baseline = SplineTerrainBaseline::open(&project_root, &request.map_id, &heights)?;
+    baseline.load_rect(&mut heights, &rebuild_rect)?;
+    let footprint = SplineFootprint::new(&curve, samples, &profile, &heights, &baseline);
+    if footprint.is_none() && profile.terrain.is_some() {
+        warnings.push("Spline does not cross any stored terrain; terrain cut skipped".to_string());
+    }
+
+    update_spline_transform(&mut object, &curve, &heights, &baseline);
+    objects.validate_object(&object)?;
+    objects.insert_object(object)?;
+
+    if profile.terrain.is_some() {
+        baseline.splines.insert(request.object_id.clone());
+    }
+    let other_cuts = collect_spline_cuts(
+        &objects,
+        &graph,
+        &mut baseline,
+        &mut heights,
+        &rebuild_rect,
+        &request.object_id,
+    )?;
+    let mut cuts: Vec<(&str, &SplineFootprint, &SplineProfile, TerrainCut)> = other_cuts
+        .iter()
+        .map(|(id, footprint, profile, cut)| (id.as_str(), footprint, profile, *cut))
+        .collect();
+    if let (Some(footprint), Some(cut)) = (&footprint, profile.terrain) {
+        cuts.push((request.object_id.as_str(), footprint, &profile, cut));
+    }
+    // EN: Overlapping cuts stack in object id order, so the result does not depend on which spline was edited.
+    // 中文: 重叠的切削按对象 id 顺序叠加，因此结果与编辑的是哪条样条无关。
+    cuts.sort_by(|left, right| left.0.cmp(right.0));
+    let terrain_patches = apply_terrain_cuts(&mut heights, &mut baseline, &cuts, &rebuild_rect)?;
+    let footprint = footprint.unwrap_or_else(|| SplineFootprint::flat(&curve));
+
+    let mut paint = PaintPackSession::open(&project_root, &request.map_id)?;
+    let paint_masks = match &profile.decal {
+        Some(decal) => match paint.layer_index(decal.paint_layer) {
+            Ok(layer_index) => apply_paint_decal(
+                &mut paint,
+                layer_index,
+                &footprint,
+                &profile,
+                decal,
+                &effect_rect,
+            )?,
+            Err(error) => {
+                warnings.push(format!(
+                    "Decal material '{}' skipped: {}",
+                    decal.material, error
+                ));
+                Vec::new()
+            }
+        },
+        None => Vec::new(),
+    };
+
+    let mut vegetation = VegetationPackSession::open(&project_root, &request.map_id)?;
+    let vegetation_masks = if profile.clears_vegetation {
+        apply_vegetation_exclusion(&mut vegetation, &footprint, &profile, &effect_rect)?
+    } else {
+        Vec::new()
+    };
+
+    let mut changed_stages = Vec::new();
+    for (stage, changed) in [
+        ("terrain", !terrain_patches.is_empty()),
+        ("paint", !paint_masks.is_empty()),
+        ("vegetation", !vegetation_masks.is_empty()),
+        ("objects", true),
+    ] {
+        if changed {
+            changed_stages.push(stage.to_string());
+        }
+    }
+
+    let scope_rect = previous_rect.map_or(effect_rect, |rect| union_rect(&rect, &effect_rect));
+    let mut scopes = scopes_for_world_rect(&graph, &scope_rect)?;
+    let (
+        updated_terrain_regions,
+        updated_paint_regions,
+        updated_vegetation_regions,
+        updated_object_cells,
+    ) = if request.dry_run {
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new())
+    } else {
+        // EN: The baseline is written first; if the height save then fails, the untouched pages no longer match the recorded output hashes and the next edit re-captures them.
+        // 中文: 先写入基线；即使随后高度保存失败，未修改的页面也会与记录的输出哈希不符，下一次编辑会重新捕获它们。
+        baseline.save()?;
+        let updated_terrain_regions = heights.save()?;
+        let updated_paint_regions = paint.save()?;
+        let (mut updated_vegetation_regions, removed_vegetation_regions) = vegetation.save()?;
+        updated_vegetation_regions.extend(removed_vegetation_regions);
+        sort_grid_keys(&mut updated_vegetation_regions);
+        let updated_object_cells = objects.save()?;
+        (
+            updated_terrain_regions,
+            updated_paint_regions,
+            updated_vegetation_regions,
+            updated_object_cells,
+        )
+    };
+    scopes
+        .partition_cells
+        .extend(updated_object_cells.iter().cloned());
+    sort_grid_keys(&mut scopes.partition_cells);
+
+    Ok(WorldSplineEditResult {
+        object_id: request.object_id,
+        archetype: archetype_id,
+        interpolation: curve.interpolation.as_str(),
+        length_meters: round_meters(curve.length()),
+        sample_count: footprint.samples.len(),
+        bounds_meters: effect_rect,
+        terrain_patches,
+        paint_masks,
+        vegetation_masks,
+        updated_terrain_regions,
+        updated_paint_regions,
+        updated_vegetation_regions,
+        updated_object_cells,
+        changed_stages,
+        scopes,
+        warnings,
+        dry_run: request.dry_run,
+    })
+}
+
+/// Place the object at the arc-length midpoint facing along the curve, with editor-style bounds.
+/// 将对象放在弧长中点并朝向曲线方向，包围盒与编辑器一致
+fn update_spline_transform(
+    object: &mut Value,
+    curve: &SplineCurve,
+    heights: &HeightPackSession,
+    baseline: &SplineTerrainBaseline,
+) {
+    let half_length = curve.length() * 0.5;
+    let [x, authored_y, z] = curve.point_at_distance(half_length);
+    let [ahead_x, _, ahead_z] = curve.point_at_distance(half_length + SPLINE_SAMPLE_SPACING_METERS);
+    let [behind_x, _, behind_z] =
+        curve.point_at_distance(half_length - SPLINE_SAMPLE_SPACING_METERS);
+    let y = if curve.has_heights() {
+        authored_y
+    } else {
+        baseline
+            .height_at(heights, x, z)
+            .map(f64::from)
+            .or_else(|| object["position"]["y"].as_f64())
+            .unwrap_or(0.0)
+    };
+    object["position"] = json!({
+        "x": json_number(round_meters(x)),
+        "y": json_number(round_meters(y)),
+        "z": json_number(round_meters(z)),
+    });
+    object["rotationY"] = json_number(round_meters((ahead_x - behind_x).atan2(ahead_z - behind_z)));
+
+    let width_meters = object["spline"]["widthMeters"]
+        .as_f64()
+        .unwrap_or(DEFAULT_SPLINE_WIDTH_METERS);
+    let rect = curve.footprint_rect((width_meters * 0.5).max(1.0));
+    object["boundsMeters"] = json!({
+        "minX": json_number(round_meters(rect.min_x)),
+        "minZ": json_number(round_meters(rect.min_z)),
+        "maxX": json_number(round_meters(rect.max_x)),
+        "maxZ": json_number(round_meters(rect.max_z)),
+    });
+}
+
+/// Footprints of the other baseline splines whose terrain cut reaches `rect`; splines that no longer exist are dropped from the baseline.
+/// 地形切削可达 `rect` 的其他基线样条的覆盖范围；已不存在的样条会从基线中移除
+fn collect_spline_cuts(
+    objects: &WorldObjectSession,
+    graph: &Value,
+    baseline: &mut SplineTerrainBaseline,
+    heights: &mut HeightPackSession,
+    rect: &WorldRect,
+    edited_id: &str,
+) -> Result<Vec<(String, SplineFootprint, SplineProfile, TerrainCut)>, String> {
+    // EN: Every page touching `rect` is rebuilt whole, so any cut reaching those pages must be re-applied, not only cuts reaching `rect` itself.
+    // 中文: 与 `rect` 相交的每个页面都会整页重建，因此凡是触及这些页面的切削都必须重新应用，而不仅是触及 `rect` 本身的切削。
+    let pages_rect = page_aligned_rect(rect, heights.layout.page_size_meters);
+    let mut cuts = Vec::new();
+    for id in baseline.splines.clone() {
+        if id == edited_id {
+            continue;
+        }
+        let Some(object) = objects
+            .loaded_objects()
+            .find(|object| object["id"] == id.as_str())
+        else {
+            baseline.splines.remove(&id);
+            continue;
+        };
+        let (Ok(curve), Some(archetype)) = (
+            SplineCurve::from_object(object),
+            object["archetype"]
+                .as_str()
+                .and_then(|archetype_id| objects.archetype(archetype_id)),
+        ) else {
+            continue;
+        };
+        let profile = SplineProfile::resolve(object, archetype, graph);
+        let Some(cut) = profile.terrain else {
+            continue;
+        };
+        let footprint_rect = curve.footprint_rect(profile.terrain_reach());
+        if !rects_overlap(&footprint_rect, &pages_rect) {
+            continue;
+        }
+        baseline.load_rect(heights, &footprint_rect)?;
+        let samples = curve.sample_by_arc_length(SPLINE_SAMPLE_SPACING_METERS);
+        if let Some(footprint) = SplineFootprint::new(&curve, samples, &profile, heights, baseline)
+        {
+            cuts.push((id, footprint, profile, cut));
+        }
+    }
+
+    Ok(cuts)
+}
+
+/// Rebuild every height page in `rect` from the baseline and the given cuts, applied in order.
+/// Only pages whose heights change are written; the baseline captures each one first.
+/// 基于基线与给定切削（按顺序应用）重建 `rect` 内的每个高度页面；只写入高度有变化的页面，写入前先由基线捕获
+fn apply_terrain_cuts(
+    heights: &mut HeightPackSession,
+    baseline: &mut SplineTerrainBaseline,
+    cuts: &[(&str, &SplineFootprint, &SplineProfile, TerrainCut)],
+    rect: &WorldRect,
+) -> Result<Vec<SplineTerrainPatch>, String> {
+    let page_size = heights.layout.page_size_meters;
+    let spacing = heights.layout.sample_spacing_meters();
+    let resolution = heights.layout.page_resolution;
+    let mut patches = Vec::new();
+    for pz in (rect.min_z / page_size).floor() as i32..=(rect.max_z / page_size).floor() as i32 {
+        for px in (rect.min_x / page_size).floor() as i32..=(rect.max_x / page_size).floor() as i32
+        {
+            let origin_x = px as f64 * page_size;
+            let origin_z = pz as f64 * page_size;
+            let page_rect = [
+                origin_x,
+                origin_z,
+                origin_x + page_size,
+                origin_z + page_size,
+            ];
+            let page_cuts: Vec<_> = cuts
+                .iter()
+                .map(|(_, footprint, profile, cut)| {
+                    let segments = footprint.segments_near(page_rect, profile.terrain_reach());
+                    (footprint, profile, *cut, segments)
+                })
+                .filter(|(_, _, _, segments)| !segments.is_empty())
+                .collect();
+            let base = baseline.pages.get(&(px, pz));
+            if page_cuts.is_empty() && base.is_none() {
+                continue;
+            }
+            let Some(current) = heights.page_heights(px, pz)? else {
+                continue;
+            };
+            let current = current.to_vec();
+
+            let base = base.cloned().unwrap_or_else(|| current.clone());
+            let mut cut_heights = base.clone();
+            for (footprint, profile, cut, segments) in &page_cuts {
+                for row in 0..resolution {
+                    for column in 0..resolution {
+                        let x = origin_x + column as f64 * spacing;
+                        let z = origin_z + row as f64 * spacing;
+                        let Some((distance, grade)) = footprint.nearest(segments, x, z) else {
+                            continue;
+                        };
+                        let index = row * resolution + column;
+                        cut_heights[index] =
+                            profile.cut_height(*cut, distance, grade, cut_heights[index] as f64)
+                                as f32;
+                    }
+                }
+            }
+
+            // EN: Negligible cuts snap back to the baseline, so the page is a pure function of the baseline and rebuilding it again changes nothing.
+            // 中文: 可忽略的切削回退到基线值，使页面完全由基线决定，再次重建不会产生任何变化。
+            let mut changed_samples = 0;
+            let mut max_delta = 0.0f32;
+            for ((cut_height, base_height), height) in
+                cut_heights.iter_mut().zip(&base).zip(&current)
+            {
+                if (*cut_height - base_height).abs() <= TERRAIN_CHANGE_EPSILON_METERS {
+                    *cut_height = *base_height;
+                }
+                if *cut_height != *height {
+                    changed_samples += 1;
+                    max_delta = max_delta.max((*cut_height - height).abs());
+                }
+            }
+            if changed_samples == 0 {
+                continue;
+            }
+
+            baseline.capture(px, pz, current, &cut_heights);
+            if let Some(page) = heights.page_heights_mut(px, pz, false)? {
+                *page = cut_heights;
+            }
+            patches.push(SplineTerrainPatch {
+                page: format_grid_key(px, pz),
+                changed_samples,
+                max_delta_meters: round_meters(max_delta as f64),
+            });
+        }
+    }
+
+    Ok(patches)
+}
+
+fn apply_paint_decal(
+    paint: &mut PaintPackSession,
+    layer_index: usize,
+    footprint: &SplineFootprint,
+    profile: &SplineProfile,
+    decal: &SplineDecal,
+    rect: &WorldRect,
+) -> Result<Vec<SplinePaintMask>, String> {
+    let slot = paint.layers[layer_index].storage_slot()?;
+    let page_size = paint.layout.page_size_meters;
+    let resolution = paint.layout.page_resolution;
+    let texel_size = page_size / resolution as f64;
+    let reach = profile.decal_reach();
+    let mut masks = Vec::new();
+    let min_page_x = paint.layout.page_for_world_coordinate(rect.min_x);
+    let max_page_x = paint.layout.page_for_world_coordinate(rect.max_x);
+    let min_page_z = paint.layout.page_for_world_coordinate(rect.min_z);
+    let max_page_z = paint.layout.page_for_world_coordinate(rect.max_z);
+    for pz in min_page_z..=max_page_z {
+        for px in min_page_x..=max_page_x {
+            let origin_x = paint.layout.page_origin_meters(px);
+            let origin_z = paint.layout.page_origin_meters(pz);
+            let page_rect = [
+                origin_x,
+                origin_z,
+                origin_x + page_size,
+                origin_z + page_size,
+            ];
+            let segments = footprint.segments_near(page_rect, reach);
+            if segments.is_empty() || paint.load_page(px, pz)?.is_none() {
+                continue;
+            }
+
+            let mut weights = vec![None; resolution * resolution];
+            for row in 0..resolution {
+                for column in 0..resolution {
+                    let x = origin_x + (column as f64 + 0.5) * texel_size;
+                    let z = origin_z + (row as f64 + 0.5) * texel_size;
+                    let Some((distance, _)) = footprint.nearest(&segments, x, z) else {
+                        continue;
+                    };
+                    let weight = (profile.decal_weight(decal, distance) * 255.0).round() as u8;
+                    let current = paint.loaded_weight_at(slot, x, z).unwrap_or(0);
+                    if weight > current {
+                        weights[row * resolution + column] = Some(weight);
+                    }
+                }
+            }
+            let texel_count = weights.iter().flatten().count();
+            if texel_count == 0 {
+                continue;
+            }
+
+            paint.write_page_layer_weights(px, pz, layer_index, true, |row, column| {
+                weights[row * resolution + column]
+            })?;
+            masks.push(SplinePaintMask {
+                page: format_grid_key(px, pz),
+                layer: decal.paint_layer.to_string(),
+                texel_count,
+            });
+        }
+    }
+
+    Ok(masks)
+}
+
+fn apply_vegetation_exclusion(
+    vegetation: &mut VegetationPackSession,
+    footprint: &SplineFootprint,
+    profile: &SplineProfile,
+    rect: &WorldRect,
+) -> Result<Vec<SplineVegetationMask>, String> {
+    let reach = profile.vegetation_reach();
+    let bounds = [rect.min_x, rect.min_z, rect.max_x, rect.max_z];
+    let segments = footprint.segments_near(bounds, reach);
+    let removed = vegetation.take_instances_where(Some(bounds), |instance| {
+        footprint
+            .nearest(&segments, instance.x as f64, instance.z as f64)
+            .is_some_and(|(distance, _)| {
+                profile.vegetation_exclusion(distance) >= VEGETATION_EXCLUSION_THRESHOLD
+            })
+    })?;
+
+    let mut counts: BTreeMap<(i32, i32), usize> = BTreeMap::new();
+    for instance in &removed {
+        let (cx, cz) = vegetation
+            .layout
+            .cell_for_position(instance.x as f64, instance.z as f64);
+        let (region_x, region_z) = vegetation.layout.region_coords_for_cell(cx, cz);
+        *counts.entry((region_z, region_x)).or_default() += 1;
+    }
+
+    Ok(counts
+        .into_iter()
+        .map(
+            |((region_z, region_x), removed_instances)| SplineVegetationMask {
+                region: format_grid_key(region_x, region_z),
+                removed_instances,
+            },
+        )
+        .collect())
+}
+
+fn union_rect(left: &WorldRect, right: &WorldRect) -> WorldRect {
+    WorldRect {
+        min_x: left.min_x.min(right.min_x),
+        min_z: left.min_z.min(right.min_z),
+        max_x: left.max_x.max(right.max_x),
+        max_z: left.max_z.max(right.max_z),
+    }
+}
+
+/// Bounds of the whole pages that `rect` touches, matching the page loop in `apply_terrain_cuts`.
+/// `rect` 触及的完整页面范围，与 `apply_terrain_cuts` 中的页面循环一致
+fn page_aligned_rect(rect: &WorldRect, page_size: f64) -> WorldRect {
+    WorldRect {
+        min_x: (rect.min_x / page_size).floor() * page_size,
+        min_z: (rect.min_z / page_size).floor() * page_size,
+        max_x: ((rect.max_x / page_size).floor() + 1.0) * page_size,
+        max_z: ((rect.max_z / page_size).floor() + 1.0) * page_size,
+    }
+}
+
+fn rects_overlap(left: &WorldRect, right: &WorldRect) -> bool {
+    left.min_x <= right.max_x
+        && left.max_x >= right.min_x
+        && left.min_z <= right.max_z
+        && left.max_z >= right.min_z
+}
+
+fn lerp3(a: [f64; 3], b: [f64; 3], t: f64) -> [f64; 3] {
+    [
+        a[0] + (b[0] - a[0]) * t,
+        a[1] + (b[1] - a[1]) * t,
+        a[2] + (b[2] - a[2]) * t,
+    ]
+}
+
+// --- Curve / 曲线 ---
+
+impl SplineInterpolation {
+    pub(crate) fn parse(value: Option<&str>) -> Result<Self, String> {
+        match value {
+            None | Some("linear") => Ok(Self::Linear),
+            Some("catmull-rom") => Ok(Self::CatmullRom),
+            Some("bezier") => Ok(Self::Bezier),
+            Some(other) => Err(format!(
+                "Unknown spline interpolation '{}'; expected linear, catmull-rom or bezier",
+                other
+            )),
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Linear => "linear",
+            Self::CatmullRom => "catmull-rom",
+            Self::Bezier => "bezier",
+        }
+    }
+}
+
+impl SplineCurve {
+    pub(crate) fn new(
+        points: Vec<SplineControlPoint>,
+        interpolation: SplineInterpolation,
+    ) -> Result<Self, String> {
+        if points.len() < 2 {
+            return Err("Spline requires at least two control points".to_string());
+        }
+        let finite = points.iter().all(|point| {
+            point.x.is_finite() && point.z.is_finite() && point.y.is_none_or(f64::is_finite)
+        });
+        if !finite {
+            return Err("Spline control points must be finite".to_string());
+        }
+        if interpolation == SplineInterpolation::Bezier && !(points.len() - 1).is_multiple_of(3) {
+            return Err(
+                "Bezier splines require 3n+1 control points (anchor, handle, handle, anchor, ...)"
+                    .to_string(),
+            );
+        }
+        // EN: Centripetal Catmull-Rom divides by knot spacing, so neighbouring points must differ.
+        // 中文: 向心 Catmull-Rom 会除以节点间距，因此相邻点不能重合。
+        if interpolation == SplineInterpolation::CatmullRom
+            && points.windows(2).any(|pair| {
+                (pair[1].x - pair[0].x).hypot(pair[1].z - pair[0].z)
+                    < MIN_CONTROL_POINT_SPACING_METERS
+            })
+        {
+            return Err("Catmull-Rom spline control points must not coincide".to_string());
+        }
+
+        let mut curve = Self {
+            points,
+            interpolation,
+            arc: Vec::new(),
+        };
+        curve.arc = curve.build_arc_table();
+        Ok(curve)
+    }
+
+    /// Curve of a world object's `spline` entry.
+    /// 世界对象 `spline` 条目对应的曲线
+    pub(crate) fn from_object(object: &Value) -> Result<Self, String> {
+        let spline = &object["spline"];
+        let points = spline["points"]
+            .as_array()
+            .ok_or_else(|| "Spline object must contain control points".to_string())?
+            .iter()
+            .map(|point| {
+                Some(SplineControlPoint {
+                    x: point["x"].as_f64()?,
+                    y: point["y"].as_f64(),
+                    z: point["z"].as_f64()?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| "Spline control points must contain numeric x and z".to_string())?;
+        Self::new(
+            points,
+            SplineInterpolation::parse(spline["interpolation"].as_str())?,
+        )
+    }
+
+    pub(crate) fn length(&self) -> f64 {
+        self.arc.last().map_or(0.0, |(_, distance)| *distance)
+    }
+
+    fn has_heights(&self) -> bool {
+        self.points.iter().all(|point| point.y.is_some())
+    }
+
+    /// XZ centerline for distance queries: control points for linear splines, arc samples otherwise.
+    /// 用于距离查询的 XZ 中心线：折线样条使用控制点，其余使用弧长采样点
+    pub(crate) fn polyline(&self) -> Vec<[f64; 2]> {
+        match self.interpolation {
+            SplineInterpolation::Linear => {
+                self.points.iter().map(|point| [point.x, point.z]).collect()
+            }
+            _ => self
+                .sample_by_arc_length(SPLINE_SAMPLE_SPACING_METERS)
+                .iter()
+                .map(|sample| [sample.position[0], sample.position[2]])
+                .collect(),
+        }
+    }
+
+    /// Evenly spaced samples along the curve, including both ends.
+    /// 沿曲线等距分布的样本，包含两端
+    pub(crate) fn sample_by_arc_length(&self, spacing_meters: f64) -> Vec<SplineSample> {
+        let length = self.length();
+        let count = ((length / spacing_meters).ceil() as usize).max(1);
+        (0..=count)
+            .map(|index| {
+                let distance = length * index as f64 / count as f64;
+                SplineSample {
+                    distance,
+                    position: self.point_at_distance(distance),
+                }
+            })
+            .collect()
+    }
+
+    /// Position at an arc length, clamped to the curve.
+    /// 指定弧长处的位置，超出范围时截取到曲线端点
+    pub(crate) fn point_at_distance(&self, distance: f64) -> [f64; 3] {
+        let distance = distance.clamp(0.0, self.length());
+        let index = self
+            .arc
+            .partition_point(|(_, arc_distance)| *arc_distance < distance)
+            .clamp(1, self.arc.len() - 1);
+        let (start, start_distance) = self.arc[index - 1];
+        let (end, end_distance) = self.arc[index];
+        let span = end_distance - start_distance;
+        let t = if span > 0.0 {
+            (distance - start_distance) / span
+        } else {
+            0.0
+        };
+        lerp3(start, end, t)
+    }
+
+    /// Bounding rectangle of the curve grown by `margin` meters.
+    /// 曲线的包围矩形，向外扩展 `margin` 米
+    pub(crate) fn footprint_rect(&self, margin: f64) -> WorldRect {
+        let mut rect = WorldRect {
+            min_x: f64::INFINITY,
+            min_z: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_z: f64::NEG_INFINITY,
+        };
+        for ([x, _, z], _) in &self.arc {
+            rect.min_x = rect.min_x.min(*x);
+            rect.min_z = rect.min_z.min(*z);
+            rect.max_x = rect.max_x.max(*x);
+            rect.max_z = rect.max_z.max(*z);
+        }
+        WorldRect {
+            min_x: rect.min_x - margin,
+            min_z: rect.min_z - margin,
+            max_x: rect.max_x + margin,
+            max_z: rect.max_z + margin,
+        }
+    }
+
+    fn segment_count(&self) -> usize {
+        match self.interpolation {
+            SplineInterpolation::Bezier => (self.points.len() - 1) / 3,
+            _ => self.points.len() - 1,
+        }
+    }
+
+    fn control(&self, index: usize) -> [f64; 3] {
+        let point = self.points[index];
+        [point.x, point.y.unwrap_or(0.0), point.z]
+    }
+
+    fn evaluate(&self, segment: usize, t: f64) -> [f64; 3] {
+        match self.interpolation {
+            SplineInterpolation::Linear => {
+                lerp3(self.control(segment), self.control(segment + 1), t)
+            }
+            SplineInterpolation::Bezier => {
+                let base = segment * 3;
+                let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|offset| self.control(base + offset));
+                let u = 1.0 - t;
+                let weights = [u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t];
+                std::array::from_fn(|axis| {
+                    weights[0] * p0[axis]
+                        + weights[1] * p1[axis]
+                        + weights[2] * p2[axis]
+                        + weights[3] * p3[axis]
+                })
+            }
+            SplineInterpolation::CatmullRom => self.evaluate_catmull_rom(segment, t),
+        }
+    }
+
+    // EN: Centripetal (alpha 0.5) Barry-Goldman form; missing end neighbours are mirrored so the curve passes through every control point without overshoot.
+    // 中文: 向心（alpha 0.5）Barry-Goldman 形式；缺失的端点邻居采用镜像，使曲线经过每个控制点且不会过冲。
+    fn evaluate_catmull_rom(&self, segment: usize, t: f64) -> [f64; 3] {
+        let p1 = self.control(segment);
+        let p2 = self.control(segment + 1);
+        let mirror = |anchor: [f64; 3], other: [f64; 3]| -> [f64; 3] {
+            std::array::from_fn(|axis| 2.0 * anchor[axis] - other[axis])
+        };
+        let p0 = if segment > 0 {
+            self.control(segment - 1)
+        } else {
+            mirror(p1, p2)
+        };
+        let p3 = if segment + 2 < self.points.len() {
+            self.control(segment + 2)
+        } else {
+            mirror(p2, p1)
+        };
+
+        let knot = |a: [f64; 3], b: [f64; 3]| {
+            (b[0] - a[0])
+                .hypot(b[2] - a[2])
+                .max(MIN_CONTROL_POINT_SPACING_METERS)
+                .sqrt()
+        };
+        let t0 = 0.0;
+        let t1 = t0 + knot(p0, p1);
+        let t2 = t1 + knot(p1, p2);
+        let t3 = t2 + knot(p2, p3);
+        let u = t1 + (t2 - t1) * t;
+        let blend = |a: [f64; 3], b: [f64; 3], start: f64, end: f64| {
+            lerp3(a, b, (u - start) / (end - start))
+        };
+        let a1 = blend(p0, p1, t0, t1);
+        let a2 = blend(p1, p2, t1, t2);
+        let a3 = blend(p2, p3, t2, t3);
+        let b1 = blend(a1, a2, t0, t2);
+        let b2 = blend(a2, a3, t1, t3);
+        blend(b1, b2, t1, t2)
+    }
+
+    fn build_arc_table(&self) -> Vec<([f64; 3], f64)> {
+        let mut table = vec![(self.evaluate(0, 0.0), 0.0)];
+        for segment in 0..self.segment_count() {
+            for step in 1..=ARC_LENGTH_SUBDIVISIONS {
+                let point = self.evaluate(segment, step as f64 / ARC_LENGTH_SUBDIVISIONS as f64);
+                let (previous, distance) = table[table.len() - 1];
+                let length = (point[0] - previous[0]).hypot(point[2] - previous[2]);
+                table.push((point, distance + length));
+            }
+        }
+        table
+    }
+}
+
+// --- Cross-section / 横截面 ---
+
+impl SplineProfile {
+    fn resolve(object: &Value, archetype: &Value, graph: &Value) -> Self {
+        let layer = object["layer"]
+            .as_str()
+            .or_else(|| archetype["layer"].as_str())
+            .unwrap_or_default()
+            .to_string();
+        let width_meters = object["spline"]["widthMeters"]
+            .as_f64()
+            .or_else(|| object["radiusMeters"].as_f64())
+            .or_else(|| archetype["editor"]["defaultRadiusMeters"].as_f64())
+            .filter(|width| width.is_finite() && *width > 0.0)
+            .unwrap_or(DEFAULT_SPLINE_WIDTH_METERS);
+
+        let operation = |operation_type: &str| {
+            graph["stages"]["terrain"]["operations"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|operation| operation["type"].as_str() == Some(operation_type))
+                .map(|operation| operation["parameters"].clone())
+                .unwrap_or(Value::Null)
+        };
+        let terrain = match layer.as_str() {
+            "road" => {
+                let parameters = operation("semantic-road-cut");
+                Some(TerrainCut::Grade {
+                    shoulder_meters: parameters["shoulderMeters"]
+                        .as_f64()
+                        .unwrap_or(DEFAULT_ROAD_SHOULDER_METERS),
+                    blend_meters: parameters["blendMeters"]
+                        .as_f64()
+                        .unwrap_or(DEFAULT_ROAD_BLEND_METERS),
+                    max_slope_degrees: parameters["maxSlopeDegrees"]
+                        .as_f64()
+                        .unwrap_or(DEFAULT_ROAD_MAX_SLOPE_DEGREES),
+                })
+            }
+            "water" => {
+                let parameters = operation("semantic-water-carve");
+                let min_depth = parameters["minDepthMeters"]
+                    .as_f64()
+                    .unwrap_or(DEFAULT_WATER_MIN_DEPTH_METERS);
+                let max_depth = parameters["maxDepthMeters"]
+                    .as_f64()
+                    .unwrap_or(DEFAULT_WATER_MAX_DEPTH_METERS)
+                    .max(min_depth);
+                Some(TerrainCut::Channel {
+                    bank_blend_meters: parameters["bankBlendMeters"]
+                        .as_f64()
+                        .unwrap_or(DEFAULT_WATER_BANK_BLEND_METERS),
+                    depth_meters: (width_meters * CHANNEL_DEPTH_WIDTH_FACTOR)
+                        .clamp(min_depth, max_depth),
+                })
+            }
+            _ => None,
+        };
+
+        let render = &archetype["render"];
+        let decal = render["decalMaterial"]
+            .as_str()
+            .or_else(|| render["surfaceMaterial"].as_str())
+            .and_then(|material| {
+                let (_, paint_layer, strength) = SPLINE_PAINT_MATERIALS
+                    .iter()
+                    .find(|(name, _, _)| *name == material)?;
+                Some(SplineDecal {
+                    material: material.to_string(),
+                    paint_layer,
+                    strength: *strength,
+                    edge_meters: render["edgeBlendMeters"]
+                        .as_f64()
+                        .or_else(|| render["bankWetnessMeters"].as_f64())
+                        .unwrap_or(0.0),
+                })
+            });
+
+        Self {
+            layer,
+            width_meters,
+            terrain,
+            decal,
+            clears_vegetation: archetype["clearsVegetation"].as_bool().unwrap_or(false),
+        }
+    }
+
+    fn half_width(&self) -> f64 {
+        self.width_meters * 0.5
+    }
+
+    /// Largest distance from the centerline at which any effect applies.
+    /// 任一影响生效的距中心线最大距离
+    fn reach(&self) -> f64 {
+        self.terrain_reach()
+            .max(self.decal_reach())
+            .max(self.vegetation_reach())
+            .max(self.half_width())
+    }
+
+    fn terrain_reach(&self) -> f64 {
+        match self.terrain {
+            Some(TerrainCut::Grade {
+                shoulder_meters, ..
+            }) => self.half_width() + shoulder_meters,
+            Some(TerrainCut::Channel {
+                bank_blend_meters, ..
+            }) => self.half_width() + bank_blend_meters,
+            None => 0.0,
+        }
+    }
+
+    fn decal_reach(&self) -> f64 {
+        self.decal
+            .as_ref()
+            .map_or(0.0, |decal| self.half_width() + decal.edge_meters)
+    }
+
+    fn vegetation_reach(&self) -> f64 {
+        if !self.clears_vegetation {
+            return 0.0;
+        }
+        match self.layer.as_str() {
+            "road" => self.width_meters * 1.35 + 4.0,
+            "water" => self.width_meters * 1.25 + 4.0,
+            _ => self.half_width(),
+        }
+    }
+
+    /// Terrain height after the cut at `distance` from the centerline.
+    /// 距中心线 `distance` 处切削后的地形高度
+    fn cut_height(&self, cut: TerrainCut, distance: f64, grade: f64, height: f64) -> f64 {
+        let half_width = self.half_width();
+        match cut {
+            TerrainCut::Grade {
+                shoulder_meters, ..
+            } => {
+                let weight = 1.0 - smoothstep(half_width, half_width + shoulder_meters, distance);
+                height + (grade - height) * weight
+            }
+            TerrainCut::Channel {
+                bank_blend_meters,
+                depth_meters,
+            } => {
+                let bed = depth_meters * (1.0 - (distance / half_width).powi(2)).max(0.0);
+                let weight = 1.0 - smoothstep(half_width, half_width + bank_blend_meters, distance);
+                // EN: Channels only ever lower the terrain.
+                // 中文: 河道只会降低地形。
+                height.min(height + (grade - bed - height) * weight)
+            }
+        }
+    }
+
+    fn decal_weight(&self, decal: &SplineDecal, distance: f64) -> f64 {
+        let half_width = self.half_width();
+        (1.0 - smoothstep(
+            half_width * DECAL_CORE_WIDTH_FACTOR,
+            half_width + decal.edge_meters,
+            distance,
+        )) * decal.strength
+    }
+
+    // EN: Road and water cores follow sampleWorldSemantics, so cleared ground matches what the ecology scatter avoids.
+    // 中文: 道路与水体核心区沿用 sampleWorldSemantics，使清除范围与生态散布所避开的区域一致。
+    fn vegetation_exclusion(&self, distance: f64) -> f64 {
+        let width = self.width_meters;
+        match self.layer.as_str() {
+            "road" => 1.0 - smoothstep(width * 0.55, width * 1.35 + 4.0, distance),
+            "water" => 1.0 - smoothstep(width * 0.55, width * 1.25 + 4.0, distance),
+            _ => {
+                if distance <= self.half_width() {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+impl SplineFootprint {
+    /// Samples with a grade line: authored heights when every control point has one, otherwise the terrain.
+    /// Returns `None` when the terrain is needed but no sample lies over stored pages.
+    /// 带坡度线的样本：所有控制点都有高度时使用编辑高度，否则取地形；需要地形但没有样本位于已存储页面上时返回 `None`
+    fn new(
+        curve: &SplineCurve,
+        samples: Vec<SplineSample>,
+        profile: &SplineProfile,
+        heights: &HeightPackSession,
+        baseline: &SplineTerrainBaseline,
+    ) -> Option<Self> {
+        let raw: Vec<f64> = if curve.has_heights() {
+            samples.iter().map(|sample| sample.position[1]).collect()
+        } else {
+            let measured: Vec<Option<f64>> = samples
+                .iter()
+                .map(|sample| {
+                    baseline
+                        .height_at(heights, sample.position[0], sample.position[2])
+                        .map(f64::from)
+                })
+                .collect();
+            fill_missing(&measured)?
+        };
+
+        let grade = match profile.terrain {
+            Some(TerrainCut::Grade {
+                blend_meters,
+                max_slope_degrees,
+                ..
+            }) => {
+                let smoothed = moving_average(&raw, blend_meters / SPLINE_SAMPLE_SPACING_METERS);
+                let step = samples
+                    .get(1)
+                    .map_or(SPLINE_SAMPLE_SPACING_METERS, |sample| sample.distance);
+                limit_slope(&smoothed, max_slope_degrees.to_radians().tan() * step)
+            }
+            Some(TerrainCut::Channel {
+                bank_blend_meters, ..
+            }) => moving_average(&raw, bank_blend_meters / SPLINE_SAMPLE_SPACING_METERS)
+                .iter()
+                .zip(&raw)
+                .map(|(smoothed, raw)| smoothed.min(*raw))
+                .collect(),
+            None => raw,
+        };
+
+        Some(Self { samples, grade })
+    }
+
+    /// Footprint without a grade line, for effects that only need distances.
+    /// 不带坡度线的覆盖范围，用于只需要距离的影响
+    fn flat(curve: &SplineCurve) -> Self {
+        let samples = curve.sample_by_arc_length(SPLINE_SAMPLE_SPACING_METERS);
+        let grade = vec![0.0; samples.len()];
+        Self { samples, grade }
+    }
+
+    /// Indices of centerline segments whose bounds, grown by `reach`, touch `rect`.
+    /// 按 `reach` 扩展后的包围盒与 `rect` 相交的中心线段索引
+    fn segments_near(&self, rect: [f64; 4], reach: f64) -> Vec<usize> {
+        (0..self.samples.len().saturating_sub(1))
+            .filter(|&index| {
+                let [ax, _, az] = self.samples[index].position;
+                let [bx, _, bz] = self.samples[index + 1].position;
+                ax.min(bx) - reach <= rect[2]
+                    && ax.max(bx) + reach >= rect[0]
+                    && az.min(bz) - reach <= rect[3]
+                    && az.max(bz) + reach >= rect[1]
+            })
+            .collect()
+    }
+
+    /// Distance to the nearest candidate segment and the grade height at its closest point.
+    /// 到最近候选线段的距离，以及其最近点处的坡度线高度
+    fn nearest(&self, segments: &[usize], x: f64, z: f64) -> Option<(f64, f64)> {
+        let mut best: Option<(f64, f64)> = None;
+        for &index in segments {
+            let [ax, _, az] = self.samples[index].position;
+            let [bx, _, bz] = self.samples[index + 1].position;
+            let (distance, t) = segment_projection([ax, az], [bx, bz], x, z);
+            if best.is_none_or(|(best_distance, _)| distance < best_distance) {
+                let grade = self.grade[index] + (self.grade[index + 1] - self.grade[index]) * t;
+                best = Some((distance, grade));
+            }
+        }
+        best
+    }
+}
+
+impl SplineTerrainBaseline {
+    fn open(
+        project_root: &Path,
+        map_id: &str,
+        heights: &HeightPackSession,
+    ) -> Result<Self, String> {
+        let directory = project_root
+            .join(MAPS_DIR)
+            .join(map_id)
+            .join(SPLINE_TERRAIN_BASELINE_DIRECTORY);
+        let page_resolution = heights.layout.page_resolution;
+        let mut baseline = Self {
+            directory,
+            page_resolution,
+            stored_pages: BTreeMap::new(),
+            pages: BTreeMap::new(),
+            captured_pages: BTreeSet::new(),
+            dropped_pages: BTreeSet::new(),
+            splines: BTreeSet::new(),
+        };
+        let manifest_path = baseline.directory.join(SPLINE_BASELINE_MANIFEST_FILE);
+        recover_safe_write(&manifest_path)?;
+        if !manifest_path.exists() {
+            return Ok(baseline);
+        }
+
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read spline terrain baseline: {}", e))?;
+        let manifest: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse spline terrain baseline: {}", e))?;
+        if manifest["pageResolution"].as_u64() != Some(page_resolution as u64) {
+            return Err(
+                "Spline terrain baseline page resolution does not match the terrain".to_string(),
+            );
+        }
+        for (key, output_hash) in manifest["pages"].as_object().into_iter().flatten() {
+            let (Some(page), Some(output_hash)) = (parse_grid_key(key), output_hash.as_str())
+            else {
+                return Err(format!("Invalid spline terrain baseline page '{}'", key));
+            };
+            baseline.stored_pages.insert(page, output_hash.to_string());
+        }
+        baseline.splines = manifest["splines"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| id.as_str().map(str::to_string))
+            .collect();
+
+        Ok(baseline)
+    }
+
+    /// Load terrain regions and stored baseline pages overlapping `rect`.
+    /// Stored pages whose live heights differ from the last spline edit's output are dropped, so the live terrain becomes their baseline.
+    /// 加载与 `rect` 相交的地形区域与已存储的基线页面；当前高度与上次样条编辑输出不同的已存储页面会被丢弃，由当前地形作为其基线
+    fn load_rect(
+        &mut self,
+        heights: &mut HeightPackSession,
+        rect: &WorldRect,
+    ) -> Result<(), String> {
+        heights.load_regions_in_rect(rect.min_x, rect.min_z, rect.max_x, rect.max_z)?;
+        let page_size = heights.layout.page_size_meters;
+        let sample_count = heights.layout.page_sample_count();
+        for pz in (rect.min_z / page_size).floor() as i32..=(rect.max_z / page_size).floor() as i32
+        {
+            for px in
+                (rect.min_x / page_size).floor() as i32..=(rect.max_x / page_size).floor() as i32
+            {
+                let Some(output_hash) = self.stored_pages.get(&(px, pz)) else {
+                    continue;
+                };
+                if self.pages.contains_key(&(px, pz)) {
+                    continue;
+                }
+                let live_matches = heights
+                    .page_heights(px, pz)?
+                    .is_some_and(|live| page_output_hash(live) == *output_hash);
+                if !live_matches {
+                    self.stored_pages.remove(&(px, pz));
+                    self.dropped_pages.insert((px, pz));
+                    continue;
+                }
+                let key = format_grid_key(px, pz);
+                let path = self.page_path(px, pz);
+                recover_safe_write(&path)?;
+                let bytes = fs::read(&path).map_err(|e| {
+                    format!(
+                        "Failed to read spline terrain baseline page '{}': {}",
+                        key, e
+                    )
+                })?;
+                if bytes.len() != sample_count * 4 {
+                    return Err(format!(
+                        "Spline terrain baseline page '{}' has {} bytes; expected {}",
+                        key,
+                        bytes.len(),
+                        sample_count * 4
+                    ));
+                }
+                let page = bytes
+                    .chunks_exact(4)
+                    .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                    .collect();
+                self.pages.insert((px, pz), page);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Baseline height at a world position; pages never changed by a spline edit read the live terrain.
+    /// 世界坐标处的基线高度；从未被样条编辑修改的页面读取当前地形
+    fn height_at(&self, heights: &HeightPackSession, x: f64, z: f64) -> Option<f32> {
+        let page_size = heights.layout.page_size_meters;
+        let px = (x / page_size).floor() as i32;
+        let pz = (z / page_size).floor() as i32;
+        match self.pages.get(&(px, pz)) {
+            Some(page) => Some(heights.layout.sample_page(page, px, pz, x, z)),
+            None => heights.loaded_height_at(x, z),
+        }
+    }
+
+    /// Record a page's heights before its first spline edit, and the hash of the heights the edit writes.
+    /// 在页面首次被样条编辑前记录其高度，并记录本次编辑写入高度的哈希
+    fn capture(&mut self, px: i32, pz: i32, before: Vec<f32>, after: &[f32]) {
+        if let Entry::Vacant(entry) = self.pages.entry((px, pz)) {
+            entry.insert(before);
+            self.captured_pages.insert((px, pz));
+        }
+        self.stored_pages.insert((px, pz), page_output_hash(after));
+    }
+
+    fn save(&self) -> Result<(), String> {
+        for &(px, pz) in &self.dropped_pages {
+            if self.stored_pages.contains_key(&(px, pz)) {
+                continue;
+            }
+            match fs::remove_file(self.page_path(px, pz)) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(format!(
+                        "Failed to remove stale spline terrain baseline page '{}': {}",
+                        format_grid_key(px, pz),
+                        e
+                    ));
+                }
+                _ => {}
+            }
+        }
+        for &(px, pz) in &self.captured_pages {
+            let bytes: Vec<u8> = self.pages[&(px, pz)]
+                .iter()
+                .flat_map(|height| height.to_le_bytes())
+                .collect();
+            safe_write(&self.page_path(px, pz), &bytes).map_err(|e| {
+                format!(
+                    "Failed to save spline terrain baseline page '{}': {}",
+                    format_grid_key(px, pz),
+                    e
+                )
+            })?;
+        }
+
+        let mut keys: Vec<(String, &String)> = self
+            .stored_pages
+            .iter()
+            .map(|((px, pz), output_hash)| (format_grid_key(*px, *pz), output_hash))
+            .collect();
+        keys.sort_by(|left, right| compare_grid_keys(&left.0, &right.0));
+        let pages: Map<String, Value> = keys
+            .into_iter()
+            .map(|(key, output_hash)| (key, json!(output_hash)))
+            .collect();
+        let manifest = json!({
+            "version": 1,
+            "sampleFormat": HEIGHT_SAMPLE_FORMAT,
+            "pageResolution": self.page_resolution,
+            "pages": pages,
+            "splines": self.splines,
+        });
+        let content = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize spline terrain baseline: {}", e))?;
+        safe_write(
+            &self.directory.join(SPLINE_BASELINE_MANIFEST_FILE),
+            format!("{}\n", content).as_bytes(),
+        )
+        .map_err(|e| format!("Failed to save spline terrain baseline: {}", e))
+    }
+
+    fn page_path(&self, px: i32, pz: i32) -> PathBuf {
+        self.directory.join(format!(
+            "p_{}_{}.{}",
+            format_grid_coordinate(px),
+            format_grid_coordinate(pz),
+            SPLINE_BASELINE_PAGE_EXTENSION
+        ))
+    }
+}
+
+/// Replace missing values with the nearest measured one; `None` when nothing was measured.
+/// 用最近的已测值替换缺失值；没有任何已测值时返回 `None`
+fn fill_missing(values: &[Option<f64>]) -> Option<Vec<f64>> {
+    let measured: Vec<(usize, f64)> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(index, value)| value.map(|value| (index, value)))
+        .collect();
+    if measured.is_empty() {
+        return None;
+    }
+
+    Some(
+        (0..values.len())
+            .map(|index| {
+                measured
+                    .iter()
+                    .min_by_key(|(measured_index, _)| measured_index.abs_diff(index))
+                    .map_or(0.0, |(_, value)| *value)
+            })
+            .collect(),
+    )
+}
+
+fn moving_average(values: &[f64], radius_samples: f64) -> Vec<f64> {
+    let radius = radius_samples.round().max(0.0) as usize;
+    let mut prefix = Vec::with_capacity(values.len() + 1);
+    prefix.push(0.0);
+    for value in values {
+        prefix.push(prefix[prefix.len() - 1] + value);
+    }
+    (0..values.len())
+        .map(|index| {
+            let start = index.saturating_sub(radius);
+            let end = (index + radius + 1).min(values.len());
+            (prefix[end] - prefix[start]) / (end - start) as f64
+        })
+        .collect()
+}
+
+// EN: Lower envelope min_j(grade[j] + rise * |i - j|): the grade is only ever cut, and neighbouring samples never differ by more than `rise`.
+// 中文: 下包络 min_j(grade[j] + rise * |i - j|)：坡度线只会被削低，相邻样本的高差不超过 `rise`。
+fn limit_slope(values: &[f64], rise: f64) -> Vec<f64> {
+    let mut limited = values.to_vec();
+    for index in 1..limited.len() {
+        limited[index] = limited[index].min(limited[index - 1] + rise);
+    }
+    for index in (0..limited.len().saturating_sub(1)).rev() {
+        limited[index] = limited[index].min(limited[index + 1] + rise);
+    }
+    limited
+}
+
+/// Hash of a page's little-endian height samples, as written to the height pack.
+/// 页面小端高度样本的哈希，与写入高度包的数据一致
+fn page_output_hash(heights: &[f32]) -> String {
+    let bytes: Vec<u8> = heights
+        .iter()
+        .flat_map(|height| height.to_le_bytes())
+        .collect();
+    sha256_hex(&bytes)
+}
@@ -0,0 +1,92 @@
+// Shared fixtures for unit tests that build synthetic projects on disk.
+// 在磁盘上构建合成项目的单元测试共享夹具
+//
+// EN: Each fixture owns a unique temporary project root and deletes it on drop, so a failing assertion never leaves directories behind.
+// 中文: 每个夹具拥有唯一的临时项目根目录，并在析构时删除，因此断言失败也不会遗留目录。
+
+use crate::commands::MAPS_DIR;
+use crate::map_layout::{
+    COOKED_MAP_FORMAT, COOKED_MAP_MANIFEST_FILE, COOKED_MAPS_DIRECTORY, sha256_hex,
+};
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const FIXTURE_MAP_ID: &str = "synthetic";
+
+/// Temporary project directory that is removed when dropped.
+/// 析构时删除的临时项目目录
+pub(crate) struct FixtureProject {
+    root: PathBuf,
+}
+
+impl FixtureProject {
+    pub(crate) fn new(module: &str, name: &str) -> Self {
+        let root = std::env::temp_dir().join(format!(
+            "open-fps-{}-{}-{}",
+            module,
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        Self { root }
+    }
+
+    pub(crate) fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Source map directory of the fixture map.
+    /// 夹具地图的源地图目录
+    pub(crate) fn map_root(&self) -> PathBuf {
+        self.root.join(MAPS_DIR).join(FIXTURE_MAP_ID)
+    }
+
+    /// Write a project-relative file and return its `{ path, byteLength, sha256 }` manifest entry.
+    /// 写入项目相对文件，并返回其 `{ path, byteLength, sha256 }` 清单条目
+    pub(crate) fn write_artifact(&self, path: &str, bytes: &[u8]) -> Value {
+        let file = self.root.join(path);
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, bytes).unwrap();
+        json!({ "path": path, "byteLength": bytes.len(), "sha256": sha256_hex(bytes) })
+    }
+
+    /// Project-relative path inside the cooked fixture map.
+    /// 夹具 cooked 地图内的项目相对路径
+    pub(crate) fn cooked_path(&self, relative_path: &str) -> String {
+        format!(
+            "{}/{}/{}",
+            COOKED_MAPS_DIRECTORY, FIXTURE_MAP_ID, relative_path
+        )
+    }
+
+    /// Write the cooked map manifest with the given asset sections.
+    /// 使用给定的资源段写入 cooked 地图清单
+    pub(crate) fn write_cooked_manifest(&self, assets: Value) {
+        let manifest = json!({
+            "format": COOKED_MAP_FORMAT,
+            "mapId": FIXTURE_MAP_ID,
+            "assets": assets,
+        });
+        self.write_artifact(
+            &self.cooked_path(COOKED_MAP_MANIFEST_FILE),
+            manifest.to_string().as_bytes(),
+        );
+    }
+}
+
+impl Drop for FixtureProject {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+pub(crate) fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-4,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
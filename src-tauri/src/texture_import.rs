@@ -0,0 +1,536 @@
+// Texture import into GPU-ready KTX2 artifacts with full mip chains.
+// 将纹理导入为带完整 mip 链的 GPU 就绪 KTX2 产物
+//
+// EN: Levels are stored as uncompressed RGBA8 KTX2 so a later BasisU pass can supercompress them without re-filtering.
+// 中文: 各级以未压缩 RGBA8 KTX2 存储，之后的 BasisU 处理可直接超压缩，无需重新滤波。
+
+use crate::commands::{
+    decode_image_rgba, recover_safe_write, safe_write, validate_cook_project_path,
+    validate_relative_file_path, validate_single_path_segment,
+};
+use crate::map_layout::{
+    COOKED_BLOB_DIRECTORY, COOKED_TEXTURE_MANIFEST_FORMAT, COOKED_TEXTURE_MANIFEST_PATH, sha256_hex,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+const COOKED_TEXTURE_MANIFEST_VERSION: u32 = 1;
+const TEXTURE_MIN_SIZE: u32 = 4;
+const TEXTURE_MAX_SIZE: u32 = 16384;
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xab, 0x4b, 0x54, 0x58, 0x20, 0x32, 0x30, 0xbb, 0x0d, 0x0a, 0x1a, 0x0a,
+];
+const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+const VK_FORMAT_R8G8B8A8_SRGB: u32 = 43;
+const KTX2_WRITER: &str = "open-fps texture import";
+
+/// How texels are filtered while building mips and which color space the KTX2 declares.
+/// 构建 mip 时的纹素滤波方式，以及 KTX2 声明的颜色空间
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TextureUsage {
+    Color,
+    Normal,
+    Data,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportTextureRequest {
+    project_path: String,
+    texture_id: String,
+    usage: TextureUsage,
+    #[serde(default)]
+    source_path: Option<String>,
+    #[serde(default)]
+    arm_sources: Option<ArmTextureSources>,
+    #[serde(default)]
+    max_size: Option<u32>,
+}
+
+/// Separate grayscale maps packed into R = ambient occlusion, G = roughness, B = metallic.
+/// 打包为 R = 环境光遮蔽、G = 粗糙度、B = 金属度的独立灰度贴图
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArmTextureSources {
+    #[serde(default)]
+    ambient_occlusion: Option<String>,
+    #[serde(default)]
+    roughness: Option<String>,
+    #[serde(default)]
+    metallic: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookedTextureSource {
+    role: String,
+    path: String,
+    sha256: String,
+}
+
+/// One cooked texture in `cooked/textures/manifest.json`.
+/// `cooked/textures/manifest.json` 中的一个 cooked 纹理
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CookedTextureEntry {
+    usage: TextureUsage,
+    container: String,
+    vk_format: u32,
+    width: u32,
+    height: u32,
+    level_count: u32,
+    blob_path: String,
+    byte_length: u64,
+    sha256: String,
+    sources: Vec<CookedTextureSource>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CookedTextureManifest {
+    version: u32,
+    format: String,
+    blob_root: String,
+    #[serde(default)]
+    textures: BTreeMap<String, CookedTextureEntry>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextureImportResult {
+    texture_id: String,
+    texture: CookedTextureEntry,
+    blob_written: bool,
+}
+
+/// One mip level as stored RGBA8; filtering converts texels on the fly so no float copy of a level is kept.
+/// 以存储格式 RGBA8 保存的单个 mip 级；滤波时逐纹素转换，不保留整级的浮点副本
+struct TextureLevel {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+// --- Texture import commands / 纹理导入命令 ---
+
+/// Import a PNG or JPEG texture (or pack separate AO/roughness/metallic maps) into a mipmapped KTX2 blob.
+/// 将 PNG 或 JPEG 纹理（或打包独立的 AO/粗糙度/金属度贴图）导入为带 mip 的 KTX2 blob
+#[tauri::command]
+pub async fn import_texture(request: ImportTextureRequest) -> Result<TextureImportResult, String> {
+    tauri::async_runtime::spawn_blocking(move || import_texture_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join texture import task: {}", e))?
+}
+
+fn import_texture_blocking(request: ImportTextureRequest) -> Result<TextureImportResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    validate_single_path_segment(&request.texture_id, "texture_id")?;
+    let max_size = request.max_size.unwrap_or(TEXTURE_MAX_SIZE);
+    if !max_size.is_power_of_two() || !(TEXTURE_MIN_SIZE..=TEXTURE_MAX_SIZE).contains(&max_size) {
+        return Err(format!(
+            "Texture max size must be a power of two between {} and {}",
+            TEXTURE_MIN_SIZE, TEXTURE_MAX_SIZE
+        ));
+    }
+
+    let mut sources = Vec::new();
+    let (usage, mut level) = match (&request.source_path, &request.arm_sources) {
+        (Some(source_path), None) => {
+            let (pixels, width, height) = read_texture_source(&project_root, source_path)?;
+            sources.push(source_record("source", source_path, &project_root)?);
+            let level = TextureLevel {
+                width,
+                height,
+                pixels,
+            };
+            (request.usage, level)
+        }
+        (None, Some(arm_sources)) => {
+            if request.usage != TextureUsage::Data {
+                return Err("Packed ARM textures must use the data usage".to_string());
+            }
+            let level = pack_arm_channels(&project_root, arm_sources, &mut sources)?;
+            (TextureUsage::Data, level)
+        }
+        _ => {
+            return Err("Texture import needs exactly one of sourcePath or armSources".to_string());
+        }
+    };
+
+    // EN: Oversized sources are reduced with the mip filter so the top level matches what mips 1+ would have been.
+    // 中文: 过大的源纹理用 mip 滤波缩小，使顶层与原本的第 1 级及以后的 mip 一致。
+    while level.width > max_size || level.height > max_size {
+        level = level.downsample(usage);
+    }
+
+    let mut levels = vec![level];
+    while let Some(last) = levels.last()
+        && (last.width > 1 || last.height > 1)
+    {
+        let next = last.downsample(usage);
+        levels.push(next);
+    }
+
+    let vk_format = match usage {
+        TextureUsage::Color => VK_FORMAT_R8G8B8A8_SRGB,
+        TextureUsage::Normal | TextureUsage::Data => VK_FORMAT_R8G8B8A8_UNORM,
+    };
+    let bytes = encode_ktx2_rgba8(&levels, vk_format);
+
+    let sha256 = sha256_hex(&bytes);
+    let blob_path = format!("{}/{}/{}.ktx2", COOKED_BLOB_DIRECTORY, &sha256[..2], sha256);
+    let blob_target = project_root.join(&blob_path);
+    // EN: Blobs are content-addressed, so an existing file with this name already holds these bytes.
+    // 中文: Blob 按内容寻址，同名文件已包含相同字节。
+    let blob_written = !blob_target.exists();
+    if blob_written {
+        safe_write(&blob_target, &bytes)
+            .map_err(|e| format!("Failed to write texture blob: {}", e))?;
+    }
+
+    let texture = CookedTextureEntry {
+        usage,
+        container: "ktx2".to_string(),
+        vk_format,
+        width: levels[0].width,
+        height: levels[0].height,
+        level_count: levels.len() as u32,
+        blob_path,
+        byte_length: bytes.len() as u64,
+        sha256,
+        sources,
+    };
+    let mut manifest = read_cooked_texture_manifest(&project_root)?;
+    manifest
+        .textures
+        .insert(request.texture_id.clone(), texture.clone());
+    write_cooked_texture_manifest(&project_root, &manifest)?;
+
+    Ok(TextureImportResult {
+        texture_id: request.texture_id,
+        texture,
+        blob_written,
+    })
+}
+
+/// Read the cooked texture manifest, or an empty one when none exists yet.
+/// 读取 cooked 纹理清单；尚不存在时返回空清单
+fn read_cooked_texture_manifest(project_root: &Path) -> Result<CookedTextureManifest, String> {
+    let path = project_root.join(COOKED_TEXTURE_MANIFEST_PATH);
+    recover_safe_write(&path)?;
+    if !path.exists() {
+        return Ok(CookedTextureManifest {
+            version: COOKED_TEXTURE_MANIFEST_VERSION,
+            format: COOKED_TEXTURE_MANIFEST_FORMAT.to_string(),
+            blob_root: COOKED_BLOB_DIRECTORY.to_string(),
+            textures: BTreeMap::new(),
+        });
+    }
+
+    let text = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read cooked texture manifest: {}", e))?;
+    let manifest: CookedTextureManifest = serde_json::from_str(&text)
+        .map_err(|e| format!("Failed to parse cooked texture manifest: {}", e))?;
+    if manifest.format != COOKED_TEXTURE_MANIFEST_FORMAT {
+        return Err(format!(
+            "Unsupported cooked texture manifest format: {}",
+            manifest.format
+        ));
+    }
+    Ok(manifest)
+}
+
+fn write_cooked_texture_manifest(
+    project_root: &Path,
+    manifest: &CookedTextureManifest,
+) -> Result<(), String> {
+    let text = serde_json::to_string_pretty(manifest)
+        .map_err(|e| format!("Failed to serialize cooked texture manifest: {}", e))?;
+    safe_write(
+        &project_root.join(COOKED_TEXTURE_MANIFEST_PATH),
+        format!("{}\n", text).as_bytes(),
+    )
+    .map_err(|e| format!("Failed to write cooked texture manifest: {}", e))
+}
+
+/// Decode a project-relative PNG or JPEG source and check that its size can carry a full mip chain.
+/// 解码项目相对路径的 PNG 或 JPEG 源，并检查其尺寸能否构建完整 mip 链
+fn read_texture_source(
+    project_root: &Path,
+    source_path: &str,
+) -> Result<(Vec<u8>, u32, u32), String> {
+    validate_relative_file_path(source_path, "source_path")?;
+    let (pixels, width, height) = decode_image_rgba(&project_root.join(source_path))
+        .map_err(|e| format!("Texture source '{}': {}", source_path, e))?;
+    if !width.is_power_of_two() || !height.is_power_of_two() {
+        return Err(format!(
+            "Texture source '{}' is {}x{}; both sides must be powers of two",
+            source_path, width, height
+        ));
+    }
+    if width.min(height) < TEXTURE_MIN_SIZE || width.max(height) > TEXTURE_MAX_SIZE {
+        return Err(format!(
+            "Texture source '{}' is {}x{}; sides must be between {} and {}",
+            source_path, width, height, TEXTURE_MIN_SIZE, TEXTURE_MAX_SIZE
+        ));
+    }
+    Ok((pixels, width, height))
+}
+
+fn source_record(
+    role: &str,
+    source_path: &str,
+    project_root: &Path,
+) -> Result<CookedTextureSource, String> {
+    let bytes = fs::read(project_root.join(source_path))
+        .map_err(|e| format!("Failed to read texture source: {}", e))?;
+    Ok(CookedTextureSource {
+        role: role.to_string(),
+        path: source_path.to_string(),
+        sha256: sha256_hex(&bytes),
+    })
+}
+
+/// Pack the red channel of each ARM source; missing maps default to AO 1, roughness 1, metallic 0.
+/// 打包每个 ARM 源的红色通道；缺失的贴图默认 AO 为 1、粗糙度为 1、金属度为 0
+fn pack_arm_channels(
+    project_root: &Path,
+    arm_sources: &ArmTextureSources,
+    sources: &mut Vec<CookedTextureSource>,
+) -> Result<TextureLevel, String> {
+    let channels = [
+        ("ambientOcclusion", &arm_sources.ambient_occlusion, 255),
+        ("roughness", &arm_sources.roughness, 255),
+        ("metallic", &arm_sources.metallic, 0),
+    ];
+    if channels.iter().all(|(_, path, _)| path.is_none()) {
+        return Err("ARM packing needs at least one source map".to_string());
+    }
+
+    let mut size = None;
+    let mut channel_values: Vec<Option<Vec<u8>>> = Vec::new();
+    for (role, path, _) in &channels {
+        let Some(path) = path else {
+            channel_values.push(None);
+            continue;
+        };
+        let (pixels, width, height) = read_texture_source(project_root, path)?;
+        if size.is_some_and(|size| size != (width, height)) {
+            return Err(format!(
+                "ARM source '{}' is {}x{}, which does not match the other sources",
+                path, width, height
+            ));
+        }
+        size = Some((width, height));
+        sources.push(source_record(role, path, project_root)?);
+        channel_values.push(Some(pixels.chunks_exact(4).map(|pixel| pixel[0]).collect()));
+    }
+
+    let (width, height) = size.unwrap_or((TEXTURE_MIN_SIZE, TEXTURE_MIN_SIZE));
+    let pixels = (0..(width * height) as usize)
+        .flat_map(|index| {
+            let channel = |slot: usize| match &channel_values[slot] {
+                Some(values) => values[index],
+                None => channels[slot].2,
+            };
+            [channel(0), channel(1), channel(2), 255]
+        })
+        .collect();
+    Ok(TextureLevel {
+        width,
+        height,
+        pixels,
+    })
+}
+
+impl TextureLevel {
+    /// Halve each side with a 2x2 box filter in filtering space: linear RGB for color, unit vectors for normals.
+    /// 在滤波空间中用 2x2 盒式滤波将每边减半：颜色为线性 RGB，法线为单位向量
+    fn downsample(&self, usage: TextureUsage) -> Self {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let srgb_lut: [f32; 256] =
+            std::array::from_fn(|value| srgb_to_linear(value as f32 / 255.0));
+        let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+        for row in 0..height {
+            for column in 0..width {
+                let mut sum = [0.0f32; 4];
+                for (dx, dz) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let x = (column * 2 + dx).min(self.width - 1);
+                    let z = (row * 2 + dz).min(self.height - 1);
+                    let offset = ((z * self.width + x) * 4) as usize;
+                    let texel = decode_texel(&self.pixels[offset..offset + 4], usage, &srgb_lut);
+                    for (total, value) in sum.iter_mut().zip(texel) {
+                        *total += value / 4.0;
+                    }
+                }
+                if usage == TextureUsage::Normal {
+                    let length = (sum[0] * sum[0] + sum[1] * sum[1] + sum[2] * sum[2]).sqrt();
+                    if length > f32::EPSILON {
+                        for value in &mut sum[..3] {
+                            *value /= length;
+                        }
+                    } else {
+                        sum[..3].copy_from_slice(&[0.0, 0.0, 1.0]);
+                    }
+                }
+                pixels.extend_from_slice(&encode_texel(sum, usage));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+fn decode_texel(pixel: &[u8], usage: TextureUsage, srgb_lut: &[f32; 256]) -> [f32; 4] {
+    let unorm = |value: u8| value as f32 / 255.0;
+    match usage {
+        TextureUsage::Color => [
+            srgb_lut[pixel[0] as usize],
+            srgb_lut[pixel[1] as usize],
+            srgb_lut[pixel[2] as usize],
+            unorm(pixel[3]),
+        ],
+        TextureUsage::Normal => [
+            unorm(pixel[0]) * 2.0 - 1.0,
+            unorm(pixel[1]) * 2.0 - 1.0,
+            unorm(pixel[2]) * 2.0 - 1.0,
+            unorm(pixel[3]),
+        ],
+        TextureUsage::Data => [
+            unorm(pixel[0]),
+            unorm(pixel[1]),
+            unorm(pixel[2]),
+            unorm(pixel[3]),
+        ],
+    }
+}
+
+fn encode_texel(texel: [f32; 4], usage: TextureUsage) -> [u8; 4] {
+    let unorm = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    match usage {
+        TextureUsage::Color => [
+            unorm(linear_to_srgb(texel[0])),
+            unorm(linear_to_srgb(texel[1])),
+            unorm(linear_to_srgb(texel[2])),
+            unorm(texel[3]),
+        ],
+        TextureUsage::Normal => [
+            unorm(texel[0] * 0.5 + 0.5),
+            unorm(texel[1] * 0.5 + 0.5),
+            unorm(texel[2] * 0.5 + 0.5),
+            unorm(texel[3]),
+        ],
+        TextureUsage::Data => texel.map(unorm),
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+/// Build a KTX2 file with uncompressed RGBA8 levels, largest level first in the index and last in the file.
+/// 构建未压缩 RGBA8 的 KTX2 文件；索引中最大级在前，文件数据中最大级在后
+fn encode_ktx2_rgba8(levels: &[TextureLevel], vk_format: u32) -> Vec<u8> {
+    let push_u32 = |bytes: &mut Vec<u8>, value: u32| bytes.extend_from_slice(&value.to_le_bytes());
+    let push_u64 = |bytes: &mut Vec<u8>, value: u64| bytes.extend_from_slice(&value.to_le_bytes());
+    let srgb = vk_format == VK_FORMAT_R8G8B8A8_SRGB;
+
+    // EN: Basic data format descriptor for 8-bit RGBA: RGBSDA model, BT.709 primaries, one sample per channel.
+    // 中文: 8 位 RGBA 的基本数据格式描述符：RGBSDA 模型、BT.709 原色、每个通道一个样本。
+    let mut dfd = Vec::new();
+    let descriptor_block_size = 24 + 16 * 4;
+    push_u32(&mut dfd, 4 + descriptor_block_size);
+    push_u32(&mut dfd, 0);
+    push_u32(&mut dfd, 2 | (descriptor_block_size << 16));
+    let transfer_function = if srgb { 2 } else { 1 };
+    push_u32(&mut dfd, 1 | (1 << 8) | (transfer_function << 16));
+    push_u32(&mut dfd, 0);
+    push_u32(&mut dfd, 4);
+    push_u32(&mut dfd, 0);
+    for (index, channel_id) in [0u32, 1, 2, 15].into_iter().enumerate() {
+        // EN: Alpha stays linear even in sRGB textures.
+        // 中文: 即使在 sRGB 纹理中，alpha 也保持线性。
+        let linear_qualifier = if srgb && channel_id == 15 { 0x10 } else { 0 };
+        push_u32(
+            &mut dfd,
+            (index as u32 * 8) | (7 << 16) | ((channel_id | linear_qualifier) << 24),
+        );
+        push_u32(&mut dfd, 0);
+        push_u32(&mut dfd, 0);
+        push_u32(&mut dfd, 255);
+    }
+
+    let mut kvd = Vec::new();
+    let key_value = format!("KTXwriter\0{}\0", KTX2_WRITER);
+    push_u32(&mut kvd, key_value.len() as u32);
+    kvd.extend_from_slice(key_value.as_bytes());
+    while kvd.len() % 4 != 0 {
+        kvd.push(0);
+    }
+
+    let header_size = 12 + 9 * 4 + 4 * 4 + 2 * 8;
+    let level_index_size = levels.len() * 3 * 8;
+    let dfd_offset = header_size + level_index_size;
+    let kvd_offset = dfd_offset + dfd.len();
+    let mut data_offset = kvd_offset + kvd.len();
+
+    // EN: Mip data runs from the smallest level to the largest, each aligned to 4 bytes.
+    // 中文: Mip 数据从最小级排到最大级，每级按 4 字节对齐。
+    let mut level_offsets = vec![0usize; levels.len()];
+    for level in (0..levels.len()).rev() {
+        data_offset = data_offset.next_multiple_of(4);
+        level_offsets[level] = data_offset;
+        data_offset += levels[level].pixels.len();
+    }
+
+    let mut bytes = Vec::with_capacity(data_offset);
+    bytes.extend_from_slice(&KTX2_IDENTIFIER);
+    push_u32(&mut bytes, vk_format);
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, levels[0].width);
+    push_u32(&mut bytes, levels[0].height);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, 1);
+    push_u32(&mut bytes, levels.len() as u32);
+    push_u32(&mut bytes, 0);
+    push_u32(&mut bytes, dfd_offset as u32);
+    push_u32(&mut bytes, dfd.len() as u32);
+    push_u32(&mut bytes, kvd_offset as u32);
+    push_u32(&mut bytes, kvd.len() as u32);
+    push_u64(&mut bytes, 0);
+    push_u64(&mut bytes, 0);
+    for (offset, level) in level_offsets.iter().zip(levels) {
+        push_u64(&mut bytes, *offset as u64);
+        push_u64(&mut bytes, level.pixels.len() as u64);
+        push_u64(&mut bytes, level.pixels.len() as u64);
+    }
+    bytes.extend_from_slice(&dfd);
+    bytes.extend_from_slice(&kvd);
+    for level in (0..levels.len()).rev() {
+        bytes.resize(level_offsets[level], 0);
+        bytes.extend_from_slice(&levels[level].pixels);
+    }
+
+    bytes
+}
@@ -0,0 +1,306 @@
+// Tool runner configuration for the Node-based world scripts.
+// 基于 Node 的世界脚本的工具运行器配置
+//
+// EN: Resolution order is environment variable, then app settings, then the scripts bundled as Tauri resources.
+// 中文: 解析顺序为环境变量、应用设置，最后是作为 Tauri 资源打包的脚本。
+
+use crate::commands::{recover_safe_write, safe_write};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tauri::Manager;
+
+const TOOL_RUNNER_SETTINGS_FILE: &str = "tool_runner.json";
+const NODE_EXECUTABLE_ENV: &str = "OPEN_FPS_NODE";
+const PNPM_EXECUTABLE_ENV: &str = "OPEN_FPS_PNPM";
+const SCRIPTS_DIRECTORY_ENV: &str = "OPEN_FPS_SCRIPTS_DIR";
+const BUNDLED_SCRIPTS_DIRECTORY: &str = "scripts";
+pub(crate) const COOK_MAP_SCRIPT: &str = "cook-map-assets.mjs";
+pub(crate) const WORLD_GENERATION_GRAPH_SCRIPT: &str = "execute-world-generation-graph.mjs";
+const REQUIRED_SCRIPTS: &[&str] = &[COOK_MAP_SCRIPT, WORLD_GENERATION_GRAPH_SCRIPT];
+
+/// User-editable tool runner overrides, stored in the app config directory.
+/// 用户可编辑的工具运行器覆盖项，保存在应用配置目录
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ToolRunnerSettings {
+    node_executable: Option<String>,
+    pnpm_executable: Option<String>,
+    scripts_directory: Option<String>,
+}
+
+/// Where a resolved tool setting came from.
+/// 已解析工具设置的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ToolSource {
+    Environment,
+    Settings,
+    Resource,
+    Default,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolProbe {
+    executable: String,
+    source: ToolSource,
+    found: bool,
+    version: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptsProbe {
+    directory: Option<String>,
+    source: Option<ToolSource>,
+    found: bool,
+    missing_scripts: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolchainReport {
+    ready: bool,
+    node: ToolProbe,
+    pnpm: ToolProbe,
+    scripts: ScriptsProbe,
+    settings_path: String,
+}
+
+/// Resolved executable and script locations for running a world script.
+/// 运行世界脚本所需的已解析可执行文件和脚本位置
+pub(crate) struct ToolRunner {
+    pub(crate) node_executable: String,
+    pub(crate) scripts_directory: PathBuf,
+}
+
+impl ToolRunner {
+    pub(crate) fn script_path(&self, script_name: &str) -> Result<PathBuf, String> {
+        let path = self.scripts_directory.join(script_name);
+        if !path.is_file() {
+            return Err(format!(
+                "Script '{}' is not available in {}",
+                script_name,
+                self.scripts_directory.display()
+            ));
+        }
+
+        Ok(path)
+    }
+}
+
+// --- Tool runner commands / 工具运行器命令 ---
+
+/// Read the tool runner overrides saved for this machine.
+/// 读取本机保存的工具运行器覆盖项
+#[tauri::command]
+pub async fn read_tool_runner_settings(
+    app: tauri::AppHandle,
+) -> Result<ToolRunnerSettings, String> {
+    load_tool_runner_settings(&tool_runner_settings_file(&app)?)
+}
+
+/// Save tool runner overrides; empty values fall back to defaults.
+/// 保存工具运行器覆盖项；空值回退为默认值
+#[tauri::command]
+pub async fn save_tool_runner_settings(
+    app: tauri::AppHandle,
+    settings: ToolRunnerSettings,
+) -> Result<(), String> {
+    let settings = ToolRunnerSettings {
+        node_executable: non_empty(settings.node_executable),
+        pnpm_executable: non_empty(settings.pnpm_executable),
+        scripts_directory: non_empty(settings.scripts_directory),
+    };
+    let mut content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize tool runner settings: {}", e))?;
+    content.push('\n');
+    safe_write(&tool_runner_settings_file(&app)?, content.as_bytes())
+        .map_err(|e| format!("Failed to save tool runner settings: {}", e))
+}
+
+/// Report which Node, pnpm and script locations would be used, with their versions.
+/// 报告将使用的 Node、pnpm 与脚本位置及其版本
+#[tauri::command]
+pub async fn check_toolchain(app: tauri::AppHandle) -> Result<ToolchainReport, String> {
+    let settings_path = tool_runner_settings_file(&app)?;
+    let settings = load_tool_runner_settings(&settings_path)?;
+    let resource_dir = app.path().resource_dir().ok();
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let (node_executable, node_source) = resolve_node_executable(&settings);
+        let (pnpm_executable, pnpm_source) = resolve_pnpm_executable(&settings);
+        let node = probe_executable(node_executable, node_source);
+        let pnpm = probe_executable(pnpm_executable, pnpm_source);
+        let scripts = probe_scripts_directory(&settings, resource_dir.as_deref());
+
+        Ok(ToolchainReport {
+            ready: node.found && scripts.found,
+            node,
+            pnpm,
+            scripts,
+            settings_path: settings_path.to_string_lossy().to_string(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Failed to join toolchain check task: {}", e))?
+}
+
+/// Resolve the Node executable and scripts directory used by cook and graph runs.
+/// 解析 cook 与图运行使用的 Node 可执行文件和脚本目录
+pub(crate) fn resolve_tool_runner(app: &tauri::AppHandle) -> Result<ToolRunner, String> {
+    let settings = load_tool_runner_settings(&tool_runner_settings_file(app)?)?;
+    let resource_dir = app.path().resource_dir().ok();
+    let (node_executable, _) = resolve_node_executable(&settings);
+    let (scripts_directory, _) = resolve_scripts_directory(&settings, resource_dir.as_deref())
+        .ok_or_else(|| {
+            format!(
+                "World scripts are not available in this build. Set {} or configure a scripts directory.",
+                SCRIPTS_DIRECTORY_ENV
+            )
+        })?;
+
+    Ok(ToolRunner {
+        node_executable,
+        scripts_directory,
+    })
+}
+
+// --- Resolution / 解析 ---
+
+fn tool_runner_settings_file(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let app_config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config dir: {}", e))?;
+
+    Ok(app_config_dir.join(TOOL_RUNNER_SETTINGS_FILE))
+}
+
+fn load_tool_runner_settings(path: &Path) -> Result<ToolRunnerSettings, String> {
+    recover_safe_write(path)?;
+    if !path.exists() {
+        return Ok(ToolRunnerSettings::default());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read tool runner settings: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse tool runner settings: {}", e))
+}
+
+fn resolve_node_executable(settings: &ToolRunnerSettings) -> (String, ToolSource) {
+    resolve_configured_value(NODE_EXECUTABLE_ENV, &settings.node_executable).unwrap_or_else(|| {
+        let executable = if cfg!(windows) { "node.exe" } else { "node" };
+        (executable.to_string(), ToolSource::Default)
+    })
+}
+
+fn resolve_pnpm_executable(settings: &ToolRunnerSettings) -> (String, ToolSource) {
+    resolve_configured_value(PNPM_EXECUTABLE_ENV, &settings.pnpm_executable).unwrap_or_else(|| {
+        let executable = if cfg!(windows) { "pnpm.cmd" } else { "pnpm" };
+        (executable.to_string(), ToolSource::Default)
+    })
+}
+
+fn resolve_scripts_directory(
+    settings: &ToolRunnerSettings,
+    resource_dir: Option<&Path>,
+) -> Option<(PathBuf, ToolSource)> {
+    // EN: An explicit override wins even when it is broken, so misconfiguration is reported instead of hidden.
+    // 中文: 显式覆盖即使无效也优先，以便报告错误配置而不是将其掩盖。
+    if let Some((directory, source)) =
+        resolve_configured_value(SCRIPTS_DIRECTORY_ENV, &settings.scripts_directory)
+    {
+        return Some((PathBuf::from(directory), source));
+    }
+
+    resource_dir
+        .map(|resource_dir| resource_dir.join(BUNDLED_SCRIPTS_DIRECTORY))
+        .filter(|directory| directory.is_dir())
+        .map(|directory| (directory, ToolSource::Resource))
+}
+
+fn resolve_configured_value(
+    env_name: &str,
+    setting: &Option<String>,
+) -> Option<(String, ToolSource)> {
+    if let Some(value) = non_empty(std::env::var(env_name).ok()) {
+        return Some((value, ToolSource::Environment));
+    }
+
+    non_empty(setting.clone()).map(|value| (value, ToolSource::Settings))
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+// --- Probing / 探测 ---
+
+fn probe_executable(executable: String, source: ToolSource) -> ToolProbe {
+    let output = Command::new(&executable)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => ToolProbe {
+            executable,
+            source,
+            found: true,
+            version: Some(String::from_utf8_lossy(&output.stdout).trim().to_string()),
+            error: None,
+        },
+        Ok(output) => ToolProbe {
+            executable,
+            source,
+            found: false,
+            version: None,
+            error: Some(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+        },
+        Err(e) => ToolProbe {
+            executable,
+            source,
+            found: false,
+            version: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+fn probe_scripts_directory(
+    settings: &ToolRunnerSettings,
+    resource_dir: Option<&Path>,
+) -> ScriptsProbe {
+    let Some((directory, source)) = resolve_scripts_directory(settings, resource_dir) else {
+        return ScriptsProbe {
+            directory: None,
+            source: None,
+            found: false,
+            missing_scripts: REQUIRED_SCRIPTS
+                .iter()
+                .map(|script| script.to_string())
+                .collect(),
+        };
+    };
+
+    let missing_scripts: Vec<String> = REQUIRED_SCRIPTS
+        .iter()
+        .filter(|script| !directory.join(script).is_file())
+        .map(|script| script.to_string())
+        .collect();
+    ScriptsProbe {
+        directory: Some(directory.to_string_lossy().to_string()),
+        source: Some(source),
+        found: missing_scripts.is_empty(),
+        missing_scripts,
+    }
+}
@@ -0,0 +1,1356 @@
+// Native vegetation-region-pack-v1 codec with instance queries and area edits.
+// 原生 vegetation-region-pack-v1 编解码，支持实例查询与区域编辑
+//
+// EN: Mirrors src/game/world/vegetation/VegetationRegionData.ts: a pack holds a cell index sorted by local cell, then each cell's instanced-f32le-v1 records (u16 model index, u16 reserved, x, y, z, rotationY, scale) in index order.
+// 中文: 与 src/game/world/vegetation/VegetationRegionData.ts 一致：区域包先存按局部 cell 排序的索引，再按索引顺序存放各 cell 的 instanced-f32le-v1 记录（u16 模型索引、u16 保留、x、y、z、rotationY、scale）。
+
+use crate::commands::{
+    MAPS_DIR, read_map_world_size, recover_safe_write, safe_write, validate_cook_project_path,
+    validate_single_path_segment,
+};
+use crate::height_pack::HeightPackSession;
+use crate::map_layout::{
+    VEGETATION_INSTANCE_FORMAT, VEGETATION_MODELS_PATH, VEGETATION_REGION_FORMAT,
+    VEGETATION_REGIONS_DIRECTORY, WorldRect, compare_grid_keys, format_grid_key, parse_grid_key,
+    region_file_name, sha256_hex,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const VEGETATION_REGION_EXTENSION: &str = "vegpack";
+const VEGETATION_REGION_PACK_MAGIC: u32 = 0x3147_5256;
+const VEGETATION_REGION_PACK_VERSION: u16 = 1;
+const VEGETATION_REGION_PACK_HEADER_BYTES: usize = 8;
+const VEGETATION_REGION_PACK_ENTRY_BYTES: usize = 8;
+const VEGETATION_INSTANCE_RECORD_BYTES: usize = 24;
+const MAX_VEGETATION_REGION_SIZE_CELLS: u64 = 8;
+const DEFAULT_VEGETATION_QUERY_LIMIT: usize = 10_000;
+const MAX_VEGETATION_QUERY_LIMIT: usize = 200_000;
+const MAX_VEGETATION_HISTOGRAM_BINS: usize = 65_536;
+const CIRCLE_AREA_INTEGRATION_STEPS: usize = 256;
+const SQUARE_METERS_PER_HECTARE: f64 = 10_000.0;
+
+/// Circle on the XZ plane in meters.
+/// 以米为单位的 XZ 平面圆形
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationCircle {
+    center_x: f64,
+    center_z: f64,
+    radius_meters: f64,
+}
+
+/// Instance filter shared by queries and edits; every field that is set must match.
+/// 查询与编辑共用的实例过滤器；所有已设置的字段都必须匹配
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationInstanceFilter {
+    #[serde(default)]
+    rect: Option<WorldRect>,
+    #[serde(default)]
+    circle: Option<VegetationCircle>,
+    #[serde(default)]
+    model_ids: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryVegetationInstancesRequest {
+    project_path: String,
+    map_id: String,
+    #[serde(default)]
+    filter: VegetationInstanceFilter,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SummarizeVegetationInstancesRequest {
+    project_path: String,
+    map_id: String,
+    #[serde(default)]
+    filter: VegetationInstanceFilter,
+    #[serde(default)]
+    bin_size_meters: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteVegetationInstancesRequest {
+    project_path: String,
+    map_id: String,
+    filter: VegetationInstanceFilter,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveVegetationInstancesRequest {
+    project_path: String,
+    map_id: String,
+    filter: VegetationInstanceFilter,
+    offset_x: f64,
+    offset_z: f64,
+    #[serde(default)]
+    offset_y: f64,
+    #[serde(default = "default_snap_to_terrain")]
+    snap_to_terrain: bool,
+}
+
+/// One stored instance; `index` is its position inside the cell payload.
+/// 一个已存储的实例；`index` 为其在 cell 数据中的位置
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationInstanceHit {
+    model_id: String,
+    cell_key: String,
+    index: usize,
+    x: f32,
+    y: f32,
+    z: f32,
+    rotation_y: f32,
+    scale: f32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationInstanceQueryResult {
+    instances: Vec<VegetationInstanceHit>,
+    matched_count: usize,
+    truncated: bool,
+    scanned_regions: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationSpeciesCount {
+    model_id: String,
+    count: usize,
+    density_per_hectare: Option<f64>,
+}
+
+/// Instance counts binned on a world-aligned grid, row-major from the minimum corner.
+/// 在世界对齐网格上分箱的实例数量，从最小角开始按行优先排列
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationDensityHistogram {
+    bin_size_meters: f64,
+    min_x: f64,
+    min_z: f64,
+    columns: usize,
+    rows: usize,
+    counts: Vec<u32>,
+    max_count: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationInstanceSummary {
+    total_count: usize,
+    area_square_meters: Option<f64>,
+    species: Vec<VegetationSpeciesCount>,
+    histogram: Option<VegetationDensityHistogram>,
+    scanned_regions: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VegetationEditResult {
+    affected_count: usize,
+    updated_regions: Vec<String>,
+    removed_regions: Vec<String>,
+}
+
+/// One decoded instanced-f32le-v1 record.
+/// 一条已解码的 instanced-f32le-v1 记录
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VegetationInstance {
+    pub(crate) model_index: u16,
+    pub(crate) x: f32,
+    pub(crate) y: f32,
+    pub(crate) z: f32,
+    pub(crate) rotation_y: f32,
+    pub(crate) scale: f32,
+}
+
+/// Cell grid shared by every vegetation region pack of one map.
+/// 同一地图所有植被区域包共享的 cell 网格
+pub(crate) struct VegetationPackLayout {
+    pub(crate) cell_size_meters: f64,
+    pub(crate) region_size_cells: i32,
+}
+
+/// One decoded vegetation region pack, keyed by local cell index.
+/// 一个已解码的植被区域包，按局部 cell 索引存储
+struct VegetationRegionPack {
+    cells: BTreeMap<u32, Vec<VegetationInstance>>,
+}
+
+/// Vegetation manifest plus lazily loaded region packs for one map.
+/// 单张地图的植被清单及按需加载的区域包
+pub(crate) struct VegetationPackSession {
+    map_root: PathBuf,
+    document: Value,
+    pub(crate) layout: VegetationPackLayout,
+    pub(crate) model_ids: Vec<String>,
+    region_masks: BTreeMap<String, u64>,
+    packs: BTreeMap<String, VegetationRegionPack>,
+    dirty_regions: BTreeSet<String>,
+    generated_regions: BTreeSet<String>,
+}
+
+// --- Vegetation pack commands / 植被区域包命令 ---
+
+/// List stored vegetation instances matching an area and species filter.
+/// 列出匹配区域与物种过滤条件的已存储植被实例
+#[tauri::command]
+pub async fn query_vegetation_instances(
+    request: QueryVegetationInstancesRequest,
+) -> Result<VegetationInstanceQueryResult, String> {
+    tauri::async_runtime::spawn_blocking(move || query_vegetation_instances_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join vegetation query task: {}", e))?
+}
+
+/// Count matching vegetation instances per species and bin them into a density histogram.
+/// 按物种统计匹配的植被实例，并分箱生成密度直方图
+#[tauri::command]
+pub async fn summarize_vegetation_instances(
+    request: SummarizeVegetationInstancesRequest,
+) -> Result<VegetationInstanceSummary, String> {
+    tauri::async_runtime::spawn_blocking(move || summarize_vegetation_instances_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join vegetation summary task: {}", e))?
+}
+
+/// Delete vegetation instances inside a rectangle or circle and rewrite the touched packs.
+/// 删除矩形或圆形内的植被实例，并重写受影响的区域包
+#[tauri::command]
+pub async fn delete_vegetation_instances(
+    request: DeleteVegetationInstancesRequest,
+) -> Result<VegetationEditResult, String> {
+    tauri::async_runtime::spawn_blocking(move || delete_vegetation_instances_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join vegetation delete task: {}", e))?
+}
+
+/// Move vegetation instances inside a rectangle or circle by a world offset.
+/// 将矩形或圆形内的植被实例按世界偏移移动
+#[tauri::command]
+pub async fn move_vegetation_instances(
+    request: MoveVegetationInstancesRequest,
+) -> Result<VegetationEditResult, String> {
+    tauri::async_runtime::spawn_blocking(move || move_vegetation_instances_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join vegetation move task: {}", e))?
+}
+
+fn query_vegetation_instances_blocking(
+    request: QueryVegetationInstancesRequest,
+) -> Result<VegetationInstanceQueryResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    request.filter.validate("Vegetation query")?;
+    let limit = request.limit.unwrap_or(DEFAULT_VEGETATION_QUERY_LIMIT);
+    if limit == 0 || limit > MAX_VEGETATION_QUERY_LIMIT {
+        return Err(format!(
+            "Vegetation query limit must be between 1 and {}",
+            MAX_VEGETATION_QUERY_LIMIT
+        ));
+    }
+
+    let mut session = VegetationPackSession::open(&project_root, &request.map_id)?;
+    let species = session.species_filter(request.filter.model_ids.as_deref())?;
+    let mut instances = Vec::new();
+    let mut matched_count = 0;
+    let scanned_regions = session.visit_instances(
+        &request.filter,
+        species.as_ref(),
+        |cell_key, index, model_id, instance| {
+            matched_count += 1;
+            if instances.len() < limit {
+                instances.push(VegetationInstanceHit {
+                    model_id: model_id.to_string(),
+                    cell_key: cell_key.to_string(),
+                    index,
+                    x: instance.x,
+                    y: instance.y,
+                    z: instance.z,
+                    rotation_y: instance.rotation_y,
+                    scale: instance.scale,
+                });
+            }
+        },
+    )?;
+
+    Ok(VegetationInstanceQueryResult {
+        truncated: matched_count > instances.len(),
+        instances,
+        matched_count,
+        scanned_regions,
+    })
+}
+
+fn summarize_vegetation_instances_blocking(
+    request: SummarizeVegetationInstancesRequest,
+) -> Result<VegetationInstanceSummary, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    request.filter.validate("Vegetation summary")?;
+    let mut session = VegetationPackSession::open(&project_root, &request.map_id)?;
+    let bin_size = request
+        .bin_size_meters
+        .unwrap_or(session.layout.cell_size_meters);
+    if !bin_size.is_finite() || bin_size <= 0.0 {
+        return Err("Vegetation histogram bin size must be a positive number".to_string());
+    }
+
+    let species = session.species_filter(request.filter.model_ids.as_deref())?;
+    let mut counts = vec![0usize; session.model_ids.len()];
+    let mut positions = Vec::new();
+    let scanned_regions =
+        session.visit_instances(&request.filter, species.as_ref(), |_, _, _, instance| {
+            counts[instance.model_index as usize] += 1;
+            positions.push((instance.x as f64, instance.z as f64));
+        })?;
+
+    let area_square_meters = request.filter.area_square_meters();
+    let species = session
+        .model_ids
+        .iter()
+        .zip(&counts)
+        .enumerate()
+        .filter(|(index, _)| {
+            species
+                .as_ref()
+                .is_none_or(|selected| selected.contains(&(*index as u16)))
+        })
+        .map(|(_, (model_id, &count))| VegetationSpeciesCount {
+            model_id: model_id.clone(),
+            count,
+            density_per_hectare: area_square_meters
+                .filter(|area| *area > 0.0)
+                .map(|area| count as f64 / area * SQUARE_METERS_PER_HECTARE),
+        })
+        .collect();
+
+    Ok(VegetationInstanceSummary {
+        total_count: positions.len(),
+        area_square_meters,
+        species,
+        histogram: build_density_histogram(request.filter.bounds(), &positions, bin_size)?,
+        scanned_regions,
+    })
+}
+
+fn delete_vegetation_instances_blocking(
+    request: DeleteVegetationInstancesRequest,
+) -> Result<VegetationEditResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    request.filter.validate("Vegetation delete")?;
+    request.filter.require_area("Vegetation delete")?;
+    let mut session = VegetationPackSession::open(&project_root, &request.map_id)?;
+    let species = session.species_filter(request.filter.model_ids.as_deref())?;
+    let removed = session.take_instances(&request.filter, species.as_ref())?;
+    let (updated_regions, removed_regions) = session.save()?;
+
+    Ok(VegetationEditResult {
+        affected_count: removed.len(),
+        updated_regions,
+        removed_regions,
+    })
+}
+
+fn move_vegetation_instances_blocking(
+    request: MoveVegetationInstancesRequest,
+) -> Result<VegetationEditResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    request.filter.validate("Vegetation move")?;
+    request.filter.require_area("Vegetation move")?;
+    if ![request.offset_x, request.offset_y, request.offset_z]
+        .iter()
+        .all(|value| value.is_finite())
+    {
+        return Err("Vegetation move offset must be finite".to_string());
+    }
+
+    let mut session = VegetationPackSession::open(&project_root, &request.map_id)?;
+    let half_world = read_map_world_size(&session.map_root)? / 2.0;
+    let species = session.species_filter(request.filter.model_ids.as_deref())?;
+    let moved = session.take_instances(&request.filter, species.as_ref())?;
+    let mut terrain = if request.snap_to_terrain && !moved.is_empty() {
+        Some(HeightPackSession::open(&project_root, &request.map_id)?)
+    } else {
+        None
+    };
+
+    for mut instance in moved.iter().copied() {
+        let (old_x, old_z) = (instance.x as f64, instance.z as f64);
+        let (new_x, new_z) = (old_x + request.offset_x, old_z + request.offset_z);
+        if new_x.abs() > half_world || new_z.abs() > half_world {
+            return Err(format!(
+                "Moving the instance at ({:.2}, {:.2}) would place it outside the map",
+                old_x, old_z
+            ));
+        }
+
+        // EN: Keep each instance's offset from the ground so sunk roots and raised rocks survive the move.
+        // 中文: 保留每个实例相对地面的偏移，使下沉的根部与抬高的岩石在移动后保持不变。
+        let mut offset_y = request.offset_y;
+        if let Some(terrain) = terrain.as_mut() {
+            let old_height = terrain.height_at(old_x, old_z)?;
+            let new_height = terrain.height_at(new_x, new_z)?;
+            if let (Some(old_height), Some(new_height)) = (old_height, new_height) {
+                offset_y += (new_height - old_height) as f64;
+            }
+        }
+
+        instance.x = new_x as f32;
+        instance.z = new_z as f32;
+        instance.y = (instance.y as f64 + offset_y) as f32;
+        session.insert_instance(instance)?;
+    }
+
+    let (updated_regions, removed_regions) = session.save()?;
+    Ok(VegetationEditResult {
+        affected_count: moved.len(),
+        updated_regions,
+        removed_regions,
+    })
+}
+
+fn default_snap_to_terrain() -> bool {
+    true
+}
+
+fn build_density_histogram(
+    bounds: Option<[f64; 4]>,
+    positions: &[(f64, f64)],
+    bin_size: f64,
+) -> Result<Option<VegetationDensityHistogram>, String> {
+    // EN: Without an area the histogram spans the matched instances, snapped outward to the bin grid.
+    // 中文: 未指定区域时，直方图覆盖匹配的实例范围，并向外对齐到分箱网格。
+    let Some([min_x, min_z, max_x, max_z]) = bounds.or_else(|| {
+        positions.iter().fold(None, |extent, &(x, z)| match extent {
+            None => Some([x, z, x, z]),
+            Some([min_x, min_z, max_x, max_z]) => {
+                Some([min_x.min(x), min_z.min(z), max_x.max(x), max_z.max(z)])
+            }
+        })
+    }) else {
+        return Ok(None);
+    };
+    let (min_x, min_z) = if bounds.is_some() {
+        (min_x, min_z)
+    } else {
+        (
+            (min_x / bin_size).floor() * bin_size,
+            (min_z / bin_size).floor() * bin_size,
+        )
+    };
+
+    let columns = (((max_x - min_x) / bin_size).ceil() as usize).max(1);
+    let rows = (((max_z - min_z) / bin_size).ceil() as usize).max(1);
+    if columns.saturating_mul(rows) > MAX_VEGETATION_HISTOGRAM_BINS {
+        return Err(format!(
+            "Vegetation histogram needs {}x{} bins; the limit is {}",
+            columns, rows, MAX_VEGETATION_HISTOGRAM_BINS
+        ));
+    }
+
+    let mut counts = vec![0u32; columns * rows];
+    for &(x, z) in positions {
+        let column = (((x - min_x) / bin_size).floor().max(0.0) as usize).min(columns - 1);
+        let row = (((z - min_z) / bin_size).floor().max(0.0) as usize).min(rows - 1);
+        counts[row * columns + column] += 1;
+    }
+
+    Ok(Some(VegetationDensityHistogram {
+        bin_size_meters: bin_size,
+        min_x,
+        min_z,
+        columns,
+        rows,
+        max_count: counts.iter().copied().max().unwrap_or(0),
+        counts,
+    }))
+}
+
+// --- Filter / 过滤器 ---
+
+impl VegetationInstanceFilter {
+    fn validate(&self, label: &str) -> Result<(), String> {
+        if let Some(rect) = &self.rect {
+            rect.validate(label)?;
+        }
+        let circle_is_valid = self.circle.is_none_or(|circle| {
+            [circle.center_x, circle.center_z, circle.radius_meters]
+                .iter()
+                .all(|value| value.is_finite())
+                && circle.radius_meters > 0.0
+        });
+        if !circle_is_valid {
+            return Err(format!(
+                "{} circle must be finite with a positive radius",
+                label
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn require_area(&self, label: &str) -> Result<(), String> {
+        if self.rect.is_none() && self.circle.is_none() {
+            return Err(format!("{} requires a rectangle or circle", label));
+        }
+
+        Ok(())
+    }
+
+    /// Bounding box `[min_x, min_z, max_x, max_z]` of the area, or `None` when no area is set.
+    /// 区域的包围盒 `[min_x, min_z, max_x, max_z]`；未设置区域时返回 `None`
+    fn bounds(&self) -> Option<[f64; 4]> {
+        let rect = self
+            .rect
+            .map(|rect| [rect.min_x, rect.min_z, rect.max_x, rect.max_z]);
+        let circle = self.circle.map(|circle| {
+            [
+                circle.center_x - circle.radius_meters,
+                circle.center_z - circle.radius_meters,
+                circle.center_x + circle.radius_meters,
+                circle.center_z + circle.radius_meters,
+            ]
+        });
+        match (rect, circle) {
+            (Some(rect), Some(circle)) => Some([
+                rect[0].max(circle[0]),
+                rect[1].max(circle[1]),
+                rect[2].min(circle[2]),
+                rect[3].min(circle[3]),
+            ]),
+            (rect, circle) => rect.or(circle),
+        }
+    }
+
+    fn area_square_meters(&self) -> Option<f64> {
+        match (self.rect, self.circle) {
+            (None, None) => None,
+            (Some(rect), None) => Some((rect.max_x - rect.min_x) * (rect.max_z - rect.min_z)),
+            (None, Some(circle)) => Some(std::f64::consts::PI * circle.radius_meters.powi(2)),
+            (Some(rect), Some(circle)) => {
+                // EN: Integrate the clipped chord length across X; exact enough for densities.
+                // 中文: 沿 X 积分被裁剪的弦长；对密度统计而言足够精确。
+                let min_x = rect.min_x.max(circle.center_x - circle.radius_meters);
+                let max_x = rect.max_x.min(circle.center_x + circle.radius_meters);
+                if min_x >= max_x {
+                    return Some(0.0);
+                }
+
+                let step = (max_x - min_x) / CIRCLE_AREA_INTEGRATION_STEPS as f64;
+                let area = (0..CIRCLE_AREA_INTEGRATION_STEPS)
+                    .map(|index| {
+                        let x = min_x + (index as f64 + 0.5) * step;
+                        let half_chord = (circle.radius_meters.powi(2)
+                            - (x - circle.center_x).powi(2))
+                        .max(0.0)
+                        .sqrt();
+                        let top = rect.max_z.min(circle.center_z + half_chord);
+                        let bottom = rect.min_z.max(circle.center_z - half_chord);
+                        (top - bottom).max(0.0) * step
+                    })
+                    .sum();
+                Some(area)
+            }
+        }
+    }
+
+    fn contains(&self, x: f64, z: f64) -> bool {
+        self.rect.is_none_or(|rect| {
+            x >= rect.min_x && x <= rect.max_x && z >= rect.min_z && z <= rect.max_z
+        }) && self.circle.is_none_or(|circle| {
+            (x - circle.center_x).powi(2) + (z - circle.center_z).powi(2)
+                <= circle.radius_meters.powi(2)
+        })
+    }
+}
+
+// --- Codec / 编解码 ---
+
+impl VegetationInstance {
+    fn decode(region_key: &str, model_count: usize, record: &[u8]) -> Result<Self, String> {
+        let model_index = u16::from_le_bytes([record[0], record[1]]);
+        if model_index as usize >= model_count {
+            return Err(format!(
+                "Vegetation region '{}' references unknown model index {}",
+                region_key, model_index
+            ));
+        }
+
+        let value = |offset: usize| {
+            let value = f32::from_le_bytes([
+                record[offset],
+                record[offset + 1],
+                record[offset + 2],
+                record[offset + 3],
+            ]);
+            if value.is_finite() {
+                Ok(value)
+            } else {
+                Err(format!(
+                    "Vegetation region '{}' contains a non-finite instance value",
+                    region_key
+                ))
+            }
+        };
+
+        Ok(Self {
+            model_index,
+            x: value(4)?,
+            y: value(8)?,
+            z: value(12)?,
+            rotation_y: value(16)?,
+            scale: value(20)?,
+        })
+    }
+
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.model_index.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        for value in [self.x, self.y, self.z, self.rotation_y, self.scale] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+impl VegetationPackLayout {
+    pub(crate) fn cell_for_position(&self, x: f64, z: f64) -> (i32, i32) {
+        (
+            (x / self.cell_size_meters).floor() as i32,
+            (z / self.cell_size_meters).floor() as i32,
+        )
+    }
+
+    pub(crate) fn region_coords_for_cell(&self, cx: i32, cz: i32) -> (i32, i32) {
+        (
+            cx.div_euclid(self.region_size_cells),
+            cz.div_euclid(self.region_size_cells),
+        )
+    }
+
+    fn local_cell_index(&self, cx: i32, cz: i32) -> u32 {
+        let size = self.region_size_cells;
+        (cz.rem_euclid(size) * size + cx.rem_euclid(size)) as u32
+    }
+
+    fn cell_for_local_index(&self, region_x: i32, region_z: i32, local_index: u32) -> (i32, i32) {
+        let size = self.region_size_cells;
+        (
+            region_x * size + local_index as i32 % size,
+            region_z * size + local_index as i32 / size,
+        )
+    }
+
+    /// Whether a cell's square touches the bounds; `None` bounds match every cell.
+    /// cell 方格是否与包围盒相交；包围盒为 `None` 时匹配所有 cell
+    fn cell_overlaps(&self, cx: i32, cz: i32, bounds: Option<[f64; 4]>, cells: i32) -> bool {
+        let size = self.cell_size_meters;
+        bounds.is_none_or(|[min_x, min_z, max_x, max_z]| {
+            cx as f64 * size <= max_x
+                && (cx + cells) as f64 * size >= min_x
+                && cz as f64 * size <= max_z
+                && (cz + cells) as f64 * size >= min_z
+        })
+    }
+}
+
+impl VegetationRegionPack {
+    fn decode(
+        region_key: &str,
+        mask: u64,
+        model_count: usize,
+        bytes: &[u8],
+    ) -> Result<Self, String> {
+        if bytes.len() < VEGETATION_REGION_PACK_HEADER_BYTES {
+            return Err(format!(
+                "Vegetation region '{}' pack is too short",
+                region_key
+            ));
+        }
+
+        let read_u16 = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes([
+                bytes[offset],
+                bytes[offset + 1],
+                bytes[offset + 2],
+                bytes[offset + 3],
+            ])
+        };
+        if read_u32(0) != VEGETATION_REGION_PACK_MAGIC {
+            return Err(format!(
+                "Vegetation region '{}' has invalid pack magic",
+                region_key
+            ));
+        }
+        if read_u16(4) != VEGETATION_REGION_PACK_VERSION {
+            return Err(format!(
+                "Vegetation region '{}' has unsupported pack version",
+                region_key
+            ));
+        }
+
+        let cell_count = read_u16(6) as usize;
+        if cell_count != mask.count_ones() as usize {
+            return Err(format!(
+                "Vegetation region '{}' cell count does not match its sparse mask",
+                region_key
+            ));
+        }
+
+        let index_byte_length =
+            VEGETATION_REGION_PACK_HEADER_BYTES + cell_count * VEGETATION_REGION_PACK_ENTRY_BYTES;
+        if bytes.len() < index_byte_length {
+            return Err(format!(
+                "Vegetation region '{}' pack has a truncated cell index",
+                region_key
+            ));
+        }
+
+        // EN: Strictly increasing entries that are all declared in a mask of equal popcount cover the mask exactly.
+        // 中文: 严格递增且均在掩码中声明的条目，在数量与掩码位数相同时恰好覆盖整个掩码。
+        let mut entries = Vec::with_capacity(cell_count);
+        for index in 0..cell_count {
+            let entry_offset =
+                VEGETATION_REGION_PACK_HEADER_BYTES + index * VEGETATION_REGION_PACK_ENTRY_BYTES;
+            let local_index = read_u16(entry_offset) as u32;
+            if local_index >= 64 || mask & (1u64 << local_index) == 0 {
+                return Err(format!(
+                    "Vegetation region '{}' pack contains undeclared cell {}",
+                    region_key, local_index
+                ));
+            }
+            if entries
+                .last()
+                .is_some_and(|(previous, _)| local_index <= *previous)
+            {
+                return Err(format!(
+                    "Vegetation region '{}' pack cell index is not sorted",
+                    region_key
+                ));
+            }
+            entries.push((local_index, read_u32(entry_offset + 4) as usize));
+        }
+
+        let mut offset = index_byte_length;
+        let mut cells = BTreeMap::new();
+        for (local_index, instance_count) in entries {
+            let end = instance_count
+                .checked_mul(VEGETATION_INSTANCE_RECORD_BYTES)
+                .and_then(|byte_length| offset.checked_add(byte_length))
+                .filter(|end| *end <= bytes.len())
+                .ok_or_else(|| {
+                    format!(
+                        "Vegetation region '{}' pack is missing bytes for cell {}",
+                        region_key, local_index
+                    )
+                })?;
+            let instances = bytes[offset..end]
+                .chunks_exact(VEGETATION_INSTANCE_RECORD_BYTES)
+                .map(|record| VegetationInstance::decode(region_key, model_count, record))
+                .collect::<Result<Vec<_>, _>>()?;
+            cells.insert(local_index, instances);
+            offset = end;
+        }
+
+        if offset != bytes.len() {
+            return Err(format!(
+                "Vegetation region '{}' pack has trailing bytes",
+                region_key
+            ));
+        }
+
+        Ok(Self { cells })
+    }
+
+    fn mask(&self) -> u64 {
+        self.cells
+            .keys()
+            .fold(0u64, |mask, local_index| mask | (1u64 << local_index))
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let instance_count: usize = self.cells.values().map(Vec::len).sum();
+        let mut bytes = Vec::with_capacity(
+            VEGETATION_REGION_PACK_HEADER_BYTES
+                + self.cells.len() * VEGETATION_REGION_PACK_ENTRY_BYTES
+                + instance_count * VEGETATION_INSTANCE_RECORD_BYTES,
+        );
+        bytes.extend_from_slice(&VEGETATION_REGION_PACK_MAGIC.to_le_bytes());
+        bytes.extend_from_slice(&VEGETATION_REGION_PACK_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.cells.len() as u16).to_le_bytes());
+        for (local_index, instances) in &self.cells {
+            bytes.extend_from_slice(&(*local_index as u16).to_le_bytes());
+            bytes.extend_from_slice(&0u16.to_le_bytes());
+            bytes.extend_from_slice(&(instances.len() as u32).to_le_bytes());
+        }
+        for instance in self.cells.values().flatten() {
+            instance.encode_into(&mut bytes);
+        }
+
+        bytes
+    }
+}
+
+impl VegetationPackSession {
+    pub(crate) fn open(project_root: &Path, map_id: &str) -> Result<Self, String> {
+        validate_single_path_segment(map_id, "map_id")?;
+        let map_root = project_root.join(MAPS_DIR).join(map_id);
+        let manifest_path = map_root.join(VEGETATION_MODELS_PATH);
+        recover_safe_write(&manifest_path)?;
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read vegetation manifest: {}", e))?;
+        let document: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse vegetation manifest: {}", e))?;
+
+        let instances = &document["instances"];
+        if instances["format"].as_str() != Some(VEGETATION_REGION_FORMAT) {
+            return Err("Vegetation manifest has invalid region pack format".to_string());
+        }
+        if instances["instanceFormat"].as_str() != Some(VEGETATION_INSTANCE_FORMAT) {
+            return Err("Vegetation manifest has invalid instance record format".to_string());
+        }
+        if instances["regionsDirectory"].as_str() != Some(VEGETATION_REGIONS_DIRECTORY) {
+            return Err("Vegetation manifest has invalid regions directory".to_string());
+        }
+
+        let layout = VegetationPackLayout {
+            cell_size_meters: instances["cellSizeMeters"]
+                .as_f64()
+                .filter(|size| size.is_finite() && *size > 0.0)
+                .ok_or_else(|| "Vegetation cell size must be a positive number".to_string())?,
+            region_size_cells: instances["regionSizeCells"]
+                .as_u64()
+                .filter(|size| *size > 0 && *size <= MAX_VEGETATION_REGION_SIZE_CELLS)
+                .ok_or_else(|| "Vegetation region size must be between 1 and 8 cells".to_string())?
+                as i32,
+        };
+
+        let model_ids = instances["modelIds"]
+            .as_array()
+            .and_then(|ids| {
+                ids.iter()
+                    .map(|id| id.as_str().map(str::to_string))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or_else(|| "Vegetation manifest must contain model id order".to_string())?;
+
+        let mut region_masks = BTreeMap::new();
+        if let Some(regions) = instances["regions"].as_object() {
+            for (key, mask) in regions {
+                if parse_grid_key(key).is_none() {
+                    return Err(format!("Invalid vegetation region key '{}'", key));
+                }
+
+                let mask = mask
+                    .as_str()
+                    .and_then(|mask| mask.strip_prefix("0x"))
+                    .and_then(|digits| u64::from_str_radix(digits, 16).ok())
+                    .filter(|mask| *mask != 0)
+                    .ok_or_else(|| format!("Vegetation region '{}' has an invalid mask", key))?;
+                region_masks.insert(key.clone(), mask);
+            }
+        }
+
+        Ok(Self {
+            map_root,
+            document,
+            layout,
+            model_ids,
+            region_masks,
+            packs: BTreeMap::new(),
+            dirty_regions: BTreeSet::new(),
+            generated_regions: BTreeSet::new(),
+        })
+    }
+
+    /// Resolve model ids to record indices; `None` selects every species.
+    /// 将模型 id 解析为记录索引；`None` 表示选择所有物种
+    pub(crate) fn species_filter(
+        &self,
+        model_ids: Option<&[String]>,
+    ) -> Result<Option<BTreeSet<u16>>, String> {
+        model_ids
+            .map(|model_ids| {
+                model_ids
+                    .iter()
+                    .map(|model_id| {
+                        self.model_ids
+                            .iter()
+                            .position(|id| id == model_id)
+                            .map(|index| index as u16)
+                            .ok_or_else(|| format!("Unknown vegetation model '{}'", model_id))
+                    })
+                    .collect()
+            })
+            .transpose()
+    }
+
+    /// Call `visit` for each matching instance in region, cell and record order; returns the scanned region count.
+    /// 按区域、cell 与记录顺序对每个匹配实例调用 `visit`；返回扫描的区域数量
+    pub(crate) fn visit_instances(
+        &mut self,
+        filter: &VegetationInstanceFilter,
+        species: Option<&BTreeSet<u16>>,
+        mut visit: impl FnMut(&str, usize, &str, &VegetationInstance),
+    ) -> Result<usize, String> {
+        let region_keys = self.load_regions_overlapping(filter.bounds())?;
+        for region_key in &region_keys {
+            let (region_x, region_z) = parse_grid_key(region_key)
+                .ok_or_else(|| format!("Invalid vegetation region key '{}'", region_key))?;
+            for (local_index, instances) in &self.packs[region_key].cells {
+                let (cx, cz) = self
+                    .layout
+                    .cell_for_local_index(region_x, region_z, *local_index);
+                if !self.layout.cell_overlaps(cx, cz, filter.bounds(), 1) {
+                    continue;
+                }
+
+                let cell_key = format_grid_key(cx, cz);
+                for (index, instance) in instances.iter().enumerate() {
+                    if species.is_none_or(|selected| selected.contains(&instance.model_index))
+                        && filter.contains(instance.x as f64, instance.z as f64)
+                    {
+                        let model_id = &self.model_ids[instance.model_index as usize];
+                        visit(&cell_key, index, model_id, instance);
+                    }
+                }
+            }
+        }
+
+        Ok(region_keys.len())
+    }
+
+    /// Remove and return matching instances; emptied cells are dropped and their regions marked for saving.
+    /// 移除并返回匹配的实例；清空的 cell 会被删除，其区域被标记为待保存
+    pub(crate) fn take_instances(
+        &mut self,
+        filter: &VegetationInstanceFilter,
+        species: Option<&BTreeSet<u16>>,
+    ) -> Result<Vec<VegetationInstance>, String> {
+        self.take_instances_where(filter.bounds(), |instance| {
+            species.is_none_or(|selected| selected.contains(&instance.model_index))
+                && filter.contains(instance.x as f64, instance.z as f64)
+        })
+    }
+
+    /// Remove and return instances accepted by `matches` inside `bounds` (`[min_x, min_z, max_x, max_z]`, or everywhere when `None`).
+    /// 移除并返回 `bounds`（`[min_x, min_z, max_x, max_z]`，为 `None` 时表示全部）内被 `matches` 接受的实例
+    pub(crate) fn take_instances_where(
+        &mut self,
+        bounds: Option<[f64; 4]>,
+        mut matches: impl FnMut(&VegetationInstance) -> bool,
+    ) -> Result<Vec<VegetationInstance>, String> {
+        let mut taken = Vec::new();
+        for region_key in self.load_regions_overlapping(bounds)? {
+            let pack = self
+                .packs
+                .get_mut(&region_key)
+                .ok_or_else(|| format!("Vegetation region pack '{}' is not loaded", region_key))?;
+            let before = taken.len();
+            pack.cells.retain(|_, instances| {
+                instances.retain(|instance| {
+                    let matched = matches(instance);
+                    if matched {
+                        taken.push(*instance);
+                    }
+                    !matched
+                });
+                !instances.is_empty()
+            });
+            if taken.len() > before {
+                self.dirty_regions.insert(region_key);
+            }
+        }
+
+        Ok(taken)
+    }
+
+    /// Append an instance to the cell containing it, creating the cell and region when needed.
+    /// 将实例追加到其所在的 cell，必要时创建 cell 与区域
+    pub(crate) fn insert_instance(&mut self, instance: VegetationInstance) -> Result<(), String> {
+        let (cx, cz) = self
+            .layout
+            .cell_for_position(instance.x as f64, instance.z as f64);
+        let (region_x, region_z) = self.layout.region_coords_for_cell(cx, cz);
+        let region_key = format_grid_key(region_x, region_z);
+        self.load_region(&region_key)?;
+        let local_index = self.layout.local_cell_index(cx, cz);
+        self.dirty_regions.insert(region_key.clone());
+        self.packs
+            .get_mut(&region_key)
+            .ok_or_else(|| format!("Vegetation region pack '{}' is not loaded", region_key))?
+            .cells
+            .entry(local_index)
+            .or_default()
+            .push(instance);
+        Ok(())
+    }
+
+    /// Region keys currently listed in the manifest.
+    /// 清单中当前列出的区域键
+    pub(crate) fn region_keys(&self) -> Vec<String> {
+        self.region_masks.keys().cloned().collect()
+    }
+
+    /// Regions listed by enabled manual patch layers.
+    /// 已启用的手动 patch 层中列出的区域
+    pub(crate) fn manual_regions(&self) -> BTreeSet<String> {
+        self.document["instances"]["patchLayers"]["layers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|layer| {
+                layer["kind"].as_str() == Some("manual")
+                    && layer["enabled"].as_bool() != Some(false)
+            })
+            .filter_map(|layer| layer["regions"].as_array())
+            .flatten()
+            .filter_map(|region| region.as_str().map(str::to_string))
+            .collect()
+    }
+
+    /// Swap one region's instances of the given species for `instances`, keeping other species untouched.
+    /// The region is also tagged for the generated patch layers on save.
+    /// 将某区域中指定物种的实例替换为 `instances`，其他物种保持不变；保存时该区域也会被加入生成类 patch 层
+    pub(crate) fn replace_region_species(
+        &mut self,
+        region_key: &str,
+        species: &BTreeSet<u16>,
+        instances: Vec<VegetationInstance>,
+    ) -> Result<(), String> {
+        self.load_region(region_key)?;
+        let layout = &self.layout;
+        let pack = self
+            .packs
+            .get_mut(region_key)
+            .ok_or_else(|| format!("Vegetation region pack '{}' is not loaded", region_key))?;
+        pack.cells.retain(|_, cell_instances| {
+            cell_instances.retain(|instance| !species.contains(&instance.model_index));
+            !cell_instances.is_empty()
+        });
+        for instance in instances {
+            let (cx, cz) = layout.cell_for_position(instance.x as f64, instance.z as f64);
+            let (region_x, region_z) = layout.region_coords_for_cell(cx, cz);
+            if format_grid_key(region_x, region_z) != region_key {
+                return Err(format!(
+                    "Vegetation instance at {}, {} lies outside region '{}'",
+                    instance.x, instance.z, region_key
+                ));
+            }
+
+            pack.cells
+                .entry(layout.local_cell_index(cx, cz))
+                .or_default()
+                .push(instance);
+        }
+
+        self.dirty_regions.insert(region_key.to_string());
+        self.generated_regions.insert(region_key.to_string());
+        Ok(())
+    }
+
+    fn load_regions_overlapping(
+        &mut self,
+        bounds: Option<[f64; 4]>,
+    ) -> Result<Vec<String>, String> {
+        let size = self.layout.region_size_cells;
+        let mut region_keys: Vec<String> = self
+            .region_masks
+            .keys()
+            .filter(|key| {
+                parse_grid_key(key).is_some_and(|(region_x, region_z)| {
+                    self.layout
+                        .cell_overlaps(region_x * size, region_z * size, bounds, size)
+                })
+            })
+            .cloned()
+            .collect();
+        region_keys.sort_by(|left, right| compare_grid_keys(left, right));
+        for region_key in &region_keys {
+            self.load_region(region_key)?;
+        }
+
+        Ok(region_keys)
+    }
+
+    fn load_region(&mut self, region_key: &str) -> Result<(), String> {
+        if self.packs.contains_key(region_key) {
+            return Ok(());
+        }
+
+        let pack = match self.region_masks.get(region_key) {
+            Some(&mask) => {
+                let path = self.region_path(region_key)?;
+                recover_safe_write(&path)?;
+                let bytes = fs::read(&path).map_err(|e| {
+                    format!(
+                        "Failed to read vegetation region pack '{}': {}",
+                        region_key, e
+                    )
+                })?;
+                VegetationRegionPack::decode(region_key, mask, self.model_ids.len(), &bytes)?
+            }
+            None => VegetationRegionPack {
+                cells: BTreeMap::new(),
+            },
+        };
+        self.packs.insert(region_key.to_string(), pack);
+        Ok(())
+    }
+
+    /// Write changed packs, then the manifest, then delete packs that became empty.
+    /// Returns the rewritten and removed region keys.
+    /// 依次写入已修改的区域包与清单，再删除已变空的区域包。返回重写与移除的区域键
+    pub(crate) fn save(mut self) -> Result<(Vec<String>, Vec<String>), String> {
+        let mut dirty_regions: Vec<String> = self.dirty_regions.iter().cloned().collect();
+        dirty_regions.sort_by(|left, right| compare_grid_keys(left, right));
+
+        // EN: Packs land before the manifest and stale packs are deleted after it, so the manifest never names a missing file.
+        // 中文: 区域包先于清单写入，过期区域包在清单之后删除，因此清单不会引用缺失的文件。
+        let mut integrity = Map::new();
+        let mut updated_regions = Vec::new();
+        let mut new_regions = Vec::new();
+        let mut removed_regions = Vec::new();
+        for region_key in dirty_regions {
+            let pack = &self.packs[&region_key];
+            if pack.cells.is_empty() {
+                if self.region_masks.remove(&region_key).is_some() {
+                    removed_regions.push(region_key);
+                }
+                continue;
+            }
+
+            let bytes = pack.encode();
+            safe_write(&self.region_path(&region_key)?, &bytes).map_err(|e| {
+                format!(
+                    "Failed to save vegetation region pack '{}': {}",
+                    region_key, e
+                )
+            })?;
+            if self
+                .region_masks
+                .insert(region_key.clone(), pack.mask())
+                .is_none()
+            {
+                new_regions.push(region_key.clone());
+            }
+            integrity.insert(
+                region_key.clone(),
+                json!({ "byteLength": bytes.len(), "sha256": sha256_hex(&bytes) }),
+            );
+            updated_regions.push(region_key);
+        }
+
+        if updated_regions.is_empty() && removed_regions.is_empty() {
+            return Ok((updated_regions, removed_regions));
+        }
+
+        self.update_manifest_regions(integrity, &new_regions, &removed_regions);
+        let mut content = serde_json::to_string_pretty(&self.document)
+            .map_err(|e| format!("Failed to serialize vegetation manifest: {}", e))?;
+        content.push('\n');
+        safe_write(
+            &self.map_root.join(VEGETATION_MODELS_PATH),
+            content.as_bytes(),
+        )
+        .map_err(|e| format!("Failed to save vegetation manifest: {}", e))?;
+
+        for region_key in &removed_regions {
+            let path = self.region_path(region_key)?;
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| {
+                    format!(
+                        "Failed to remove vegetation region pack '{}': {}",
+                        region_key, e
+                    )
+                })?;
+            }
+        }
+
+        Ok((updated_regions, removed_regions))
+    }
+
+    fn update_manifest_regions(
+        &mut self,
+        integrity: Map<String, Value>,
+        new_regions: &[String],
+        removed_regions: &[String],
+    ) {
+        let mut region_keys: Vec<String> = self.region_masks.keys().cloned().collect();
+        region_keys.sort_by(|left, right| compare_grid_keys(left, right));
+        let instances = &mut self.document["instances"];
+        let previous_integrity = instances["regionIntegrity"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+
+        let mut regions = Map::new();
+        let mut region_integrity = Map::new();
+        for key in &region_keys {
+            regions.insert(
+                key.clone(),
+                Value::String(format!("0x{:016x}", self.region_masks[key])),
+            );
+            if let Some(entry) = integrity.get(key).or_else(|| previous_integrity.get(key)) {
+                region_integrity.insert(key.clone(), entry.clone());
+            }
+        }
+        instances["regions"] = Value::Object(regions);
+        instances["regionIntegrity"] = Value::Object(region_integrity);
+
+        // EN: Removed regions leave every patch layer; new regions join the base layer like the TS editor save, and scattered regions also join the generated layers.
+        // 中文: 被移除的区域从所有 patch 层中删除；新区域与 TS 编辑器保存一致地加入基础层，散布生成的区域同时加入生成类图层。
+        let Some(layers) = instances["patchLayers"]["layers"].as_array_mut() else {
+            return;
+        };
+        for layer in layers {
+            let mut layer_regions: Vec<String> = layer["regions"]
+                .as_array()
+                .map(|regions| {
+                    regions
+                        .iter()
+                        .filter_map(|region| region.as_str().map(str::to_string))
+                        .filter(|region| !removed_regions.contains(region))
+                        .collect()
+                })
+                .unwrap_or_default();
+            match layer["kind"].as_str() {
+                Some("base") => layer_regions.extend(new_regions.iter().cloned()),
+                Some("generated" | "procedural") => layer_regions.extend(
+                    self.generated_regions
+                        .iter()
+                        .filter(|region| self.region_masks.contains_key(*region))
+                        .cloned(),
+                ),
+                _ => {}
+            }
+            layer_regions.sort_by(|left, right| compare_grid_keys(left, right));
+            layer_regions.dedup();
+            layer["regions"] = json!(layer_regions);
+        }
+    }
+
+    fn region_path(&self, region_key: &str) -> Result<PathBuf, String> {
+        let (region_x, region_z) = parse_grid_key(region_key)
+            .ok_or_else(|| format!("Invalid vegetation region key '{}'", region_key))?;
+        Ok(self
+            .map_root
+            .join(VEGETATION_REGIONS_DIRECTORY)
+            .join(region_file_name(
+                region_x,
+                region_z,
+                VEGETATION_REGION_EXTENSION,
+            )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance(model_index: u16, x: f32, z: f32) -> VegetationInstance {
+        VegetationInstance {
+            model_index,
+            x,
+            y: 12.5,
+            z,
+            rotation_y: 1.25,
+            scale: 0.8,
+        }
+    }
+
+    fn sample_pack() -> VegetationRegionPack {
+        VegetationRegionPack {
+            cells: BTreeMap::from([
+                (0, vec![instance(0, 1.0, 2.0), instance(1, 3.0, 4.0)]),
+                (9, vec![instance(1, 40.0, 41.0)]),
+                (63, Vec::new()),
+            ]),
+        }
+    }
+
+    #[test]
+    fn region_pack_round_trips() {
+        let pack = sample_pack();
+        let bytes = pack.encode();
+        assert_eq!(
+            bytes.len(),
+            VEGETATION_REGION_PACK_HEADER_BYTES
+                + 3 * VEGETATION_REGION_PACK_ENTRY_BYTES
+                + 3 * VEGETATION_INSTANCE_RECORD_BYTES
+        );
+
+        let decoded = VegetationRegionPack::decode("0,0", pack.mask(), 2, &bytes).unwrap();
+        assert_eq!(decoded.mask(), pack.mask());
+        assert_eq!(decoded.encode(), bytes);
+        let restored = &decoded.cells[&9][0];
+        assert_eq!(restored.model_index, 1);
+        assert_eq!((restored.x, restored.y, restored.z), (40.0, 12.5, 41.0));
+        assert_eq!((restored.rotation_y, restored.scale), (1.25, 0.8));
+        assert!(decoded.cells[&63].is_empty());
+    }
+
+    #[test]
+    fn region_pack_rejects_inconsistent_bytes() {
+        let pack = sample_pack();
+        let bytes = pack.encode();
+        let mask = pack.mask();
+
+        assert!(VegetationRegionPack::decode("0,0", mask & !1, 2, &bytes).is_err());
+        assert!(VegetationRegionPack::decode("0,0", mask, 1, &bytes).is_err());
+        assert!(VegetationRegionPack::decode("0,0", mask, 2, &bytes[..bytes.len() - 1]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(VegetationRegionPack::decode("0,0", mask, 2, &trailing).is_err());
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] ^= 0xff;
+        assert!(VegetationRegionPack::decode("0,0", mask, 2, &bad_magic).is_err());
+
+        let first_record =
+            VEGETATION_REGION_PACK_HEADER_BYTES + 3 * VEGETATION_REGION_PACK_ENTRY_BYTES;
+        let mut non_finite = bytes.clone();
+        non_finite[first_record + 4..first_record + 8].copy_from_slice(&f32::NAN.to_le_bytes());
+        assert!(VegetationRegionPack::decode("0,0", mask, 2, &non_finite).is_err());
+
+        // EN: Swap the first two cell index entries so local indices are no longer increasing.
+        // 中文: 交换前两个 cell 索引条目，使局部索引不再递增。
+        let mut unsorted = bytes;
+        let header = VEGETATION_REGION_PACK_HEADER_BYTES;
+        let entry = VEGETATION_REGION_PACK_ENTRY_BYTES;
+        let (first, second) = unsorted[header..header + 2 * entry].split_at(entry);
+        let swapped = [second, first].concat();
+        unsorted[header..header + 2 * entry].copy_from_slice(&swapped);
+        assert!(VegetationRegionPack::decode("0,0", mask, 2, &unsorted).is_err());
+    }
+
+    #[test]
+    fn layout_maps_cells_to_regions_and_back() {
+        let layout = VegetationPackLayout {
+            cell_size_meters: 32.0,
+            region_size_cells: 8,
+        };
+        for (x, z) in [(0.0, 0.0), (-1.0, 300.0), (-257.0, -33.0)] {
+            let (cx, cz) = layout.cell_for_position(x, z);
+            let (region_x, region_z) = layout.region_coords_for_cell(cx, cz);
+            let local_index = layout.local_cell_index(cx, cz);
+            assert!(local_index < 64);
+            assert_eq!(
+                layout.cell_for_local_index(region_x, region_z, local_index),
+                (cx, cz)
+            );
+        }
+    }
+}
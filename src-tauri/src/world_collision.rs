@@ -0,0 +1,1265 @@
+// Native world-collision-cell-pack-v1 loader with raycast, sphere sweep, overlap and ground height queries.
+// 原生 world-collision-cell-pack-v1 加载器，提供射线、球体扫掠、重叠与地面高度查询
+//
+// EN: Shapes follow src/game/systems/physicsSystem.ts: boxes are axis-aligned over their boundsMeters, cylinders stand on their position, and both rise heightMeters above position.y. Terrain heightfields sample the cooked height regions; water volumes are not solid and only show up in overlap and ground queries.
+// 中文: 形状语义与 src/game/systems/physicsSystem.ts 一致：盒体按 boundsMeters 轴对齐，圆柱立于其 position 上，二者均从 position.y 向上延伸 heightMeters。地形高度场采样 cooked 高度区域；水体不是实体，只出现在重叠与地面查询中。
+
+use crate::commands::validate_cook_project_path;
+use crate::cooked_map::{CookedCellGrid, CookedMap, CookedTerrain};
+use crate::map_layout::{
+    COOKED_COLLISION_CELL_FORMAT, WORLD_OBJECT_CELL_FORMAT, WorldRect, distance_to_polyline,
+};
+use crate::spline::SplineCurve;
+use crate::world_objects::round_meters;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+
+const MAX_QUERY_DISTANCE_METERS: f64 = 4096.0;
+const MAX_QUERY_RADIUS_METERS: f64 = 256.0;
+const MAX_GROUND_SAMPLES: usize = 4096;
+const TERRAIN_BISECTION_STEPS: usize = 24;
+const DIRECTION_EPSILON: f64 = 1e-9;
+const DEFAULT_SHAPE_RADIUS_METERS: f64 = 1.0;
+const DEFAULT_SHAPE_HEIGHT_METERS: f64 = 2.0;
+const SPHERE_TERRAIN_RING_DIRECTIONS: usize = 8;
+const SPHERE_TERRAIN_RING_FRACTIONS: &[f64] = &[0.5, 0.9];
+
+/// World-space vector in meters.
+/// 世界空间向量（米）
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct CollisionVector {
+    x: f64,
+    y: f64,
+    z: f64,
+}
+
+/// Horizontal world position in meters.
+/// 水平世界坐标（米）
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct CollisionGroundPoint {
+    x: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RaycastWorldCollisionRequest {
+    project_path: String,
+    map_id: String,
+    origin: CollisionVector,
+    direction: CollisionVector,
+    max_distance_meters: f64,
+    #[serde(default)]
+    ignore_object_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepWorldCollisionSphereRequest {
+    project_path: String,
+    map_id: String,
+    origin: CollisionVector,
+    direction: CollisionVector,
+    max_distance_meters: f64,
+    radius_meters: f64,
+    #[serde(default)]
+    ignore_object_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlapWorldCollisionSphereRequest {
+    project_path: String,
+    map_id: String,
+    center: CollisionVector,
+    radius_meters: f64,
+    #[serde(default)]
+    ignore_object_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SampleWorldGroundHeightsRequest {
+    project_path: String,
+    map_id: String,
+    points: Vec<CollisionGroundPoint>,
+    /// Let solid object tops count as ground, like standing on a crate.
+    /// 将实体对象顶部视为地面，例如站在箱子上
+    #[serde(default)]
+    include_objects: bool,
+    #[serde(default)]
+    ignore_object_ids: Vec<String>,
+}
+
+/// First contact of a ray or sweep.
+/// 射线或扫掠的首个接触
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionHit {
+    shape_id: String,
+    shape_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    object_id: Option<String>,
+    distance_meters: f64,
+    point: CollisionVector,
+    normal: CollisionVector,
+    /// Sphere center at the moment of contact; sweeps only.
+    /// 接触时刻的球心；仅扫掠查询提供
+    #[serde(skip_serializing_if = "Option::is_none")]
+    center: Option<CollisionVector>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionCastResult {
+    hit: Option<CollisionHit>,
+    scanned_cells: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionOverlap {
+    shape_id: String,
+    shape_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    object_id: Option<String>,
+    /// How far the sphere reaches into the shape.
+    /// 球体侵入形状的深度
+    depth_meters: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollisionOverlapResult {
+    overlaps: Vec<CollisionOverlap>,
+    scanned_cells: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundHeightSample {
+    x: f64,
+    z: f64,
+    terrain_height: Option<f64>,
+    /// Terrain height, or the highest solid top above it when objects are included.
+    /// 地形高度；包含对象时取其上方最高的实体顶部
+    height: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shape_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    object_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    water_object_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundHeightResult {
+    samples: Vec<GroundHeightSample>,
+    scanned_cells: usize,
+}
+
+#[derive(Debug, Clone)]
+enum CollisionGeometry {
+    Terrain,
+    Water {
+        bounds: WorldRect,
+        half_width: f64,
+        path: Option<Vec<[f64; 2]>>,
+    },
+    /// Axis-aligned box; y bounds are infinite when the shape has no position.
+    /// 轴对齐盒体；形状没有 position 时 y 范围为无限
+    Box {
+        min: [f64; 3],
+        max: [f64; 3],
+    },
+    Cylinder {
+        center: [f64; 2],
+        radius: f64,
+        min_y: f64,
+        max_y: f64,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct CollisionShape {
+    id: String,
+    shape_type: String,
+    object_id: Option<String>,
+    geometry: CollisionGeometry,
+}
+
+/// Cooked collision cells and terrain of one map, loaded by area.
+/// 单张地图的 cooked 碰撞单元与地形，按区域加载
+pub(crate) struct CollisionWorld {
+    map: CookedMap,
+    grid: CookedCellGrid,
+    objects: Option<CookedCellGrid>,
+    terrain: CookedTerrain,
+    cells: BTreeMap<String, Vec<CollisionShape>>,
+    ignored_objects: BTreeSet<String>,
+}
+
+/// Ray in world space with a normalized direction.
+/// 世界空间射线，方向已归一化
+#[derive(Debug, Clone, Copy)]
+struct Ray {
+    origin: [f64; 3],
+    direction: [f64; 3],
+    max_distance: f64,
+}
+
+// --- World collision commands / 世界碰撞命令 ---
+
+/// Cast a ray against terrain and solid object shapes.
+/// 对地形与实体对象形状投射射线
+#[tauri::command]
+pub async fn raycast_world_collision(
+    request: RaycastWorldCollisionRequest,
+) -> Result<CollisionCastResult, String> {
+    tauri::async_runtime::spawn_blocking(move || raycast_world_collision_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join collision raycast task: {}", e))?
+}
+
+/// Sweep a sphere along a ray and report the first contact.
+/// 沿射线扫掠球体并报告首个接触
+#[tauri::command]
+pub async fn sweep_world_collision_sphere(
+    request: SweepWorldCollisionSphereRequest,
+) -> Result<CollisionCastResult, String> {
+    tauri::async_runtime::spawn_blocking(move || sweep_world_collision_sphere_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join collision sweep task: {}", e))?
+}
+
+/// List shapes, terrain and water volumes overlapping a sphere.
+/// 列出与球体重叠的形状、地形与水体
+#[tauri::command]
+pub async fn overlap_world_collision_sphere(
+    request: OverlapWorldCollisionSphereRequest,
+) -> Result<CollisionOverlapResult, String> {
+    tauri::async_runtime::spawn_blocking(move || overlap_world_collision_sphere_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join collision overlap task: {}", e))?
+}
+
+/// Ground height at many points, for snapping objects and probing gameplay spaces.
+/// 批量查询地面高度，用于对象贴地与玩法空间探测
+#[tauri::command]
+pub async fn sample_world_ground_heights(
+    request: SampleWorldGroundHeightsRequest,
+) -> Result<GroundHeightResult, String> {
+    tauri::async_runtime::spawn_blocking(move || sample_world_ground_heights_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join ground height task: {}", e))?
+}
+
+fn raycast_world_collision_blocking(
+    request: RaycastWorldCollisionRequest,
+) -> Result<CollisionCastResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let ray = Ray::new(
+        request.origin,
+        request.direction,
+        request.max_distance_meters,
+    )?;
+    let mut world = CollisionWorld::open(&project_root, &request.map_id)?;
+    world.ignore_objects(request.ignore_object_ids);
+    world.load_rect(&ray.footprint(0.0))?;
+
+    Ok(CollisionCastResult {
+        hit: world.cast(&ray, 0.0),
+        scanned_cells: world.cells.len(),
+    })
+}
+
+fn sweep_world_collision_sphere_blocking(
+    request: SweepWorldCollisionSphereRequest,
+) -> Result<CollisionCastResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let radius = validate_radius(request.radius_meters)?;
+    let ray = Ray::new(
+        request.origin,
+        request.direction,
+        request.max_distance_meters,
+    )?;
+    let mut world = CollisionWorld::open(&project_root, &request.map_id)?;
+    world.ignore_objects(request.ignore_object_ids);
+    world.load_rect(&ray.footprint(radius))?;
+
+    Ok(CollisionCastResult {
+        hit: world.cast(&ray, radius),
+        scanned_cells: world.cells.len(),
+    })
+}
+
+fn overlap_world_collision_sphere_blocking(
+    request: OverlapWorldCollisionSphereRequest,
+) -> Result<CollisionOverlapResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let radius = validate_radius(request.radius_meters)?;
+    let center = finite_vector(request.center, "Overlap center")?;
+    let mut world = CollisionWorld::open(&project_root, &request.map_id)?;
+    world.ignore_objects(request.ignore_object_ids);
+    world.load_rect(&WorldRect {
+        min_x: center[0] - radius,
+        min_z: center[2] - radius,
+        max_x: center[0] + radius,
+        max_z: center[2] + radius,
+    })?;
+
+    Ok(CollisionOverlapResult {
+        overlaps: world.overlap_sphere(center, radius),
+        scanned_cells: world.cells.len(),
+    })
+}
+
+fn sample_world_ground_heights_blocking(
+    request: SampleWorldGroundHeightsRequest,
+) -> Result<GroundHeightResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    if request.points.is_empty() || request.points.len() > MAX_GROUND_SAMPLES {
+        return Err(format!(
+            "Ground height queries need between 1 and {} points",
+            MAX_GROUND_SAMPLES
+        ));
+    }
+    if request
+        .points
+        .iter()
+        .any(|point| !point.x.is_finite() || !point.z.is_finite())
+    {
+        return Err("Ground height points must be finite".to_string());
+    }
+
+    let mut world = CollisionWorld::open(&project_root, &request.map_id)?;
+    world.ignore_objects(request.ignore_object_ids);
+    let mut cell_keys = BTreeSet::new();
+    for point in &request.points {
+        cell_keys.insert(world.grid.cell_key_for_position(point.x, point.z));
+    }
+    for cell_key in &cell_keys {
+        world.load_cell(cell_key)?;
+    }
+
+    let samples = request
+        .points
+        .iter()
+        .map(|point| world.ground_height(point.x, point.z, request.include_objects))
+        .collect();
+    Ok(GroundHeightResult {
+        samples,
+        scanned_cells: world.cells.len(),
+    })
+}
+
+fn validate_radius(radius: f64) -> Result<f64, String> {
+    if !radius.is_finite() || radius <= 0.0 || radius > MAX_QUERY_RADIUS_METERS {
+        return Err(format!(
+            "Collision query radius must be within (0, {}] meters",
+            MAX_QUERY_RADIUS_METERS
+        ));
+    }
+
+    Ok(radius)
+}
+
+fn finite_vector(vector: CollisionVector, label: &str) -> Result<[f64; 3], String> {
+    let value = [vector.x, vector.y, vector.z];
+    if !value.iter().all(|component| component.is_finite()) {
+        return Err(format!("{} must be finite", label));
+    }
+
+    Ok(value)
+}
+
+fn vector(value: [f64; 3]) -> CollisionVector {
+    CollisionVector {
+        x: round_meters(value[0]),
+        y: round_meters(value[1]),
+        z: round_meters(value[2]),
+    }
+}
+
+/// Unit vector rounded to four decimals, enough for a surface normal.
+/// 保留四位小数的单位向量，足以表示表面法线
+fn normal_vector(value: [f64; 3]) -> CollisionVector {
+    let round = |component: f64| (component * 10_000.0).round() / 10_000.0 + 0.0;
+    CollisionVector {
+        x: round(value[0]),
+        y: round(value[1]),
+        z: round(value[2]),
+    }
+}
+
+fn normalize(value: [f64; 3]) -> [f64; 3] {
+    let length = (value[0] * value[0] + value[1] * value[1] + value[2] * value[2]).sqrt();
+    if length < DIRECTION_EPSILON {
+        return [0.0, 1.0, 0.0];
+    }
+    [value[0] / length, value[1] / length, value[2] / length]
+}
+
+impl Ray {
+    fn new(
+        origin: CollisionVector,
+        direction: CollisionVector,
+        max_distance: f64,
+    ) -> Result<Self, String> {
+        let origin = finite_vector(origin, "Ray origin")?;
+        let direction = finite_vector(direction, "Ray direction")?;
+        let length = (direction[0] * direction[0]
+            + direction[1] * direction[1]
+            + direction[2] * direction[2])
+            .sqrt();
+        if length < DIRECTION_EPSILON {
+            return Err("Ray direction must not be zero".to_string());
+        }
+        if !max_distance.is_finite()
+            || max_distance <= 0.0
+            || max_distance > MAX_QUERY_DISTANCE_METERS
+        {
+            return Err(format!(
+                "Ray max distance must be within (0, {}] meters",
+                MAX_QUERY_DISTANCE_METERS
+            ));
+        }
+
+        Ok(Self {
+            origin,
+            direction: [
+                direction[0] / length,
+                direction[1] / length,
+                direction[2] / length,
+            ],
+            max_distance,
+        })
+    }
+
+    fn at(&self, distance: f64) -> [f64; 3] {
+        [
+            self.origin[0] + self.direction[0] * distance,
+            self.origin[1] + self.direction[1] * distance,
+            self.origin[2] + self.direction[2] * distance,
+        ]
+    }
+
+    /// Horizontal rectangle covered by the ray, widened by `margin`.
+    /// 射线覆盖的水平矩形，按 `margin` 外扩
+    fn footprint(&self, margin: f64) -> WorldRect {
+        let end = self.at(self.max_distance);
+        WorldRect {
+            min_x: self.origin[0].min(end[0]) - margin,
+            min_z: self.origin[2].min(end[2]) - margin,
+            max_x: self.origin[0].max(end[0]) + margin,
+            max_z: self.origin[2].max(end[2]) + margin,
+        }
+    }
+}
+
+impl CollisionWorld {
+    pub(crate) fn open(project_root: &std::path::Path, map_id: &str) -> Result<Self, String> {
+        let map = CookedMap::open(project_root, map_id)?;
+        let grid = map.cell_grid("collision", COOKED_COLLISION_CELL_FORMAT)?;
+        let terrain = map.terrain()?;
+        Ok(Self {
+            map,
+            grid,
+            objects: None,
+            terrain,
+            cells: BTreeMap::new(),
+            ignored_objects: BTreeSet::new(),
+        })
+    }
+
+    fn ignore_objects(&mut self, object_ids: Vec<String>) {
+        self.ignored_objects.extend(object_ids);
+    }
+
+    /// Load every cooked collision cell touching a world rectangle.
+    /// 加载与世界矩形相交的所有 cooked 碰撞单元
+    pub(crate) fn load_rect(&mut self, rect: &WorldRect) -> Result<(), String> {
+        for cell_key in self
+            .grid
+            .cell_keys_in_rect(rect.min_x, rect.min_z, rect.max_x, rect.max_z)
+        {
+            self.load_cell(&cell_key)?;
+        }
+
+        Ok(())
+    }
+
+    fn load_cell(&mut self, cell_key: &str) -> Result<(), String> {
+        if self.cells.contains_key(cell_key) || !self.grid.contains(cell_key) {
+            return Ok(());
+        }
+
+        let pack = self
+            .map
+            .read_cell_pack(&self.grid, cell_key, COOKED_COLLISION_CELL_FORMAT)?
+            .unwrap_or_default();
+        let mut shapes = Vec::new();
+        let mut object_pack: Option<Value> = None;
+        for shape in pack["shapes"].as_array().into_iter().flatten() {
+            let id = shape["id"].as_str().unwrap_or_default().to_string();
+            let shape_type = shape["type"].as_str().unwrap_or_default().to_string();
+            let object_id = shape["objectId"].as_str().map(str::to_string);
+            let geometry = match shape_type.as_str() {
+                "terrain-heightfield" => {
+                    let regions: Vec<&str> = shape["terrainRegions"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_str)
+                        .collect();
+                    self.terrain.load_regions(&self.map, &regions)?;
+                    CollisionGeometry::Terrain
+                }
+                "water-volume" => {
+                    let Some(bounds) = read_bounds(&shape["boundsMeters"]) else {
+                        continue;
+                    };
+                    if object_pack.is_none() {
+                        object_pack = Some(self.read_object_cell(cell_key)?);
+                    }
+                    let path = object_id.as_deref().and_then(|object_id| {
+                        water_path(object_pack.as_ref().unwrap_or(&Value::Null), object_id)
+                    });
+                    CollisionGeometry::Water {
+                        bounds,
+                        half_width: shape["widthMeters"].as_f64().unwrap_or(0.0).max(0.0) * 0.5,
+                        path,
+                    }
+                }
+                _ => match solid_geometry(shape) {
+                    Some(geometry) => geometry,
+                    None => continue,
+                },
+            };
+            shapes.push(CollisionShape {
+                id,
+                shape_type,
+                object_id,
+                geometry,
+            });
+        }
+        self.cells.insert(cell_key.to_string(), shapes);
+        Ok(())
+    }
+
+    /// Cooked object cell holding the water splines referenced by a collision cell.
+    /// 存放碰撞单元所引用水体样条的 cooked 对象单元
+    fn read_object_cell(&mut self, cell_key: &str) -> Result<Value, String> {
+        if self.objects.is_none() {
+            self.objects = Some(self.map.cell_grid("objects", WORLD_OBJECT_CELL_FORMAT)?);
+        }
+        let Some(objects) = &self.objects else {
+            return Ok(Value::Null);
+        };
+        Ok(self
+            .map
+            .read_cell_pack(objects, cell_key, WORLD_OBJECT_CELL_FORMAT)?
+            .unwrap_or_default())
+    }
+
+    fn shapes(&self) -> impl Iterator<Item = &CollisionShape> {
+        self.cells.values().flatten().filter(|shape| {
+            shape
+                .object_id
+                .as_ref()
+                .is_none_or(|object_id| !self.ignored_objects.contains(object_id))
+        })
+    }
+
+    fn terrain_shape_at(&self, x: f64, z: f64) -> Option<&CollisionShape> {
+        self.cells
+            .get(&self.grid.cell_key_for_position(x, z))?
+            .iter()
+            .find(|shape| matches!(shape.geometry, CollisionGeometry::Terrain))
+    }
+
+    /// First contact of a ray (`radius` 0) or a swept sphere.
+    /// Sphere sweeps inflate boxes by the radius, which slightly overestimates contact near box edges.
+    /// 射线（`radius` 为 0）或扫掠球体的首个接触。球体扫掠按半径外扩盒体，在盒体棱边附近会略微提前接触
+    fn cast(&self, ray: &Ray, radius: f64) -> Option<CollisionHit> {
+        let mut best: Option<(f64, [f64; 3], &CollisionShape)> = None;
+        for shape in self.shapes() {
+            let hit = match &shape.geometry {
+                CollisionGeometry::Box { min, max } => ray_box(
+                    ray,
+                    [min[0] - radius, min[1] - radius, min[2] - radius],
+                    [max[0] + radius, max[1] + radius, max[2] + radius],
+                ),
+                CollisionGeometry::Cylinder {
+                    center,
+                    radius: cylinder_radius,
+                    min_y,
+                    max_y,
+                } => ray_cylinder(
+                    ray,
+                    *center,
+                    cylinder_radius + radius,
+                    min_y - radius,
+                    max_y + radius,
+                ),
+                CollisionGeometry::Terrain | CollisionGeometry::Water { .. } => None,
+            };
+            let closer = hit.filter(|(distance, _)| {
+                best.is_none_or(|(best_distance, _, _)| *distance < best_distance)
+            });
+            if let Some((distance, normal)) = closer {
+                best = Some((distance, normal, shape));
+            }
+        }
+
+        let limit = best.map_or(ray.max_distance, |(distance, _, _)| distance);
+        let terrain_hit = self.cast_terrain(ray, radius, limit);
+        let (distance, normal, shape_id, shape_type, object_id) = match (terrain_hit, best) {
+            (Some((distance, normal)), _) => {
+                let center = ray.at(distance);
+                let shape = self.terrain_shape_at(center[0], center[2]);
+                (
+                    distance,
+                    normal,
+                    shape.map(|shape| shape.id.clone()).unwrap_or_default(),
+                    "terrain-heightfield".to_string(),
+                    None,
+                )
+            }
+            (None, Some((distance, normal, shape))) => (
+                distance,
+                normal,
+                shape.id.clone(),
+                shape.shape_type.clone(),
+                shape.object_id.clone(),
+            ),
+            (None, None) => return None,
+        };
+
+        let center = ray.at(distance);
+        let point = [
+            center[0] - normal[0] * radius,
+            center[1] - normal[1] * radius,
+            center[2] - normal[2] * radius,
+        ];
+        Some(CollisionHit {
+            shape_id,
+            shape_type,
+            object_id,
+            distance_meters: round_meters(distance),
+            point: vector(point),
+            normal: normal_vector(normal),
+            center: (radius > 0.0).then(|| vector(center)),
+        })
+    }
+
+    /// March along the ray in half-sample steps and bisect the first crossing below the terrain.
+    /// 以半个采样间距沿射线步进，并对首次穿入地形的位置二分求解
+    fn cast_terrain(&self, ray: &Ray, radius: f64, limit: f64) -> Option<(f64, [f64; 3])> {
+        let step = self.terrain.layout.sample_spacing_meters() * 0.5;
+        let clearance = |distance: f64| self.terrain_clearance(ray.at(distance), radius);
+        let mut previous = (0.0, clearance(0.0));
+        if previous.1.is_some_and(|value| value <= 0.0) {
+            let origin = ray.origin;
+            return Some((0.0, self.terrain_normal(origin[0], origin[2])));
+        }
+
+        let mut distance = 0.0;
+        while distance < limit {
+            distance = (distance + step).min(limit);
+            let current = clearance(distance);
+            let crossed = previous.1.is_some_and(|before| before > 0.0)
+                && current.is_some_and(|after| after <= 0.0);
+            if crossed {
+                let (mut low, mut high) = (previous.0, distance);
+                for _ in 0..TERRAIN_BISECTION_STEPS {
+                    let middle = (low + high) * 0.5;
+                    if clearance(middle).is_some_and(|value| value > 0.0) {
+                        low = middle;
+                    } else {
+                        high = middle;
+                    }
+                }
+                let point = ray.at(high);
+                return Some((high, self.terrain_normal(point[0], point[2])));
+            }
+            previous = (distance, current);
+        }
+
+        None
+    }
+
+    /// Height of a point, or of a sphere's lower surface, above the terrain beneath it.
+    /// 点（或球体下表面）相对其下方地形的高度
+    fn terrain_clearance(&self, point: [f64; 3], radius: f64) -> Option<f64> {
+        let mut clearance = point[1] - self.terrain.height_at(point[0], point[2])?;
+        if radius <= 0.0 {
+            return Some(clearance);
+        }
+
+        clearance -= radius;
+        for fraction in SPHERE_TERRAIN_RING_FRACTIONS {
+            let offset = radius * fraction;
+            let drop = (radius * radius - offset * offset).sqrt();
+            for index in 0..SPHERE_TERRAIN_RING_DIRECTIONS {
+                let angle =
+                    index as f64 * std::f64::consts::TAU / SPHERE_TERRAIN_RING_DIRECTIONS as f64;
+                let x = point[0] + angle.cos() * offset;
+                let z = point[2] + angle.sin() * offset;
+                if let Some(height) = self.terrain.height_at(x, z) {
+                    clearance = clearance.min(point[1] - drop - height);
+                }
+            }
+        }
+        Some(clearance)
+    }
+
+    fn terrain_normal(&self, x: f64, z: f64) -> [f64; 3] {
+        let spacing = self.terrain.layout.sample_spacing_meters();
+        let sample = |dx: f64, dz: f64| self.terrain.height_at(x + dx, z + dz);
+        match (
+            sample(-spacing, 0.0),
+            sample(spacing, 0.0),
+            sample(0.0, -spacing),
+            sample(0.0, spacing),
+        ) {
+            (Some(left), Some(right), Some(back), Some(forward)) => normalize([
+                (left - right) / (spacing * 2.0),
+                1.0,
+                (back - forward) / (spacing * 2.0),
+            ]),
+            _ => [0.0, 1.0, 0.0],
+        }
+    }
+
+    fn overlap_sphere(&self, center: [f64; 3], radius: f64) -> Vec<CollisionOverlap> {
+        let mut overlaps = Vec::new();
+        let mut terrain_reported = false;
+        for shape in self.shapes() {
+            let depth = match &shape.geometry {
+                CollisionGeometry::Box { min, max } => {
+                    let closest = [
+                        center[0].clamp(min[0], max[0]),
+                        center[1].clamp(min[1], max[1]),
+                        center[2].clamp(min[2], max[2]),
+                    ];
+                    let distance = ((closest[0] - center[0]).powi(2)
+                        + (closest[1] - center[1]).powi(2)
+                        + (closest[2] - center[2]).powi(2))
+                    .sqrt();
+                    Some(radius - distance)
+                }
+                CollisionGeometry::Cylinder {
+                    center: axis,
+                    radius: cylinder_radius,
+                    min_y,
+                    max_y,
+                } => {
+                    let horizontal = ((axis[0] - center[0]).hypot(axis[1] - center[2])
+                        - cylinder_radius)
+                        .max(0.0);
+                    let vertical = (min_y - center[1]).max(center[1] - max_y).max(0.0);
+                    Some(radius - horizontal.hypot(vertical))
+                }
+                CollisionGeometry::Water {
+                    bounds,
+                    half_width,
+                    path,
+                } => Some(radius - water_distance(bounds, *half_width, path.as_deref(), center)),
+                CollisionGeometry::Terrain => {
+                    // EN: Terrain is reported once, for the cell under the sphere center.
+                    // 中文: 地形只报告一次，取球心所在单元的地形形状。
+                    if terrain_reported
+                        || self
+                            .terrain_shape_at(center[0], center[2])
+                            .is_none_or(|terrain| terrain.id != shape.id)
+                    {
+                        None
+                    } else {
+                        terrain_reported = true;
+                        self.terrain_clearance(center, radius)
+                            .map(|clearance| -clearance)
+                    }
+                }
+            };
+            if let Some(depth) = depth.filter(|depth| *depth > 0.0) {
+                overlaps.push(CollisionOverlap {
+                    shape_id: shape.id.clone(),
+                    shape_type: shape.shape_type.clone(),
+                    object_id: shape.object_id.clone(),
+                    depth_meters: round_meters(depth),
+                });
+            }
+        }
+
+        overlaps.sort_by(|left, right| right.depth_meters.total_cmp(&left.depth_meters));
+        overlaps
+    }
+
+    fn ground_height(&self, x: f64, z: f64, include_objects: bool) -> GroundHeightSample {
+        let terrain_height = self.terrain.height_at(x, z);
+        let mut height = terrain_height;
+        let mut shape_id = terrain_height
+            .and_then(|_| self.terrain_shape_at(x, z))
+            .map(|shape| shape.id.clone());
+        let mut object_id = None;
+        let mut water_object_id = None;
+        for shape in self.shapes() {
+            let top = match &shape.geometry {
+                CollisionGeometry::Box { min, max }
+                    if x >= min[0] && x <= max[0] && z >= min[2] && z <= max[2] =>
+                {
+                    Some(max[1])
+                }
+                CollisionGeometry::Cylinder {
+                    center,
+                    radius,
+                    max_y,
+                    ..
+                } if (center[0] - x).hypot(center[1] - z) <= *radius => Some(*max_y),
+                CollisionGeometry::Water {
+                    bounds,
+                    half_width,
+                    path,
+                } => {
+                    if water_object_id.is_none()
+                        && water_distance(bounds, *half_width, path.as_deref(), [x, 0.0, z]) <= 0.0
+                    {
+                        water_object_id = shape.object_id.clone();
+                    }
+                    None
+                }
+                _ => None,
+            };
+            let Some(top) = top.filter(|top| include_objects && top.is_finite()) else {
+                continue;
+            };
+            if height.is_none_or(|height| top > height) {
+                height = Some(top);
+                shape_id = Some(shape.id.clone());
+                object_id = shape.object_id.clone();
+            }
+        }
+
+        GroundHeightSample {
+            x,
+            z,
+            terrain_height: terrain_height.map(round_meters),
+            height: height.map(round_meters),
+            shape_id,
+            object_id,
+            water_object_id,
+        }
+    }
+}
+
+/// Solid geometry for object shapes, matching the runtime's box and cylinder handling.
+/// 对象形状的实体几何，与运行时的盒体和圆柱处理一致
+fn solid_geometry(shape: &Value) -> Option<CollisionGeometry> {
+    let position = &shape["position"];
+    let base = position["y"].as_f64().filter(|y| y.is_finite());
+    let height = shape["heightMeters"]
+        .as_f64()
+        .filter(|height| height.is_finite() && *height > 0.0)
+        .unwrap_or(DEFAULT_SHAPE_HEIGHT_METERS);
+    let (min_y, max_y) = base.map_or((f64::NEG_INFINITY, f64::INFINITY), |base| {
+        (base, base + height)
+    });
+
+    if let (Some("cylinder"), Some(x), Some(z)) = (
+        shape["type"].as_str(),
+        position["x"].as_f64(),
+        position["z"].as_f64(),
+    ) {
+        return Some(CollisionGeometry::Cylinder {
+            center: [x, z],
+            radius: shape["radiusMeters"]
+                .as_f64()
+                .filter(|radius| radius.is_finite() && *radius > 0.0)
+                .unwrap_or(DEFAULT_SHAPE_RADIUS_METERS),
+            min_y,
+            max_y,
+        });
+    }
+
+    let bounds = read_bounds(&shape["boundsMeters"])?;
+    Some(CollisionGeometry::Box {
+        min: [bounds.min_x, min_y, bounds.min_z],
+        max: [bounds.max_x, max_y, bounds.max_z],
+    })
+}
+
+fn read_bounds(value: &Value) -> Option<WorldRect> {
+    WorldRect::from_value(value).filter(|rect| rect.validate("Collision shape bounds").is_ok())
+}
+
+fn water_path(object_pack: &Value, object_id: &str) -> Option<Vec<[f64; 2]>> {
+    let object = object_pack["objects"]
+        .as_array()?
+        .iter()
+        .find(|object| object["id"].as_str() == Some(object_id))?;
+    SplineCurve::from_object(object)
+        .ok()
+        .map(|curve| curve.polyline())
+}
+
+/// Horizontal distance from a point to a water volume's surface footprint; negative inside.
+/// Falls back to the bounds when the spline is unavailable.
+/// 点到水体水面覆盖范围的水平距离，位于内部时为负；无法获得样条时退回使用包围盒
+fn water_distance(
+    bounds: &WorldRect,
+    half_width: f64,
+    path: Option<&[[f64; 2]]>,
+    point: [f64; 3],
+) -> f64 {
+    let (x, z) = (point[0], point[2]);
+    match path.filter(|path| !path.is_empty()) {
+        Some(path) => distance_to_polyline(path, x, z) - half_width,
+        None => {
+            let outside_x = (bounds.min_x - x).max(x - bounds.max_x);
+            let outside_z = (bounds.min_z - z).max(z - bounds.max_z);
+            if outside_x <= 0.0 && outside_z <= 0.0 {
+                outside_x.max(outside_z)
+            } else {
+                outside_x.max(0.0).hypot(outside_z.max(0.0))
+            }
+        }
+    }
+}
+
+/// Slab test; a ray starting inside reports distance 0 facing back along the ray.
+/// 平板法求交；射线起点位于盒体内部时返回距离 0，法线朝向射线反方向
+fn ray_box(ray: &Ray, min: [f64; 3], max: [f64; 3]) -> Option<(f64, [f64; 3])> {
+    let mut near = 0.0_f64;
+    let mut far = ray.max_distance;
+    let mut normal = [-ray.direction[0], -ray.direction[1], -ray.direction[2]];
+    for axis in 0..3 {
+        let origin = ray.origin[axis];
+        let direction = ray.direction[axis];
+        if direction.abs() < DIRECTION_EPSILON {
+            if origin < min[axis] || origin > max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let (mut entry, mut exit) = (
+            (min[axis] - origin) / direction,
+            (max[axis] - origin) / direction,
+        );
+        if entry > exit {
+            std::mem::swap(&mut entry, &mut exit);
+        }
+        if entry > near {
+            near = entry;
+            normal = [0.0; 3];
+            normal[axis] = -direction.signum();
+        }
+        far = far.min(exit);
+        if near > far {
+            return None;
+        }
+    }
+
+    Some((near, normal))
+}
+
+fn ray_cylinder(
+    ray: &Ray,
+    center: [f64; 2],
+    radius: f64,
+    min_y: f64,
+    max_y: f64,
+) -> Option<(f64, [f64; 3])> {
+    let [ox, oy, oz] = ray.origin;
+    let [dx, dy, dz] = ray.direction;
+    let (rx, rz) = (ox - center[0], oz - center[1]);
+    let inside_circle = rx * rx + rz * rz <= radius * radius;
+    if inside_circle && oy >= min_y && oy <= max_y {
+        return Some((0.0, [-dx, -dy, -dz]));
+    }
+
+    let mut best: Option<(f64, [f64; 3])> = None;
+    let a = dx * dx + dz * dz;
+    if a > DIRECTION_EPSILON {
+        let b = 2.0 * (rx * dx + rz * dz);
+        let c = rx * rx + rz * rz - radius * radius;
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant >= 0.0 {
+            let distance = (-b - discriminant.sqrt()) / (2.0 * a);
+            let y = oy + dy * distance;
+            if distance >= 0.0 && distance <= ray.max_distance && y >= min_y && y <= max_y {
+                let hx = rx + dx * distance;
+                let hz = rz + dz * distance;
+                best = Some((distance, [hx / radius, 0.0, hz / radius]));
+            }
+        }
+    }
+
+    if dy.abs() > DIRECTION_EPSILON {
+        for (cap_y, normal_y) in [(max_y, 1.0), (min_y, -1.0)] {
+            let distance = (cap_y - oy) / dy;
+            if !distance.is_finite() || distance < 0.0 || distance > ray.max_distance {
+                continue;
+            }
+            let hx = rx + dx * distance;
+            let hz = rz + dz * distance;
+            if hx * hx + hz * hz <= radius * radius
+                && best.is_none_or(|(best_distance, _)| distance < best_distance)
+            {
+                best = Some((distance, [0.0, normal_y, 0.0]));
+            }
+        }
+    }
+
+    best
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map_layout::HEIGHT_REGION_FORMAT;
+    use crate::test_fixtures::{FIXTURE_MAP_ID, FixtureProject, assert_close};
+    use serde_json::json;
+
+    fn ray(origin: [f64; 3], direction: [f64; 3], max_distance: f64) -> Ray {
+        Ray::new(
+            CollisionVector {
+                x: origin[0],
+                y: origin[1],
+                z: origin[2],
+            },
+            CollisionVector {
+                x: direction[0],
+                y: direction[1],
+                z: direction[2],
+            },
+            max_distance,
+        )
+        .unwrap()
+    }
+
+    fn terrain_height(x: f64) -> f64 {
+        2.0 + 0.25 * x
+    }
+
+    /// Cooked map of four 16 m pages sloping along x, with one crate and one brook.
+    /// 由四个 16 米页面组成、沿 x 方向倾斜的 cooked 地图，包含一个箱子和一条小溪
+    fn synthetic_world(name: &str) -> (FixtureProject, CollisionWorld) {
+        let project = FixtureProject::new("collision", name);
+
+        let mut region = Vec::new();
+        for page in 0..4 {
+            let page_x = f64::from(page % 2);
+            for _row in 0..3 {
+                for column in 0..3 {
+                    let x = page_x * 16.0 + f64::from(column) * 8.0;
+                    region.extend_from_slice(&(terrain_height(x) as f32).to_le_bytes());
+                }
+            }
+        }
+        let mut region_entry =
+            project.write_artifact(&project.cooked_path("terrain/r_0_0.heightpack"), &region);
+        region_entry["mask"] = json!("0xf");
+
+        let cell = json!({
+            "format": COOKED_COLLISION_CELL_FORMAT,
+            "cell": { "key": "0,0" },
+            "shapes": [
+                { "id": "terrain:0,0", "type": "terrain-heightfield", "terrainRegions": ["0,0"] },
+                {
+                    "id": "crate-01:box",
+                    "type": "box",
+                    "objectId": "crate-01",
+                    "position": { "x": 21.0, "y": 7.0, "z": 21.0 },
+                    "heightMeters": 2.0,
+                    "boundsMeters": { "minX": 20.0, "minZ": 20.0, "maxX": 22.0, "maxZ": 22.0 }
+                },
+                {
+                    "id": "brook-01:water",
+                    "type": "water-volume",
+                    "objectId": "brook-01",
+                    "widthMeters": 4.0,
+                    "boundsMeters": { "minX": 0.0, "minZ": 0.0, "maxX": 4.0, "maxZ": 32.0 }
+                }
+            ]
+        });
+        let cell_entry = project.write_artifact(
+            &project.cooked_path("collision/c_0_0.json"),
+            cell.to_string().as_bytes(),
+        );
+
+        project.write_cooked_manifest(json!({
+                "terrain": {
+                    "format": HEIGHT_REGION_FORMAT,
+                    "pageResolution": 3,
+                    "pageSizeMeters": 16.0,
+                    "regionSizePages": 2,
+                    "regions": { "0,0": region_entry }
+                },
+                "collision": {
+                    "format": COOKED_COLLISION_CELL_FORMAT,
+                    "cellSizeMeters": 32.0,
+                    "cells": { "0,0": cell_entry }
+                },
+                "objects": {
+                    "format": WORLD_OBJECT_CELL_FORMAT,
+                    "cellSizeMeters": 32.0,
+                    "cells": {}
+                }
+        }));
+
+        let mut world = CollisionWorld::open(project.root(), FIXTURE_MAP_ID).unwrap();
+        world
+            .load_rect(&WorldRect {
+                min_x: 0.0,
+                min_z: 0.0,
+                max_x: 31.0,
+                max_z: 31.0,
+            })
+            .unwrap();
+        (project, world)
+    }
+
+    #[test]
+    fn ray_box_reports_entry_face() {
+        let hit = ray_box(
+            &ray([-5.0, 1.0, 0.5], [1.0, 0.0, 0.0], 10.0),
+            [0.0, 0.0, 0.0],
+            [1.0, 2.0, 1.0],
+        )
+        .unwrap();
+        assert_close(hit.0, 5.0);
+        assert_eq!(hit.1, [-1.0, 0.0, 0.0]);
+
+        let miss = ray([-5.0, 3.0, 0.5], [1.0, 0.0, 0.0], 10.0);
+        assert!(ray_box(&miss, [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]).is_none());
+        let short = ray([-5.0, 1.0, 0.5], [1.0, 0.0, 0.0], 4.0);
+        assert!(ray_box(&short, [0.0, 0.0, 0.0], [1.0, 2.0, 1.0]).is_none());
+
+        let inside = ray_box(
+            &ray([0.5, 1.0, 0.5], [0.0, 0.0, 1.0], 10.0),
+            [0.0, 0.0, 0.0],
+            [1.0, 2.0, 1.0],
+        )
+        .unwrap();
+        assert_eq!(inside, (0.0, [0.0, 0.0, -1.0]));
+    }
+
+    #[test]
+    fn ray_cylinder_hits_side_and_cap() {
+        let side = ray_cylinder(
+            &ray([-10.0, 1.0, 0.0], [1.0, 0.0, 0.0], 20.0),
+            [0.0, 0.0],
+            2.0,
+            0.0,
+            3.0,
+        )
+        .unwrap();
+        assert_close(side.0, 8.0);
+        assert_close(side.1[0], -1.0);
+        assert_close(side.1[2], 0.0);
+
+        let cap = ray_cylinder(
+            &ray([0.5, 10.0, 0.5], [0.0, -1.0, 0.0], 20.0),
+            [0.0, 0.0],
+            2.0,
+            0.0,
+            3.0,
+        )
+        .unwrap();
+        assert_close(cap.0, 7.0);
+        assert_eq!(cap.1, [0.0, 1.0, 0.0]);
+
+        let above = ray([-10.0, 4.0, 0.0], [1.0, 0.0, 0.0], 20.0);
+        assert!(ray_cylinder(&above, [0.0, 0.0], 2.0, 0.0, 3.0).is_none());
+    }
+
+    #[test]
+    fn water_distance_uses_path_then_bounds() {
+        let bounds = WorldRect {
+            min_x: 0.0,
+            min_z: -2.0,
+            max_x: 10.0,
+            max_z: 2.0,
+        };
+        let path = [[0.0, 0.0], [10.0, 0.0]];
+        assert_close(
+            water_distance(&bounds, 2.0, Some(&path), [5.0, 0.0, 1.0]),
+            -1.0,
+        );
+        assert_close(
+            water_distance(&bounds, 2.0, Some(&path), [13.0, 0.0, 4.0]),
+            3.0,
+        );
+
+        assert_close(water_distance(&bounds, 2.0, None, [5.0, 0.0, 1.5]), -0.5);
+        assert_close(
+            water_distance(&bounds, 2.0, Some(&[]), [13.0, 0.0, 6.0]),
+            5.0,
+        );
+    }
+
+    #[test]
+    fn cast_terrain_bisects_the_crossing() {
+        let (_project, world) = synthetic_world("cast");
+
+        let down = ray([12.0, 20.0, 12.0], [0.0, -1.0, 0.0], 40.0);
+        let (distance, normal) = world.cast_terrain(&down, 0.0, down.max_distance).unwrap();
+        assert_close(distance, 20.0 - terrain_height(12.0));
+        let expected = normalize([-0.25, 1.0, 0.0]);
+        for axis in 0..3 {
+            assert_close(normal[axis], expected[axis]);
+        }
+
+        // EN: A shallow ray toward the rising slope crosses it between march steps.
+        // 中文: 朝上升坡面的低角度射线在两次步进之间穿过地形。
+        let slanted = ray([6.0, 6.0, 16.0], [1.0, -0.05, 0.0], 30.0);
+        let (distance, _) = world
+            .cast_terrain(&slanted, 0.0, slanted.max_distance)
+            .unwrap();
+        let point = slanted.at(distance);
+        assert_close(point[1], terrain_height(point[0]));
+
+        let up = ray([12.0, 20.0, 12.0], [0.0, 1.0, 0.0], 40.0);
+        assert!(world.cast_terrain(&up, 0.0, up.max_distance).is_none());
+    }
+
+    #[test]
+    fn cast_prefers_the_nearest_solid() {
+        let (_project, world) = synthetic_world("solid");
+
+        let hit = world
+            .cast(&ray([21.0, 20.0, 21.0], [0.0, -1.0, 0.0], 40.0), 0.0)
+            .unwrap();
+        assert_eq!(hit.object_id.as_deref(), Some("crate-01"));
+        assert_close(hit.distance_meters, 11.0);
+
+        let hit = world
+            .cast(&ray([12.0, 20.0, 12.0], [0.0, -1.0, 0.0], 40.0), 0.0)
+            .unwrap();
+        assert_eq!(hit.shape_type, "terrain-heightfield");
+        assert_eq!(hit.shape_id, "terrain:0,0");
+    }
+
+    #[test]
+    fn ground_height_reports_terrain_objects_and_water() {
+        let (_project, world) = synthetic_world("ground");
+
+        let terrain_only = world.ground_height(21.0, 21.0, false);
+        assert_close(terrain_only.height.unwrap(), terrain_height(21.0));
+        assert_eq!(terrain_only.object_id, None);
+
+        let with_objects = world.ground_height(21.0, 21.0, true);
+        assert_close(with_objects.height.unwrap(), 9.0);
+        assert_close(with_objects.terrain_height.unwrap(), terrain_height(21.0));
+        assert_eq!(with_objects.object_id.as_deref(), Some("crate-01"));
+
+        let brook = world.ground_height(2.0, 10.0, true);
+        assert_eq!(brook.water_object_id.as_deref(), Some("brook-01"));
+        assert_eq!(brook.shape_id.as_deref(), Some("terrain:0,0"));
+
+        assert!(world.ground_height(40.0, 10.0, true).height.is_none());
+    }
+}
@@ -0,0 +1,1174 @@
+// Native world-nav-cell-pack-v1 pathfinding: hierarchical A* over cross-cell portals, nearest walkable points and reachability.
+// 原生 world-nav-cell-pack-v1 寻路：基于跨单元入口的分层 A*、最近可行走点与可达性
+//
+// EN: Mirrors src/game/world/partition/WorldNavQuery.ts but loads nav cells on demand across the whole map instead of only the streamed ones. Portals land on the nearest walkable node of their target cell, and link costs are rebuilt from node costs after swapping the flat road and water terms baked by cooked-assets.mjs for the archetype navCost of the cell's modifiers: road nodes take the cheapest road archetype, and walkable nodes of cells crossed by water take the costliest water archetype. The slope term is left as baked.
+// 中文: 与 src/game/world/partition/WorldNavQuery.ts 一致，但按需加载整张地图的导航单元，而不只是已流式加载的单元。入口落在目标单元最近的可行走节点上；连接代价由节点代价重建：先将 cooked-assets.mjs 烘焙的固定道路项与水体项替换为单元修饰器对应原型的 navCost，道路节点采用最便宜的道路原型，被水体穿过的单元中的可行走节点采用代价最高的水体原型；坡度项保持烘焙值不变。
+
+use crate::commands::validate_cook_project_path;
+use crate::cooked_map::{CookedCellGrid, CookedMap};
+use crate::map_layout::{
+    COOKED_NAV_CELL_FORMAT, WORLD_OBJECT_CELL_FORMAT, format_grid_key, parse_grid_key,
+};
+use crate::world_objects::round_meters;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap};
+
+const MAX_NAV_SEARCH_STATES: usize = 1_000_000;
+const MAX_NAV_QUERY_POINTS: usize = 1024;
+const DEFAULT_NAV_NODE_COST: f64 = 1.0;
+/// Road term createNavNodes bakes into every road node's cost in place of the 1.0 ground term.
+/// createNavNodes 为每个道路节点烘焙的道路代价项，用于替代 1.0 的地面项
+const COOKED_ROAD_COST_TERM: f64 = 0.55;
+/// Surcharge createNavNodes adds to every walkable node of a cell that contains water.
+/// createNavNodes 为含水体单元中每个可行走节点附加的代价
+const COOKED_WATER_COST_TERM: f64 = 0.55;
+/// Lower bound of the cost per node spacing, which keeps the A* heuristic admissible.
+/// 每个节点间距的代价下界，用于保证 A* 启发函数可采纳
+const HEURISTIC_COST_PER_SPACING: f64 = 0.25;
+const SEARCH_COST_EPSILON: f64 = 1e-9;
+
+/// Horizontal world position in meters.
+/// 水平世界坐标（米）
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct NavQueryPoint {
+    x: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindWorldNavPathRequest {
+    project_path: String,
+    map_id: String,
+    start: NavQueryPoint,
+    end: NavQueryPoint,
+    #[serde(default)]
+    max_snap_distance_meters: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindNearestWorldNavPointsRequest {
+    project_path: String,
+    map_id: String,
+    points: Vec<NavQueryPoint>,
+    #[serde(default)]
+    max_distance_meters: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct NavReachabilityTarget {
+    #[serde(default)]
+    id: Option<String>,
+    x: f64,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckWorldNavReachabilityRequest {
+    project_path: String,
+    map_id: String,
+    start: NavQueryPoint,
+    /// Points to test; every cooked POI object is used when omitted.
+    /// 待检测的位置；省略时使用所有 cooked POI 对象
+    #[serde(default)]
+    targets: Option<Vec<NavReachabilityTarget>>,
+    #[serde(default)]
+    max_snap_distance_meters: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavPathPoint {
+    node_id: String,
+    cell_key: String,
+    x: f64,
+    y: f64,
+    z: f64,
+    cost: f64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldNavPathResult {
+    /// `ok` or `unreachable`, matching WorldNavQuery.
+    /// `ok` 或 `unreachable`，与 WorldNavQuery 一致
+    status: String,
+    start_node: Option<NavPathPoint>,
+    end_node: Option<NavPathPoint>,
+    cost: Option<f64>,
+    points: Vec<NavPathPoint>,
+    cells: Vec<String>,
+    portal_count: usize,
+    loaded_cells: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NearestNavPoint {
+    x: f64,
+    z: f64,
+    node: Option<NavPathPoint>,
+    distance_meters: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NearestWorldNavPointsResult {
+    points: Vec<NearestNavPoint>,
+    loaded_cells: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavReachability {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    x: f64,
+    z: f64,
+    reachable: bool,
+    node_id: Option<String>,
+    cost: Option<f64>,
+    snap_distance_meters: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldNavReachabilityResult {
+    start_node: Option<NavPathPoint>,
+    targets: Vec<NavReachability>,
+    reachable_count: usize,
+    unreachable_count: usize,
+    reachable_nodes: usize,
+    loaded_cells: usize,
+}
+
+/// Node addressed by its nav cell and index in that cell's pack.
+/// 由导航单元及其在单元包中的索引定位的节点
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct NavNodeRef {
+    cell: String,
+    index: usize,
+}
+
+#[derive(Debug)]
+struct NavNode {
+    id: String,
+    position: [f64; 3],
+    walkable: bool,
+    cost: f64,
+    tags: Vec<String>,
+}
+
+#[derive(Debug)]
+struct NavPortal {
+    from: usize,
+    target_cell: String,
+    cost: f64,
+}
+
+#[derive(Debug)]
+struct NavCell {
+    spacing: f64,
+    nodes: Vec<NavNode>,
+    links: Vec<Vec<(usize, f64)>>,
+    portals: Vec<NavPortal>,
+}
+
+/// Min-heap entry ordered by score, then node for deterministic ties.
+/// 按评分排序的最小堆条目，平局时按节点排序以保证确定性
+#[derive(Debug)]
+struct QueueEntry {
+    score: f64,
+    node: NavNodeRef,
+}
+
+/// Cooked nav cells of one map, loaded as searches reach them.
+/// 单张地图的 cooked 导航单元，在搜索到达时加载
+struct NavWorld {
+    map: CookedMap,
+    grid: CookedCellGrid,
+    /// navCost of road and water archetypes, keyed by archetype id.
+    /// 道路与水体原型的 navCost，按原型 id 索引
+    nav_costs: BTreeMap<String, f64>,
+    cells: BTreeMap<String, NavCell>,
+}
+
+// --- World nav commands / 世界导航命令 ---
+
+/// Find a path between two points, snapping both to the nearest walkable nav node.
+/// 查找两点之间的路径，两端均吸附到最近的可行走导航节点
+#[tauri::command]
+pub async fn find_world_nav_path(
+    request: FindWorldNavPathRequest,
+) -> Result<WorldNavPathResult, String> {
+    tauri::async_runtime::spawn_blocking(move || find_world_nav_path_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join nav path task: {}", e))?
+}
+
+/// Nearest walkable nav node for each point, searching outward cell ring by ring.
+/// 为每个位置查找最近的可行走导航节点，逐圈向外搜索单元
+#[tauri::command]
+pub async fn find_nearest_world_nav_points(
+    request: FindNearestWorldNavPointsRequest,
+) -> Result<NearestWorldNavPointsResult, String> {
+    tauri::async_runtime::spawn_blocking(move || find_nearest_world_nav_points_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join nav nearest point task: {}", e))?
+}
+
+/// Check which targets can be reached from a start point across the whole map.
+/// 检查从起点出发可以到达整张地图上的哪些目标
+#[tauri::command]
+pub async fn check_world_nav_reachability(
+    request: CheckWorldNavReachabilityRequest,
+) -> Result<WorldNavReachabilityResult, String> {
+    tauri::async_runtime::spawn_blocking(move || check_world_nav_reachability_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join nav reachability task: {}", e))?
+}
+
+fn find_world_nav_path_blocking(
+    request: FindWorldNavPathRequest,
+) -> Result<WorldNavPathResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    validate_query_point(request.start, "Nav path start")?;
+    validate_query_point(request.end, "Nav path end")?;
+    let max_snap = validate_max_distance(request.max_snap_distance_meters)?;
+    let mut world = NavWorld::open(&project_root, &request.map_id)?;
+
+    let start = world.nearest_walkable(request.start.x, request.start.z, max_snap)?;
+    let end = world.nearest_walkable(request.end.x, request.end.z, max_snap)?;
+    let (Some((start, _)), Some((end, _))) = (start.clone(), end.clone()) else {
+        return Ok(WorldNavPathResult {
+            status: "unreachable".to_string(),
+            start_node: start.map(|(node, _)| world.path_point(&node)),
+            end_node: end.map(|(node, _)| world.path_point(&node)),
+            cost: None,
+            points: Vec::new(),
+            cells: Vec::new(),
+            portal_count: 0,
+            loaded_cells: world.cells.len(),
+        });
+    };
+
+    let path = world.find_path(&start, &end)?;
+    let (status, cost, nodes) = match path {
+        Some((cost, nodes)) => ("ok", Some(round_cost(cost)), nodes),
+        None => ("unreachable", None, Vec::new()),
+    };
+    let mut cells: Vec<String> = Vec::new();
+    for node in &nodes {
+        if cells.last() != Some(&node.cell) {
+            cells.push(node.cell.clone());
+        }
+    }
+
+    Ok(WorldNavPathResult {
+        status: status.to_string(),
+        start_node: Some(world.path_point(&start)),
+        end_node: Some(world.path_point(&end)),
+        cost,
+        points: nodes.iter().map(|node| world.path_point(node)).collect(),
+        portal_count: cells.len().saturating_sub(1),
+        cells,
+        loaded_cells: world.cells.len(),
+    })
+}
+
+fn find_nearest_world_nav_points_blocking(
+    request: FindNearestWorldNavPointsRequest,
+) -> Result<NearestWorldNavPointsResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    if request.points.is_empty() || request.points.len() > MAX_NAV_QUERY_POINTS {
+        return Err(format!(
+            "Nearest nav point queries need between 1 and {} points",
+            MAX_NAV_QUERY_POINTS
+        ));
+    }
+    for point in &request.points {
+        validate_query_point(*point, "Nav query point")?;
+    }
+    let max_distance = validate_max_distance(request.max_distance_meters)?;
+    let mut world = NavWorld::open(&project_root, &request.map_id)?;
+
+    let mut points = Vec::with_capacity(request.points.len());
+    for point in &request.points {
+        let nearest = world.nearest_walkable(point.x, point.z, max_distance)?;
+        points.push(NearestNavPoint {
+            x: point.x,
+            z: point.z,
+            distance_meters: nearest
+                .as_ref()
+                .map(|(_, distance)| round_meters(*distance)),
+            node: nearest.map(|(node, _)| world.path_point(&node)),
+        });
+    }
+
+    Ok(NearestWorldNavPointsResult {
+        points,
+        loaded_cells: world.cells.len(),
+    })
+}
+
+fn check_world_nav_reachability_blocking(
+    request: CheckWorldNavReachabilityRequest,
+) -> Result<WorldNavReachabilityResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    validate_query_point(request.start, "Nav reachability start")?;
+    let max_snap = validate_max_distance(request.max_snap_distance_meters)?;
+    let mut world = NavWorld::open(&project_root, &request.map_id)?;
+    let targets = match request.targets {
+        Some(targets) => targets,
+        None => poi_targets(&world.map)?,
+    };
+    if targets.len() > MAX_NAV_QUERY_POINTS {
+        return Err(format!(
+            "Nav reachability checks accept at most {} targets",
+            MAX_NAV_QUERY_POINTS
+        ));
+    }
+    for target in &targets {
+        validate_query_point(
+            NavQueryPoint {
+                x: target.x,
+                z: target.z,
+            },
+            "Nav reachability target",
+        )?;
+    }
+
+    let start = world.nearest_walkable(request.start.x, request.start.z, max_snap)?;
+    let costs = match &start {
+        Some((node, _)) => world.flood(node)?,
+        None => BTreeMap::new(),
+    };
+
+    let mut results = Vec::with_capacity(targets.len());
+    for target in targets {
+        let snapped = world.nearest_walkable(target.x, target.z, max_snap)?;
+        let cost = snapped
+            .as_ref()
+            .and_then(|(node, _)| costs.get(node))
+            .copied();
+        results.push(NavReachability {
+            id: target.id,
+            x: target.x,
+            z: target.z,
+            reachable: cost.is_some(),
+            node_id: snapped
+                .as_ref()
+                .map(|(node, _)| world.node(node).id.clone()),
+            cost: cost.map(round_cost),
+            snap_distance_meters: snapped.map(|(_, distance)| round_meters(distance)),
+        });
+    }
+
+    let reachable_count = results.iter().filter(|target| target.reachable).count();
+    Ok(WorldNavReachabilityResult {
+        start_node: start.map(|(node, _)| world.path_point(&node)),
+        unreachable_count: results.len() - reachable_count,
+        reachable_count,
+        targets: results,
+        reachable_nodes: costs.len(),
+        loaded_cells: world.cells.len(),
+    })
+}
+
+fn validate_query_point(point: NavQueryPoint, label: &str) -> Result<(), String> {
+    if !point.x.is_finite() || !point.z.is_finite() {
+        return Err(format!("{} must be finite", label));
+    }
+
+    Ok(())
+}
+
+fn validate_max_distance(value: Option<f64>) -> Result<f64, String> {
+    match value {
+        None => Ok(f64::INFINITY),
+        Some(distance) if distance.is_finite() && distance >= 0.0 => Ok(distance),
+        Some(_) => Err("Nav snap distance must be a non-negative number".to_string()),
+    }
+}
+
+fn round_cost(value: f64) -> f64 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Every cooked object on the `poi` layer, as reachability targets.
+/// 所有位于 `poi` 图层的 cooked 对象，作为可达性目标
+fn poi_targets(map: &CookedMap) -> Result<Vec<NavReachabilityTarget>, String> {
+    let grid = map.cell_grid("objects", WORLD_OBJECT_CELL_FORMAT)?;
+    let mut targets = Vec::new();
+    for cell_key in grid.keys() {
+        let Some(pack) = map.read_cell_pack(&grid, cell_key, WORLD_OBJECT_CELL_FORMAT)? else {
+            continue;
+        };
+        for object in pack["objects"].as_array().into_iter().flatten() {
+            if object["layer"].as_str() != Some("poi") {
+                continue;
+            }
+            let (Some(x), Some(z)) = (
+                object["position"]["x"].as_f64(),
+                object["position"]["z"].as_f64(),
+            ) else {
+                continue;
+            };
+            targets.push(NavReachabilityTarget {
+                id: object["id"].as_str().map(str::to_string),
+                x,
+                z,
+            });
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Shortest costs from `source` to every node of one cell, with predecessors for path refinement.
+/// 单元内从 `source` 到各节点的最短代价，并记录前驱用于路径细化
+fn cell_distances(cell: &NavCell, source: usize) -> (Vec<f64>, Vec<Option<usize>>) {
+    let mut distances = vec![f64::INFINITY; cell.nodes.len()];
+    let mut previous = vec![None; cell.nodes.len()];
+    let mut settled = vec![false; cell.nodes.len()];
+    distances[source] = 0.0;
+    loop {
+        let next = (0..cell.nodes.len())
+            .filter(|index| !settled[*index] && distances[*index].is_finite())
+            .min_by(|left, right| distances[*left].total_cmp(&distances[*right]));
+        let Some(current) = next else {
+            break;
+        };
+        settled[current] = true;
+        for (target, cost) in &cell.links[current] {
+            let distance = distances[current] + cost;
+            if distance < distances[*target] {
+                distances[*target] = distance;
+                previous[*target] = Some(current);
+            }
+        }
+    }
+
+    (distances, previous)
+}
+
+fn ring_cells(center_x: i32, center_z: i32, ring: i32) -> Vec<String> {
+    let mut keys = Vec::new();
+    for dz in -ring..=ring {
+        for dx in -ring..=ring {
+            if dx.abs().max(dz.abs()) == ring {
+                keys.push(format_grid_key(center_x + dx, center_z + dz));
+            }
+        }
+    }
+    keys
+}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .score
+            .total_cmp(&self.score)
+            .then_with(|| other.node.cmp(&self.node))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for QueueEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for QueueEntry {}
+
+impl NavWorld {
+    fn open(project_root: &std::path::Path, map_id: &str) -> Result<Self, String> {
+        let map = CookedMap::open(project_root, map_id)?;
+        let grid = map.cell_grid("nav", COOKED_NAV_CELL_FORMAT)?;
+        let nav_costs = map.asset("objects")["archetypes"]
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(_, archetype)| matches!(archetype["layer"].as_str(), Some("road" | "water")))
+            .filter_map(|(id, archetype)| {
+                archetype["navCost"]
+                    .as_f64()
+                    .filter(|cost| cost.is_finite() && *cost > 0.0)
+                    .map(|cost| (id.clone(), cost))
+            })
+            .collect();
+
+        Ok(Self {
+            map,
+            grid,
+            nav_costs,
+            cells: BTreeMap::new(),
+        })
+    }
+
+    /// Load a nav cell if it was cooked; returns whether it is available.
+    /// 若导航单元已 cook 则加载；返回该单元是否可用
+    fn load_cell(&mut self, cell_key: &str) -> Result<bool, String> {
+        if self.cells.contains_key(cell_key) {
+            return Ok(true);
+        }
+        let Some(pack) = self
+            .map
+            .read_cell_pack(&self.grid, cell_key, COOKED_NAV_CELL_FORMAT)?
+        else {
+            return Ok(false);
+        };
+
+        // EN: Road nodes swap the baked road term for the cheapest road archetype crossing the cell; walkable nodes of a water cell swap the baked water surcharge for the costliest water archetype.
+        // 中文: 道路节点将烘焙的道路项替换为穿过该单元的最便宜道路原型代价；含水体单元的可行走节点将烘焙的水体附加项替换为代价最高的水体原型代价。
+        let modifier_costs = |layer: &'static str| {
+            pack["modifiers"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(move |modifier| modifier["layer"].as_str() == Some(layer))
+                .filter_map(|modifier| {
+                    self.nav_costs
+                        .get(modifier["archetype"].as_str().unwrap_or_default())
+                })
+                .copied()
+        };
+        let road_cost = modifier_costs("road").reduce(f64::min);
+        let water_cost = modifier_costs("water").reduce(f64::max);
+
+        let mut nodes = Vec::new();
+        let mut index_by_id = BTreeMap::new();
+        for node in pack["nodes"].as_array().into_iter().flatten() {
+            let id = node["id"].as_str().unwrap_or_default().to_string();
+            let position = &node["position"];
+            let tags: Vec<String> = node["tags"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect();
+            let mut cost = node["cost"]
+                .as_f64()
+                .filter(|cost| cost.is_finite() && *cost > 0.0)
+                .unwrap_or(DEFAULT_NAV_NODE_COST);
+            let walkable = node["walkable"].as_bool().unwrap_or(false);
+            if walkable {
+                if let Some(road_cost) = road_cost.filter(|_| tags.iter().any(|tag| tag == "road"))
+                {
+                    cost += road_cost - COOKED_ROAD_COST_TERM;
+                }
+                if let Some(water_cost) = water_cost {
+                    cost += water_cost - COOKED_WATER_COST_TERM;
+                }
+            }
+            index_by_id.insert(id.clone(), nodes.len());
+            nodes.push(NavNode {
+                id,
+                position: [
+                    position["x"].as_f64().unwrap_or_default(),
+                    position["y"].as_f64().unwrap_or_default(),
+                    position["z"].as_f64().unwrap_or_default(),
+                ],
+                walkable,
+                cost,
+                tags,
+            });
+        }
+
+        let walkable_index = |value: &Value| {
+            value
+                .as_str()
+                .and_then(|id| index_by_id.get(id))
+                .copied()
+                .filter(|index| nodes[*index].walkable)
+        };
+        let mut links = vec![Vec::new(); nodes.len()];
+        for link in pack["links"].as_array().into_iter().flatten() {
+            let (Some(from), Some(to)) =
+                (walkable_index(&link["from"]), walkable_index(&link["to"]))
+            else {
+                continue;
+            };
+            // EN: Same averaging as createNavLinks, applied to the navCost-adjusted node costs.
+            // 中文: 与 createNavLinks 相同的平均方式，作用于按 navCost 调整后的节点代价。
+            let cost = (nodes[from].cost + nodes[to].cost) * 0.5;
+            links[from].push((to, cost));
+            links[to].push((from, cost));
+        }
+
+        let mut portals = Vec::new();
+        for portal in pack["crossCellLinks"].as_array().into_iter().flatten() {
+            let (Some(from), Some(target_cell)) = (
+                walkable_index(&portal["from"]),
+                portal["targetCell"].as_str(),
+            ) else {
+                continue;
+            };
+            portals.push(NavPortal {
+                from,
+                target_cell: target_cell.to_string(),
+                cost: nodes[from].cost,
+            });
+        }
+
+        let spacing = pack["grid"]["nodeSpacingMeters"]
+            .as_f64()
+            .filter(|spacing| spacing.is_finite() && *spacing > 0.0)
+            .unwrap_or(self.grid.cell_size_meters);
+        self.cells.insert(
+            cell_key.to_string(),
+            NavCell {
+                spacing,
+                nodes,
+                links,
+                portals,
+            },
+        );
+        Ok(true)
+    }
+
+    fn node(&self, node: &NavNodeRef) -> &NavNode {
+        &self.cells[&node.cell].nodes[node.index]
+    }
+
+    fn path_point(&self, node: &NavNodeRef) -> NavPathPoint {
+        let nav_node = self.node(node);
+        NavPathPoint {
+            node_id: nav_node.id.clone(),
+            cell_key: node.cell.clone(),
+            x: nav_node.position[0],
+            y: nav_node.position[1],
+            z: nav_node.position[2],
+            cost: round_cost(nav_node.cost),
+            tags: nav_node.tags.clone(),
+        }
+    }
+
+    fn nearest_in_cell(&self, cell_key: &str, x: f64, z: f64) -> Option<(usize, f64)> {
+        let mut best: Option<(usize, f64)> = None;
+        for (index, node) in self.cells.get(cell_key)?.nodes.iter().enumerate() {
+            let distance = (node.position[0] - x).hypot(node.position[2] - z);
+            if node.walkable && best.is_none_or(|(_, best_distance)| distance < best_distance) {
+                best = Some((index, distance));
+            }
+        }
+        best
+    }
+
+    /// Nearest walkable node across the map, loading rings of cells until no closer node can exist.
+    /// 整张地图上最近的可行走节点，逐圈加载单元直到不可能存在更近的节点
+    fn nearest_walkable(
+        &mut self,
+        x: f64,
+        z: f64,
+        max_distance: f64,
+    ) -> Result<Option<(NavNodeRef, f64)>, String> {
+        let size = self.grid.cell_size_meters;
+        let center_x = (x / size).floor() as i32;
+        let center_z = (z / size).floor() as i32;
+        let Some(max_ring) = self
+            .grid
+            .keys()
+            .filter_map(|key| parse_grid_key(key))
+            .map(|(cell_x, cell_z)| (cell_x - center_x).abs().max((cell_z - center_z).abs()))
+            .max()
+        else {
+            return Ok(None);
+        };
+
+        let mut best: Option<(NavNodeRef, f64)> = None;
+        for ring in 0..=max_ring {
+            // EN: Cells in ring r are at least r - 1 cell sizes away from any point in the center cell.
+            // 中文: 第 r 圈的单元与中心单元内任意点的距离至少为 r - 1 个单元尺寸。
+            let ring_distance = (f64::from(ring) - 1.0).max(0.0) * size;
+            if ring_distance > max_distance
+                || best
+                    .as_ref()
+                    .is_some_and(|(_, distance)| ring_distance > *distance)
+            {
+                break;
+            }
+            for cell_key in ring_cells(center_x, center_z, ring) {
+                if !self.grid.contains(&cell_key) || !self.load_cell(&cell_key)? {
+                    continue;
+                }
+                let closer = self
+                    .nearest_in_cell(&cell_key, x, z)
+                    .filter(|(_, distance)| {
+                        best.as_ref()
+                            .is_none_or(|(_, best_distance)| distance < best_distance)
+                    });
+                if let Some((index, distance)) = closer {
+                    best = Some((
+                        NavNodeRef {
+                            cell: cell_key,
+                            index,
+                        },
+                        distance,
+                    ));
+                }
+            }
+        }
+
+        Ok(best.filter(|(_, distance)| *distance <= max_distance))
+    }
+
+    /// Walkable node a portal lands on in its target cell, as WorldNavQuery resolves it.
+    /// 入口在目标单元中落到的可行走节点，解析方式与 WorldNavQuery 一致
+    fn resolve_portal(
+        &mut self,
+        source: [f64; 3],
+        target_cell: &str,
+    ) -> Result<Option<NavNodeRef>, String> {
+        if !self.grid.contains(target_cell) || !self.load_cell(target_cell)? {
+            return Ok(None);
+        }
+
+        Ok(self
+            .nearest_in_cell(target_cell, source[0], source[2])
+            .map(|(index, _)| NavNodeRef {
+                cell: target_cell.to_string(),
+                index,
+            }))
+    }
+
+    /// Edges of the portal graph: in-cell shortest routes to portal nodes or the goal, plus portal crossings.
+    /// 入口图的边：单元内到入口节点或终点的最短路线，以及穿越入口的连接
+    fn portal_edges(
+        &mut self,
+        node: &NavNodeRef,
+        goal: &NavNodeRef,
+    ) -> Result<Vec<(NavNodeRef, f64)>, String> {
+        let cell = &self.cells[&node.cell];
+        let (distances, _) = cell_distances(cell, node.index);
+        let mut edges = Vec::new();
+        let mut crossings = Vec::new();
+        for portal in &cell.portals {
+            if portal.from == node.index {
+                crossings.push((portal.target_cell.clone(), portal.cost));
+            } else if distances[portal.from].is_finite() {
+                edges.push((
+                    NavNodeRef {
+                        cell: node.cell.clone(),
+                        index: portal.from,
+                    },
+                    distances[portal.from],
+                ));
+            }
+        }
+        if goal.cell == node.cell && goal.index != node.index && distances[goal.index].is_finite() {
+            edges.push((goal.clone(), distances[goal.index]));
+        }
+
+        let source = cell.nodes[node.index].position;
+        for (target_cell, cost) in crossings {
+            if let Some(target) = self.resolve_portal(source, &target_cell)? {
+                edges.push((target, cost));
+            }
+        }
+        Ok(edges)
+    }
+
+    /// Every node edge: in-cell links plus portal crossings.
+    /// 节点的全部边：单元内连接与入口穿越
+    fn node_edges(&mut self, node: &NavNodeRef) -> Result<Vec<(NavNodeRef, f64)>, String> {
+        let cell = &self.cells[&node.cell];
+        let mut edges: Vec<(NavNodeRef, f64)> = cell.links[node.index]
+            .iter()
+            .map(|(index, cost)| {
+                (
+                    NavNodeRef {
+                        cell: node.cell.clone(),
+                        index: *index,
+                    },
+                    *cost,
+                )
+            })
+            .collect();
+        let source = cell.nodes[node.index].position;
+        let crossings: Vec<(String, f64)> = cell
+            .portals
+            .iter()
+            .filter(|portal| portal.from == node.index)
+            .map(|portal| (portal.target_cell.clone(), portal.cost))
+            .collect();
+        for (target_cell, cost) in crossings {
+            if let Some(target) = self.resolve_portal(source, &target_cell)? {
+                edges.push((target, cost));
+            }
+        }
+        Ok(edges)
+    }
+
+    /// A* over the portal graph, then each in-cell step is refined into node-by-node routes.
+    /// 先在入口图上执行 A*，再将每段单元内步骤细化为逐节点路线
+    fn find_path(
+        &mut self,
+        start: &NavNodeRef,
+        goal: &NavNodeRef,
+    ) -> Result<Option<(f64, Vec<NavNodeRef>)>, String> {
+        let spacing = self.cells[&start.cell].spacing;
+        let goal_position = self.node(goal).position;
+        let heuristic = |position: [f64; 3]| {
+            (position[0] - goal_position[0]).hypot(position[2] - goal_position[2]) / spacing
+                * HEURISTIC_COST_PER_SPACING
+        };
+
+        let mut best_costs = BTreeMap::from([(start.clone(), 0.0)]);
+        let mut came_from: BTreeMap<NavNodeRef, NavNodeRef> = BTreeMap::new();
+        let mut open = BinaryHeap::from([QueueEntry {
+            score: heuristic(self.node(start).position),
+            node: start.clone(),
+        }]);
+        let mut expanded = 0;
+        while let Some(QueueEntry { score, node }) = open.pop() {
+            let cost = best_costs[&node];
+            if node == *goal {
+                return Ok(Some((cost, self.refine_path(&came_from, goal))));
+            }
+            if score > cost + heuristic(self.node(&node).position) + SEARCH_COST_EPSILON {
+                continue;
+            }
+            expanded += 1;
+            if expanded > MAX_NAV_SEARCH_STATES {
+                return Err(format!(
+                    "Nav path search exceeded {} states",
+                    MAX_NAV_SEARCH_STATES
+                ));
+            }
+
+            for (next, step) in self.portal_edges(&node, goal)? {
+                let next_cost = cost + step;
+                if best_costs.get(&next).is_some_and(|best| next_cost >= *best) {
+                    continue;
+                }
+                best_costs.insert(next.clone(), next_cost);
+                came_from.insert(next.clone(), node.clone());
+                open.push(QueueEntry {
+                    score: next_cost + heuristic(self.node(&next).position),
+                    node: next,
+                });
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn refine_path(
+        &self,
+        came_from: &BTreeMap<NavNodeRef, NavNodeRef>,
+        goal: &NavNodeRef,
+    ) -> Vec<NavNodeRef> {
+        let mut chain = vec![goal.clone()];
+        while let Some(previous) = chain.last().and_then(|node| came_from.get(node)) {
+            chain.push(previous.clone());
+        }
+        chain.reverse();
+
+        let mut path = vec![chain[0].clone()];
+        for pair in chain.windows(2) {
+            let (from, to) = (&pair[0], &pair[1]);
+            if from.cell != to.cell {
+                path.push(to.clone());
+                continue;
+            }
+
+            let (_, previous) = cell_distances(&self.cells[&from.cell], from.index);
+            let mut segment = Vec::new();
+            let mut index = to.index;
+            while index != from.index {
+                segment.push(NavNodeRef {
+                    cell: from.cell.clone(),
+                    index,
+                });
+                match previous[index] {
+                    Some(before) => index = before,
+                    None => break,
+                }
+            }
+            path.extend(segment.into_iter().rev());
+        }
+        path
+    }
+
+    /// Dijkstra flood from `start` over every reachable node of the map.
+    /// 从 `start` 出发对整张地图所有可达节点执行 Dijkstra 泛洪
+    fn flood(&mut self, start: &NavNodeRef) -> Result<BTreeMap<NavNodeRef, f64>, String> {
+        let mut costs = BTreeMap::from([(start.clone(), 0.0)]);
+        let mut open = BinaryHeap::from([QueueEntry {
+            score: 0.0,
+            node: start.clone(),
+        }]);
+        while let Some(QueueEntry { score, node }) = open.pop() {
+            if score > costs[&node] + SEARCH_COST_EPSILON {
+                continue;
+            }
+            if costs.len() > MAX_NAV_SEARCH_STATES {
+                return Err(format!(
+                    "Nav reachability flood exceeded {} nodes",
+                    MAX_NAV_SEARCH_STATES
+                ));
+            }
+
+            for (next, step) in self.node_edges(&node)? {
+                let next_cost = score + step;
+                if costs.get(&next).is_some_and(|best| next_cost >= *best) {
+                    continue;
+                }
+                costs.insert(next.clone(), next_cost);
+                open.push(QueueEntry {
+                    score: next_cost,
+                    node: next,
+                });
+            }
+        }
+
+        Ok(costs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{FIXTURE_MAP_ID, FixtureProject, assert_close};
+    use serde_json::json;
+
+    const CELL_SIZE: f64 = 32.0;
+    const RESOLUTION: i32 = 4;
+
+    /// Hand-built 4x4 nav cell in the createNavCellPack layout; nodes cost 1 unless overridden.
+    /// 按 createNavCellPack 布局手工构建的 4x4 导航单元；节点代价默认为 1
+    fn nav_cell(
+        cx: i32,
+        cz: i32,
+        blocked: &[(i32, i32)],
+        overrides: &[((i32, i32), f64, &[&str])],
+        modifiers: Value,
+    ) -> Value {
+        let key = format_grid_key(cx, cz);
+        let node_id = |x: i32, z: i32| format!("{}:{},{}", key, x, z);
+        let walkable = |x: i32, z: i32| !blocked.contains(&(x, z));
+        let spacing = CELL_SIZE / f64::from(RESOLUTION);
+
+        let mut nodes = Vec::new();
+        let mut links = Vec::new();
+        let mut portals = Vec::new();
+        for z in 0..RESOLUTION {
+            for x in 0..RESOLUTION {
+                let (cost, tags) = overrides
+                    .iter()
+                    .find(|(grid, _, _)| *grid == (x, z))
+                    .map_or((1.0, Vec::new()), |(_, cost, tags)| (*cost, tags.to_vec()));
+                nodes.push(json!({
+                    "id": node_id(x, z),
+                    "position": {
+                        "x": f64::from(cx) * CELL_SIZE + (f64::from(x) + 0.5) * spacing,
+                        "y": 0.0,
+                        "z": f64::from(cz) * CELL_SIZE + (f64::from(z) + 0.5) * spacing,
+                    },
+                    "walkable": walkable(x, z),
+                    "cost": if walkable(x, z) { cost } else { 9999.0 },
+                    "tags": tags,
+                }));
+                if !walkable(x, z) {
+                    continue;
+                }
+                for (dx, dz) in [(1, 0), (0, 1)] {
+                    if x + dx < RESOLUTION && z + dz < RESOLUTION && walkable(x + dx, z + dz) {
+                        links.push(json!({ "from": node_id(x, z), "to": node_id(x + dx, z + dz) }));
+                    }
+                }
+                for (edge, target) in [
+                    (x == 0, (cx - 1, cz)),
+                    (x == RESOLUTION - 1, (cx + 1, cz)),
+                    (z == 0, (cx, cz - 1)),
+                    (z == RESOLUTION - 1, (cx, cz + 1)),
+                ] {
+                    if edge {
+                        portals.push(json!({
+                            "from": node_id(x, z),
+                            "targetCell": format_grid_key(target.0, target.1),
+                        }));
+                    }
+                }
+            }
+        }
+
+        json!({
+            "format": COOKED_NAV_CELL_FORMAT,
+            "cell": { "key": key },
+            "grid": { "resolution": RESOLUTION, "nodeSpacingMeters": spacing },
+            "nodes": nodes,
+            "links": links,
+            "crossCellLinks": portals,
+            "modifiers": modifiers,
+        })
+    }
+
+    fn synthetic_world(name: &str, cells: Vec<Value>) -> (FixtureProject, NavWorld) {
+        let project = FixtureProject::new("nav", name);
+
+        let mut entries = serde_json::Map::new();
+        for cell in cells {
+            let key = cell["cell"]["key"].as_str().unwrap().to_string();
+            let (cx, cz) = parse_grid_key(&key).unwrap();
+            let path = project.cooked_path(&format!("nav/c_{}_{}.json", cx, cz));
+            entries.insert(
+                key,
+                project.write_artifact(&path, cell.to_string().as_bytes()),
+            );
+        }
+        project.write_cooked_manifest(json!({
+                "nav": {
+                    "format": COOKED_NAV_CELL_FORMAT,
+                    "cellSizeMeters": CELL_SIZE,
+                    "cells": entries,
+                },
+                "objects": {
+                    "archetypes": {
+                        "road-dirt-segment": { "layer": "road", "navCost": 0.45 },
+                        "road-rocky-segment": { "layer": "road", "navCost": 0.65 },
+                        "stream-segment": { "layer": "water", "navCost": 2.5 },
+                        "river-segment": { "layer": "water", "navCost": 3.5 },
+                    }
+                }
+        }));
+
+        let world = NavWorld::open(project.root(), FIXTURE_MAP_ID).unwrap();
+        (project, world)
+    }
+
+    fn node_ref(cell: &str, x: i32, z: i32) -> NavNodeRef {
+        NavNodeRef {
+            cell: cell.to_string(),
+            index: (z * RESOLUTION + x) as usize,
+        }
+    }
+
+    /// Every consecutive pair is an in-cell neighbour or a portal crossing to the adjacent cell.
+    /// 每对相邻路径点要么是单元内邻居，要么是跨到相邻单元的入口
+    fn assert_connected(world: &NavWorld, path: &[NavNodeRef]) {
+        for pair in path.windows(2) {
+            let from = world.node(&pair[0]).position;
+            let to = world.node(&pair[1]).position;
+            let step = (from[0] - to[0]).abs() + (from[2] - to[2]).abs();
+            assert_close(step, CELL_SIZE / f64::from(RESOLUTION));
+            assert!(world.node(&pair[1]).walkable);
+        }
+    }
+
+    #[test]
+    fn find_path_crosses_cells_and_matches_flood() {
+        let (_project, mut world) = synthetic_world(
+            "cross",
+            vec![
+                nav_cell(0, 0, &[], &[], json!([])),
+                nav_cell(1, 0, &[], &[], json!([])),
+            ],
+        );
+        world.load_cell("0,0").unwrap();
+        world.load_cell("1,0").unwrap();
+
+        let start = node_ref("0,0", 0, 0);
+        let goal = node_ref("1,0", 3, 1);
+        let (cost, path) = world.find_path(&start, &goal).unwrap().unwrap();
+        assert_close(cost, 8.0);
+        assert_eq!(path.first(), Some(&start));
+        assert_eq!(path.last(), Some(&goal));
+        assert_eq!(path.len(), 9);
+        assert_connected(&world, &path);
+
+        let costs = world.flood(&start).unwrap();
+        assert_eq!(costs.len(), 32);
+        assert_close(costs[&goal], cost);
+    }
+
+    #[test]
+    fn find_path_detours_around_walls_and_reports_unreachable() {
+        let wall = [(2, 0), (2, 1), (2, 2)];
+        let (_project, mut world) = synthetic_world(
+            "detour",
+            vec![
+                nav_cell(0, 0, &wall, &[], json!([])),
+                nav_cell(3, 0, &[], &[], json!([])),
+            ],
+        );
+        world.load_cell("0,0").unwrap();
+
+        let start = node_ref("0,0", 0, 0);
+        let (cost, path) = world
+            .find_path(&start, &node_ref("0,0", 3, 0))
+            .unwrap()
+            .unwrap();
+        assert_close(cost, 9.0);
+        assert_connected(&world, &path);
+        assert!(path.iter().all(|node| node.cell == "0,0"));
+
+        // EN: Cell 3,0 is an island: no cooked cell sits between it and cell 0,0.
+        // 中文: 单元 3,0 是孤岛：它与单元 0,0 之间没有任何已 cook 的单元。
+        let (snapped, _) = world.nearest_walkable(100.0, 4.0, 64.0).unwrap().unwrap();
+        assert_eq!(snapped, node_ref("3,0", 0, 0));
+        assert!(world.find_path(&start, &snapped).unwrap().is_none());
+        let costs = world.flood(&start).unwrap();
+        assert_eq!(costs.len(), 13);
+        assert!(!costs.contains_key(&snapped));
+    }
+
+    #[test]
+    fn load_cell_replaces_baked_road_and_water_terms() {
+        let (_project, mut world) = synthetic_world(
+            "costs",
+            vec![
+                nav_cell(
+                    0,
+                    0,
+                    &[],
+                    &[((0, 0), 0.55, &["road"]), ((1, 0), 0.8, &["road"])],
+                    json!([
+                        { "layer": "road", "archetype": "road-dirt-segment" },
+                        { "layer": "road", "archetype": "road-rocky-segment" },
+                    ]),
+                ),
+                nav_cell(
+                    1,
+                    0,
+                    &[(3, 3)],
+                    &[((0, 0), 1.1, &["road"]), ((1, 0), 2.05, &[])],
+                    json!([
+                        { "layer": "road", "archetype": "road-rocky-segment" },
+                        { "layer": "water", "archetype": "stream-segment" },
+                        { "layer": "water", "archetype": "river-segment" },
+                    ]),
+                ),
+            ],
+        );
+        world.load_cell("0,0").unwrap();
+        world.load_cell("1,0").unwrap();
+        let cost =
+            |world: &NavWorld, cell: &str, x: i32, z: i32| world.node(&node_ref(cell, x, z)).cost;
+
+        // EN: Only the 0.55 road term changes, so a road node's slope surcharge is kept.
+        // 中文: 只替换 0.55 的道路项，因此道路节点的坡度附加代价保持不变。
+        assert_close(cost(&world, "0,0", 0, 0), 0.45);
+        assert_close(cost(&world, "0,0", 1, 0), 0.7);
+        assert_close(cost(&world, "0,0", 2, 0), 1.0);
+
+        assert_close(cost(&world, "1,0", 0, 0), 0.65 + 3.5);
+        assert_close(cost(&world, "1,0", 1, 0), 2.05 - 0.55 + 3.5);
+        assert_close(cost(&world, "1,0", 2, 0), 1.0 - 0.55 + 3.5);
+        assert_close(cost(&world, "1,0", 3, 3), 9999.0);
+    }
+}
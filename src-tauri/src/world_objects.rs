@@ -0,0 +1,1080 @@
+// Native world-object-cell-pack-v1 reader and per-object editing commands.
+// 原生 world-object-cell-pack-v1 读取与逐对象编辑命令
+//
+// EN: Mirrors src/editor/runtime/world-objects/WorldObjectStorage.ts: each object lives in the 512 m cell containing its position, packs are pretty JSON, and the manifest carries objectCount, byteLength and sha256 per cell.
+// 中文: 与 src/editor/runtime/world-objects/WorldObjectStorage.ts 一致：每个对象位于其坐标所在的 512 米单元，单元包为格式化 JSON，清单为每个单元记录 objectCount、byteLength 与 sha256。
+
+use crate::commands::{
+    MAPS_DIR, recover_safe_write, safe_write, validate_cook_project_path,
+    validate_relative_file_path, validate_single_path_segment,
+};
+use crate::map_layout::{
+    WORLD_OBJECT_CELL_FORMAT, WORLD_OBJECT_CELLS_DIRECTORY, WORLD_OBJECT_MANIFEST_FORMAT,
+    WORLD_OBJECTS_PATH, WorldRect, cell_file_name, compare_grid_keys, format_grid_key,
+    parse_grid_key, sha256_hex,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const WORLD_OBJECT_CELL_EXTENSION: &str = "objectpack";
+const WORLD_OBJECT_CELL_VERSION: u64 = 1;
+const DEFAULT_OBJECT_RADIUS_METERS: f64 = 4.0;
+const OBJECT_POSITION_PRECISION: f64 = 1000.0;
+const PROTECTED_OBJECT_FIELDS: &[&str] = &["id", "position"];
+
+/// World-space position of an object in meters.
+/// 对象的世界空间位置（米）
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct WorldObjectPosition {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) z: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWorldObjectsRequest {
+    project_path: String,
+    map_id: String,
+    #[serde(default)]
+    rect: Option<WorldRect>,
+    #[serde(default)]
+    layer: Option<String>,
+    #[serde(default)]
+    archetype: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadWorldObjectCellRequest {
+    project_path: String,
+    map_id: String,
+    cell_key: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertWorldObjectsRequest {
+    project_path: String,
+    map_id: String,
+    objects: Vec<Value>,
+}
+
+/// Transform and field changes for one object; omitted values stay unchanged.
+/// 单个对象的变换与字段修改；未提供的值保持不变
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldObjectUpdate {
+    id: String,
+    #[serde(default)]
+    position: Option<WorldObjectPosition>,
+    #[serde(default)]
+    rotation_y: Option<f64>,
+    #[serde(default)]
+    scale: Option<f64>,
+    #[serde(default)]
+    fields: Option<Map<String, Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateWorldObjectsRequest {
+    project_path: String,
+    map_id: String,
+    updates: Vec<WorldObjectUpdate>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveWorldObjectsRequest {
+    project_path: String,
+    map_id: String,
+    ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldObjectSummary {
+    id: String,
+    cell_key: String,
+    layer: String,
+    archetype: String,
+    position: WorldObjectPosition,
+    rotation_y: f64,
+    scale: f64,
+    bounds_meters: Option<WorldRect>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldObjectListResult {
+    objects: Vec<WorldObjectSummary>,
+    scanned_cells: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldObjectCellContents {
+    cell_key: String,
+    path: String,
+    object_count: usize,
+    byte_length: usize,
+    sha256: String,
+    cell: Value,
+    objects: Vec<Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldObjectEditResult {
+    affected_count: usize,
+    updated_cells: Vec<String>,
+}
+
+/// One decoded cell pack plus the bytes it was read from.
+/// 一个已解码的单元包及其读取时的原始字节
+struct WorldObjectCell {
+    pack: Value,
+    original_bytes: Option<Vec<u8>>,
+}
+
+/// Object manifest plus lazily loaded cell packs for one map.
+/// 单张地图的对象清单及按需加载的单元包
+pub(crate) struct WorldObjectSession {
+    map_root: PathBuf,
+    document: Value,
+    pub(crate) cell_size_meters: f64,
+    cells_directory: String,
+    cells: BTreeMap<String, WorldObjectCell>,
+    dirty_cells: BTreeSet<String>,
+}
+
+// --- World object commands / 世界对象命令 ---
+
+/// List objects with their archetype and transform, optionally filtered by area, layer or archetype.
+/// 列出对象及其原型与变换，可按区域、图层或原型过滤
+#[tauri::command]
+pub async fn list_world_objects(
+    request: ListWorldObjectsRequest,
+) -> Result<WorldObjectListResult, String> {
+    tauri::async_runtime::spawn_blocking(move || list_world_objects_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join world object list task: {}", e))?
+}
+
+/// Decode one cell pack after checking it against the manifest integrity entry.
+/// 按清单完整性条目校验后解码单个单元包
+#[tauri::command]
+pub async fn read_world_object_cell(
+    request: ReadWorldObjectCellRequest,
+) -> Result<WorldObjectCellContents, String> {
+    tauri::async_runtime::spawn_blocking(move || read_world_object_cell_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join world object cell read task: {}", e))?
+}
+
+/// Insert new objects into the cells containing their positions.
+/// 将新对象插入其坐标所在的单元
+#[tauri::command]
+pub async fn insert_world_objects(
+    request: InsertWorldObjectsRequest,
+) -> Result<WorldObjectEditResult, String> {
+    tauri::async_runtime::spawn_blocking(move || insert_world_objects_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join world object insert task: {}", e))?
+}
+
+/// Update objects by id; a moved object carries its bounds and spline along and may change cell.
+/// 按 id 更新对象；移动的对象会同步平移包围盒与样条，并可能更换单元
+#[tauri::command]
+pub async fn update_world_objects(
+    request: UpdateWorldObjectsRequest,
+) -> Result<WorldObjectEditResult, String> {
+    tauri::async_runtime::spawn_blocking(move || update_world_objects_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join world object update task: {}", e))?
+}
+
+/// Remove objects by id.
+/// 按 id 删除对象
+#[tauri::command]
+pub async fn remove_world_objects(
+    request: RemoveWorldObjectsRequest,
+) -> Result<WorldObjectEditResult, String> {
+    tauri::async_runtime::spawn_blocking(move || remove_world_objects_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join world object remove task: {}", e))?
+}
+
+fn list_world_objects_blocking(
+    request: ListWorldObjectsRequest,
+) -> Result<WorldObjectListResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    if let Some(rect) = &request.rect {
+        rect.validate("World object list rect")?;
+    }
+
+    let mut session = WorldObjectSession::open(&project_root, &request.map_id)?;
+    let cell_keys = session.cell_keys_overlapping(request.rect.as_ref());
+    let mut objects = Vec::new();
+    for cell_key in &cell_keys {
+        for object in session.cell_objects(cell_key)? {
+            let summary = summarize_object(cell_key, object)?;
+            let matches_rect = request.rect.as_ref().is_none_or(|rect| {
+                summary.position.x >= rect.min_x
+                    && summary.position.x <= rect.max_x
+                    && summary.position.z >= rect.min_z
+                    && summary.position.z <= rect.max_z
+            });
+            if matches_rect
+                && request
+                    .layer
+                    .as_ref()
+                    .is_none_or(|layer| *layer == summary.layer)
+                && request
+                    .archetype
+                    .as_ref()
+                    .is_none_or(|archetype| *archetype == summary.archetype)
+            {
+                objects.push(summary);
+            }
+        }
+    }
+
+    Ok(WorldObjectListResult {
+        objects,
+        scanned_cells: cell_keys.len(),
+    })
+}
+
+fn read_world_object_cell_blocking(
+    request: ReadWorldObjectCellRequest,
+) -> Result<WorldObjectCellContents, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let mut session = WorldObjectSession::open(&project_root, &request.map_id)?;
+    let cell_ref = session.cell_ref(&request.cell_key)?.clone();
+    let objects = session.cell_objects(&request.cell_key)?.to_vec();
+    let pack = &session.cells[&request.cell_key].pack;
+
+    Ok(WorldObjectCellContents {
+        cell_key: request.cell_key,
+        path: cell_ref["path"].as_str().unwrap_or_default().to_string(),
+        object_count: objects.len(),
+        byte_length: cell_ref["byteLength"].as_u64().unwrap_or_default() as usize,
+        sha256: cell_ref["sha256"].as_str().unwrap_or_default().to_string(),
+        cell: pack["cell"].clone(),
+        objects,
+    })
+}
+
+fn insert_world_objects_blocking(
+    request: InsertWorldObjectsRequest,
+) -> Result<WorldObjectEditResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    if request.objects.is_empty() {
+        return Err("World object insert requires at least one object".to_string());
+    }
+
+    let mut session = WorldObjectSession::open(&project_root, &request.map_id)?;
+    session.load_all_cells()?;
+    let affected_count = request.objects.len();
+    for object in request.objects {
+        let object = session.normalize_new_object(object)?;
+        session.insert_object(object)?;
+    }
+
+    Ok(WorldObjectEditResult {
+        affected_count,
+        updated_cells: session.save()?,
+    })
+}
+
+fn update_world_objects_blocking(
+    request: UpdateWorldObjectsRequest,
+) -> Result<WorldObjectEditResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    if request.updates.is_empty() {
+        return Err("World object update requires at least one change".to_string());
+    }
+
+    let mut session = WorldObjectSession::open(&project_root, &request.map_id)?;
+    session.load_all_cells()?;
+    let affected_count = request.updates.len();
+    for update in request.updates {
+        let mut object = session
+            .take_object(&update.id)?
+            .ok_or_else(|| format!("Unknown world object '{}'", update.id))?;
+        apply_object_update(&mut object, &update)?;
+        session.validate_object(&object)?;
+        session.insert_object(object)?;
+    }
+
+    Ok(WorldObjectEditResult {
+        affected_count,
+        updated_cells: session.save()?,
+    })
+}
+
+fn remove_world_objects_blocking(
+    request: RemoveWorldObjectsRequest,
+) -> Result<WorldObjectEditResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    if request.ids.is_empty() {
+        return Err("World object remove requires at least one id".to_string());
+    }
+
+    let mut session = WorldObjectSession::open(&project_root, &request.map_id)?;
+    session.load_all_cells()?;
+    let ids: BTreeSet<&String> = request.ids.iter().collect();
+    for id in &ids {
+        session
+            .take_object(id)?
+            .ok_or_else(|| format!("Unknown world object '{}'", id))?;
+    }
+
+    Ok(WorldObjectEditResult {
+        affected_count: ids.len(),
+        updated_cells: session.save()?,
+    })
+}
+
+fn summarize_object(cell_key: &str, object: &Value) -> Result<WorldObjectSummary, String> {
+    let id = object["id"].as_str().unwrap_or_default();
+    let position = read_position(object)
+        .ok_or_else(|| format!("World object '{}' has an invalid position", id))?;
+    Ok(WorldObjectSummary {
+        id: id.to_string(),
+        cell_key: cell_key.to_string(),
+        layer: object["layer"].as_str().unwrap_or_default().to_string(),
+        archetype: object["archetype"].as_str().unwrap_or_default().to_string(),
+        position,
+        rotation_y: object["rotationY"].as_f64().unwrap_or(0.0),
+        scale: object["scale"].as_f64().unwrap_or(1.0),
+        bounds_meters: WorldRect::from_value(&object["boundsMeters"]),
+    })
+}
+
+/// Apply one update; moving an object shifts its bounds and spline points by the same offset.
+/// 应用一次更新；移动对象时其包围盒与样条点按相同偏移平移
+fn apply_object_update(object: &mut Value, update: &WorldObjectUpdate) -> Result<(), String> {
+    if let Some(fields) = &update.fields {
+        for (key, value) in fields {
+            if PROTECTED_OBJECT_FIELDS.contains(&key.as_str()) {
+                return Err(format!(
+                    "World object field '{}' cannot be changed through fields",
+                    key
+                ));
+            }
+            if value.is_null() {
+                if let Some(entry) = object.as_object_mut() {
+                    entry.remove(key);
+                }
+            } else {
+                object[key] = value.clone();
+            }
+        }
+    }
+
+    if let Some(rotation_y) = update.rotation_y {
+        if !rotation_y.is_finite() {
+            return Err("World object rotationY must be finite".to_string());
+        }
+        object["rotationY"] = json_number(rotation_y);
+    }
+    if let Some(scale) = update.scale {
+        if !scale.is_finite() || scale <= 0.0 {
+            return Err("World object scale must be a positive number".to_string());
+        }
+        object["scale"] = json_number(scale);
+    }
+
+    let Some(position) = update.position else {
+        return Ok(());
+    };
+    if ![position.x, position.y, position.z]
+        .iter()
+        .all(|value| value.is_finite())
+    {
+        return Err("World object position must be finite".to_string());
+    }
+    let previous = read_position(object)
+        .ok_or_else(|| format!("World object '{}' has an invalid position", update.id))?;
+    let offset_x = round_meters(position.x) - previous.x;
+    let offset_z = round_meters(position.z) - previous.z;
+    object["position"] = json!({
+        "x": json_number(round_meters(position.x)),
+        "y": json_number(round_meters(position.y)),
+        "z": json_number(round_meters(position.z)),
+    });
+    if let Some(bounds) = object
+        .get_mut("boundsMeters")
+        .and_then(Value::as_object_mut)
+    {
+        for (key, offset) in [
+            ("minX", offset_x),
+            ("maxX", offset_x),
+            ("minZ", offset_z),
+            ("maxZ", offset_z),
+        ] {
+            if let Some(value) = bounds.get(key).and_then(Value::as_f64) {
+                bounds.insert(key.to_string(), json_number(round_meters(value + offset)));
+            }
+        }
+    }
+    if let Some(points) = object
+        .get_mut("spline")
+        .and_then(|spline| spline.get_mut("points"))
+        .and_then(Value::as_array_mut)
+    {
+        for point in points {
+            for (key, offset) in [("x", offset_x), ("z", offset_z)] {
+                if let Some(value) = point[key].as_f64() {
+                    point[key] = json_number(round_meters(value + offset));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn read_position(object: &Value) -> Option<WorldObjectPosition> {
+    let position = &object["position"];
+    Some(WorldObjectPosition {
+        x: position["x"].as_f64().filter(|value| value.is_finite())?,
+        y: position["y"].as_f64().filter(|value| value.is_finite())?,
+        z: position["z"].as_f64().filter(|value| value.is_finite())?,
+    })
+}
+
+fn bounds_value(min_x: f64, min_z: f64, max_x: f64, max_z: f64) -> Value {
+    json!({
+        "minX": json_number(round_meters(min_x)),
+        "minZ": json_number(round_meters(min_z)),
+        "maxX": json_number(round_meters(max_x)),
+        "maxZ": json_number(round_meters(max_z)),
+    })
+}
+
+pub(crate) fn round_meters(value: f64) -> f64 {
+    (value * OBJECT_POSITION_PRECISION).round() / OBJECT_POSITION_PRECISION
+}
+
+/// JSON number that prints whole values without a fraction, like `JSON.stringify`.
+/// 整数值不带小数部分输出的 JSON 数字，与 `JSON.stringify` 一致
+pub(crate) fn json_number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 9_007_199_254_740_992.0 {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+impl WorldObjectSession {
+    pub(crate) fn open(project_root: &Path, map_id: &str) -> Result<Self, String> {
+        validate_single_path_segment(map_id, "map_id")?;
+        let map_root = project_root.join(MAPS_DIR).join(map_id);
+        let manifest_path = map_root.join(WORLD_OBJECTS_PATH);
+        recover_safe_write(&manifest_path)?;
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| format!("Failed to read world object manifest: {}", e))?;
+        let document: Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse world object manifest: {}", e))?;
+
+        if document["format"].as_str() != Some(WORLD_OBJECT_MANIFEST_FORMAT) {
+            return Err(format!(
+                "World object manifest must use the {} format",
+                WORLD_OBJECT_MANIFEST_FORMAT
+            ));
+        }
+        if document["cellFormat"].as_str() != Some(WORLD_OBJECT_CELL_FORMAT) {
+            return Err(format!(
+                "World object cells must use the {} format",
+                WORLD_OBJECT_CELL_FORMAT
+            ));
+        }
+
+        let cell_size_meters = document["cellSizeMeters"]
+            .as_f64()
+            .filter(|size| size.is_finite() && *size > 0.0)
+            .ok_or_else(|| "World object cell size must be a positive number".to_string())?;
+        let cells_directory = document["cellsDirectory"]
+            .as_str()
+            .unwrap_or(WORLD_OBJECT_CELLS_DIRECTORY)
+            .to_string();
+        validate_relative_file_path(&cells_directory, "World object cells directory")?;
+        let cells = document["cells"]
+            .as_object()
+            .ok_or_else(|| "World object manifest must contain cell metadata".to_string())?;
+        if let Some(key) = cells.keys().find(|key| parse_grid_key(key).is_none()) {
+            return Err(format!("Invalid world object cell key '{}'", key));
+        }
+
+        Ok(Self {
+            map_root,
+            document,
+            cell_size_meters,
+            cells_directory,
+            cells: BTreeMap::new(),
+            dirty_cells: BTreeSet::new(),
+        })
+    }
+
+    /// Archetype definition from the manifest.
+    /// 清单中的原型定义
+    pub(crate) fn archetype(&self, archetype_id: &str) -> Option<&Value> {
+        self.document["archetypes"]
+            .get(archetype_id)
+            .filter(|archetype| archetype.is_object())
+    }
+
+    /// Cell key containing a world position, matching getWorldObjectCellKey.
+    /// 包含世界坐标的单元键，与 getWorldObjectCellKey 一致
+    pub(crate) fn cell_key_for_position(&self, x: f64, z: f64) -> String {
+        format_grid_key(
+            (x / self.cell_size_meters).floor() as i32,
+            (z / self.cell_size_meters).floor() as i32,
+        )
+    }
+
+    /// Manifest cell keys in grid order, limited to cells touching `rect` when given.
+    /// 按网格顺序返回清单中的单元键；提供 `rect` 时仅包含与其相交的单元
+    pub(crate) fn cell_keys_overlapping(&self, rect: Option<&WorldRect>) -> Vec<String> {
+        let size = self.cell_size_meters;
+        let mut keys: Vec<String> = self.document["cells"]
+            .as_object()
+            .into_iter()
+            .flat_map(|cells| cells.keys())
+            .filter(|key| {
+                parse_grid_key(key).is_some_and(|(cx, cz)| {
+                    rect.is_none_or(|rect| {
+                        cx as f64 * size <= rect.max_x
+                            && (cx + 1) as f64 * size >= rect.min_x
+                            && cz as f64 * size <= rect.max_z
+                            && (cz + 1) as f64 * size >= rect.min_z
+                    })
+                })
+            })
+            .cloned()
+            .collect();
+        keys.sort_by(|left, right| compare_grid_keys(left, right));
+        keys
+    }
+
+    /// Objects stored in one cell.
+    /// 单个单元中存储的对象
+    pub(crate) fn cell_objects(&mut self, cell_key: &str) -> Result<&[Value], String> {
+        self.load_cell(cell_key)?;
+        Ok(self.cells[cell_key].pack["objects"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default())
+    }
+
+    pub(crate) fn load_all_cells(&mut self) -> Result<(), String> {
+        for cell_key in self.cell_keys_overlapping(None) {
+            self.load_cell(&cell_key)?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove an object by id from whichever loaded cell holds it.
+    /// 从持有该对象的已加载单元中按 id 移除对象
+    pub(crate) fn take_object(&mut self, id: &str) -> Result<Option<Value>, String> {
+        for (cell_key, cell) in &mut self.cells {
+            let Some(objects) = cell.pack["objects"].as_array_mut() else {
+                continue;
+            };
+            if let Some(index) = objects
+                .iter()
+                .position(|object| object["id"].as_str() == Some(id))
+            {
+                self.dirty_cells.insert(cell_key.clone());
+                return Ok(Some(objects.remove(index)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Insert an object into its cell, keeping objects sorted by id; ids must be unique across loaded cells.
+    /// 将对象插入所在单元并保持按 id 排序；id 在已加载单元中必须唯一
+    pub(crate) fn insert_object(&mut self, object: Value) -> Result<String, String> {
+        let id = object["id"].as_str().unwrap_or_default().to_string();
+        if self.contains_object(&id) {
+            return Err(format!("World object id '{}' already exists", id));
+        }
+
+        let position = read_position(&object)
+            .ok_or_else(|| format!("World object '{}' has an invalid position", id))?;
+        let cell_key = self.cell_key_for_position(position.x, position.z);
+        self.load_cell(&cell_key)?;
+        let objects = self
+            .cells
+            .get_mut(&cell_key)
+            .and_then(|cell| cell.pack["objects"].as_array_mut())
+            .ok_or_else(|| format!("World object cell '{}' is not loaded", cell_key))?;
+        let index = objects
+            .partition_point(|existing| existing["id"].as_str().unwrap_or_default() < id.as_str());
+        objects.insert(index, object);
+        self.dirty_cells.insert(cell_key.clone());
+        Ok(cell_key)
+    }
+
+    /// Every object in the loaded cells, including cells created during this session.
+    /// 已加载单元中的全部对象，包括本次会话新建的单元
+    pub(crate) fn loaded_objects(&self) -> impl Iterator<Item = &Value> {
+        self.cells
+            .values()
+            .filter_map(|cell| cell.pack["objects"].as_array())
+            .flatten()
+    }
+
+    /// Cells changed in this session, in grid order; these are the cells `save` will write.
+    /// 本次会话中修改过的单元（按网格顺序），即 `save` 将写入的单元
+    pub(crate) fn dirty_cell_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.dirty_cells.iter().cloned().collect();
+        keys.sort_by(|left, right| compare_grid_keys(left, right));
+        keys
+    }
+
+    pub(crate) fn contains_object(&self, id: &str) -> bool {
+        self.cells.values().any(|cell| {
+            cell.pack["objects"].as_array().is_some_and(|objects| {
+                objects
+                    .iter()
+                    .any(|object| object["id"].as_str() == Some(id))
+            })
+        })
+    }
+
+    /// Fill layer and bounds the way the editor's createObjectEntry does, then validate.
+    /// 按编辑器 createObjectEntry 的方式补全图层与包围盒，然后校验
+    pub(crate) fn normalize_new_object(&self, mut object: Value) -> Result<Value, String> {
+        if !object.is_object() {
+            return Err("World object entries must be JSON objects".to_string());
+        }
+
+        let archetype_id = object["archetype"].as_str().unwrap_or_default().to_string();
+        let archetype = self
+            .archetype(&archetype_id)
+            .ok_or_else(|| format!("Unknown world object archetype '{}'", archetype_id))?;
+        if object["layer"].is_null() {
+            object["layer"] = archetype["layer"].clone();
+        }
+        if let Some(position) = read_position(&object) {
+            object["position"] = json!({
+                "x": json_number(round_meters(position.x)),
+                "y": json_number(round_meters(position.y)),
+                "z": json_number(round_meters(position.z)),
+            });
+        }
+        if object["boundsMeters"].is_null() {
+            let points: Vec<(f64, f64)> = object["spline"]["points"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|point| Some((point["x"].as_f64()?, point["z"].as_f64()?)))
+                .collect();
+            let radius = object["radiusMeters"]
+                .as_f64()
+                .or_else(|| archetype["editor"]["defaultRadiusMeters"].as_f64())
+                .unwrap_or(DEFAULT_OBJECT_RADIUS_METERS);
+            if !points.is_empty() {
+                let width_meters = object["spline"]["widthMeters"].as_f64().unwrap_or(radius);
+                let half_width = (width_meters * 0.5).max(1.0);
+                let (min_x, max_x) = points
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (x, _)| {
+                        (min.min(*x), max.max(*x))
+                    });
+                let (min_z, max_z) = points
+                    .iter()
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (_, z)| {
+                        (min.min(*z), max.max(*z))
+                    });
+                object["boundsMeters"] = bounds_value(
+                    min_x - half_width,
+                    min_z - half_width,
+                    max_x + half_width,
+                    max_z + half_width,
+                );
+            } else if let Some(position) = read_position(&object) {
+                object["boundsMeters"] = bounds_value(
+                    position.x - radius,
+                    position.z - radius,
+                    position.x + radius,
+                    position.z + radius,
+                );
+            }
+        }
+
+        self.validate_object(&object)?;
+        Ok(object)
+    }
+
+    /// Entry checks from validateWorldObjectEntries in validate-map-assets.mjs, plus a known archetype.
+    /// 沿用 validate-map-assets.mjs 中 validateWorldObjectEntries 的条目检查，并要求原型已知
+    pub(crate) fn validate_object(&self, object: &Value) -> Result<(), String> {
+        let id = object["id"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| "World object entries must have a non-empty id".to_string())?;
+        if read_position(object).is_none() {
+            return Err(format!(
+                "World object '{}' must contain a finite position",
+                id
+            ));
+        }
+        if WorldRect::from_value(&object["boundsMeters"]).is_none() {
+            return Err(format!("World object '{}' must contain boundsMeters", id));
+        }
+        let (Some(_), Some(archetype)) = (object["layer"].as_str(), object["archetype"].as_str())
+        else {
+            return Err(format!(
+                "World object '{}' must contain layer and archetype",
+                id
+            ));
+        };
+        if self.archetype(archetype).is_none() {
+            return Err(format!(
+                "World object '{}' uses unknown archetype '{}'",
+                id, archetype
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn cell_ref(&self, cell_key: &str) -> Result<&Value, String> {
+        self.document["cells"]
+            .get(cell_key)
+            .ok_or_else(|| format!("Unknown world object cell '{}'", cell_key))
+    }
+
+    fn load_cell(&mut self, cell_key: &str) -> Result<(), String> {
+        if self.cells.contains_key(cell_key) {
+            return Ok(());
+        }
+
+        let cell = match self.document["cells"].get(cell_key) {
+            Some(cell_ref) => {
+                let path = self.cell_path(cell_key, cell_ref)?;
+                recover_safe_write(&path)?;
+                let bytes = fs::read(&path).map_err(|e| {
+                    format!("Failed to read world object cell '{}': {}", cell_key, e)
+                })?;
+                let matches_manifest = cell_ref["byteLength"].as_u64() == Some(bytes.len() as u64)
+                    && cell_ref["sha256"].as_str() == Some(sha256_hex(&bytes).as_str());
+                if !matches_manifest {
+                    return Err(format!(
+                        "World object cell '{}' does not match its manifest integrity entry",
+                        cell_key
+                    ));
+                }
+
+                let pack: Value = serde_json::from_slice(&bytes).map_err(|e| {
+                    format!("Failed to parse world object cell '{}': {}", cell_key, e)
+                })?;
+                if pack["format"].as_str() != Some(WORLD_OBJECT_CELL_FORMAT)
+                    || pack["cell"]["key"].as_str() != Some(cell_key)
+                    || !pack["objects"].is_array()
+                {
+                    return Err(format!(
+                        "World object cell '{}' is not a valid {} pack",
+                        cell_key, WORLD_OBJECT_CELL_FORMAT
+                    ));
+                }
+                if cell_ref["objectCount"].as_u64()
+                    != pack["objects"]
+                        .as_array()
+                        .map(|objects| objects.len() as u64)
+                {
+                    return Err(format!(
+                        "World object cell '{}' object count is stale",
+                        cell_key
+                    ));
+                }
+
+                WorldObjectCell {
+                    pack,
+                    original_bytes: Some(bytes),
+                }
+            }
+            None => {
+                let (cx, cz) = parse_grid_key(cell_key)
+                    .ok_or_else(|| format!("Invalid world object cell key '{}'", cell_key))?;
+                let size = self.cell_size_meters;
+                WorldObjectCell {
+                    pack: json!({
+                        "version": WORLD_OBJECT_CELL_VERSION,
+                        "format": WORLD_OBJECT_CELL_FORMAT,
+                        "cell": {
+                            "key": cell_key,
+                            "x": cx,
+                            "z": cz,
+                            "boundsMeters": bounds_value(
+                                cx as f64 * size,
+                                cz as f64 * size,
+                                (cx + 1) as f64 * size,
+                                (cz + 1) as f64 * size,
+                            ),
+                        },
+                        "objects": [],
+                    }),
+                    original_bytes: None,
+                }
+            }
+        };
+        self.cells.insert(cell_key.to_string(), cell);
+        Ok(())
+    }
+
+    /// Write changed cells, then the manifest with fresh counts and integrity.
+    /// If any write fails, cells already written are restored so the manifest and packs stay consistent.
+    /// 先写入已修改的单元，再写入带有最新数量与完整性的清单；任一写入失败时恢复已写入的单元，使清单与单元包保持一致
+    pub(crate) fn save(mut self) -> Result<Vec<String>, String> {
+        let dirty_cells = self.dirty_cell_keys();
+        if dirty_cells.is_empty() {
+            return Ok(dirty_cells);
+        }
+
+        let mut written: Vec<(PathBuf, Option<Vec<u8>>)> = Vec::new();
+        let mut cell_refs = Vec::new();
+        let mut result = Ok(());
+        for cell_key in &dirty_cells {
+            let cell = &self.cells[cell_key];
+            let mut content = match serde_json::to_string_pretty(&cell.pack) {
+                Ok(content) => content,
+                Err(e) => {
+                    result = Err(format!(
+                        "Failed to serialize world object cell '{}': {}",
+                        cell_key, e
+                    ));
+                    break;
+                }
+            };
+            content.push('\n');
+            let relative_path = match self.document["cells"].get(cell_key) {
+                Some(cell_ref) => cell_ref["path"].as_str().unwrap_or_default().to_string(),
+                None => {
+                    let (cx, cz) = parse_grid_key(cell_key).unwrap_or_default();
+                    format!(
+                        "{}/{}",
+                        self.cells_directory,
+                        cell_file_name(cx, cz, WORLD_OBJECT_CELL_EXTENSION)
+                    )
+                }
+            };
+            let path = self.map_root.join(&relative_path);
+            if let Err(e) = safe_write(&path, content.as_bytes()) {
+                result = Err(format!(
+                    "Failed to save world object cell '{}': {}",
+                    cell_key, e
+                ));
+                break;
+            }
+
+            written.push((path, cell.original_bytes.clone()));
+            cell_refs.push((
+                cell_key.clone(),
+                json!({
+                    "path": relative_path,
+                    "objectCount": cell.pack["objects"].as_array().map_or(0, Vec::len),
+                    "byteLength": content.len(),
+                    "sha256": sha256_hex(content.as_bytes()),
+                }),
+            ));
+        }
+
+        if result.is_ok() {
+            result = self.write_manifest(cell_refs);
+        }
+        if let Err(error) = result {
+            // EN: Best-effort rollback; a cell created by this save is removed again.
+            // 中文: 尽力回滚；本次保存新建的单元会被再次删除。
+            for (path, original_bytes) in written {
+                let _ = match original_bytes {
+                    Some(bytes) => safe_write(&path, &bytes),
+                    None => fs::remove_file(&path).map_err(|e| e.to_string()),
+                };
+            }
+            return Err(error);
+        }
+
+        Ok(dirty_cells)
+    }
+
+    fn write_manifest(&mut self, cell_refs: Vec<(String, Value)>) -> Result<(), String> {
+        let cells = self.document["cells"]
+            .as_object_mut()
+            .ok_or_else(|| "World object manifest must contain cell metadata".to_string())?;
+        for (cell_key, cell_ref) in cell_refs {
+            cells.insert(cell_key, cell_ref);
+        }
+        let mut entries: Vec<(String, Value)> = std::mem::take(cells).into_iter().collect();
+        entries.sort_by(|(left, _), (right, _)| compare_grid_keys(left, right));
+        *cells = entries.into_iter().collect();
+
+        let mut content = serde_json::to_string_pretty(&self.document)
+            .map_err(|e| format!("Failed to serialize world object manifest: {}", e))?;
+        content.push('\n');
+        safe_write(&self.map_root.join(WORLD_OBJECTS_PATH), content.as_bytes())
+            .map_err(|e| format!("Failed to save world object manifest: {}", e))
+    }
+
+    fn cell_path(&self, cell_key: &str, cell_ref: &Value) -> Result<PathBuf, String> {
+        let relative_path = cell_ref["path"]
+            .as_str()
+            .ok_or_else(|| format!("World object cell '{}' has no path", cell_key))?;
+        validate_relative_file_path(relative_path, "World object cell path")?;
+        Ok(self.map_root.join(relative_path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{FIXTURE_MAP_ID as MAP_ID, FixtureProject};
+
+    /// Project with an empty object manifest of 64 m cells and one prop archetype.
+    /// 含空对象清单（64 米单元）与一个道具原型的项目
+    fn synthetic_project(name: &str) -> FixtureProject {
+        let project = FixtureProject::new("objects", name);
+        let manifest = json!({
+            "version": 1,
+            "format": WORLD_OBJECT_MANIFEST_FORMAT,
+            "cellFormat": WORLD_OBJECT_CELL_FORMAT,
+            "cellSizeMeters": 64.0,
+            "cellsDirectory": WORLD_OBJECT_CELLS_DIRECTORY,
+            "archetypes": {
+                "crate": {
+                    "layer": "prop",
+                    "editor": { "placement": "single", "defaultRadiusMeters": 2.0 }
+                }
+            },
+            "cells": {}
+        });
+        project.write_artifact(
+            &format!("{}/{}/{}", MAPS_DIR, MAP_ID, WORLD_OBJECTS_PATH),
+            serde_json::to_string_pretty(&manifest).unwrap().as_bytes(),
+        );
+        project
+    }
+
+    fn crate_object(id: &str, x: f64, z: f64) -> Value {
+        json!({
+            "id": id,
+            "archetype": "crate",
+            "position": { "x": x, "y": 3.0, "z": z }
+        })
+    }
+
+    fn insert(session: &mut WorldObjectSession, id: &str, x: f64, z: f64) -> String {
+        let object = session
+            .normalize_new_object(crate_object(id, x, z))
+            .unwrap();
+        session.insert_object(object).unwrap()
+    }
+
+    fn object_ids(session: &mut WorldObjectSession, cell_key: &str) -> Vec<String> {
+        session
+            .cell_objects(cell_key)
+            .unwrap()
+            .iter()
+            .map(|object| object["id"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn saved_cells_reload_with_matching_integrity() {
+        let project = synthetic_project("reload");
+
+        let mut session = WorldObjectSession::open(project.root(), MAP_ID).unwrap();
+        assert_eq!(insert(&mut session, "crate-b", 10.0, 10.0), "0,0");
+        assert_eq!(insert(&mut session, "crate-a", 20.0, 5.0), "0,0");
+        assert_eq!(insert(&mut session, "crate-c", -10.0, 70.0), "-1,1");
+        assert!(
+            session
+                .insert_object(crate_object("crate-a", 1.0, 1.0))
+                .is_err()
+        );
+        assert_eq!(session.save().unwrap(), vec!["0,0", "-1,1"]);
+
+        let map_root = project.map_root();
+        let manifest: Value =
+            serde_json::from_str(&fs::read_to_string(map_root.join(WORLD_OBJECTS_PATH)).unwrap())
+                .unwrap();
+        let cell_ref = &manifest["cells"]["-1,1"];
+        assert_eq!(cell_ref["path"], "objects/cells/c_m1_1.objectpack");
+        assert_eq!(cell_ref["objectCount"], 1);
+        let bytes = fs::read(map_root.join(cell_ref["path"].as_str().unwrap())).unwrap();
+        assert_eq!(cell_ref["byteLength"], bytes.len());
+        assert_eq!(cell_ref["sha256"], sha256_hex(&bytes));
+
+        let mut session = WorldObjectSession::open(project.root(), MAP_ID).unwrap();
+        assert_eq!(session.cell_keys_overlapping(None), vec!["0,0", "-1,1"]);
+        assert_eq!(object_ids(&mut session, "0,0"), vec!["crate-a", "crate-b"]);
+        let restored = &session.cell_objects("0,0").unwrap()[1];
+        assert_eq!(restored["layer"], "prop");
+        assert_eq!(
+            WorldRect::from_value(&restored["boundsMeters"]).map(|rect| rect.min_x),
+            Some(8.0)
+        );
+
+        // EN: Moving an object across cells rewrites both cells and their counts.
+        // 中文: 跨单元移动对象会重写两个单元及其数量。
+        session.load_all_cells().unwrap();
+        let mut moved = session.take_object("crate-b").unwrap().unwrap();
+        moved["position"]["x"] = json!(-20.0);
+        moved["position"]["z"] = json!(80.0);
+        assert_eq!(session.insert_object(moved).unwrap(), "-1,1");
+        assert_eq!(session.save().unwrap(), vec!["0,0", "-1,1"]);
+
+        let mut session = WorldObjectSession::open(project.root(), MAP_ID).unwrap();
+        assert_eq!(object_ids(&mut session, "0,0"), vec!["crate-a"]);
+        assert_eq!(object_ids(&mut session, "-1,1"), vec!["crate-b", "crate-c"]);
+    }
+
+    #[test]
+    fn tampered_or_stale_cells_are_rejected() {
+        let project = synthetic_project("tamper");
+        let mut session = WorldObjectSession::open(project.root(), MAP_ID).unwrap();
+        insert(&mut session, "crate-a", 10.0, 10.0);
+        session.save().unwrap();
+
+        let map_root = project.map_root();
+        let cell_path = map_root.join("objects/cells/c_0_0.objectpack");
+        let original = fs::read_to_string(&cell_path).unwrap();
+        fs::write(&cell_path, original.replace("crate-a", "crate-z")).unwrap();
+        let mut session = WorldObjectSession::open(project.root(), MAP_ID).unwrap();
+        assert!(
+            session
+                .cell_objects("0,0")
+                .unwrap_err()
+                .contains("does not match its manifest integrity entry")
+        );
+
+        // EN: Rehash the cell but leave objectCount behind so only the count is stale.
+        // 中文: 重新计算单元哈希但保留旧的 objectCount，使只有数量过期。
+        let stale = original.replace("\"objects\": [", "\"objects\": [],\n  \"ignored\": [");
+        let manifest_path = map_root.join(WORLD_OBJECTS_PATH);
+        let mut manifest: Value =
+            serde_json::from_str(&fs::read_to_string(&manifest_path).unwrap()).unwrap();
+        manifest["cells"]["0,0"]["byteLength"] = json!(stale.len());
+        manifest["cells"]["0,0"]["sha256"] = json!(sha256_hex(stale.as_bytes()));
+        fs::write(&cell_path, &stale).unwrap();
+        fs::write(&manifest_path, manifest.to_string()).unwrap();
+        let mut session = WorldObjectSession::open(project.root(), MAP_ID).unwrap();
+        assert!(
+            session
+                .cell_objects("0,0")
+                .unwrap_err()
+                .contains("object count is stale")
+        );
+    }
+}
@@ -0,0 +1,1181 @@
+// Project prefab format and expansion of prefab instances into world object cells.
+// 项目预制体格式，以及将预制体实例展开到世界对象单元
+//
+// EN: A prefab lives at prefabs/<id>.prefab.json and lists archetype members placed relative to an anchor. Every expanded object records its prefab, instance, member, anchor and seed under `prefabInstance`, so instances can be rebuilt deterministically after the prefab changes.
+// 中文: 预制体位于 prefabs/<id>.prefab.json，列出相对锚点放置的原型成员。每个展开后的对象在 `prefabInstance` 下记录预制体、实例、成员、锚点与种子，因此预制体修改后可以确定性地重建实例。
+
+use crate::commands::{
+    MAPS_DIR, PROJECT_FILE, recover_safe_write, safe_write, validate_cook_project_path,
+    validate_single_path_segment,
+};
+use crate::height_pack::HeightPackSession;
+use crate::map_layout::{
+    WORLD_OBJECTS_PATH, WORLD_PREFAB_FORMAT, WORLD_PREFABS_DIRECTORY, distance_to_polyline,
+    splitmix64,
+};
+use crate::spline::SplineCurve;
+use crate::world_objects::{
+    WorldObjectPosition, WorldObjectSession, json_number, read_position, round_meters,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+use std::f64::consts::TAU;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const WORLD_PREFAB_VERSION: u64 = 1;
+const WORLD_PREFAB_EXTENSION: &str = ".prefab.json";
+const PREFAB_ROOT_MEMBER: &str = "root";
+const DEFAULT_MEMBER_RADIUS_METERS: f64 = 4.0;
+const DEFAULT_SPLINE_WIDTH_METERS: f64 = 4.0;
+const MAX_REPORTED_CONFLICTS: usize = 8;
+const CHANNEL_CHANCE: u64 = 1;
+const CHANNEL_OFFSET_DISTANCE: u64 = 2;
+const CHANNEL_OFFSET_ANGLE: u64 = 3;
+const CHANNEL_ROTATION: u64 = 4;
+const CHANNEL_SCALE: u64 = 5;
+
+/// Prefab document stored under the project's prefabs directory.
+/// 存储在项目 prefabs 目录下的预制体文档
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefab {
+    #[serde(default)]
+    version: u64,
+    #[serde(default)]
+    format: String,
+    id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    /// Optional archetype placed at the anchor itself, like the editor's prefab root.
+    /// 可选的锚点原型，对应编辑器中的预制体根对象
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    root_archetype: Option<String>,
+    /// Radius around the anchor that must stay clear of objects outside the instance.
+    /// 锚点周围必须避开实例外对象的半径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    clearance_radius_meters: Option<f64>,
+    members: Vec<WorldPrefabMember>,
+}
+
+/// One archetype placement relative to the prefab anchor.
+/// 相对预制体锚点的一个原型放置
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabMember {
+    key: String,
+    archetype: String,
+    offset: WorldPrefabOffset,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotation_y: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scale: Option<f64>,
+    /// Clearance around the member; defaults to the archetype's editor radius.
+    /// 成员周围的净空半径；默认使用原型的编辑器半径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    clearance_meters: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    randomize: Option<WorldPrefabRandomize>,
+}
+
+/// Offset in the anchor's local frame; `y` is added to the terrain height.
+/// 锚点局部坐标系中的偏移；`y` 叠加在地形高度之上
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct WorldPrefabOffset {
+    x: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    y: Option<f64>,
+    z: f64,
+}
+
+/// Per-instance randomization, seeded by the instance so re-expansion is stable.
+/// 按实例播种的随机化规则，使重新展开结果保持稳定
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabRandomize {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    offset_meters: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotation_radians: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scale_range: Option<[f64; 2]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chance: Option<f64>,
+}
+
+/// Anchor position; `y` defaults to the terrain height.
+/// 锚点位置；`y` 默认取地形高度
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct WorldPrefabAnchor {
+    x: f64,
+    #[serde(default)]
+    y: Option<f64>,
+    z: f64,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListWorldPrefabsRequest {
+    project_path: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveWorldPrefabRequest {
+    project_path: String,
+    prefab: WorldPrefab,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandWorldPrefabRequest {
+    project_path: String,
+    map_id: String,
+    prefab_id: String,
+    #[serde(default)]
+    instance_id: Option<String>,
+    position: WorldPrefabAnchor,
+    #[serde(default)]
+    rotation_y: Option<f64>,
+    #[serde(default)]
+    seed: Option<u32>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReexpandWorldPrefabRequest {
+    project_path: String,
+    prefab_id: String,
+    #[serde(default)]
+    map_ids: Option<Vec<String>>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabSummary {
+    id: String,
+    label: Option<String>,
+    path: String,
+    root_archetype: Option<String>,
+    member_count: usize,
+    clearance_radius_meters: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabListResult {
+    prefabs: Vec<WorldPrefabSummary>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabSaveResult {
+    prefab_id: String,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabExpandedObject {
+    id: String,
+    member: String,
+    archetype: String,
+    cell_key: String,
+    position: WorldObjectPosition,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefabClearanceConflict {
+    instance_id: String,
+    /// Member whose clearance is violated; absent for the prefab-wide radius.
+    /// 净空被侵占的成员；预制体整体半径冲突时为空
+    member: Option<String>,
+    object_id: String,
+    clearance_meters: f64,
+    distance_meters: f64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabExpandResult {
+    prefab_id: String,
+    instance_id: String,
+    seed: u32,
+    objects: Vec<PrefabExpandedObject>,
+    skipped_members: Vec<String>,
+    clearance_conflicts: Vec<PrefabClearanceConflict>,
+    updated_cells: Vec<String>,
+    warnings: Vec<String>,
+    dry_run: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabMapReexpansion {
+    map_id: String,
+    instances: Vec<String>,
+    removed_objects: usize,
+    inserted_objects: usize,
+    skipped_members: usize,
+    clearance_conflicts: Vec<PrefabClearanceConflict>,
+    updated_cells: Vec<String>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorldPrefabReexpandResult {
+    prefab_id: String,
+    maps: Vec<WorldPrefabMapReexpansion>,
+    dry_run: bool,
+}
+
+/// Where and how one prefab instance is placed; stored on every expanded object.
+/// 单个预制体实例的放置参数；记录在每个展开后的对象上
+#[derive(Debug, Clone)]
+struct PrefabPlacement {
+    prefab_id: String,
+    instance_id: String,
+    anchor: [f64; 3],
+    rotation_y: f64,
+    seed: u32,
+}
+
+/// Objects built for one instance, before they are inserted.
+/// 为单个实例构建、尚未插入的对象
+struct PrefabExpansion {
+    objects: Vec<(String, Value)>,
+    skipped_members: Vec<String>,
+    conflicts: Vec<PrefabClearanceConflict>,
+    terrain_missing: bool,
+}
+
+/// Footprint of an existing object for clearance checks.
+/// 用于净空检查的现有对象覆盖范围
+enum ObstacleShape {
+    Point {
+        x: f64,
+        z: f64,
+        radius: f64,
+    },
+    Path {
+        points: Vec<[f64; 2]>,
+        half_width: f64,
+    },
+}
+
+/// Final transform of one expanded member.
+/// 单个展开成员的最终变换
+struct MemberTransform {
+    position: [f64; 3],
+    rotation_y: f64,
+    scale: f64,
+}
+
+struct PrefabObstacle {
+    id: String,
+    shape: ObstacleShape,
+}
+
+// --- World prefab commands / 世界预制体命令 ---
+
+/// List the project's prefabs; unreadable files are reported as warnings.
+/// 列出项目中的预制体；无法读取的文件以警告形式报告
+#[tauri::command]
+pub async fn list_world_prefabs(
+    request: ListWorldPrefabsRequest,
+) -> Result<WorldPrefabListResult, String> {
+    tauri::async_runtime::spawn_blocking(move || list_world_prefabs_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join prefab list task: {}", e))?
+}
+
+/// Validate and write one prefab document.
+/// 校验并写入一个预制体文档
+#[tauri::command]
+pub async fn save_world_prefab(
+    request: SaveWorldPrefabRequest,
+) -> Result<WorldPrefabSaveResult, String> {
+    tauri::async_runtime::spawn_blocking(move || save_world_prefab_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join prefab save task: {}", e))?
+}
+
+/// Expand a prefab at an anchor into the object cells containing each member.
+/// 在锚点处展开预制体，将每个成员写入其所在的对象单元
+#[tauri::command]
+pub async fn expand_world_prefab(
+    request: ExpandWorldPrefabRequest,
+) -> Result<WorldPrefabExpandResult, String> {
+    tauri::async_runtime::spawn_blocking(move || expand_world_prefab_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join prefab expand task: {}", e))?
+}
+
+/// Rebuild every instance of a prefab from its stored anchor and seed after the prefab changed.
+/// 预制体修改后，按记录的锚点与种子重建其全部实例
+#[tauri::command]
+pub async fn reexpand_world_prefab(
+    request: ReexpandWorldPrefabRequest,
+) -> Result<WorldPrefabReexpandResult, String> {
+    tauri::async_runtime::spawn_blocking(move || reexpand_world_prefab_blocking(request))
+        .await
+        .map_err(|e| format!("Failed to join prefab re-expand task: {}", e))?
+}
+
+fn list_world_prefabs_blocking(
+    request: ListWorldPrefabsRequest,
+) -> Result<WorldPrefabListResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let directory = project_root.join(WORLD_PREFABS_DIRECTORY);
+    let mut prefabs = Vec::new();
+    let mut warnings = Vec::new();
+    if !directory.is_dir() {
+        return Ok(WorldPrefabListResult { prefabs, warnings });
+    }
+
+    let mut prefab_ids: Vec<String> = fs::read_dir(&directory)
+        .map_err(|e| format!("Failed to read prefab directory: {}", e))?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name().into_string().ok()?;
+            name.strip_suffix(WORLD_PREFAB_EXTENSION)
+                .map(str::to_string)
+        })
+        .collect();
+    prefab_ids.sort();
+    for prefab_id in prefab_ids {
+        match read_world_prefab(&project_root, &prefab_id) {
+            Ok(prefab) => prefabs.push(WorldPrefabSummary {
+                path: prefab_relative_path(&prefab.id),
+                id: prefab.id,
+                label: prefab.label,
+                root_archetype: prefab.root_archetype,
+                member_count: prefab.members.len(),
+                clearance_radius_meters: prefab.clearance_radius_meters,
+            }),
+            Err(error) => warnings.push(error),
+        }
+    }
+
+    Ok(WorldPrefabListResult { prefabs, warnings })
+}
+
+fn save_world_prefab_blocking(
+    request: SaveWorldPrefabRequest,
+) -> Result<WorldPrefabSaveResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let mut prefab = request.prefab;
+    prefab.version = WORLD_PREFAB_VERSION;
+    prefab.format = WORLD_PREFAB_FORMAT.to_string();
+    validate_prefab(&prefab)?;
+
+    let relative_path = prefab_relative_path(&prefab.id);
+    let mut document = serde_json::to_value(&prefab)
+        .map_err(|e| format!("Failed to serialize prefab '{}': {}", prefab.id, e))?;
+    compact_whole_numbers(&mut document);
+    let mut content = serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize prefab '{}': {}", prefab.id, e))?;
+    content.push('\n');
+    safe_write(&project_root.join(&relative_path), content.as_bytes())
+        .map_err(|e| format!("Failed to save prefab '{}': {}", prefab.id, e))?;
+
+    Ok(WorldPrefabSaveResult {
+        prefab_id: prefab.id,
+        path: relative_path,
+    })
+}
+
+fn expand_world_prefab_blocking(
+    request: ExpandWorldPrefabRequest,
+) -> Result<WorldPrefabExpandResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let prefab = read_world_prefab(&project_root, &request.prefab_id)?;
+    let position = request.position;
+    let rotation_y = request.rotation_y.unwrap_or(0.0);
+    if ![position.x, position.z, rotation_y]
+        .iter()
+        .chain(position.y.iter())
+        .all(|value| value.is_finite())
+    {
+        return Err("Prefab anchor position and rotationY must be finite".to_string());
+    }
+
+    let mut session = WorldObjectSession::open(&project_root, &request.map_id)?;
+    session.load_all_cells()?;
+    validate_prefab_archetypes(&prefab, &session)?;
+    let instance_id = match request.instance_id {
+        Some(instance_id) => {
+            validate_prefab_key(&instance_id, "Prefab instance id")?;
+            if instance_exists(&session, &instance_id) {
+                return Err(format!(
+                    "Prefab instance '{}' already exists; re-expand it instead",
+                    instance_id
+                ));
+            }
+            instance_id
+        }
+        None => next_instance_id(&session, &prefab.id),
+    };
+
+    let mut heights = HeightPackSession::open(&project_root, &request.map_id)?;
+    let mut warnings = Vec::new();
+    let anchor_y = match position.y {
+        Some(y) => y,
+        None => match heights.height_at(position.x, position.z)? {
+            Some(height) => f64::from(height),
+            None => {
+                warnings.push("Prefab anchor is outside stored terrain; using y = 0".to_string());
+                0.0
+            }
+        },
+    };
+    let placement = PrefabPlacement {
+        prefab_id: prefab.id.clone(),
+        seed: request
+            .seed
+            .unwrap_or_else(|| default_instance_seed(&instance_id)),
+        instance_id,
+        anchor: [
+            round_meters(position.x),
+            round_meters(anchor_y),
+            round_meters(position.z),
+        ],
+        rotation_y: round_meters(rotation_y),
+    };
+
+    let expansion = expand_instance(&prefab, &placement, &session, &mut heights)?;
+    if expansion.terrain_missing {
+        warnings
+            .push("Some members are outside stored terrain and use the anchor height".to_string());
+    }
+    if !request.dry_run {
+        reject_conflicts(&expansion.conflicts)?;
+    }
+
+    let mut objects = Vec::new();
+    for (member, object) in expansion.objects {
+        let object = session.normalize_new_object(object)?;
+        let id = object["id"].as_str().unwrap_or_default().to_string();
+        let archetype = object["archetype"].as_str().unwrap_or_default().to_string();
+        let position = read_position(&object)
+            .ok_or_else(|| format!("World object '{}' has an invalid position", id))?;
+        let cell_key = session.insert_object(object)?;
+        objects.push(PrefabExpandedObject {
+            id,
+            member,
+            archetype,
+            cell_key,
+            position,
+        });
+    }
+
+    let updated_cells = if request.dry_run {
+        session.dirty_cell_keys()
+    } else {
+        session.save()?
+    };
+
+    Ok(WorldPrefabExpandResult {
+        prefab_id: prefab.id,
+        instance_id: placement.instance_id,
+        seed: placement.seed,
+        objects,
+        skipped_members: expansion.skipped_members,
+        clearance_conflicts: expansion.conflicts,
+        updated_cells,
+        warnings,
+        dry_run: request.dry_run,
+    })
+}
+
+// EN: All maps are expanded in memory first and only saved when none of them has a clearance conflict, so a failed re-expansion leaves every map untouched.
+// 中文: 先在内存中展开所有地图，只有全部地图都没有净空冲突时才保存，因此失败的重新展开不会改动任何地图。
+fn reexpand_world_prefab_blocking(
+    request: ReexpandWorldPrefabRequest,
+) -> Result<WorldPrefabReexpandResult, String> {
+    let project_root = validate_cook_project_path(&request.project_path)?;
+    let prefab = read_world_prefab(&project_root, &request.prefab_id)?;
+    let map_ids = match request.map_ids {
+        Some(map_ids) => map_ids,
+        None => read_project_map_ids(&project_root)?
+            .into_iter()
+            .filter(|map_id| {
+                project_root
+                    .join(MAPS_DIR)
+                    .join(map_id)
+                    .join(WORLD_OBJECTS_PATH)
+                    .exists()
+            })
+            .collect(),
+    };
+
+    let mut sessions = Vec::new();
+    let mut maps = Vec::new();
+    for map_id in map_ids {
+        let mut session = WorldObjectSession::open(&project_root, &map_id)?;
+        session.load_all_cells()?;
+        let instances = collect_instances(&session, &prefab.id)?;
+        if instances.is_empty() {
+            continue;
+        }
+        validate_prefab_archetypes(&prefab, &session)
+            .map_err(|error| format!("Map '{}': {}", map_id, error))?;
+
+        let mut removed_objects = 0;
+        for object_ids in instances.values().map(|(_, object_ids)| object_ids) {
+            for object_id in object_ids {
+                session.take_object(object_id)?;
+                removed_objects += 1;
+            }
+        }
+
+        let mut heights = HeightPackSession::open(&project_root, &map_id)?;
+        let mut inserted_objects = 0;
+        let mut skipped_members = 0;
+        let mut clearance_conflicts = Vec::new();
+        let mut warnings = Vec::new();
+        for (placement, _) in instances.values() {
+            let expansion = expand_instance(&prefab, placement, &session, &mut heights)?;
+            if expansion.terrain_missing {
+                warnings.push(format!(
+                    "Instance '{}' has members outside stored terrain",
+                    placement.instance_id
+                ));
+            }
+            skipped_members += expansion.skipped_members.len();
+            clearance_conflicts.extend(expansion.conflicts);
+            for (_, object) in expansion.objects {
+                let object = session.normalize_new_object(object)?;
+                session.insert_object(object)?;
+                inserted_objects += 1;
+            }
+        }
+
+        maps.push(WorldPrefabMapReexpansion {
+            map_id,
+            instances: instances.into_keys().collect(),
+            removed_objects,
+            inserted_objects,
+            skipped_members,
+            clearance_conflicts,
+            updated_cells: session.dirty_cell_keys(),
+            warnings,
+        });
+        sessions.push(session);
+    }
+
+    if !request.dry_run {
+        reject_conflicts(maps.iter().flat_map(|map| &map.clearance_conflicts))?;
+        for (map, session) in maps.iter_mut().zip(sessions) {
+            map.updated_cells = session.save()?;
+        }
+    }
+
+    Ok(WorldPrefabReexpandResult {
+        prefab_id: prefab.id,
+        maps,
+        dry_run: request.dry_run,
+    })
+}
+
+/// Build the member objects of one instance and check their clearance against objects outside it.
+/// 构建单个实例的成员对象，并检查其与实例外对象的净空
+fn expand_instance(
+    prefab: &WorldPrefab,
+    placement: &PrefabPlacement,
+    session: &WorldObjectSession,
+    heights: &mut HeightPackSession,
+) -> Result<PrefabExpansion, String> {
+    let obstacles = collect_obstacles(session, &placement.instance_id);
+    let [anchor_x, anchor_y, anchor_z] = placement.anchor;
+    let (sin, cos) = placement.rotation_y.sin_cos();
+    let mut expansion = PrefabExpansion {
+        objects: Vec::new(),
+        skipped_members: Vec::new(),
+        conflicts: Vec::new(),
+        terrain_missing: false,
+    };
+    if let Some(radius) = prefab
+        .clearance_radius_meters
+        .filter(|radius| *radius > 0.0)
+    {
+        check_clearance(
+            &obstacles,
+            placement,
+            None,
+            anchor_x,
+            anchor_z,
+            radius,
+            &mut expansion.conflicts,
+        );
+    }
+
+    if let Some(root_archetype) = &prefab.root_archetype {
+        let archetype = session
+            .archetype(root_archetype)
+            .ok_or_else(|| format!("Unknown world object archetype '{}'", root_archetype))?;
+        let object = member_object(
+            placement.instance_id.clone(),
+            root_archetype,
+            archetype,
+            placement,
+            PREFAB_ROOT_MEMBER,
+            MemberTransform {
+                position: [anchor_x, anchor_y, anchor_z],
+                rotation_y: placement.rotation_y,
+                scale: 1.0,
+            },
+            &[],
+        );
+        expansion
+            .objects
+            .push((PREFAB_ROOT_MEMBER.to_string(), object));
+    }
+
+    for member in &prefab.members {
+        let randomize = member.randomize.unwrap_or(WorldPrefabRandomize {
+            offset_meters: None,
+            rotation_radians: None,
+            scale_range: None,
+            chance: None,
+        });
+        let random = |channel: u64| member_random(placement.seed, &member.key, channel);
+        if randomize
+            .chance
+            .is_some_and(|chance| random(CHANNEL_CHANCE) >= chance)
+        {
+            expansion.skipped_members.push(member.key.clone());
+            continue;
+        }
+
+        let jitter = randomize.offset_meters.unwrap_or(0.0);
+        let jitter_distance = jitter * random(CHANNEL_OFFSET_DISTANCE).sqrt();
+        let jitter_angle = random(CHANNEL_OFFSET_ANGLE) * TAU;
+        let (local_x, local_z) = (member.offset.x, member.offset.z);
+        let x = anchor_x + local_x * cos + local_z * sin + jitter_distance * jitter_angle.cos();
+        let z = anchor_z - local_x * sin + local_z * cos + jitter_distance * jitter_angle.sin();
+        let terrain_y = heights.height_at(x, z)?.map(f64::from);
+        if terrain_y.is_none() {
+            expansion.terrain_missing = true;
+        }
+        let y = terrain_y.unwrap_or(anchor_y) + member.offset.y.unwrap_or(0.0);
+
+        let rotation_jitter = randomize.rotation_radians.unwrap_or(0.0);
+        let rotation_y = placement.rotation_y
+            + member.rotation_y.unwrap_or(0.0)
+            + (random(CHANNEL_ROTATION) * 2.0 - 1.0) * rotation_jitter;
+        let [min_scale, max_scale] = randomize.scale_range.unwrap_or([1.0, 1.0]);
+        let scale = member.scale.unwrap_or(1.0)
+            * (min_scale + random(CHANNEL_SCALE) * (max_scale - min_scale));
+
+        let archetype = session
+            .archetype(&member.archetype)
+            .ok_or_else(|| format!("Unknown world object archetype '{}'", member.archetype))?;
+        let clearance = member
+            .clearance_meters
+            .unwrap_or_else(|| archetype_radius(archetype));
+        if clearance > 0.0 {
+            check_clearance(
+                &obstacles,
+                placement,
+                Some(&member.key),
+                x,
+                z,
+                clearance,
+                &mut expansion.conflicts,
+            );
+        }
+
+        let object = member_object(
+            format!("{}-{}", placement.instance_id, member.key),
+            &member.archetype,
+            archetype,
+            placement,
+            &member.key,
+            MemberTransform {
+                position: [x, y, z],
+                rotation_y,
+                scale,
+            },
+            &member.tags,
+        );
+        expansion.objects.push((member.key.clone(), object));
+    }
+
+    Ok(expansion)
+}
+
+/// Object entry shaped like the editor's createObjectEntry, plus the prefab instance record.
+/// 形如编辑器 createObjectEntry 的对象条目，并附带预制体实例记录
+fn member_object(
+    id: String,
+    archetype_id: &str,
+    archetype: &Value,
+    placement: &PrefabPlacement,
+    member: &str,
+    transform: MemberTransform,
+    extra_tags: &[String],
+) -> Value {
+    let layer = archetype["layer"].as_str().unwrap_or_default();
+    let radius = archetype_radius(archetype);
+    let mut tags = vec![json!(layer)];
+    if member == PREFAB_ROOT_MEMBER {
+        tags.push(json!("prefab-root"));
+    } else {
+        tags.push(json!("prefab-child"));
+        tags.push(json!(placement.instance_id));
+    }
+    tags.extend(extra_tags.iter().map(|tag| json!(tag)));
+
+    let [x, y, z] = transform.position;
+    let mut object = json!({
+        "id": id,
+        "layer": layer,
+        "archetype": archetype_id,
+        "position": {
+            "x": json_number(round_meters(x)),
+            "y": json_number(round_meters(y)),
+            "z": json_number(round_meters(z)),
+        },
+        "rotationY": json_number(round_meters(transform.rotation_y)),
+    });
+    if (transform.scale - 1.0).abs() > f64::EPSILON {
+        object["scale"] = json_number(round_meters(transform.scale));
+    }
+    object["radiusMeters"] = json_number(radius);
+    object["tags"] = Value::Array(tags);
+    match &archetype["collision"] {
+        collision @ Value::Object(_) => object["collision"] = collision.clone(),
+        Value::Bool(true) => {
+            object["collision"] = json!({
+                "type": "box",
+                "radiusMeters": json_number(radius),
+                "heightMeters": json_number(round_meters((radius * 0.75).max(2.0))),
+            });
+        }
+        _ => {}
+    }
+    object["prefabInstance"] = json!({
+        "prefab": placement.prefab_id,
+        "instance": placement.instance_id,
+        "member": member,
+        "anchor": {
+            "x": json_number(placement.anchor[0]),
+            "y": json_number(placement.anchor[1]),
+            "z": json_number(placement.anchor[2]),
+        },
+        "rotationY": json_number(placement.rotation_y),
+        "seed": placement.seed,
+    });
+    object
+}
+
+/// Footprints of every loaded object outside the given instance.
+/// 给定实例之外所有已加载对象的覆盖范围
+fn collect_obstacles(session: &WorldObjectSession, instance_id: &str) -> Vec<PrefabObstacle> {
+    session
+        .loaded_objects()
+        .filter(|object| object["prefabInstance"]["instance"].as_str() != Some(instance_id))
+        .filter_map(|object| {
+            let id = object["id"].as_str()?.to_string();
+            let shape = if object["spline"].is_object() {
+                let curve = SplineCurve::from_object(object).ok()?;
+                let width_meters = object["spline"]["widthMeters"]
+                    .as_f64()
+                    .unwrap_or(DEFAULT_SPLINE_WIDTH_METERS);
+                ObstacleShape::Path {
+                    points: curve.polyline(),
+                    half_width: width_meters * 0.5,
+                }
+            } else {
+                let position = read_position(object)?;
+                let radius = object["radiusMeters"].as_f64().unwrap_or_else(|| {
+                    object["archetype"]
+                        .as_str()
+                        .and_then(|archetype| session.archetype(archetype))
+                        .map_or(DEFAULT_MEMBER_RADIUS_METERS, archetype_radius)
+                });
+                ObstacleShape::Point {
+                    x: position.x,
+                    z: position.z,
+                    radius,
+                }
+            };
+            Some(PrefabObstacle { id, shape })
+        })
+        .collect()
+}
+
+fn check_clearance(
+    obstacles: &[PrefabObstacle],
+    placement: &PrefabPlacement,
+    member: Option<&str>,
+    x: f64,
+    z: f64,
+    clearance_meters: f64,
+    conflicts: &mut Vec<PrefabClearanceConflict>,
+) {
+    for obstacle in obstacles {
+        let distance = match &obstacle.shape {
+            ObstacleShape::Point {
+                x: obstacle_x,
+                z: obstacle_z,
+                radius,
+            } => (obstacle_x - x).hypot(obstacle_z - z) - radius,
+            ObstacleShape::Path { points, half_width } => {
+                distance_to_polyline(points, x, z) - half_width
+            }
+        };
+        if distance < clearance_meters {
+            conflicts.push(PrefabClearanceConflict {
+                instance_id: placement.instance_id.clone(),
+                member: member.map(str::to_string),
+                object_id: obstacle.id.clone(),
+                clearance_meters: round_meters(clearance_meters),
+                distance_meters: round_meters(distance),
+            });
+        }
+    }
+}
+
+fn reject_conflicts<'a>(
+    conflicts: impl IntoIterator<Item = &'a PrefabClearanceConflict>,
+) -> Result<(), String> {
+    let conflicts: Vec<&PrefabClearanceConflict> = conflicts.into_iter().collect();
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+
+    let listed: Vec<String> = conflicts
+        .iter()
+        .take(MAX_REPORTED_CONFLICTS)
+        .map(|conflict| {
+            format!(
+                "{}/{} vs '{}' ({} m < {} m)",
+                conflict.instance_id,
+                conflict.member.as_deref().unwrap_or(PREFAB_ROOT_MEMBER),
+                conflict.object_id,
+                conflict.distance_meters,
+                conflict.clearance_meters
+            )
+        })
+        .collect();
+    Err(format!(
+        "Prefab clearance blocked by {} object(s): {}",
+        conflicts.len(),
+        listed.join(", ")
+    ))
+}
+
+fn archetype_radius(archetype: &Value) -> f64 {
+    archetype["editor"]["defaultRadiusMeters"]
+        .as_f64()
+        .filter(|radius| radius.is_finite() && *radius > 0.0)
+        .unwrap_or(DEFAULT_MEMBER_RADIUS_METERS)
+}
+
+/// Uniform value in [0, 1) from the instance seed, the member key and a channel.
+/// 由实例种子、成员键与通道得到的 [0, 1) 均匀值
+fn member_random(seed: u32, member_key: &str, channel: u64) -> f64 {
+    let mut state = splitmix64(u64::from(seed) ^ fnv1a64(member_key));
+    state = splitmix64(state ^ channel.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    (state >> 11) as f64 / (1u64 << 53) as f64
+}
+
+fn default_instance_seed(instance_id: &str) -> u32 {
+    (fnv1a64(instance_id) & u64::from(u32::MAX)) as u32
+}
+
+fn fnv1a64(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn instance_exists(session: &WorldObjectSession, instance_id: &str) -> bool {
+    session.contains_object(instance_id)
+        || session
+            .loaded_objects()
+            .any(|object| object["prefabInstance"]["instance"].as_str() == Some(instance_id))
+}
+
+/// First free `<prefab>-NN` instance id.
+/// 第一个未被占用的 `<prefab>-NN` 实例 id
+fn next_instance_id(session: &WorldObjectSession, prefab_id: &str) -> String {
+    let used: BTreeSet<&str> = session
+        .loaded_objects()
+        .flat_map(|object| {
+            [
+                object["id"].as_str(),
+                object["prefabInstance"]["instance"].as_str(),
+            ]
+        })
+        .flatten()
+        .collect();
+    (0..)
+        .map(|index| format!("{}-{:02}", prefab_id, index))
+        .find(|candidate| !used.contains(candidate.as_str()))
+        .unwrap_or_default()
+}
+
+/// Instances of a prefab in one map, with their placement and current object ids.
+/// 单张地图中某预制体的实例，以及其放置参数与当前对象 id
+fn collect_instances(
+    session: &WorldObjectSession,
+    prefab_id: &str,
+) -> Result<BTreeMap<String, (PrefabPlacement, Vec<String>)>, String> {
+    let mut instances: BTreeMap<String, (PrefabPlacement, Vec<String>)> = BTreeMap::new();
+    for object in session.loaded_objects() {
+        let record = &object["prefabInstance"];
+        if record["prefab"].as_str() != Some(prefab_id) {
+            continue;
+        }
+
+        let object_id = object["id"].as_str().unwrap_or_default().to_string();
+        let anchor = &record["anchor"];
+        let placement = match (
+            record["instance"].as_str(),
+            anchor["x"].as_f64(),
+            anchor["y"].as_f64(),
+            anchor["z"].as_f64(),
+            record["seed"]
+                .as_u64()
+                .and_then(|seed| u32::try_from(seed).ok()),
+        ) {
+            (Some(instance_id), Some(x), Some(y), Some(z), Some(seed)) => PrefabPlacement {
+                prefab_id: prefab_id.to_string(),
+                instance_id: instance_id.to_string(),
+                anchor: [x, y, z],
+                rotation_y: record["rotationY"].as_f64().unwrap_or(0.0),
+                seed,
+            },
+            _ => {
+                return Err(format!(
+                    "World object '{}' has an invalid prefabInstance record",
+                    object_id
+                ));
+            }
+        };
+        instances
+            .entry(placement.instance_id.clone())
+            .or_insert_with(|| (placement, Vec::new()))
+            .1
+            .push(object_id);
+    }
+
+    Ok(instances)
+}
+
+fn read_world_prefab(project_root: &Path, prefab_id: &str) -> Result<WorldPrefab, String> {
+    validate_prefab_key(prefab_id, "Prefab id")?;
+    let path = project_root.join(prefab_relative_path(prefab_id));
+    recover_safe_write(&path)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read prefab '{}': {}", prefab_id, e))?;
+    let prefab: WorldPrefab = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse prefab '{}': {}", prefab_id, e))?;
+    if prefab.format != WORLD_PREFAB_FORMAT {
+        return Err(format!(
+            "Prefab '{}' must use the {} format",
+            prefab_id, WORLD_PREFAB_FORMAT
+        ));
+    }
+    if prefab.id != prefab_id {
+        return Err(format!(
+            "Prefab file '{}' declares id '{}'",
+            prefab_id, prefab.id
+        ));
+    }
+    validate_prefab(&prefab)?;
+    Ok(prefab)
+}
+
+/// Print whole numbers without a fraction so saved prefabs match the editor's JSON output.
+/// 整数值不带小数部分输出，使保存的预制体与编辑器的 JSON 输出一致
+fn compact_whole_numbers(value: &mut Value) {
+    match value {
+        Value::Number(number) => {
+            if let Some(float) = number.as_f64().filter(|_| number.is_f64()) {
+                *value = json_number(float);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(compact_whole_numbers),
+        Value::Object(entries) => entries.values_mut().for_each(compact_whole_numbers),
+        _ => {}
+    }
+}
+
+fn prefab_relative_path(prefab_id: &str) -> String {
+    format!(
+        "{}/{}{}",
+        WORLD_PREFABS_DIRECTORY, prefab_id, WORLD_PREFAB_EXTENSION
+    )
+}
+
+fn read_project_map_ids(project_root: &Path) -> Result<Vec<String>, String> {
+    let path: PathBuf = project_root.join(PROJECT_FILE);
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read project metadata: {}", e))?;
+    let project: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse project metadata: {}", e))?;
+    let map_ids: Vec<String> = project["maps"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|map_id| map_id.as_str().map(str::to_string))
+        .collect();
+    for map_id in &map_ids {
+        validate_single_path_segment(map_id, "map_id")?;
+    }
+
+    Ok(map_ids)
+}
+
+/// Ids used for prefabs, instances and member keys: ASCII letters, digits, '-' and '_'.
+/// 预制体、实例与成员键使用的 id：ASCII 字母、数字、'-' 与 '_'
+fn validate_prefab_key(value: &str, label: &str) -> Result<(), String> {
+    if value.is_empty()
+        || !value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "{} '{}' must use ASCII letters, digits, '-' or '_'",
+            label, value
+        ));
+    }
+
+    Ok(())
+}
+
+fn validate_prefab(prefab: &WorldPrefab) -> Result<(), String> {
+    validate_prefab_key(&prefab.id, "Prefab id")?;
+    if prefab.version != WORLD_PREFAB_VERSION {
+        return Err(format!(
+            "Prefab '{}' has unsupported version {}",
+            prefab.id, prefab.version
+        ));
+    }
+    if prefab.members.is_empty() && prefab.root_archetype.is_none() {
+        return Err(format!(
+            "Prefab '{}' must contain a root archetype or at least one member",
+            prefab.id
+        ));
+    }
+    if prefab
+        .clearance_radius_meters
+        .is_some_and(|radius| !radius.is_finite() || radius < 0.0)
+    {
+        return Err(format!(
+            "Prefab '{}' clearanceRadiusMeters must be a non-negative number",
+            prefab.id
+        ));
+    }
+
+    let mut keys = BTreeSet::new();
+    for member in &prefab.members {
+        validate_prefab_key(&member.key, "Prefab member key")?;
+        if member.key == PREFAB_ROOT_MEMBER {
+            return Err(format!(
+                "Prefab member key '{}' is reserved for the root archetype",
+                PREFAB_ROOT_MEMBER
+            ));
+        }
+        if !keys.insert(member.key.as_str()) {
+            return Err(format!(
+                "Prefab '{}' contains duplicate member key '{}'",
+                prefab.id, member.key
+            ));
+        }
+
+        let label = format!("Prefab member '{}'", member.key);
+        let offset = member.offset;
+        if ![offset.x, offset.z]
+            .iter()
+            .chain(offset.y.iter())
+            .chain(member.rotation_y.iter())
+            .all(|value| value.is_finite())
+        {
+            return Err(format!("{} offset and rotationY must be finite", label));
+        }
+        if member
+            .scale
+            .is_some_and(|scale| !scale.is_finite() || scale <= 0.0)
+        {
+            return Err(format!("{} scale must be a positive number", label));
+        }
+        if member
+            .clearance_meters
+            .is_some_and(|clearance| !clearance.is_finite() || clearance < 0.0)
+        {
+            return Err(format!(
+                "{} clearanceMeters must be a non-negative number",
+                label
+            ));
+        }
+
+        let Some(randomize) = member.randomize else {
+            continue;
+        };
+        if [randomize.offset_meters, randomize.rotation_radians]
+            .iter()
+            .flatten()
+            .any(|value| !value.is_finite() || *value < 0.0)
+        {
+            return Err(format!(
+                "{} randomize offsetMeters and rotationRadians must be non-negative numbers",
+                label
+            ));
+        }
+        if randomize.scale_range.is_some_and(|[min, max]| {
+            !min.is_finite() || !max.is_finite() || min <= 0.0 || min > max
+        }) {
+            return Err(format!(
+                "{} randomize scaleRange must be [min, max] with 0 < min <= max",
+                label
+            ));
+        }
+        if randomize
+            .chance
+            .is_some_and(|chance| !(0.0..=1.0).contains(&chance))
+        {
+            return Err(format!("{} randomize chance must be within [0, 1]", label));
+        }
+    }
+
+    Ok(())
+}
+
+/// Archetypes live in each map's object manifest, so a prefab is checked against the map it expands into.
+/// 原型定义位于各地图的对象清单中，因此预制体需针对其展开的地图进行校验
+fn validate_prefab_archetypes(
+    prefab: &WorldPrefab,
+    session: &WorldObjectSession,
+) -> Result<(), String> {
+    for archetype_id in prefab
+        .root_archetype
+        .iter()
+        .chain(prefab.members.iter().map(|member| &member.archetype))
+    {
+        let archetype = session
+            .archetype(archetype_id)
+            .ok_or_else(|| format!("Unknown world object archetype '{}'", archetype_id))?;
+        if archetype["editor"]["placement"].as_str() == Some("spline") {
+            return Err(format!(
+                "Prefab '{}' cannot place spline archetype '{}'",
+                prefab.id, archetype_id
+            ));
+        }
+    }
+
+    Ok(())
+}
@@ -418,6 +418,12 @@ export function formatCookMapResult(result: PlatformCookMapResult): string {
   if (stderr) {
     lines.push(`stderr:\n${stderr}`);
   }
+  if (result.logPath) {
+    lines.push(`log: ${result.logPath}`);
+  }
+  for (const warning of result.warnings ?? []) {
+    lines.push(`warning: ${warning}`);
+  }
 
   return lines.join("\n\n");
 }
@@ -42,6 +42,7 @@ export const DEFAULT_VEGETATION_LOD1_DISTANCE_METERS = 70;
 export const DEFAULT_VEGETATION_LOD2_DISTANCE_METERS = 130;
 export const DEFAULT_VEGETATION_MAX_VISIBLE_DISTANCE_METERS = 220;
 export const DEFAULT_VEGETATION_SHADOW_DISTANCE_METERS = 55;
+export const VEGETATION_IMPOSTOR_FORMAT = "vegetation-impostor-atlas-v1";
 
 export type VegetationBrushMode = "place" | "erase";
 
@@ -59,6 +60,25 @@ export interface VegetationModelDefinition {
   receiveShadow: boolean;
   maxVisibleDistanceMeters: number;
   shadowDistanceMeters: number;
+  impostor?: VegetationImpostorDefinition;
+}
+
+// EN: Baked far-LOD atlas; frames are orthographic azimuth views laid out row-major from the top-left.
+// 中文: 烘焙的远景 LOD 图集；每帧为正交方位视图，从左上角按行排列。
+export interface VegetationImpostorDefinition {
+  format: typeof VEGETATION_IMPOSTOR_FORMAT;
+  albedoPath: string;
+  normalPath: string;
+  frameCount: number;
+  columns: number;
+  rows: number;
+  frameSize: number;
+  frameWorldSize: number;
+  frameCenter: [number, number, number];
+  sourceMinY: number;
+  sourceHeightMeters: number;
+  startDistanceMeters: number;
+  maxVisibleDistanceMeters: number;
 }
 
 export interface VegetationModelLevelStats {
@@ -116,7 +136,7 @@ export function cloneVegetationData(data: VegetationMapData): VegetationMapData
   return {
     version: VEGETATION_DATA_VERSION,
     models: Object.fromEntries(
-      Object.entries(data.models).map(([id, model]) => [id, { ...model }]),
+      Object.entries(data.models).map(([id, model]) => [id, cloneVegetationModel(model)]),
     ),
     instances: data.instances.map((instance) => ({ ...instance })),
   };
@@ -386,6 +406,52 @@ function normalizeModelDefinition(id: string, value: unknown): VegetationModelDe
       maxVisibleDistanceMeters,
       DEFAULT_VEGETATION_SHADOW_DISTANCE_METERS,
     ),
+    ...normalizeImpostorDefinition(id, value.impostor),
+  };
+}
+
+function normalizeImpostorDefinition(
+  id: string,
+  value: unknown,
+): { impostor?: VegetationImpostorDefinition } {
+  if (value === undefined || value === null) {
+    return {};
+  }
+
+  if (!isRecord(value) || value.format !== VEGETATION_IMPOSTOR_FORMAT) {
+    throw new Error(`Vegetation model '${id}' has an unsupported impostor atlas`);
+  }
+
+  const albedoPath = normalizePath(readString(value.albedoPath));
+  const normalPath = normalizePath(readString(value.normalPath));
+  const frameCenter = Array.isArray(value.frameCenter) ? value.frameCenter : [];
+  if (!albedoPath || !normalPath || frameCenter.length !== 3) {
+    throw new Error(`Vegetation model '${id}' impostor atlas is incomplete`);
+  }
+
+  const frameCount = Math.round(clampNumber(value.frameCount, 1, 64, 8));
+  const columns = Math.round(clampNumber(value.columns, 1, 64, Math.ceil(Math.sqrt(frameCount))));
+  const startDistanceMeters = clampNumber(value.startDistanceMeters, 10, 2000, DEFAULT_VEGETATION_MAX_VISIBLE_DISTANCE_METERS);
+  return {
+    impostor: {
+      format: VEGETATION_IMPOSTOR_FORMAT,
+      albedoPath,
+      normalPath,
+      frameCount,
+      columns,
+      rows: Math.max(Math.ceil(frameCount / columns), Math.round(readFiniteNumber(value.rows, 1))),
+      frameSize: Math.round(clampNumber(value.frameSize, 16, 2048, 256)),
+      frameWorldSize: clampNumber(value.frameWorldSize, 0.001, 10000, 1),
+      frameCenter: [
+        readFiniteNumber(frameCenter[0], 0),
+        readFiniteNumber(frameCenter[1], 0),
+        readFiniteNumber(frameCenter[2], 0),
+      ],
+      sourceMinY: readFiniteNumber(value.sourceMinY, 0),
+      sourceHeightMeters: clampNumber(value.sourceHeightMeters, 0.001, 10000, 1),
+      startDistanceMeters,
+      maxVisibleDistanceMeters: clampNumber(value.maxVisibleDistanceMeters, startDistanceMeters, 2000, startDistanceMeters),
+    },
   };
 }
 
@@ -511,10 +577,16 @@ function cloneVegetationModels(
   models: Record<string, VegetationModelDefinition>,
 ): Record<string, VegetationModelDefinition> {
   return Object.fromEntries(
-    Object.entries(models).map(([id, model]) => [id, { ...model }]),
+    Object.entries(models).map(([id, model]) => [id, cloneVegetationModel(model)]),
   );
 }
 
+function cloneVegetationModel(model: VegetationModelDefinition): VegetationModelDefinition {
+  return model.impostor
+    ? { ...model, impostor: { ...model.impostor, frameCenter: [...model.impostor.frameCenter] } }
+    : { ...model };
+}
+
 function validateVegetationCellSize(cellSizeMeters: number): void {
   if (!Number.isFinite(cellSizeMeters) || cellSizeMeters <= 0) {
     throw new Error("Vegetation cell size must be a positive finite number");
@@ -9,6 +9,7 @@ import type {
   PlatformOpenFileOptions,
   PlatformPngRgbaData,
   PlatformSaveFileOptions,
+  PlatformWorldRebuildPlan,
 } from "./types";
 
 const BROWSER_FILE_PREFIX = "browser-file://";
@@ -338,6 +339,10 @@ export function createBrowserPlatform(): PlatformHost {
       async runGenerationGraph(_request: PlatformCookMapRequest): Promise<PlatformCookMapResult> {
         unsupported("World generation graph execution");
       },
+
+      async planRebuild(_request: PlatformCookMapRequest): Promise<PlatformWorldRebuildPlan> {
+        unsupported("World rebuild planning");
+      },
     },
   };
 }
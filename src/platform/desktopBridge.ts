@@ -6,8 +6,11 @@ import type {
   PlatformHost,
   PlatformOpenFileOptions,
   PlatformOpenFolderOptions,
+  PlatformPngReadOptions,
   PlatformPngRgbaData,
+  PlatformPngWriteOptions,
   PlatformSaveFileOptions,
+  PlatformWorldRebuildPlan,
 } from "./types";
 import { normalizeAssetPath } from "./pathUtils";
 
@@ -24,6 +27,7 @@ const DESKTOP_CAPABILITIES = new Set<PlatformCapability>([
   "pngRgbaCodec",
   "worldCookExecution",
   "worldGraphExecution",
+  "worldRebuildPlanning",
 ]);
 
 let coreModule: Promise<TauriCore> | null = null;
@@ -124,20 +128,30 @@ export function createDesktopPlatform(): PlatformHost {
         await invokeCommand<void>("write_binary_file_base64", { path, base64 });
       },
 
-      async readPngRgba(path: string): Promise<PlatformPngRgbaData> {
+      async readPngRgba(
+        path: string,
+        options?: PlatformPngReadOptions,
+      ): Promise<PlatformPngRgbaData> {
         const [base64Pixels, width, height] = await invokeCommand<[string, number, number]>(
           "read_png_rgba",
-          { path },
+          { path, bitDepth: options?.bitDepth },
         );
         return { base64Pixels, width, height };
       },
 
-      async writePngRgba(path: string, data: PlatformPngRgbaData): Promise<void> {
+      async writePngRgba(
+        path: string,
+        data: PlatformPngRgbaData,
+        options?: PlatformPngWriteOptions,
+      ): Promise<void> {
         await invokeCommand<void>("write_png_rgba", {
           path,
           base64Pixels: data.base64Pixels,
           width: data.width,
           height: data.height,
+          channels: options?.channels,
+          bitDepth: options?.bitDepth,
+          compression: options?.compression,
         });
       },
 
@@ -257,6 +271,10 @@ export function createDesktopPlatform(): PlatformHost {
       runGenerationGraph(request: PlatformCookMapRequest): Promise<PlatformCookMapResult> {
         return invokeCommand<PlatformCookMapResult>("run_world_generation_graph", { request });
       },
+
+      planRebuild(request: PlatformCookMapRequest): Promise<PlatformWorldRebuildPlan> {
+        return invokeCommand<PlatformWorldRebuildPlan>("plan_world_rebuild", { request });
+      },
     },
   };
 }
@@ -7,7 +7,8 @@ export type PlatformCapability =
     | "windowCloseControl"
     | "pngRgbaCodec"
     | "worldCookExecution"
-    | "worldGraphExecution";
+    | "worldGraphExecution"
+    | "worldRebuildPlanning";
 
 export type PlatformDialogFilter = {
     name: string;
@@ -53,6 +54,22 @@ export type PlatformPngRgbaData = {
     height: number;
 };
 
+export type PlatformPngChannelLayout = "rgba" | "rgb" | "grayscaleAlpha" | "grayscale";
+
+export type PlatformPngCompressionLevel = "none" | "fastest" | "fast" | "balanced" | "high";
+
+export type PlatformPngReadOptions = {
+    // 16 returns RGBA16 little-endian samples at full precision.
+    bitDepth?: 8 | 16;
+};
+
+export type PlatformPngWriteOptions = {
+    channels?: PlatformPngChannelLayout;
+    // 16-bit samples are passed little-endian.
+    bitDepth?: 8 | 16;
+    compression?: PlatformPngCompressionLevel;
+};
+
 export type PlatformCookMapScopes = {
     terrainRegions: string[];
     paintRegions: string[];
@@ -69,12 +86,109 @@ export type PlatformCookMapRequest = {
     scopes: PlatformCookMapScopes;
 };
 
+export type PlatformCookReportStage = {
+    stage: string;
+    executor: string;
+    keyCount: number;
+    status: "planned" | "executed" | "cached";
+};
+
+export type PlatformCookReportCell = {
+    key: string;
+    objectCount: number;
+    collisionShapeCount: number;
+    navNodeCount: number;
+    navLinkCount: number;
+    rawBytes: number;
+    compressedBytes: number;
+    estimatedCost: number;
+    rating: "ok" | "watch" | "over";
+};
+
+export type PlatformCookReportMap = {
+    mapId: string;
+    planId: string | null;
+    mode: "full" | "scoped";
+    blocked: boolean;
+    stages: PlatformCookReportStage[];
+    cacheHit: boolean | null;
+    manifestPath: string | null;
+    artifacts: {
+        written: string[];
+        reused: string[];
+    };
+    cells: PlatformCookReportCell[];
+    warnings: string[];
+    errors: string[];
+};
+
+export type PlatformCookReport = {
+    version: number;
+    format: "open-fps-cook-report-v1";
+    workflow: "cook" | "graph";
+    status: "running" | "succeeded" | "failed";
+    dryRun: boolean;
+    project: string;
+    startedAt: string | null;
+    finishedAt: string | null;
+    maps: PlatformCookReportMap[];
+    warnings: string[];
+    errors: string[];
+};
+
 export type PlatformCookMapResult = {
+    historyId: string;
     command: string[];
     exitCode: number;
     stdout: string;
     stderr: string;
     durationMs: number;
+    report: PlatformCookReport | null;
+    reportPath: string;
+    logPath: string;
+    warnings?: string[];
+};
+
+export type PlatformWorldRebuildAction = {
+    stage: string;
+    kind?: string;
+    executor: string;
+    scope: string;
+    keys: string[];
+    invalidates: string[];
+};
+
+export type PlatformWorldRebuildBudget = {
+    estimatedArtifacts: number;
+    terrainRegionCount: number;
+    paintRegionCount: number;
+    vegetationRegionCount: number;
+    partitionCellCount: number;
+    maxPartitionCellsPerScopedCook: number | null;
+    maxEstimatedArtifactsPerScopedCook: number | null;
+    exceeded: boolean;
+    errors: string[];
+    warnings: string[];
+};
+
+export type PlatformWorldRebuildPlan = {
+    version: number;
+    format: "open-fps-world-rebuild-plan-v1";
+    mode: "full" | "scoped";
+    mapId: string;
+    planId: string;
+    request: {
+        dryRun: boolean;
+        full: boolean;
+        allowBudgetOverrun: boolean;
+        targetStages: string[];
+        changedStages: string[];
+        scopes: PlatformCookMapScopes;
+    };
+    stages: string[];
+    scopes: PlatformCookMapScopes;
+    actions: PlatformWorldRebuildAction[];
+    budget: PlatformWorldRebuildBudget;
 };
 
 export interface PlatformDialogs {
@@ -92,8 +206,12 @@ export interface PlatformFiles {
     rename(oldPath: string, newPath: string): Promise<void>;
     readBinaryBase64(path: string): Promise<string>;
     writeBinaryBase64(path: string, base64: string): Promise<void>;
-    readPngRgba(path: string): Promise<PlatformPngRgbaData>;
-    writePngRgba(path: string, data: PlatformPngRgbaData): Promise<void>;
+    readPngRgba(path: string, options?: PlatformPngReadOptions): Promise<PlatformPngRgbaData>;
+    writePngRgba(
+        path: string,
+        data: PlatformPngRgbaData,
+        options?: PlatformPngWriteOptions,
+    ): Promise<void>;
     resolveAssetUrl(path: string): Promise<string>;
 }
 
@@ -128,6 +246,7 @@ export interface PlatformWindow {
 export interface PlatformWorld {
     runCookMap(request: PlatformCookMapRequest): Promise<PlatformCookMapResult>;
     runGenerationGraph(request: PlatformCookMapRequest): Promise<PlatformCookMapResult>;
+    planRebuild(request: PlatformCookMapRequest): Promise<PlatformWorldRebuildPlan>;
 }
 
 export interface PlatformHost {